@@ -0,0 +1,526 @@
+//! Consistency checks and arbitrage for events with mutually exclusive markets.
+//!
+//! In an event whose markets are mutually exclusive (range brackets, "who wins"), at most
+//! one market resolves Yes, and if the markets cover every outcome exactly one does. The
+//! Yes prices across the event should therefore sum to about 100¢:
+//!
+//! - **Buy every Yes** costs the sum of the Yes asks and pays 100¢ when the markets are
+//!   exhaustive. It is an arbitrage when the asks sum to less than 100¢ after fees.
+//!   Exhaustiveness is not published by the exchange, so these baskets are only reported
+//!   after [`EventAnalyzer::assume_exhaustive`].
+//! - **Buy every No** costs the sum of the No asks (`100 − Yes bid`) and pays
+//!   `(N − 1) × 100¢`, since at most one No loses. It is an arbitrage when the Yes bids sum
+//!   to more than 100¢ after fees. This needs only mutual exclusivity.
+//!
+//! [`EventAnalyzer`] reads the books of an event's markets, from REST or from an
+//! [`OrderbookCache`] fed by the WebSocket, and reports the sums, the implied probability
+//! of each market and the baskets that can be executed at a profit. Baskets walk the books
+//! level by level and stop at the first level that would lose money after taker fees, so
+//! their size is what the books can absorb now.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::EventAnalyzer;
+//!
+//! let analysis = EventAnalyzer::new()
+//!     .assume_exhaustive(true)
+//!     .analyze_event(&kalshi, "KXHIGHNY-24JAN01")
+//!     .await?;
+//! println!("asks sum to {:?}¢", analysis.sum_of_asks);
+//! for basket in &analysis.baskets {
+//!     println!("{:?} x{} for {}¢ profit", basket.kind, basket.count, basket.profit);
+//!     kalshi.batch_create_order(basket.orders()).await?;
+//! }
+//! ```
+
+use crate::backend::event_with_markets;
+use crate::fees::{FeeCalculator, FeeRole};
+use crate::kalshi_error::*;
+use crate::{
+    Action, Event, Market, MarketData, OrderCreationField, OrderType, Orderbook, OrderbookCache,
+    Side, TimeInForce,
+};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Price levels as (price in cents, quantity), best first.
+type Ladder = Vec<(i32, i64)>;
+
+/// Best prices of one market, in cents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketQuote {
+    pub ticker: String,
+    pub yes_bid: Option<i32>,
+    pub yes_bid_size: i64,
+    /// `100 − best No bid`.
+    pub yes_ask: Option<i32>,
+    pub yes_ask_size: i64,
+    /// Midpoint of the Yes bid and ask, when both exist.
+    pub mid: Option<f64>,
+    /// The mid divided by the sum of the event's mids.
+    pub implied_probability: Option<f64>,
+}
+
+/// Which side of every market a basket buys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BasketKind {
+    /// Buy Yes in every market; pays 100¢ per basket if exactly one market resolves Yes.
+    BuyAllYes,
+    /// Buy No in every market; pays `(N − 1) × 100¢` per basket if at most one resolves Yes.
+    BuyAllNo,
+}
+
+/// One market's order in a basket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasketLeg {
+    pub ticker: String,
+    pub side: Side,
+    pub count: i32,
+    /// Worst price reached in the book; the limit price to send.
+    pub limit_price: i32,
+    /// Cost of the leg before fees.
+    pub cost: i64,
+}
+
+/// An executable arbitrage basket. Amounts are in cents for the whole basket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbBasket {
+    pub kind: BasketKind,
+    /// Number of baskets, i.e. contracts per leg.
+    pub count: i32,
+    pub legs: Vec<BasketLeg>,
+    pub cost: i64,
+    /// Estimated taker fees.
+    pub fees: i64,
+    /// Guaranteed payout.
+    pub payout: i64,
+    /// `payout − cost − fees`.
+    pub profit: i64,
+}
+
+impl ArbBasket {
+    /// Fill-or-kill limit orders for every leg, for [`Kalshi::batch_create_order`].
+    ///
+    /// Legs are not atomic: one may fill and another be killed if the book moved.
+    ///
+    /// [`Kalshi::batch_create_order`]: crate::Kalshi::batch_create_order
+    pub fn orders(&self) -> Vec<OrderCreationField> {
+        self.legs
+            .iter()
+            .map(|leg| OrderCreationField {
+                action: Action::Buy,
+                client_order_id: None,
+                count: leg.count,
+                side: leg.side,
+                ticker: leg.ticker.clone(),
+                input_type: OrderType::Limit,
+                buy_max_cost: None,
+                expiration_ts: None,
+                yes_price: (leg.side == Side::Yes).then_some(leg.limit_price as i64),
+                no_price: (leg.side == Side::No).then_some(leg.limit_price as i64),
+                sell_position_floor: None,
+                yes_price_dollars: None,
+                no_price_dollars: None,
+                time_in_force: Some(TimeInForce::FillOrKill),
+                post_only: None,
+                reduce_only: None,
+                self_trade_prevention_type: None,
+                order_group_id: None,
+                cancel_order_on_pause: None,
+            })
+            .collect()
+    }
+}
+
+/// Result of [`EventAnalyzer::analyze`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventAnalysis {
+    pub event_ticker: String,
+    pub mutually_exclusive: bool,
+    /// One quote per market, in the event's order.
+    pub markets: Vec<MarketQuote>,
+    /// Sum of the best Yes bids, or `None` if a market has no bid.
+    pub sum_of_bids: Option<i64>,
+    /// Sum of the best Yes asks, or `None` if a market has no ask.
+    pub sum_of_asks: Option<i64>,
+    /// Profitable baskets, if any. Empty for events that are not mutually exclusive.
+    pub baskets: Vec<ArbBasket>,
+}
+
+/// Analyzes events for pricing consistency and arbitrage.
+/// See the [module documentation](crate::arbitrage).
+#[derive(Debug, Clone)]
+pub struct EventAnalyzer {
+    fees: FeeCalculator,
+    exhaustive: bool,
+}
+
+impl Default for EventAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventAnalyzer {
+    pub fn new() -> Self {
+        EventAnalyzer {
+            fees: FeeCalculator::default(),
+            exhaustive: false,
+        }
+    }
+
+    /// Estimates taker fees with `fees` instead of the default fee schedule.
+    pub fn with_fee_calculator(mut self, fees: FeeCalculator) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Whether the markets cover every outcome (default `false`). Mutual exclusivity does
+    /// not imply it, for example if brackets are still being listed, so buying every Yes
+    /// is only reported as [`BasketKind::BuyAllYes`] once the caller asserts it.
+    pub fn assume_exhaustive(mut self, exhaustive: bool) -> Self {
+        self.exhaustive = exhaustive;
+        self
+    }
+
+    /// Fetches `event_ticker`, its markets and their books, and analyzes them.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - Where to read the event and books from.
+    /// * `event_ticker` - The event to analyze.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(EventAnalysis)`: The analysis.
+    /// - `Err(KalshiError)`: A request failed.
+    ///
+    pub async fn analyze_event<B: MarketData>(
+        &self,
+        backend: &B,
+        event_ticker: &str,
+    ) -> Result<EventAnalysis, KalshiError> {
+        let event = event_with_markets(backend, event_ticker).await?;
+        let mut books = HashMap::new();
+        for market in event.markets.iter().flatten() {
+            let book = backend.get_orderbook(&market.ticker, None).await?;
+            books.insert(market.ticker.clone(), book);
+        }
+        Ok(self.analyze(&event, &books))
+    }
+
+    /// Analyzes `event` with books kept by a WebSocket [`OrderbookCache`]. Markets without
+    /// a cached book are treated as empty.
+    pub fn analyze_cached(&self, event: &Event, cache: &OrderbookCache) -> EventAnalysis {
+        let books = event
+            .markets
+            .iter()
+            .flatten()
+            .filter_map(|m| cache.get(&m.ticker).map(|b| (m.ticker.clone(), b)))
+            .collect();
+        self.analyze(event, &books)
+    }
+
+    /// Analyzes `event`, whose `markets` must be set, with the given books by ticker.
+    /// Markets without a book are treated as empty.
+    pub fn analyze(&self, event: &Event, books: &HashMap<String, Orderbook>) -> EventAnalysis {
+        let markets: Vec<&Market> = event.markets.iter().flatten().collect();
+        let empty = Vec::new();
+        // Yes bids and asks of each market as (price, qty) levels, best first.
+        let ladders: Vec<(Ladder, Ladder)> = markets
+            .iter()
+            .map(|m| {
+                let book = books.get(&m.ticker);
+                let yes = book.and_then(|b| b.yes.as_ref()).unwrap_or(&empty);
+                let no = book.and_then(|b| b.no.as_ref()).unwrap_or(&empty);
+                (yes_bids(yes), yes_asks(no))
+            })
+            .collect();
+
+        let mut quotes: Vec<MarketQuote> = markets
+            .iter()
+            .zip(&ladders)
+            .map(|(m, (bids, asks))| {
+                let bid = bids.first().copied();
+                let ask = asks.first().copied();
+                MarketQuote {
+                    ticker: m.ticker.clone(),
+                    yes_bid: bid.map(|l| l.0),
+                    yes_bid_size: bid.map_or(0, |l| l.1),
+                    yes_ask: ask.map(|l| l.0),
+                    yes_ask_size: ask.map_or(0, |l| l.1),
+                    mid: bid.zip(ask).map(|(b, a)| (b.0 + a.0) as f64 / 2.0),
+                    implied_probability: None,
+                }
+            })
+            .collect();
+        let mid_sum: f64 = quotes.iter().filter_map(|q| q.mid).sum();
+        if mid_sum > 0.0 {
+            for q in &mut quotes {
+                q.implied_probability = q.mid.map(|m| m / mid_sum);
+            }
+        }
+        let sum = |price: fn(&MarketQuote) -> Option<i32>| -> Option<i64> {
+            quotes.iter().map(|q| price(q).map(i64::from)).sum()
+        };
+        let sum_of_bids = sum(|q| q.yes_bid);
+        let sum_of_asks = sum(|q| q.yes_ask);
+
+        let mut baskets = Vec::new();
+        if event.mutually_exclusive && markets.len() > 1 {
+            let now = Utc::now();
+            if self.exhaustive {
+                let legs = markets
+                    .iter()
+                    .zip(&ladders)
+                    .map(|(m, (_, asks))| (m.ticker.as_str(), Side::Yes, asks.clone()))
+                    .collect();
+                baskets.extend(self.walk(BasketKind::BuyAllYes, legs, 100, now));
+            }
+            // A Yes bid at p is a No ask at 100 − p.
+            let legs = markets
+                .iter()
+                .zip(&ladders)
+                .map(|(m, (bids, _))| {
+                    let no_asks = bids.iter().map(|&(p, q)| (100 - p, q)).collect();
+                    (m.ticker.as_str(), Side::No, no_asks)
+                })
+                .collect();
+            let payout = (markets.len() as i64 - 1) * 100;
+            baskets.extend(self.walk(BasketKind::BuyAllNo, legs, payout, now));
+        }
+
+        EventAnalysis {
+            event_ticker: event.event_ticker.clone(),
+            mutually_exclusive: event.mutually_exclusive,
+            markets: quotes,
+            sum_of_bids,
+            sum_of_asks,
+            baskets,
+        }
+    }
+
+    /// Buys every leg level by level while each extra block of baskets is profitable.
+    ///
+    /// `legs` hold the ask levels of each leg, cheapest first. Fees are estimated per level
+    /// and rounded up each time, so they are slightly conservative.
+    fn walk(
+        &self,
+        kind: BasketKind,
+        legs: Vec<(&str, Side, Ladder)>,
+        payout: i64,
+        now: DateTime<Utc>,
+    ) -> Option<ArbBasket> {
+        let mut basket = ArbBasket {
+            kind,
+            count: 0,
+            legs: legs
+                .iter()
+                .map(|(ticker, side, _)| BasketLeg {
+                    ticker: ticker.to_string(),
+                    side: *side,
+                    count: 0,
+                    limit_price: 0,
+                    cost: 0,
+                })
+                .collect(),
+            cost: 0,
+            fees: 0,
+            payout: 0,
+            profit: 0,
+        };
+        // Current level and quantity left on it, per leg.
+        let mut cursor: Vec<(usize, i64)> = legs
+            .iter()
+            .map(|(_, _, levels)| (0, levels.first().map_or(0, |l| l.1)))
+            .collect();
+        loop {
+            let mut chunk = i64::MAX;
+            for ((_, _, levels), (level, left)) in legs.iter().zip(&cursor) {
+                if *level >= levels.len() {
+                    chunk = 0;
+                }
+                chunk = chunk.min(*left);
+            }
+            if chunk <= 0 {
+                break;
+            }
+            let mut cost = 0;
+            let mut fees = 0;
+            for ((ticker, _, levels), (level, _)) in legs.iter().zip(&cursor) {
+                let price = levels[*level].0;
+                cost += chunk * price as i64;
+                fees += self
+                    .fees
+                    .fee_for(ticker, chunk as i32, price, FeeRole::Taker, now);
+            }
+            if payout * chunk - cost - fees <= 0 {
+                break;
+            }
+            basket.count += chunk as i32;
+            basket.cost += cost;
+            basket.fees += fees;
+            for (((_, _, levels), (level, left)), leg) in
+                legs.iter().zip(&mut cursor).zip(&mut basket.legs)
+            {
+                let price = levels[*level].0;
+                leg.count += chunk as i32;
+                leg.cost += chunk * price as i64;
+                leg.limit_price = price;
+                *left -= chunk;
+                if *left == 0 {
+                    *level += 1;
+                    *left = levels.get(*level).map_or(0, |l| l.1);
+                }
+            }
+        }
+        if basket.count == 0 {
+            return None;
+        }
+        basket.payout = payout * basket.count as i64;
+        basket.profit = basket.payout - basket.cost - basket.fees;
+        Some(basket)
+    }
+}
+
+/// Yes bids as (price, qty), highest first.
+fn yes_bids(yes: &[Vec<i32>]) -> Ladder {
+    let mut levels = to_levels(yes, |p| p);
+    levels.sort_by_key(|l| std::cmp::Reverse(l.0));
+    levels
+}
+
+/// Yes asks implied by No bids as (price, qty), lowest first.
+fn yes_asks(no: &[Vec<i32>]) -> Ladder {
+    let mut levels = to_levels(no, |p| 100 - p);
+    levels.sort_by_key(|l| l.0);
+    levels
+}
+
+fn to_levels(levels: &[Vec<i32>], price: fn(i32) -> i32) -> Ladder {
+    levels
+        .iter()
+        .filter_map(|l| match l.as_slice() {
+            [p, q, ..] if *q > 0 => Some((price(*p), *q as i64)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OrderbookDeltaMsg, OrderbookSnapshotMsg, WebSocketMessage};
+
+    fn event(tickers: &[&str]) -> Event {
+        let mut event: Event = serde_json::from_value(serde_json::json!({
+            "event_ticker": "KXTEST-24JAN01", "series_ticker": "KXTEST", "title": "",
+            "sub_title": "", "mutually_exclusive": true, "category": ""
+        }))
+        .unwrap();
+        event.markets = Some(
+            tickers
+                .iter()
+                .map(|t| crate::test_market(serde_json::json!({ "ticker": t })))
+                .collect(),
+        );
+        event
+    }
+
+    fn book(yes: Vec<Vec<i32>>, no: Vec<Vec<i32>>) -> Orderbook {
+        Orderbook {
+            yes: Some(yes),
+            no: Some(no),
+            yes_dollars: Vec::new(),
+            no_dollars: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_cheap_asks_form_a_yes_basket_net_of_fees() {
+        let tickers = [
+            "KXTEST-24JAN01-B1",
+            "KXTEST-24JAN01-B2",
+            "KXTEST-24JAN01-B3",
+        ];
+        let event = event(&tickers);
+        // Yes asks 30¢ x10 then 35¢ x5 in every market; Yes bids 20¢.
+        let books = tickers
+            .iter()
+            .map(|t| {
+                let b = book(vec![vec![20, 10]], vec![vec![65, 5], vec![70, 10]]);
+                (t.to_string(), b)
+            })
+            .collect();
+        let analysis = EventAnalyzer::new()
+            .assume_exhaustive(true)
+            .analyze(&event, &books);
+
+        assert_eq!(analysis.sum_of_asks, Some(90));
+        assert_eq!(analysis.sum_of_bids, Some(60));
+        let total: f64 = analysis
+            .markets
+            .iter()
+            .filter_map(|q| q.implied_probability)
+            .sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        // 10 baskets at 90¢; the 35¢ level would cost 105¢. Fees: 15¢ per leg.
+        assert_eq!(analysis.baskets.len(), 1);
+        let basket = &analysis.baskets[0];
+        assert_eq!(basket.kind, BasketKind::BuyAllYes);
+        assert_eq!(basket.count, 10);
+        assert_eq!((basket.cost, basket.fees, basket.profit), (900, 45, 55));
+        let orders = basket.orders();
+        assert_eq!(orders.len(), 3);
+        assert_eq!(orders[0].yes_price, Some(30));
+
+        // Without the exhaustive assumption buying every Yes is not risk-free.
+        assert!(EventAnalyzer::new()
+            .analyze(&event, &books)
+            .baskets
+            .is_empty());
+    }
+
+    #[test]
+    fn test_cached_books_follow_deltas() {
+        let tickers = ["KXTEST-24JAN01-B1", "KXTEST-24JAN01-B2"];
+        let cache = OrderbookCache::new();
+        for t in tickers {
+            cache.handle_message(&WebSocketMessage::OrderbookSnapshot(OrderbookSnapshotMsg {
+                market_ticker: t.to_string(),
+                yes: vec![vec![45, 10]],
+                yes_dollars: Vec::new(),
+                no: vec![vec![50, 10]],
+                no_dollars: Vec::new(),
+                sid: None,
+                seq: None,
+            }));
+        }
+        let analyzer = EventAnalyzer::new();
+        assert!(analyzer
+            .analyze_cached(&event(&tickers), &cache)
+            .baskets
+            .is_empty());
+
+        // A 60¢ Yes bid in B1: the bids now sum to 105¢, so buying both No legs
+        // costs 40 + 55 = 95¢ for a 100¢ payout.
+        cache.handle_message(&WebSocketMessage::OrderbookDelta(OrderbookDeltaMsg {
+            market_ticker: tickers[0].to_string(),
+            price: 60,
+            price_dollars: "0.60".to_string(),
+            delta: 10,
+            side: "yes".to_string(),
+            sid: None,
+            seq: None,
+        }));
+        let analysis = analyzer.analyze_cached(&event(&tickers), &cache);
+        assert_eq!(analysis.sum_of_bids, Some(105));
+        let basket = &analysis.baskets[0];
+        assert_eq!(basket.kind, BasketKind::BuyAllNo);
+        assert_eq!(basket.count, 10);
+        assert_eq!(basket.legs[0].limit_price, 40);
+        assert_eq!(basket.profit, basket.payout - 950 - basket.fees);
+    }
+}
@@ -118,10 +118,10 @@ impl Kalshi {
                     body_text
                 )));
             } else if status.is_client_error() {
-                return Err(KalshiError::UserInputError(format!(
-                    "Request failed with status {}: {}",
-                    status, body_text
-                )));
+                return Err(KalshiError::HttpError {
+                    status,
+                    body: body_text,
+                });
             } else {
                 return Err(KalshiError::InternalError(format!(
                     "Server error {}: {}",
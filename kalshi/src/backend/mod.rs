@@ -0,0 +1,1882 @@
+//! Backend traits for market data, trading and portfolio queries.
+//!
+//! Strategies that take `impl Trading` (or [`MarketData`], [`Portfolio`]) instead of a
+//! concrete [`Kalshi`] can run unchanged against:
+//!
+//! - [`Kalshi`] — the live or demo exchange,
+//! - [`PaperKalshi`] — local matching against real market data,
+//! - [`InMemoryKalshi`] — canned markets, books and trades for tests.
+//!
+//! Trait methods take the same arguments and return the same types as the inherent
+//! `Kalshi` methods of the same name, so switching a bot over is a matter of changing its
+//! parameter types. Every returned future is `Send`, so backends can be used from spawned
+//! tasks.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::{Action, MarketData, OrderType, Side, Trading};
+//!
+//! async fn buy_if_cheap<B: MarketData + Trading>(backend: &B, ticker: &str) -> Result<(), KalshiError> {
+//!     let market = backend.get_market(ticker).await?;
+//!     if market.yes_ask < 20 {
+//!         backend.create_order(
+//!             Action::Buy, None, 1, Side::Yes, ticker.to_string(), OrderType::Limit,
+//!             None, None, Some(market.yes_ask as i64), None, None, None, None, None, None, None,
+//!             None, None, None,
+//!         ).await?;
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use crate::kalshi_error::*;
+use crate::paper::{api_error, paginate};
+use crate::risk::series_ticker_of;
+use crate::utils::parse_ts;
+use crate::{
+    Action, AmendOrderResponse, Candle, Candlestick, Event, EventMetadata, EventPosition, Fill,
+    FillMsg, ForecastPercentileHistory, Kalshi, Market, MarketCandlesticks, MarketPosition,
+    MveFilter, Order, OrderCreationField, OrderGroup, OrderQueuePosition, OrderStatus, OrderType,
+    Orderbook, PaperKalshi, SelfTradePreventionType, Series, Settlement, Side, TimeInForce, Trade,
+    TradeMsg, WebSocketMessage,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::Mutex;
+
+/// Read-only market data: markets, books, trades, candlesticks, series and events.
+pub trait MarketData {
+    /// See [`Kalshi::get_markets`].
+    #[allow(clippy::too_many_arguments)]
+    fn get_markets(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        event_ticker: Option<String>,
+        series_ticker: Option<String>,
+        status: Option<String>,
+        tickers: Option<String>,
+        min_close_ts: Option<i64>,
+        max_close_ts: Option<i64>,
+        min_created_ts: Option<i64>,
+        max_created_ts: Option<i64>,
+        min_settled_ts: Option<i64>,
+        max_settled_ts: Option<i64>,
+        mve_filter: Option<MveFilter>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Market>), KalshiError>> + Send;
+
+    /// See [`Kalshi::get_market`].
+    fn get_market(&self, ticker: &str) -> impl Future<Output = Result<Market, KalshiError>> + Send;
+
+    /// See [`Kalshi::get_orderbook`].
+    fn get_orderbook(
+        &self,
+        ticker: &str,
+        depth: Option<i32>,
+    ) -> impl Future<Output = Result<Orderbook, KalshiError>> + Send;
+
+    /// See [`Kalshi::get_market_candlesticks`].
+    fn get_market_candlesticks(
+        &self,
+        ticker: &str,
+        series_ticker: &str,
+        start_ts: Option<i64>,
+        end_ts: Option<i64>,
+        period_interval: Option<i32>,
+    ) -> impl Future<Output = Result<Vec<Candle>, KalshiError>> + Send;
+
+    /// See [`Kalshi::batch_get_market_candlesticks`].
+    fn batch_get_market_candlesticks(
+        &self,
+        market_tickers: Vec<String>,
+        start_ts: i64,
+        end_ts: i64,
+        period_interval: i32,
+        include_latest_before_start: Option<bool>,
+    ) -> impl Future<Output = Result<Vec<MarketCandlesticks>, KalshiError>> + Send;
+
+    /// See [`Kalshi::get_trades`].
+    fn get_trades(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Trade>), KalshiError>> + Send;
+
+    /// See [`Kalshi::get_series_list`].
+    fn get_series_list(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        category: Option<String>,
+        tags: Option<String>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Series>), KalshiError>> + Send;
+
+    /// See [`Kalshi::get_series`].
+    fn get_series(
+        &self,
+        series_ticker: &str,
+    ) -> impl Future<Output = Result<Series, KalshiError>> + Send;
+
+    /// See [`Kalshi::get_events`].
+    #[allow(clippy::too_many_arguments)]
+    fn get_events(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        status: Option<String>,
+        series_ticker: Option<String>,
+        with_nested_markets: Option<bool>,
+        with_milestones: Option<bool>,
+        min_close_ts: Option<i64>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Event>), KalshiError>> + Send;
+
+    /// See [`Kalshi::get_event`].
+    fn get_event(
+        &self,
+        event_ticker: &str,
+    ) -> impl Future<Output = Result<Event, KalshiError>> + Send;
+
+    /// See [`Kalshi::get_orderbook_full`].
+    fn get_orderbook_full(
+        &self,
+        ticker: &str,
+    ) -> impl Future<Output = Result<Orderbook, KalshiError>> + Send;
+
+    /// See [`Kalshi::get_event_candlesticks`].
+    fn get_event_candlesticks(
+        &self,
+        series_ticker: &str,
+        event_ticker: &str,
+        start_ts: Option<i64>,
+        end_ts: Option<i64>,
+        period_interval: Option<String>,
+    ) -> impl Future<Output = Result<Vec<Candlestick>, KalshiError>> + Send;
+
+    /// See [`Kalshi::get_event_metadata`].
+    fn get_event_metadata(
+        &self,
+        event_ticker: &str,
+    ) -> impl Future<Output = Result<EventMetadata, KalshiError>> + Send;
+
+    /// See [`Kalshi::get_event_forecast_percentile_history`].
+    fn get_event_forecast_percentile_history(
+        &self,
+        event_ticker: &str,
+    ) -> impl Future<Output = Result<ForecastPercentileHistory, KalshiError>> + Send;
+
+    /// See [`Kalshi::get_multivariate_events`].
+    fn get_multivariate_events(
+        &self,
+        limit: Option<i32>,
+        cursor: Option<String>,
+        series_ticker: Option<String>,
+        collection_ticker: Option<String>,
+        with_nested_markets: Option<bool>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Event>), KalshiError>> + Send;
+}
+
+/// Order entry and order queries.
+pub trait Trading {
+    /// See [`Kalshi::create_order`].
+    #[allow(clippy::too_many_arguments)]
+    fn create_order(
+        &self,
+        action: Action,
+        client_order_id: Option<String>,
+        count: i32,
+        side: Side,
+        ticker: String,
+        input_type: OrderType,
+        buy_max_cost: Option<i64>,
+        expiration_ts: Option<i64>,
+        yes_price: Option<i64>,
+        no_price: Option<i64>,
+        sell_position_floor: Option<i32>,
+        yes_price_dollars: Option<String>,
+        no_price_dollars: Option<String>,
+        time_in_force: Option<TimeInForce>,
+        post_only: Option<bool>,
+        reduce_only: Option<bool>,
+        self_trade_prevention_type: Option<SelfTradePreventionType>,
+        order_group_id: Option<String>,
+        cancel_order_on_pause: Option<bool>,
+    ) -> impl Future<Output = Result<Order, KalshiError>> + Send;
+
+    /// See [`Kalshi::cancel_order`].
+    fn cancel_order(
+        &self,
+        order_id: &str,
+    ) -> impl Future<Output = Result<(Order, i32), KalshiError>> + Send;
+
+    /// See [`Kalshi::decrease_order`].
+    fn decrease_order(
+        &self,
+        order_id: &str,
+        reduce_by: Option<i32>,
+        reduce_to: Option<i32>,
+    ) -> impl Future<Output = Result<Order, KalshiError>> + Send;
+
+    /// See [`Kalshi::amend_order`].
+    #[allow(clippy::too_many_arguments)]
+    fn amend_order(
+        &self,
+        order_id: &str,
+        ticker: &str,
+        side: Side,
+        action: Action,
+        client_order_id: &str,
+        updated_client_order_id: &str,
+        yes_price: Option<i32>,
+        no_price: Option<i32>,
+        yes_price_dollars: Option<String>,
+        no_price_dollars: Option<String>,
+        count: Option<i32>,
+    ) -> impl Future<Output = Result<AmendOrderResponse, KalshiError>> + Send;
+
+    /// See [`Kalshi::batch_create_order`].
+    fn batch_create_order(
+        &self,
+        batch: Vec<OrderCreationField>,
+    ) -> impl Future<Output = Result<Vec<Result<Order, KalshiError>>, KalshiError>> + Send;
+
+    /// See [`Kalshi::batch_cancel_order`].
+    #[allow(clippy::type_complexity)]
+    fn batch_cancel_order(
+        &self,
+        ids: Vec<String>,
+    ) -> impl Future<Output = Result<Vec<Result<(Order, i32), KalshiError>>, KalshiError>> + Send;
+
+    /// See [`Kalshi::get_orders`].
+    #[allow(clippy::too_many_arguments)]
+    fn get_orders(
+        &self,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+        status: Option<OrderStatus>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Order>), KalshiError>> + Send;
+
+    /// See [`Kalshi::get_single_order`].
+    #[allow(clippy::ptr_arg)]
+    fn get_single_order(
+        &self,
+        order_id: &String,
+    ) -> impl Future<Output = Result<Order, KalshiError>> + Send;
+}
+
+/// Balance, positions, fills, settlements, resting order queues and order groups.
+pub trait Portfolio {
+    /// See [`Kalshi::get_balance`].
+    fn get_balance(&self) -> impl Future<Output = Result<i64, KalshiError>> + Send;
+
+    /// See [`Kalshi::get_positions`].
+    #[allow(clippy::type_complexity)]
+    fn get_positions(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        settlement_status: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        count_filter: Option<String>,
+    ) -> impl Future<
+        Output = Result<(Option<String>, Vec<EventPosition>, Vec<MarketPosition>), KalshiError>,
+    > + Send;
+
+    /// See [`Kalshi::get_fills`].
+    fn get_fills(
+        &self,
+        ticker: Option<String>,
+        order_id: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Fill>), KalshiError>> + Send;
+
+    /// See [`Kalshi::get_settlements`].
+    fn get_settlements(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Settlement>), KalshiError>> + Send;
+
+    /// See [`Kalshi::get_total_resting_order_value`].
+    fn get_total_resting_order_value(
+        &self,
+    ) -> impl Future<Output = Result<i64, KalshiError>> + Send;
+
+    /// See [`Kalshi::get_queue_positions`].
+    fn get_queue_positions(
+        &self,
+        order_ids: Vec<String>,
+    ) -> impl Future<Output = Result<Vec<OrderQueuePosition>, KalshiError>> + Send;
+
+    /// See [`Kalshi::get_order_groups`].
+    fn get_order_groups(&self)
+        -> impl Future<Output = Result<Vec<OrderGroup>, KalshiError>> + Send;
+
+    /// See [`Kalshi::get_order_group`].
+    fn get_order_group(
+        &self,
+        order_group_id: &str,
+    ) -> impl Future<Output = Result<OrderGroup, KalshiError>> + Send;
+}
+
+impl MarketData for Kalshi {
+    fn get_markets(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        event_ticker: Option<String>,
+        series_ticker: Option<String>,
+        status: Option<String>,
+        tickers: Option<String>,
+        min_close_ts: Option<i64>,
+        max_close_ts: Option<i64>,
+        min_created_ts: Option<i64>,
+        max_created_ts: Option<i64>,
+        min_settled_ts: Option<i64>,
+        max_settled_ts: Option<i64>,
+        mve_filter: Option<MveFilter>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Market>), KalshiError>> + Send {
+        Kalshi::get_markets(
+            self,
+            limit,
+            cursor,
+            event_ticker,
+            series_ticker,
+            status,
+            tickers,
+            min_close_ts,
+            max_close_ts,
+            min_created_ts,
+            max_created_ts,
+            min_settled_ts,
+            max_settled_ts,
+            mve_filter,
+        )
+    }
+
+    fn get_market(&self, ticker: &str) -> impl Future<Output = Result<Market, KalshiError>> + Send {
+        Kalshi::get_market(self, ticker)
+    }
+
+    fn get_orderbook(
+        &self,
+        ticker: &str,
+        depth: Option<i32>,
+    ) -> impl Future<Output = Result<Orderbook, KalshiError>> + Send {
+        Kalshi::get_orderbook(self, ticker, depth)
+    }
+
+    fn get_market_candlesticks(
+        &self,
+        ticker: &str,
+        series_ticker: &str,
+        start_ts: Option<i64>,
+        end_ts: Option<i64>,
+        period_interval: Option<i32>,
+    ) -> impl Future<Output = Result<Vec<Candle>, KalshiError>> + Send {
+        Kalshi::get_market_candlesticks(
+            self,
+            ticker,
+            series_ticker,
+            start_ts,
+            end_ts,
+            period_interval,
+        )
+    }
+
+    fn batch_get_market_candlesticks(
+        &self,
+        market_tickers: Vec<String>,
+        start_ts: i64,
+        end_ts: i64,
+        period_interval: i32,
+        include_latest_before_start: Option<bool>,
+    ) -> impl Future<Output = Result<Vec<MarketCandlesticks>, KalshiError>> + Send {
+        Kalshi::batch_get_market_candlesticks(
+            self,
+            market_tickers,
+            start_ts,
+            end_ts,
+            period_interval,
+            include_latest_before_start,
+        )
+    }
+
+    fn get_trades(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Trade>), KalshiError>> + Send {
+        Kalshi::get_trades(self, limit, cursor, ticker, min_ts, max_ts)
+    }
+
+    fn get_series_list(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        category: Option<String>,
+        tags: Option<String>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Series>), KalshiError>> + Send {
+        Kalshi::get_series_list(self, limit, cursor, category, tags)
+    }
+
+    fn get_series(
+        &self,
+        series_ticker: &str,
+    ) -> impl Future<Output = Result<Series, KalshiError>> + Send {
+        Kalshi::get_series(self, series_ticker)
+    }
+
+    fn get_events(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        status: Option<String>,
+        series_ticker: Option<String>,
+        with_nested_markets: Option<bool>,
+        with_milestones: Option<bool>,
+        min_close_ts: Option<i64>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Event>), KalshiError>> + Send {
+        Kalshi::get_events(
+            self,
+            limit,
+            cursor,
+            status,
+            series_ticker,
+            with_nested_markets,
+            with_milestones,
+            min_close_ts,
+        )
+    }
+
+    fn get_event(
+        &self,
+        event_ticker: &str,
+    ) -> impl Future<Output = Result<Event, KalshiError>> + Send {
+        Kalshi::get_event(self, event_ticker)
+    }
+
+    fn get_orderbook_full(
+        &self,
+        ticker: &str,
+    ) -> impl Future<Output = Result<Orderbook, KalshiError>> + Send {
+        Kalshi::get_orderbook_full(self, ticker)
+    }
+
+    fn get_event_candlesticks(
+        &self,
+        series_ticker: &str,
+        event_ticker: &str,
+        start_ts: Option<i64>,
+        end_ts: Option<i64>,
+        period_interval: Option<String>,
+    ) -> impl Future<Output = Result<Vec<Candlestick>, KalshiError>> + Send {
+        Kalshi::get_event_candlesticks(
+            self,
+            series_ticker,
+            event_ticker,
+            start_ts,
+            end_ts,
+            period_interval,
+        )
+    }
+
+    fn get_event_metadata(
+        &self,
+        event_ticker: &str,
+    ) -> impl Future<Output = Result<EventMetadata, KalshiError>> + Send {
+        Kalshi::get_event_metadata(self, event_ticker)
+    }
+
+    fn get_event_forecast_percentile_history(
+        &self,
+        event_ticker: &str,
+    ) -> impl Future<Output = Result<ForecastPercentileHistory, KalshiError>> + Send {
+        Kalshi::get_event_forecast_percentile_history(self, event_ticker)
+    }
+
+    fn get_multivariate_events(
+        &self,
+        limit: Option<i32>,
+        cursor: Option<String>,
+        series_ticker: Option<String>,
+        collection_ticker: Option<String>,
+        with_nested_markets: Option<bool>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Event>), KalshiError>> + Send {
+        Kalshi::get_multivariate_events(
+            self,
+            limit,
+            cursor,
+            series_ticker,
+            collection_ticker,
+            with_nested_markets,
+        )
+    }
+}
+
+impl Trading for Kalshi {
+    fn create_order(
+        &self,
+        action: Action,
+        client_order_id: Option<String>,
+        count: i32,
+        side: Side,
+        ticker: String,
+        input_type: OrderType,
+        buy_max_cost: Option<i64>,
+        expiration_ts: Option<i64>,
+        yes_price: Option<i64>,
+        no_price: Option<i64>,
+        sell_position_floor: Option<i32>,
+        yes_price_dollars: Option<String>,
+        no_price_dollars: Option<String>,
+        time_in_force: Option<TimeInForce>,
+        post_only: Option<bool>,
+        reduce_only: Option<bool>,
+        self_trade_prevention_type: Option<SelfTradePreventionType>,
+        order_group_id: Option<String>,
+        cancel_order_on_pause: Option<bool>,
+    ) -> impl Future<Output = Result<Order, KalshiError>> + Send {
+        Kalshi::create_order(
+            self,
+            action,
+            client_order_id,
+            count,
+            side,
+            ticker,
+            input_type,
+            buy_max_cost,
+            expiration_ts,
+            yes_price,
+            no_price,
+            sell_position_floor,
+            yes_price_dollars,
+            no_price_dollars,
+            time_in_force,
+            post_only,
+            reduce_only,
+            self_trade_prevention_type,
+            order_group_id,
+            cancel_order_on_pause,
+        )
+    }
+
+    fn cancel_order(
+        &self,
+        order_id: &str,
+    ) -> impl Future<Output = Result<(Order, i32), KalshiError>> + Send {
+        Kalshi::cancel_order(self, order_id)
+    }
+
+    fn decrease_order(
+        &self,
+        order_id: &str,
+        reduce_by: Option<i32>,
+        reduce_to: Option<i32>,
+    ) -> impl Future<Output = Result<Order, KalshiError>> + Send {
+        Kalshi::decrease_order(self, order_id, reduce_by, reduce_to)
+    }
+
+    fn amend_order(
+        &self,
+        order_id: &str,
+        ticker: &str,
+        side: Side,
+        action: Action,
+        client_order_id: &str,
+        updated_client_order_id: &str,
+        yes_price: Option<i32>,
+        no_price: Option<i32>,
+        yes_price_dollars: Option<String>,
+        no_price_dollars: Option<String>,
+        count: Option<i32>,
+    ) -> impl Future<Output = Result<AmendOrderResponse, KalshiError>> + Send {
+        Kalshi::amend_order(
+            self,
+            order_id,
+            ticker,
+            side,
+            action,
+            client_order_id,
+            updated_client_order_id,
+            yes_price,
+            no_price,
+            yes_price_dollars,
+            no_price_dollars,
+            count,
+        )
+    }
+
+    fn batch_create_order(
+        &self,
+        batch: Vec<OrderCreationField>,
+    ) -> impl Future<Output = Result<Vec<Result<Order, KalshiError>>, KalshiError>> + Send {
+        Kalshi::batch_create_order(self, batch)
+    }
+
+    fn batch_cancel_order(
+        &self,
+        ids: Vec<String>,
+    ) -> impl Future<Output = Result<Vec<Result<(Order, i32), KalshiError>>, KalshiError>> + Send
+    {
+        Kalshi::batch_cancel_order(self, ids)
+    }
+
+    fn get_orders(
+        &self,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+        status: Option<OrderStatus>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Order>), KalshiError>> + Send {
+        Kalshi::get_orders(
+            self,
+            ticker,
+            event_ticker,
+            min_ts,
+            max_ts,
+            status,
+            limit,
+            cursor,
+        )
+    }
+
+    fn get_single_order(
+        &self,
+        order_id: &String,
+    ) -> impl Future<Output = Result<Order, KalshiError>> + Send {
+        Kalshi::get_single_order(self, order_id)
+    }
+}
+
+impl Portfolio for Kalshi {
+    fn get_balance(&self) -> impl Future<Output = Result<i64, KalshiError>> + Send {
+        Kalshi::get_balance(self)
+    }
+
+    fn get_positions(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        settlement_status: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        count_filter: Option<String>,
+    ) -> impl Future<
+        Output = Result<(Option<String>, Vec<EventPosition>, Vec<MarketPosition>), KalshiError>,
+    > + Send {
+        Kalshi::get_positions(
+            self,
+            limit,
+            cursor,
+            settlement_status,
+            ticker,
+            event_ticker,
+            count_filter,
+        )
+    }
+
+    fn get_fills(
+        &self,
+        ticker: Option<String>,
+        order_id: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Fill>), KalshiError>> + Send {
+        Kalshi::get_fills(self, ticker, order_id, min_ts, max_ts, limit, cursor)
+    }
+
+    fn get_settlements(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Settlement>), KalshiError>> + Send {
+        Kalshi::get_settlements(self, limit, cursor, ticker, event_ticker, min_ts, max_ts)
+    }
+
+    fn get_total_resting_order_value(
+        &self,
+    ) -> impl Future<Output = Result<i64, KalshiError>> + Send {
+        Kalshi::get_total_resting_order_value(self)
+    }
+
+    fn get_queue_positions(
+        &self,
+        order_ids: Vec<String>,
+    ) -> impl Future<Output = Result<Vec<OrderQueuePosition>, KalshiError>> + Send {
+        Kalshi::get_queue_positions(self, order_ids)
+    }
+
+    fn get_order_groups(
+        &self,
+    ) -> impl Future<Output = Result<Vec<OrderGroup>, KalshiError>> + Send {
+        Kalshi::get_order_groups(self)
+    }
+
+    fn get_order_group(
+        &self,
+        order_group_id: &str,
+    ) -> impl Future<Output = Result<OrderGroup, KalshiError>> + Send {
+        Kalshi::get_order_group(self, order_group_id)
+    }
+}
+
+impl Trading for PaperKalshi {
+    fn create_order(
+        &self,
+        action: Action,
+        client_order_id: Option<String>,
+        count: i32,
+        side: Side,
+        ticker: String,
+        input_type: OrderType,
+        buy_max_cost: Option<i64>,
+        expiration_ts: Option<i64>,
+        yes_price: Option<i64>,
+        no_price: Option<i64>,
+        sell_position_floor: Option<i32>,
+        yes_price_dollars: Option<String>,
+        no_price_dollars: Option<String>,
+        time_in_force: Option<TimeInForce>,
+        post_only: Option<bool>,
+        reduce_only: Option<bool>,
+        self_trade_prevention_type: Option<SelfTradePreventionType>,
+        order_group_id: Option<String>,
+        cancel_order_on_pause: Option<bool>,
+    ) -> impl Future<Output = Result<Order, KalshiError>> + Send {
+        PaperKalshi::create_order(
+            self,
+            action,
+            client_order_id,
+            count,
+            side,
+            ticker,
+            input_type,
+            buy_max_cost,
+            expiration_ts,
+            yes_price,
+            no_price,
+            sell_position_floor,
+            yes_price_dollars,
+            no_price_dollars,
+            time_in_force,
+            post_only,
+            reduce_only,
+            self_trade_prevention_type,
+            order_group_id,
+            cancel_order_on_pause,
+        )
+    }
+
+    fn cancel_order(
+        &self,
+        order_id: &str,
+    ) -> impl Future<Output = Result<(Order, i32), KalshiError>> + Send {
+        PaperKalshi::cancel_order(self, order_id)
+    }
+
+    fn decrease_order(
+        &self,
+        order_id: &str,
+        reduce_by: Option<i32>,
+        reduce_to: Option<i32>,
+    ) -> impl Future<Output = Result<Order, KalshiError>> + Send {
+        PaperKalshi::decrease_order(self, order_id, reduce_by, reduce_to)
+    }
+
+    fn amend_order(
+        &self,
+        order_id: &str,
+        ticker: &str,
+        side: Side,
+        action: Action,
+        client_order_id: &str,
+        updated_client_order_id: &str,
+        yes_price: Option<i32>,
+        no_price: Option<i32>,
+        yes_price_dollars: Option<String>,
+        no_price_dollars: Option<String>,
+        count: Option<i32>,
+    ) -> impl Future<Output = Result<AmendOrderResponse, KalshiError>> + Send {
+        PaperKalshi::amend_order(
+            self,
+            order_id,
+            ticker,
+            side,
+            action,
+            client_order_id,
+            updated_client_order_id,
+            yes_price,
+            no_price,
+            yes_price_dollars,
+            no_price_dollars,
+            count,
+        )
+    }
+
+    fn batch_create_order(
+        &self,
+        batch: Vec<OrderCreationField>,
+    ) -> impl Future<Output = Result<Vec<Result<Order, KalshiError>>, KalshiError>> + Send {
+        PaperKalshi::batch_create_order(self, batch)
+    }
+
+    fn batch_cancel_order(
+        &self,
+        ids: Vec<String>,
+    ) -> impl Future<Output = Result<Vec<Result<(Order, i32), KalshiError>>, KalshiError>> + Send
+    {
+        PaperKalshi::batch_cancel_order(self, ids)
+    }
+
+    fn get_orders(
+        &self,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+        status: Option<OrderStatus>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Order>), KalshiError>> + Send {
+        PaperKalshi::get_orders(
+            self,
+            ticker,
+            event_ticker,
+            min_ts,
+            max_ts,
+            status,
+            limit,
+            cursor,
+        )
+    }
+
+    fn get_single_order(
+        &self,
+        order_id: &String,
+    ) -> impl Future<Output = Result<Order, KalshiError>> + Send {
+        PaperKalshi::get_single_order(self, order_id)
+    }
+}
+
+impl Portfolio for PaperKalshi {
+    fn get_balance(&self) -> impl Future<Output = Result<i64, KalshiError>> + Send {
+        PaperKalshi::get_balance(self)
+    }
+
+    fn get_positions(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        settlement_status: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        count_filter: Option<String>,
+    ) -> impl Future<
+        Output = Result<(Option<String>, Vec<EventPosition>, Vec<MarketPosition>), KalshiError>,
+    > + Send {
+        PaperKalshi::get_positions(
+            self,
+            limit,
+            cursor,
+            settlement_status,
+            ticker,
+            event_ticker,
+            count_filter,
+        )
+    }
+
+    fn get_fills(
+        &self,
+        ticker: Option<String>,
+        order_id: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Fill>), KalshiError>> + Send {
+        PaperKalshi::get_fills(self, ticker, order_id, min_ts, max_ts, limit, cursor)
+    }
+
+    fn get_settlements(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Settlement>), KalshiError>> + Send {
+        PaperKalshi::get_settlements(self, limit, cursor, ticker, event_ticker, min_ts, max_ts)
+    }
+
+    fn get_total_resting_order_value(
+        &self,
+    ) -> impl Future<Output = Result<i64, KalshiError>> + Send {
+        PaperKalshi::get_total_resting_order_value(self)
+    }
+
+    fn get_queue_positions(
+        &self,
+        order_ids: Vec<String>,
+    ) -> impl Future<Output = Result<Vec<OrderQueuePosition>, KalshiError>> + Send {
+        PaperKalshi::get_queue_positions(self, order_ids)
+    }
+
+    fn get_order_groups(
+        &self,
+    ) -> impl Future<Output = Result<Vec<OrderGroup>, KalshiError>> + Send {
+        PaperKalshi::get_order_groups(self)
+    }
+
+    fn get_order_group(
+        &self,
+        order_group_id: &str,
+    ) -> impl Future<Output = Result<OrderGroup, KalshiError>> + Send {
+        PaperKalshi::get_order_group(self, order_group_id)
+    }
+}
+
+/// An in-memory backend for tests.
+///
+/// Market data comes from whatever was inserted with the `insert_*`/`set_*` methods.
+/// Orders go to an embedded [`PaperKalshi`], which sees every book and trade added here,
+/// so resting orders fill as the test feeds data.
+///
+/// Filters the exchange applies server-side are honoured where the stored data carries
+/// the field (tickers, statuses, categories, close and trade times); the rest
+/// (`mve_filter`, created/settled timestamps, `with_milestones`, candlestick periods) are
+/// ignored. Trading and portfolio queries go to the paper engine, which does not simulate
+/// order groups.
+pub struct InMemoryKalshi {
+    data: Mutex<MemoryData>,
+    paper: PaperKalshi,
+}
+
+#[derive(Default)]
+struct MemoryData {
+    markets: BTreeMap<String, Market>,
+    events: BTreeMap<String, Event>,
+    series: BTreeMap<String, Series>,
+    orderbooks: HashMap<String, Orderbook>,
+    /// Trades in the order they were added.
+    trades: Vec<Trade>,
+    candles: HashMap<String, Vec<Candle>>,
+    event_candles: HashMap<String, Vec<Candlestick>>,
+    event_metadata: HashMap<String, EventMetadata>,
+    forecasts: HashMap<String, ForecastPercentileHistory>,
+    /// Multivariate events by event ticker, with their collection ticker.
+    multivariate_events: BTreeMap<String, (String, Event)>,
+}
+
+impl InMemoryKalshi {
+    /// Creates an empty backend with `balance` cents of paper cash.
+    pub fn new(balance: i64) -> Self {
+        Self::with_paper(PaperKalshi::new(balance))
+    }
+
+    /// Creates an empty backend that trades through `paper`.
+    pub fn with_paper(paper: PaperKalshi) -> Self {
+        InMemoryKalshi {
+            data: Mutex::new(MemoryData::default()),
+            paper,
+        }
+    }
+
+    /// The paper engine used for trading and portfolio queries.
+    pub fn paper(&self) -> &PaperKalshi {
+        &self.paper
+    }
+
+    /// Adds or replaces a market.
+    pub fn insert_market(&self, market: Market) {
+        self.lock().markets.insert(market.ticker.clone(), market);
+    }
+
+    /// Adds or replaces an event. Nested markets are stored as markets.
+    pub fn insert_event(&self, mut event: Event) {
+        let markets = event.markets.take().unwrap_or_default();
+        let mut data = self.lock();
+        for market in markets {
+            data.markets.insert(market.ticker.clone(), market);
+        }
+        data.events.insert(event.event_ticker.clone(), event);
+    }
+
+    /// Adds or replaces a series. Series without a ticker are ignored.
+    pub fn insert_series(&self, series: Series) {
+        if let Some(ticker) = series.ticker.clone() {
+            self.lock().series.insert(ticker, series);
+        }
+    }
+
+    /// Replaces a market's book and passes it to the paper engine.
+    ///
+    /// Returns fills on paper orders the new book crosses.
+    pub fn set_orderbook(&self, ticker: &str, book: Orderbook) -> Vec<FillMsg> {
+        let fills = self.paper.apply_orderbook(ticker, &book);
+        self.lock().orderbooks.insert(ticker.to_string(), book);
+        fills
+    }
+
+    /// Records a public trade and passes it to the paper engine.
+    ///
+    /// Returns fills on paper orders the trade reaches.
+    pub fn push_trade(&self, trade: Trade) -> Vec<FillMsg> {
+        let ts = parse_ts(&trade.created_time).unwrap_or_default();
+        let fills = self
+            .paper
+            .handle_message(&WebSocketMessage::Trade(TradeMsg {
+                market_ticker: trade.ticker.clone(),
+                yes_price: trade.yes_price,
+                no_price: trade.no_price,
+                count: trade.count,
+                taker_side: trade.taker_side.clone(),
+                ts,
+            }));
+        self.lock().trades.push(trade);
+        fills
+    }
+
+    /// Replaces a market's candlesticks.
+    pub fn set_candles(&self, ticker: &str, candles: Vec<Candle>) {
+        self.lock().candles.insert(ticker.to_string(), candles);
+    }
+
+    /// Replaces an event's candlesticks.
+    pub fn set_event_candles(&self, event_ticker: &str, candles: Vec<Candlestick>) {
+        self.lock()
+            .event_candles
+            .insert(event_ticker.to_string(), candles);
+    }
+
+    /// Replaces an event's metadata.
+    pub fn set_event_metadata(&self, event_ticker: &str, metadata: EventMetadata) {
+        self.lock()
+            .event_metadata
+            .insert(event_ticker.to_string(), metadata);
+    }
+
+    /// Replaces an event's forecast percentile history.
+    pub fn set_forecast_history(&self, event_ticker: &str, history: ForecastPercentileHistory) {
+        self.lock()
+            .forecasts
+            .insert(event_ticker.to_string(), history);
+    }
+
+    /// Adds or replaces a multivariate event in `collection_ticker`. Nested markets are
+    /// stored as markets.
+    pub fn insert_multivariate_event(&self, collection_ticker: &str, mut event: Event) {
+        let markets = event.markets.take().unwrap_or_default();
+        let mut data = self.lock();
+        for market in markets {
+            data.markets.insert(market.ticker.clone(), market);
+        }
+        data.multivariate_events.insert(
+            event.event_ticker.clone(),
+            (collection_ticker.to_string(), event),
+        );
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MemoryData> {
+        self.data.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn candles_between(
+        &self,
+        ticker: &str,
+        start_ts: Option<i64>,
+        end_ts: Option<i64>,
+    ) -> Vec<Candle> {
+        self.lock()
+            .candles
+            .get(ticker)
+            .map(|candles| {
+                candles
+                    .iter()
+                    .filter(|c| start_ts.is_none_or(|s| c.end_ts >= s))
+                    .filter(|c| end_ts.is_none_or(|e| c.end_ts <= e))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl MarketData for InMemoryKalshi {
+    async fn get_markets(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        event_ticker: Option<String>,
+        series_ticker: Option<String>,
+        status: Option<String>,
+        tickers: Option<String>,
+        min_close_ts: Option<i64>,
+        max_close_ts: Option<i64>,
+        _min_created_ts: Option<i64>,
+        _max_created_ts: Option<i64>,
+        _min_settled_ts: Option<i64>,
+        _max_settled_ts: Option<i64>,
+        _mve_filter: Option<MveFilter>,
+    ) -> Result<(Option<String>, Vec<Market>), KalshiError> {
+        let tickers: Option<Vec<&str>> = tickers.as_deref().map(|t| t.split(',').collect());
+        let markets: Vec<Market> = self
+            .lock()
+            .markets
+            .values()
+            .filter(|m| event_ticker.as_ref().is_none_or(|e| &m.event_ticker == e))
+            .filter(|m| {
+                series_ticker
+                    .as_ref()
+                    .is_none_or(|s| &series_ticker_of(&m.ticker) == s)
+            })
+            .filter(|m| status.as_ref().is_none_or(|s| &m.status == s))
+            .filter(|m| {
+                tickers
+                    .as_ref()
+                    .is_none_or(|t| t.contains(&m.ticker.as_str()))
+            })
+            .filter(|m| {
+                let close = parse_ts(&m.close_time);
+                min_close_ts.is_none_or(|min| close.is_some_and(|c| c >= min))
+                    && max_close_ts.is_none_or(|max| close.is_some_and(|c| c <= max))
+            })
+            .cloned()
+            .collect();
+        Ok(paginate(markets, limit.map(|l| l as i32), cursor))
+    }
+
+    async fn get_market(&self, ticker: &str) -> Result<Market, KalshiError> {
+        self.lock()
+            .markets
+            .get(ticker)
+            .cloned()
+            .ok_or_else(|| api_error("not_found", "market not found"))
+    }
+
+    async fn get_orderbook(
+        &self,
+        ticker: &str,
+        depth: Option<i32>,
+    ) -> Result<Orderbook, KalshiError> {
+        let mut book = self
+            .lock()
+            .orderbooks
+            .get(ticker)
+            .cloned()
+            .ok_or_else(|| api_error("not_found", "orderbook not found"))?;
+        if let Some(depth) = depth.filter(|d| *d > 0) {
+            let depth = depth as usize;
+            for levels in [&mut book.yes, &mut book.no].into_iter().flatten() {
+                keep_best(levels, depth, |l| l.first().copied().unwrap_or_default());
+            }
+            keep_best(&mut book.yes_dollars, depth, |l| (l.0 * 10_000.0) as i32);
+            keep_best(&mut book.no_dollars, depth, |l| (l.0 * 10_000.0) as i32);
+        }
+        Ok(book)
+    }
+
+    async fn get_market_candlesticks(
+        &self,
+        ticker: &str,
+        _series_ticker: &str,
+        start_ts: Option<i64>,
+        end_ts: Option<i64>,
+        _period_interval: Option<i32>,
+    ) -> Result<Vec<Candle>, KalshiError> {
+        Ok(self.candles_between(ticker, start_ts, end_ts))
+    }
+
+    async fn batch_get_market_candlesticks(
+        &self,
+        market_tickers: Vec<String>,
+        start_ts: i64,
+        end_ts: i64,
+        _period_interval: i32,
+        _include_latest_before_start: Option<bool>,
+    ) -> Result<Vec<MarketCandlesticks>, KalshiError> {
+        Ok(market_tickers
+            .into_iter()
+            .map(|ticker| MarketCandlesticks {
+                candlesticks: self.candles_between(&ticker, Some(start_ts), Some(end_ts)),
+                ticker,
+            })
+            .collect())
+    }
+
+    async fn get_trades(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> Result<(Option<String>, Vec<Trade>), KalshiError> {
+        let trades: Vec<Trade> = self
+            .lock()
+            .trades
+            .iter()
+            .rev()
+            .filter(|t| ticker.as_ref().is_none_or(|tk| &t.ticker == tk))
+            .filter(|t| {
+                let ts = parse_ts(&t.created_time).unwrap_or_default();
+                min_ts.is_none_or(|m| ts >= m) && max_ts.is_none_or(|m| ts <= m)
+            })
+            .cloned()
+            .collect();
+        Ok(paginate(trades, limit.map(|l| l as i32), cursor))
+    }
+
+    async fn get_series_list(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        category: Option<String>,
+        tags: Option<String>,
+    ) -> Result<(Option<String>, Vec<Series>), KalshiError> {
+        let tags: Option<Vec<&str>> = tags.as_deref().map(|t| t.split(',').collect());
+        let series: Vec<Series> = self
+            .lock()
+            .series
+            .values()
+            .filter(|s| category.is_none() || s.category == category)
+            .filter(|s| {
+                tags.as_ref()
+                    .is_none_or(|t| s.tags.iter().any(|tag| t.contains(&tag.as_str())))
+            })
+            .cloned()
+            .collect();
+        Ok(paginate(series, limit.map(|l| l as i32), cursor))
+    }
+
+    async fn get_series(&self, series_ticker: &str) -> Result<Series, KalshiError> {
+        self.lock()
+            .series
+            .get(series_ticker)
+            .cloned()
+            .ok_or_else(|| api_error("not_found", "series not found"))
+    }
+
+    async fn get_events(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        status: Option<String>,
+        series_ticker: Option<String>,
+        with_nested_markets: Option<bool>,
+        _with_milestones: Option<bool>,
+        min_close_ts: Option<i64>,
+    ) -> Result<(Option<String>, Vec<Event>), KalshiError> {
+        let data = self.lock();
+        let events: Vec<Event> = data
+            .events
+            .values()
+            .filter(|e| series_ticker.as_ref().is_none_or(|s| &e.series_ticker == s))
+            .filter_map(|e| {
+                let markets: Vec<&Market> = data
+                    .markets
+                    .values()
+                    .filter(|m| m.event_ticker == e.event_ticker)
+                    .collect();
+                // An event matches a status or close-time filter through its markets.
+                let status_ok = status
+                    .as_ref()
+                    .is_none_or(|s| markets.iter().any(|m| &m.status == s));
+                let close_ok = min_close_ts.is_none_or(|min| {
+                    markets
+                        .iter()
+                        .any(|m| parse_ts(&m.close_time).is_some_and(|c| c >= min))
+                });
+                (status_ok && close_ok).then(|| {
+                    let mut event = e.clone();
+                    event.markets = with_nested_markets
+                        .unwrap_or(false)
+                        .then(|| markets.into_iter().cloned().collect());
+                    event
+                })
+            })
+            .collect();
+        drop(data);
+        Ok(paginate(events, limit.map(|l| l as i32), cursor))
+    }
+
+    async fn get_event(&self, event_ticker: &str) -> Result<Event, KalshiError> {
+        let data = self.lock();
+        let mut event = data
+            .events
+            .get(event_ticker)
+            .cloned()
+            .ok_or_else(|| api_error("not_found", "event not found"))?;
+        event.markets = Some(
+            data.markets
+                .values()
+                .filter(|m| m.event_ticker == event_ticker)
+                .cloned()
+                .collect(),
+        );
+        Ok(event)
+    }
+
+    async fn get_orderbook_full(&self, ticker: &str) -> Result<Orderbook, KalshiError> {
+        MarketData::get_orderbook(self, ticker, None).await
+    }
+
+    async fn get_event_candlesticks(
+        &self,
+        _series_ticker: &str,
+        event_ticker: &str,
+        start_ts: Option<i64>,
+        end_ts: Option<i64>,
+        _period_interval: Option<String>,
+    ) -> Result<Vec<Candlestick>, KalshiError> {
+        Ok(self
+            .lock()
+            .event_candles
+            .get(event_ticker)
+            .map(|candles| {
+                candles
+                    .iter()
+                    .filter(|c| {
+                        let ts = parse_ts(&c.ts);
+                        start_ts.is_none_or(|s| ts.is_some_and(|t| t >= s))
+                            && end_ts.is_none_or(|e| ts.is_some_and(|t| t <= e))
+                    })
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    async fn get_event_metadata(&self, event_ticker: &str) -> Result<EventMetadata, KalshiError> {
+        self.lock()
+            .event_metadata
+            .get(event_ticker)
+            .cloned()
+            .ok_or_else(|| api_error("not_found", "event metadata not found"))
+    }
+
+    async fn get_event_forecast_percentile_history(
+        &self,
+        event_ticker: &str,
+    ) -> Result<ForecastPercentileHistory, KalshiError> {
+        self.lock()
+            .forecasts
+            .get(event_ticker)
+            .cloned()
+            .ok_or_else(|| api_error("not_found", "forecast history not found"))
+    }
+
+    async fn get_multivariate_events(
+        &self,
+        limit: Option<i32>,
+        cursor: Option<String>,
+        series_ticker: Option<String>,
+        collection_ticker: Option<String>,
+        with_nested_markets: Option<bool>,
+    ) -> Result<(Option<String>, Vec<Event>), KalshiError> {
+        if series_ticker.is_some() && collection_ticker.is_some() {
+            return Err(KalshiError::UserInputError(
+                "Cannot use both series_ticker and collection_ticker - these filters are mutually exclusive".to_string()
+            ));
+        }
+        let data = self.lock();
+        let events: Vec<Event> = data
+            .multivariate_events
+            .values()
+            .filter(|(_, e)| series_ticker.as_ref().is_none_or(|s| &e.series_ticker == s))
+            .filter(|(c, _)| collection_ticker.as_ref().is_none_or(|t| c == t))
+            .map(|(_, e)| {
+                let mut event = e.clone();
+                event.markets = with_nested_markets.unwrap_or(false).then(|| {
+                    data.markets
+                        .values()
+                        .filter(|m| m.event_ticker == e.event_ticker)
+                        .cloned()
+                        .collect()
+                });
+                event
+            })
+            .collect();
+        drop(data);
+        Ok(paginate(events, limit, cursor))
+    }
+}
+
+impl Trading for InMemoryKalshi {
+    fn create_order(
+        &self,
+        action: Action,
+        client_order_id: Option<String>,
+        count: i32,
+        side: Side,
+        ticker: String,
+        input_type: OrderType,
+        buy_max_cost: Option<i64>,
+        expiration_ts: Option<i64>,
+        yes_price: Option<i64>,
+        no_price: Option<i64>,
+        sell_position_floor: Option<i32>,
+        yes_price_dollars: Option<String>,
+        no_price_dollars: Option<String>,
+        time_in_force: Option<TimeInForce>,
+        post_only: Option<bool>,
+        reduce_only: Option<bool>,
+        self_trade_prevention_type: Option<SelfTradePreventionType>,
+        order_group_id: Option<String>,
+        cancel_order_on_pause: Option<bool>,
+    ) -> impl Future<Output = Result<Order, KalshiError>> + Send {
+        self.paper.create_order(
+            action,
+            client_order_id,
+            count,
+            side,
+            ticker,
+            input_type,
+            buy_max_cost,
+            expiration_ts,
+            yes_price,
+            no_price,
+            sell_position_floor,
+            yes_price_dollars,
+            no_price_dollars,
+            time_in_force,
+            post_only,
+            reduce_only,
+            self_trade_prevention_type,
+            order_group_id,
+            cancel_order_on_pause,
+        )
+    }
+
+    fn cancel_order(
+        &self,
+        order_id: &str,
+    ) -> impl Future<Output = Result<(Order, i32), KalshiError>> + Send {
+        self.paper.cancel_order(order_id)
+    }
+
+    fn decrease_order(
+        &self,
+        order_id: &str,
+        reduce_by: Option<i32>,
+        reduce_to: Option<i32>,
+    ) -> impl Future<Output = Result<Order, KalshiError>> + Send {
+        self.paper.decrease_order(order_id, reduce_by, reduce_to)
+    }
+
+    fn amend_order(
+        &self,
+        order_id: &str,
+        ticker: &str,
+        side: Side,
+        action: Action,
+        client_order_id: &str,
+        updated_client_order_id: &str,
+        yes_price: Option<i32>,
+        no_price: Option<i32>,
+        yes_price_dollars: Option<String>,
+        no_price_dollars: Option<String>,
+        count: Option<i32>,
+    ) -> impl Future<Output = Result<AmendOrderResponse, KalshiError>> + Send {
+        self.paper.amend_order(
+            order_id,
+            ticker,
+            side,
+            action,
+            client_order_id,
+            updated_client_order_id,
+            yes_price,
+            no_price,
+            yes_price_dollars,
+            no_price_dollars,
+            count,
+        )
+    }
+
+    fn batch_create_order(
+        &self,
+        batch: Vec<OrderCreationField>,
+    ) -> impl Future<Output = Result<Vec<Result<Order, KalshiError>>, KalshiError>> + Send {
+        self.paper.batch_create_order(batch)
+    }
+
+    fn batch_cancel_order(
+        &self,
+        ids: Vec<String>,
+    ) -> impl Future<Output = Result<Vec<Result<(Order, i32), KalshiError>>, KalshiError>> + Send
+    {
+        self.paper.batch_cancel_order(ids)
+    }
+
+    fn get_orders(
+        &self,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+        status: Option<OrderStatus>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Order>), KalshiError>> + Send {
+        self.paper
+            .get_orders(ticker, event_ticker, min_ts, max_ts, status, limit, cursor)
+    }
+
+    fn get_single_order(
+        &self,
+        order_id: &String,
+    ) -> impl Future<Output = Result<Order, KalshiError>> + Send {
+        self.paper.get_single_order(order_id)
+    }
+}
+
+impl Portfolio for InMemoryKalshi {
+    fn get_balance(&self) -> impl Future<Output = Result<i64, KalshiError>> + Send {
+        self.paper.get_balance()
+    }
+
+    fn get_positions(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        settlement_status: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        count_filter: Option<String>,
+    ) -> impl Future<
+        Output = Result<(Option<String>, Vec<EventPosition>, Vec<MarketPosition>), KalshiError>,
+    > + Send {
+        self.paper.get_positions(
+            limit,
+            cursor,
+            settlement_status,
+            ticker,
+            event_ticker,
+            count_filter,
+        )
+    }
+
+    fn get_fills(
+        &self,
+        ticker: Option<String>,
+        order_id: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Fill>), KalshiError>> + Send {
+        self.paper
+            .get_fills(ticker, order_id, min_ts, max_ts, limit, cursor)
+    }
+
+    fn get_settlements(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> impl Future<Output = Result<(Option<String>, Vec<Settlement>), KalshiError>> + Send {
+        self.paper
+            .get_settlements(limit, cursor, ticker, event_ticker, min_ts, max_ts)
+    }
+
+    fn get_total_resting_order_value(
+        &self,
+    ) -> impl Future<Output = Result<i64, KalshiError>> + Send {
+        self.paper.get_total_resting_order_value()
+    }
+
+    fn get_queue_positions(
+        &self,
+        order_ids: Vec<String>,
+    ) -> impl Future<Output = Result<Vec<OrderQueuePosition>, KalshiError>> + Send {
+        self.paper.get_queue_positions(order_ids)
+    }
+
+    fn get_order_groups(
+        &self,
+    ) -> impl Future<Output = Result<Vec<OrderGroup>, KalshiError>> + Send {
+        self.paper.get_order_groups()
+    }
+
+    fn get_order_group(
+        &self,
+        order_group_id: &str,
+    ) -> impl Future<Output = Result<OrderGroup, KalshiError>> + Send {
+        self.paper.get_order_group(order_group_id)
+    }
+}
+
+/// Fetches an event with its markets, listing them separately when the event response
+/// does not nest them.
+pub(crate) async fn event_with_markets<B: MarketData>(
+    backend: &B,
+    event_ticker: &str,
+) -> Result<Event, KalshiError> {
+    let mut event = backend.get_event(event_ticker).await?;
+    if event.markets.as_ref().is_none_or(|m| m.is_empty()) {
+        let mut markets = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (next, page) = backend
+                .get_markets(
+                    Some(1000),
+                    cursor.take(),
+                    Some(event_ticker.to_string()),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+            let empty = page.is_empty();
+            markets.extend(page);
+            match next {
+                Some(c) if !c.is_empty() && !empty => cursor = Some(c),
+                _ => break,
+            }
+        }
+        event.markets = Some(markets);
+    }
+    Ok(event)
+}
+
+/// Keeps the `depth` highest-priced levels, in their original order.
+fn keep_best<T>(levels: &mut Vec<T>, depth: usize, price: impl Fn(&T) -> i32) {
+    if levels.len() <= depth {
+        return;
+    }
+    let mut prices: Vec<i32> = levels.iter().map(&price).collect();
+    prices.sort_unstable_by(|a, b| b.cmp(a));
+    let cutoff = prices[depth - 1];
+    let mut kept = 0;
+    levels.retain(|l| {
+        let keep = price(l) >= cutoff && kept < depth;
+        kept += keep as usize;
+        keep
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn best_yes_bid<B: MarketData>(backend: &B, ticker: &str) -> Option<i32> {
+        let book = backend.get_orderbook(ticker, Some(1)).await.ok()?;
+        book.yes?.first().and_then(|l| l.first().copied())
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_backend_serves_data_and_trades() {
+        let backend = InMemoryKalshi::new(10_000);
+        let ticker = "KXTEST-24JAN01-T1";
+        backend.set_orderbook(
+            ticker,
+            Orderbook {
+                yes: Some(vec![vec![40, 10], vec![45, 5]]),
+                no: Some(vec![vec![50, 10]]),
+                yes_dollars: Vec::new(),
+                no_dollars: Vec::new(),
+            },
+        );
+        assert_eq!(best_yes_bid(&backend, ticker).await, Some(45));
+        assert!(MarketData::get_market(&backend, ticker).await.is_err());
+
+        // YES asks come from NO bids: 100 - 50 = 50¢.
+        let order = Trading::create_order(
+            &backend,
+            Action::Buy,
+            None,
+            3,
+            Side::Yes,
+            ticker.to_string(),
+            OrderType::Limit,
+            None,
+            None,
+            Some(50),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(order.status, OrderStatus::Executed);
+
+        let (_, _, positions) =
+            Portfolio::get_positions(&backend, None, None, None, None, None, None)
+                .await
+                .unwrap();
+        assert_eq!(positions[0].position, 3);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_backend_serves_events_and_queues() {
+        let backend = InMemoryKalshi::new(10_000);
+        let ticker = "KXTEST-24JAN01-T1";
+        backend.set_orderbook(
+            ticker,
+            Orderbook {
+                yes: Some(vec![vec![40, 10]]),
+                no: Some(vec![vec![50, 10]]),
+                yes_dollars: Vec::new(),
+                no_dollars: Vec::new(),
+            },
+        );
+        let book = MarketData::get_orderbook_full(&backend, ticker)
+            .await
+            .unwrap();
+        assert_eq!(book.yes, Some(vec![vec![40, 10]]));
+
+        let event: Event = serde_json::from_value(serde_json::json!({
+            "event_ticker": "KXMVE-24JAN01", "series_ticker": "KXMVE", "title": "",
+            "sub_title": "", "mutually_exclusive": false, "category": "",
+            "markets": [crate::test_market(serde_json::json!({
+                "ticker": "KXMVE-24JAN01-A", "event_ticker": "KXMVE-24JAN01"
+            }))]
+        }))
+        .unwrap();
+        backend.insert_multivariate_event("KXMVECOLL", event);
+        let (_, events) =
+            MarketData::get_multivariate_events(&backend, None, None, None, None, Some(true))
+                .await
+                .unwrap();
+        assert_eq!(events[0].markets.as_ref().map(Vec::len), Some(1));
+        let (_, other) = MarketData::get_multivariate_events(
+            &backend,
+            None,
+            None,
+            None,
+            Some("OTHER".to_string()),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(other.is_empty());
+        assert!(MarketData::get_event_metadata(&backend, "KXMVE-24JAN01")
+            .await
+            .unwrap_err()
+            .is_not_found());
+
+        // Rests behind the 10 displayed at 40¢.
+        let order = Trading::create_order(
+            &backend,
+            Action::Buy,
+            None,
+            5,
+            Side::Yes,
+            ticker.to_string(),
+            OrderType::Limit,
+            None,
+            None,
+            Some(40),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let queue = Portfolio::get_queue_positions(
+            &backend,
+            vec![order.order_id.clone(), "missing".to_string()],
+        )
+        .await
+        .unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].queue_position, Some(10));
+        assert_eq!(queue[0].total_queue_depth, Some(15));
+        assert_eq!(
+            Portfolio::get_total_resting_order_value(&backend)
+                .await
+                .unwrap(),
+            200
+        );
+        assert!(Portfolio::get_order_groups(&backend)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_event_with_markets_follows_cursor() {
+        let (url, seen) = crate::test_server(|line| {
+            let body = if line.contains("/events/") {
+                serde_json::json!({
+                    "event_ticker": "KXTEST-24JAN01", "series_ticker": "KXTEST", "title": "",
+                    "sub_title": "", "mutually_exclusive": false, "category": ""
+                })
+            } else if line.contains("cursor=p2") {
+                serde_json::json!({"cursor": "", "markets": [crate::test_market(
+                    serde_json::json!({"ticker": "KXTEST-24JAN01-T2"})
+                )]})
+            } else {
+                serde_json::json!({"cursor": "p2", "markets": [crate::test_market(
+                    serde_json::json!({})
+                )]})
+            };
+            ("200 OK", body.to_string())
+        })
+        .await;
+        let mut kalshi = Kalshi::test_client();
+        kalshi.base_url = url;
+
+        let event = event_with_markets(&kalshi, "KXTEST-24JAN01").await.unwrap();
+        let tickers: Vec<String> = event
+            .markets
+            .unwrap()
+            .into_iter()
+            .map(|m| m.ticker)
+            .collect();
+        assert_eq!(tickers, ["KXTEST-24JAN01-T1", "KXTEST-24JAN01-T2"]);
+        assert_eq!(seen.lock().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,882 @@
+//! Event-driven backtesting over recorded market data.
+//!
+//! A [`Backtest`] merges historical data into one timeline:
+//!
+//! - public trades from [`get_trades`](crate::MarketData::get_trades),
+//! - candlesticks from [`get_market_candlesticks`](crate::MarketData::get_market_candlesticks)
+//!   or [`batch_get_market_candlesticks`](crate::MarketData::batch_get_market_candlesticks),
+//! - recorded WebSocket frames (orderbook snapshots, deltas and trades),
+//! - settlements.
+//!
+//! It replays the timeline through a [`Strategy`] and simulates its orders on a
+//! [`PaperKalshi`], so fills follow the paper matching model: taker orders sweep the
+//! replayed book, resting orders fill as trades reach them.
+//!
+//! ## Simulation model
+//!
+//! - Orders and cancels reach the simulator `latency_ms` after the strategy sends them.
+//!   Market data keeps flowing in the meantime.
+//! - Candles carry no book. Each candle with volume is replayed as two trades, one at its
+//!   YES low and one at its YES high, which fill resting orders the candle traded through.
+//! - Fees come from the configured [`FeeCalculator`]. `slippage_cents` is charged per
+//!   contract on every taker fill, on top of the simulated fill price.
+//! - Settlements pay out positions and cancel resting orders in that market.
+//! - Open positions are marked at the last trade or candle close.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::{Backtest, BacktestConfig, MarketEvent, Strategy, StrategyContext};
+//!
+//! struct BuyDips;
+//!
+//! impl Strategy for BuyDips {
+//!     fn on_event(&mut self, ctx: &mut StrategyContext<'_>, event: &MarketEvent) {
+//!         if let MarketEvent::Trade(t) = event {
+//!             if t.yes_price < 20 && ctx.position(&t.ticker) == 0 {
+//!                 ctx.submit(OrderCreationField { /* buy 10 YES at 20¢ */ });
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! let mut backtest = Backtest::new(BacktestConfig { latency_ms: 250, ..Default::default() });
+//! backtest.fetch_trades(&kalshi, "KXHIGHNY-24JAN01-T60", None, None).await?;
+//! backtest.add_settlement("KXHIGHNY-24JAN01-T60", settled_ms, SettlementResult::Yes);
+//! let report = backtest.run(&mut BuyDips).await?;
+//! println!("P&L {}¢, max drawdown {}¢, Sharpe {:?}", report.pnl, report.max_drawdown, report.sharpe);
+//! ```
+
+use crate::fees::FeeCalculator;
+use crate::kalshi_error::*;
+use crate::utils::parse_time;
+use crate::{
+    Candle, FillMsg, MarketCandlesticks, MarketData, Order, OrderCreationField, PaperKalshi,
+    SettlementResult, Trade, TradeMsg, WebSocketMessage,
+};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::broadcast;
+
+/// Milliseconds in a day, the default Sharpe sampling period.
+const DAY_MS: i64 = 86_400_000;
+
+/// Page size used when fetching trades.
+const PAGE_SIZE: i64 = 1000;
+
+/// Backtest settings.
+#[derive(Debug, Clone)]
+pub struct BacktestConfig {
+    /// Starting cash in cents.
+    pub initial_balance: i64,
+    /// Delay between the strategy sending an order or cancel and the simulator seeing it.
+    pub latency_ms: i64,
+    /// Extra cost in cents per contract on every taker fill.
+    pub slippage_cents: i64,
+    /// Fee schedule applied to fills.
+    pub fees: FeeCalculator,
+    /// Length of the periods whose returns feed the Sharpe ratio.
+    pub sharpe_period_ms: i64,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        BacktestConfig {
+            initial_balance: 100_000,
+            latency_ms: 0,
+            slippage_cents: 0,
+            fees: FeeCalculator::default(),
+            sharpe_period_ms: DAY_MS,
+        }
+    }
+}
+
+/// One item of the replayed timeline.
+#[derive(Debug)]
+pub enum MarketEvent {
+    /// A public trade.
+    Trade(Trade),
+    /// A candlestick, delivered at its end time.
+    Candle { ticker: String, candle: Candle },
+    /// A recorded WebSocket message.
+    Message(WebSocketMessage),
+    /// A market settled.
+    Settlement {
+        ticker: String,
+        result: SettlementResult,
+    },
+}
+
+impl MarketEvent {
+    /// The market the event belongs to, when it has one.
+    pub fn ticker(&self) -> Option<&str> {
+        match self {
+            MarketEvent::Trade(t) => Some(&t.ticker),
+            MarketEvent::Candle { ticker, .. } | MarketEvent::Settlement { ticker, .. } => {
+                Some(ticker)
+            }
+            MarketEvent::Message(msg) => match msg {
+                WebSocketMessage::OrderbookSnapshot(m) => Some(&m.market_ticker),
+                WebSocketMessage::OrderbookDelta(m) => Some(&m.market_ticker),
+                WebSocketMessage::Trade(m) => Some(&m.market_ticker),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// A trading strategy driven by the backtester.
+///
+/// Callbacks receive a [`StrategyContext`] to read simulated state and send orders.
+pub trait Strategy {
+    /// Called for every market event, after the simulator has applied it.
+    fn on_event(&mut self, ctx: &mut StrategyContext<'_>, event: &MarketEvent);
+
+    /// Called when an order reaches the simulator and is accepted.
+    fn on_order(&mut self, _ctx: &mut StrategyContext<'_>, _order: &Order) {}
+
+    /// Called when the simulator rejects an order.
+    fn on_reject(
+        &mut self,
+        _ctx: &mut StrategyContext<'_>,
+        _order: &OrderCreationField,
+        _error: &KalshiError,
+    ) {
+    }
+
+    /// Called for every fill on the strategy's orders.
+    fn on_fill(&mut self, _ctx: &mut StrategyContext<'_>, _fill: &FillMsg) {}
+}
+
+/// Simulated state visible to a [`Strategy`], and its outbox.
+pub struct StrategyContext<'a> {
+    now_ms: i64,
+    balance: i64,
+    positions: &'a HashMap<String, i32>,
+    marks: &'a HashMap<String, i32>,
+    outbox: Vec<Request>,
+}
+
+impl StrategyContext<'_> {
+    /// Current replay time in milliseconds since the Unix epoch.
+    pub fn now_ms(&self) -> i64 {
+        self.now_ms
+    }
+
+    /// Current replay time.
+    pub fn now(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(self.now_ms).unwrap_or_default()
+    }
+
+    /// Cash balance in cents.
+    pub fn balance(&self) -> i64 {
+        self.balance
+    }
+
+    /// Position in YES contracts; negative for NO.
+    pub fn position(&self, ticker: &str) -> i32 {
+        self.positions.get(ticker).copied().unwrap_or(0)
+    }
+
+    /// Last traded YES price in cents.
+    pub fn last_price(&self, ticker: &str) -> Option<i32> {
+        self.marks.get(ticker).copied()
+    }
+
+    /// Sends an order. It reaches the simulator after the configured latency.
+    pub fn submit(&mut self, order: OrderCreationField) {
+        self.outbox.push(Request::Submit(Box::new(order)));
+    }
+
+    /// Cancels a resting order. The cancel reaches the simulator after the configured latency.
+    pub fn cancel(&mut self, order_id: &str) {
+        self.outbox.push(Request::Cancel(order_id.to_string()));
+    }
+}
+
+#[derive(Debug)]
+enum Request {
+    Submit(Box<OrderCreationField>),
+    Cancel(String),
+}
+
+/// Results for one market.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MarketReport {
+    pub ticker: String,
+    /// Position at the end of the run.
+    pub position: i32,
+    /// Realized P&L in cents, before fees and slippage.
+    pub realized_pnl: i64,
+    /// Unrealized P&L in cents of the final position at the last mark.
+    pub unrealized_pnl: i64,
+    pub fees_paid: i64,
+    pub slippage: i64,
+    pub fills: usize,
+    pub contracts_filled: i64,
+}
+
+impl MarketReport {
+    /// Realized plus unrealized P&L, net of fees and slippage.
+    pub fn net_pnl(&self) -> i64 {
+        self.realized_pnl + self.unrealized_pnl - self.fees_paid - self.slippage
+    }
+}
+
+/// Outcome of [`Backtest::run`].
+#[derive(Debug, Clone, Default)]
+pub struct BacktestReport {
+    pub initial_balance: i64,
+    /// Cash plus marked positions, less slippage, at the end of the run.
+    pub final_equity: i64,
+    /// `final_equity - initial_balance`.
+    pub pnl: i64,
+    pub fees_paid: i64,
+    pub slippage: i64,
+    /// Largest peak-to-trough fall in equity, in cents.
+    pub max_drawdown: i64,
+    /// Largest peak-to-trough fall as a fraction of the peak.
+    pub max_drawdown_pct: f64,
+    /// Annualized Sharpe ratio of per-period returns; `None` with fewer than two periods
+    /// or no variation.
+    pub sharpe: Option<f64>,
+    pub orders_submitted: usize,
+    pub orders_rejected: usize,
+    pub contracts_submitted: i64,
+    pub contracts_filled: i64,
+    /// `(timestamp_ms, equity)` each time equity changed.
+    pub equity_curve: Vec<(i64, i64)>,
+    /// Per-market results, sorted by ticker.
+    pub markets: Vec<MarketReport>,
+}
+
+impl BacktestReport {
+    /// Fraction of submitted contracts that filled.
+    pub fn fill_rate(&self) -> f64 {
+        if self.contracts_submitted == 0 {
+            0.0
+        } else {
+            self.contracts_filled as f64 / self.contracts_submitted as f64
+        }
+    }
+}
+
+/// Replays historical data through a [`Strategy`].
+///
+/// See the [module documentation](crate::backtest) for the simulation model.
+pub struct Backtest {
+    config: BacktestConfig,
+    /// `(timestamp_ms, event)`, sorted stably by timestamp before the run.
+    timeline: Vec<(i64, MarketEvent)>,
+}
+
+impl Backtest {
+    pub fn new(config: BacktestConfig) -> Self {
+        Backtest {
+            config,
+            timeline: Vec::new(),
+        }
+    }
+
+    /// Adds public trades. Trades with an unparseable `created_time` are skipped.
+    pub fn add_trades(&mut self, trades: impl IntoIterator<Item = Trade>) {
+        for trade in trades {
+            if let Some(ts) = parse_time(&trade.created_time).map(|t| t.timestamp_millis()) {
+                self.timeline.push((ts, MarketEvent::Trade(trade)));
+            }
+        }
+    }
+
+    /// Adds candlesticks for one market, delivered at each candle's end time.
+    pub fn add_candles(&mut self, ticker: &str, candles: impl IntoIterator<Item = Candle>) {
+        for candle in candles {
+            self.timeline.push((
+                candle.end_ts * 1000,
+                MarketEvent::Candle {
+                    ticker: ticker.to_string(),
+                    candle,
+                },
+            ));
+        }
+    }
+
+    /// Adds the result of `batch_get_market_candlesticks`.
+    pub fn add_market_candlesticks(
+        &mut self,
+        markets: impl IntoIterator<Item = MarketCandlesticks>,
+    ) {
+        for market in markets {
+            self.add_candles(&market.ticker, market.candlesticks);
+        }
+    }
+
+    /// Adds a WebSocket message received at `ts_ms`.
+    pub fn add_message(&mut self, ts_ms: i64, msg: WebSocketMessage) {
+        self.timeline.push((ts_ms, MarketEvent::Message(msg)));
+    }
+
+    /// Adds a recorded WebSocket log.
+    ///
+    /// Each non-empty line holds the receive time in milliseconds since the Unix epoch,
+    /// whitespace, then the raw frame as received.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(usize)`: The number of messages added.
+    /// - `Err(KalshiError)`: A `UserInputError` naming the first malformed line; nothing is
+    ///   added in that case.
+    ///
+    pub fn add_ws_log(&mut self, log: &str) -> Result<usize, KalshiError> {
+        let mut parsed = Vec::new();
+        for (i, line) in log.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let malformed = |why: String| {
+                KalshiError::UserInputError(format!("ws log line {}: {}", i + 1, why))
+            };
+            let (ts, frame) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| malformed("expected `<ts_ms> <frame>`".to_string()))?;
+            let ts: i64 = ts.parse().map_err(|e| malformed(format!("{}", e)))?;
+            let msg =
+                WebSocketMessage::parse(frame.trim()).map_err(|e| malformed(e.to_string()))?;
+            parsed.push((ts, MarketEvent::Message(msg)));
+        }
+        let count = parsed.len();
+        self.timeline.extend(parsed);
+        Ok(count)
+    }
+
+    /// Settles `ticker` with `result` at `ts_ms`.
+    pub fn add_settlement(&mut self, ticker: &str, ts_ms: i64, result: SettlementResult) {
+        self.timeline.push((
+            ts_ms,
+            MarketEvent::Settlement {
+                ticker: ticker.to_string(),
+                result,
+            },
+        ));
+    }
+
+    /// Fetches every public trade of `ticker` in the window and adds it.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(usize)`: The number of trades added.
+    /// - `Err(KalshiError)`: An error if a page request failed.
+    ///
+    pub async fn fetch_trades<B: MarketData>(
+        &mut self,
+        backend: &B,
+        ticker: &str,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> Result<usize, KalshiError> {
+        let mut added = 0;
+        let mut cursor = None;
+        loop {
+            let (next, trades) = backend
+                .get_trades(
+                    Some(PAGE_SIZE),
+                    cursor,
+                    Some(ticker.to_string()),
+                    min_ts,
+                    max_ts,
+                )
+                .await?;
+            let done = trades.is_empty();
+            added += trades.len();
+            self.add_trades(trades);
+            match next {
+                Some(c) if !c.is_empty() && !done => cursor = Some(c),
+                _ => break,
+            }
+        }
+        Ok(added)
+    }
+
+    /// Fetches candlesticks for several markets and adds them.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(usize)`: The number of candles added.
+    /// - `Err(KalshiError)`: An error if the request failed.
+    ///
+    pub async fn fetch_candlesticks<B: MarketData>(
+        &mut self,
+        backend: &B,
+        market_tickers: Vec<String>,
+        start_ts: i64,
+        end_ts: i64,
+        period_interval: i32,
+    ) -> Result<usize, KalshiError> {
+        let markets = backend
+            .batch_get_market_candlesticks(market_tickers, start_ts, end_ts, period_interval, None)
+            .await?;
+        let added = markets.iter().map(|m| m.candlesticks.len()).sum();
+        self.add_market_candlesticks(markets);
+        Ok(added)
+    }
+
+    /// Replays the timeline through `strategy` and reports the results.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(BacktestReport)`: The results of the run.
+    /// - `Err(KalshiError)`: Simulated fills were lost because more were produced at once
+    ///   than the simulator's fill channel holds, so positions and P&L would be wrong.
+    ///
+    pub async fn run<S: Strategy>(
+        mut self,
+        strategy: &mut S,
+    ) -> Result<BacktestReport, KalshiError> {
+        self.timeline.sort_by_key(|(ts, _)| *ts);
+        let paper =
+            PaperKalshi::new(self.config.initial_balance).with_fees(self.config.fees.clone());
+        let mut sim = Simulation {
+            fills: paper.subscribe(),
+            paper,
+            config: self.config,
+            pending: VecDeque::new(),
+            positions: HashMap::new(),
+            marks: HashMap::new(),
+            market_stats: HashMap::new(),
+            report: BacktestReport::default(),
+            now_ms: 0,
+        };
+        sim.report.initial_balance = sim.config.initial_balance;
+
+        for (ts, event) in &self.timeline {
+            sim.execute_due(*ts, strategy).await?;
+            sim.set_time(*ts);
+            sim.apply(event);
+            sim.deliver_fills(strategy).await?;
+            let mut ctx = sim.context().await;
+            strategy.on_event(&mut ctx, event);
+            sim.enqueue(ctx.outbox);
+            sim.sample_equity().await;
+        }
+        sim.execute_due(i64::MAX, strategy).await?;
+        Ok(sim.finish().await)
+    }
+}
+
+/// Mutable state of a run.
+struct Simulation {
+    paper: PaperKalshi,
+    fills: broadcast::Receiver<FillMsg>,
+    config: BacktestConfig,
+    /// Requests in arrival order, with the time they reach the simulator.
+    pending: VecDeque<(i64, Request)>,
+    positions: HashMap<String, i32>,
+    marks: HashMap<String, i32>,
+    /// Per market: fills, contracts filled, slippage.
+    market_stats: HashMap<String, (usize, i64, i64)>,
+    report: BacktestReport,
+    now_ms: i64,
+}
+
+impl Simulation {
+    fn set_time(&mut self, ts: i64) {
+        self.now_ms = ts;
+        if let Some(now) = DateTime::from_timestamp_millis(ts) {
+            self.paper.set_time(now);
+        }
+    }
+
+    fn apply(&mut self, event: &MarketEvent) {
+        match event {
+            MarketEvent::Trade(trade) => {
+                self.marks.insert(trade.ticker.clone(), trade.yes_price);
+                self.paper
+                    .handle_message(&WebSocketMessage::Trade(TradeMsg {
+                        market_ticker: trade.ticker.clone(),
+                        yes_price: trade.yes_price,
+                        no_price: trade.no_price,
+                        count: trade.count,
+                        taker_side: trade.taker_side.clone(),
+                        ts: self.now_ms / 1000,
+                    }));
+            }
+            MarketEvent::Candle { ticker, candle } => {
+                self.marks.insert(ticker.clone(), candle.yes_close);
+                if candle.volume > 0 {
+                    let count = (candle.volume / 2).clamp(1, i32::MAX as i64) as i32;
+                    // The low was printed by sellers of YES, the high by buyers.
+                    for (yes_price, taker_side) in
+                        [(candle.yes_low, "no"), (candle.yes_high, "yes")]
+                    {
+                        self.paper
+                            .handle_message(&WebSocketMessage::Trade(TradeMsg {
+                                market_ticker: ticker.clone(),
+                                yes_price,
+                                no_price: 100 - yes_price,
+                                count,
+                                taker_side: taker_side.to_string(),
+                                ts: self.now_ms / 1000,
+                            }));
+                    }
+                }
+            }
+            MarketEvent::Message(msg) => {
+                if let WebSocketMessage::Trade(trade) = msg {
+                    self.marks
+                        .insert(trade.market_ticker.clone(), trade.yes_price);
+                }
+                self.paper.handle_message(msg);
+            }
+            MarketEvent::Settlement { ticker, result } => {
+                self.paper.settle(ticker, *result);
+                self.positions.remove(ticker);
+                let mark = match result {
+                    SettlementResult::Yes | SettlementResult::AllYes => Some(100),
+                    SettlementResult::No | SettlementResult::AllNo => Some(0),
+                    SettlementResult::Void => None,
+                };
+                if let Some(mark) = mark {
+                    self.marks.insert(ticker.clone(), mark);
+                }
+            }
+        }
+    }
+
+    async fn context(&self) -> StrategyContext<'_> {
+        StrategyContext {
+            now_ms: self.now_ms,
+            balance: self.paper.get_balance().await.unwrap_or_default(),
+            positions: &self.positions,
+            marks: &self.marks,
+            outbox: Vec::new(),
+        }
+    }
+
+    fn enqueue(&mut self, requests: Vec<Request>) {
+        let due = self.now_ms.saturating_add(self.config.latency_ms);
+        self.pending.extend(requests.into_iter().map(|r| (due, r)));
+    }
+
+    /// Sends every request due by `until` to the simulator, in order.
+    async fn execute_due<S: Strategy>(
+        &mut self,
+        until: i64,
+        strategy: &mut S,
+    ) -> Result<(), KalshiError> {
+        while self.pending.front().is_some_and(|(due, _)| *due <= until) {
+            let Some((due, request)) = self.pending.pop_front() else {
+                break;
+            };
+            self.set_time(due.max(self.now_ms));
+            match request {
+                Request::Submit(order) => {
+                    self.report.orders_submitted += 1;
+                    self.report.contracts_submitted += order.count as i64;
+                    let result = match self.paper.batch_create_order(vec![(*order).clone()]).await {
+                        Ok(mut results) => results.pop().unwrap_or_else(|| {
+                            Err(KalshiError::InternalError(
+                                "empty batch response".to_string(),
+                            ))
+                        }),
+                        Err(e) => Err(e),
+                    };
+                    self.deliver_fills(strategy).await?;
+                    let mut ctx = self.context().await;
+                    match &result {
+                        Ok(accepted) => strategy.on_order(&mut ctx, accepted),
+                        Err(e) => strategy.on_reject(&mut ctx, &order, e),
+                    }
+                    let outbox = ctx.outbox;
+                    if result.is_err() {
+                        self.report.orders_rejected += 1;
+                    }
+                    self.enqueue(outbox);
+                }
+                Request::Cancel(order_id) => {
+                    // Cancelling an order that already filled or expired is not an error
+                    // worth surfacing to the strategy.
+                    let _ = self.paper.cancel_order(&order_id).await;
+                }
+            }
+            self.sample_equity().await;
+        }
+        Ok(())
+    }
+
+    /// Books pending fills and passes them to the strategy. Fails if fills were dropped
+    /// by the channel.
+    async fn deliver_fills<S: Strategy>(&mut self, strategy: &mut S) -> Result<(), KalshiError> {
+        loop {
+            let fill = match self.fills.try_recv() {
+                Ok(fill) => fill,
+                Err(broadcast::error::TryRecvError::Lagged(missed)) => {
+                    return Err(KalshiError::InternalError(format!(
+                        "backtest fill channel overflowed; {} fills were lost",
+                        missed
+                    )));
+                }
+                Err(_) => return Ok(()),
+            };
+            self.positions
+                .insert(fill.market_ticker.clone(), fill.post_position);
+            let slippage = if fill.is_taker == Some(true) {
+                self.config.slippage_cents * fill.count as i64
+            } else {
+                0
+            };
+            let stats = self
+                .market_stats
+                .entry(fill.market_ticker.clone())
+                .or_default();
+            stats.0 += 1;
+            stats.1 += fill.count as i64;
+            stats.2 += slippage;
+            self.report.contracts_filled += fill.count as i64;
+            self.report.slippage += slippage;
+            let mut ctx = self.context().await;
+            strategy.on_fill(&mut ctx, &fill);
+            let outbox = ctx.outbox;
+            self.enqueue(outbox);
+        }
+    }
+
+    /// Value in cents of `position` contracts at a YES price of `mark`.
+    fn position_value(position: i32, mark: i32) -> i64 {
+        if position >= 0 {
+            position as i64 * mark as i64
+        } else {
+            -(position as i64) * (100 - mark) as i64
+        }
+    }
+
+    async fn equity(&self) -> i64 {
+        let balance = self.paper.get_balance().await.unwrap_or_default();
+        let held: i64 = self
+            .positions
+            .iter()
+            .map(|(ticker, &pos)| {
+                Self::position_value(pos, self.marks.get(ticker).copied().unwrap_or(50))
+            })
+            .sum();
+        balance + held - self.report.slippage
+    }
+
+    async fn sample_equity(&mut self) {
+        let equity = self.equity().await;
+        if self
+            .report
+            .equity_curve
+            .last()
+            .is_none_or(|(_, e)| *e != equity)
+        {
+            self.report.equity_curve.push((self.now_ms, equity));
+        }
+    }
+
+    async fn finish(mut self) -> BacktestReport {
+        let final_equity = self.equity().await;
+        let mut report = std::mem::take(&mut self.report);
+        report.final_equity = final_equity;
+        report.pnl = final_equity - report.initial_balance;
+
+        let (max_drawdown, max_drawdown_pct) =
+            max_drawdown(report.initial_balance, &report.equity_curve);
+        report.max_drawdown = max_drawdown;
+        report.max_drawdown_pct = max_drawdown_pct;
+        report.sharpe = sharpe(
+            report.initial_balance,
+            &report.equity_curve,
+            self.config.sharpe_period_ms,
+        );
+
+        let positions = self
+            .paper
+            .get_positions(None, None, Some("all".to_string()), None, None, None)
+            .await
+            .map(|(_, _, markets)| markets)
+            .unwrap_or_default();
+        for p in positions {
+            let (fills, contracts_filled, slippage) = self
+                .market_stats
+                .get(&p.ticker)
+                .copied()
+                .unwrap_or_default();
+            let unrealized_pnl = self
+                .marks
+                .get(&p.ticker)
+                .filter(|_| p.position != 0)
+                .map(|&mark| Self::position_value(p.position, mark) - p.market_exposure)
+                .unwrap_or(0);
+            report.fees_paid += p.fees_paid;
+            report.markets.push(MarketReport {
+                ticker: p.ticker,
+                position: p.position,
+                realized_pnl: p.realized_pnl,
+                unrealized_pnl,
+                fees_paid: p.fees_paid,
+                slippage,
+                fills,
+                contracts_filled,
+            });
+        }
+        report
+    }
+}
+
+/// Largest peak-to-trough fall of an equity curve starting at `initial`.
+fn max_drawdown(initial: i64, curve: &[(i64, i64)]) -> (i64, f64) {
+    let mut peak = initial;
+    let mut worst = 0;
+    let mut worst_pct = 0.0;
+    for &(_, equity) in curve {
+        peak = peak.max(equity);
+        let drawdown = peak - equity;
+        worst = worst.max(drawdown);
+        if peak > 0 {
+            worst_pct = f64::max(worst_pct, drawdown as f64 / peak as f64);
+        }
+    }
+    (worst, worst_pct)
+}
+
+/// Annualized Sharpe ratio of returns over consecutive `period_ms` buckets, using the
+/// last equity value seen in each bucket.
+fn sharpe(initial: i64, curve: &[(i64, i64)], period_ms: i64) -> Option<f64> {
+    let (first, _) = *curve.first()?;
+    if period_ms <= 0 {
+        return None;
+    }
+    let mut closes: Vec<i64> = vec![initial];
+    let mut bucket = first / period_ms;
+    let mut last = initial;
+    for &(ts, equity) in curve {
+        while ts / period_ms > bucket {
+            closes.push(last);
+            bucket += 1;
+        }
+        last = equity;
+    }
+    closes.push(last);
+
+    let returns: Vec<f64> = closes
+        .windows(2)
+        .filter(|w| w[0] != 0)
+        .map(|w| (w[1] - w[0]) as f64 / w[0] as f64)
+        .collect();
+    if returns.len() < 2 {
+        return None;
+    }
+    let n = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / n;
+    let var = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let std = var.sqrt();
+    if std == 0.0 {
+        return None;
+    }
+    let periods_per_year = 365.0 * DAY_MS as f64 / period_ms as f64;
+    Some(mean / std * periods_per_year.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, OrderType, Side};
+
+    fn trade(ts: &str, yes_price: i32, taker_side: &str) -> Trade {
+        Trade {
+            trade_id: ts.to_string(),
+            taker_side: taker_side.to_string(),
+            ticker: "KXTEST-24JAN01-T1".to_string(),
+            count: 10,
+            yes_price,
+            no_price: 100 - yes_price,
+            created_time: ts.to_string(),
+        }
+    }
+
+    /// Bids 40¢ for 10 YES on the first trade it sees.
+    struct BidOnce {
+        sent: bool,
+        accepted_at: Option<i64>,
+    }
+
+    impl Strategy for BidOnce {
+        fn on_event(&mut self, ctx: &mut StrategyContext<'_>, event: &MarketEvent) {
+            if self.sent || !matches!(event, MarketEvent::Trade(_)) {
+                return;
+            }
+            self.sent = true;
+            ctx.submit(OrderCreationField {
+                action: Action::Buy,
+                client_order_id: None,
+                count: 10,
+                side: Side::Yes,
+                ticker: "KXTEST-24JAN01-T1".to_string(),
+                input_type: OrderType::Limit,
+                buy_max_cost: None,
+                expiration_ts: None,
+                yes_price: Some(40),
+                no_price: None,
+                sell_position_floor: None,
+                yes_price_dollars: None,
+                no_price_dollars: None,
+                time_in_force: None,
+                post_only: None,
+                reduce_only: None,
+                self_trade_prevention_type: None,
+                order_group_id: None,
+                cancel_order_on_pause: None,
+            });
+        }
+
+        fn on_order(&mut self, ctx: &mut StrategyContext<'_>, _order: &Order) {
+            self.accepted_at = Some(ctx.now_ms());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_latency_delays_order_and_settlement_pays_out() {
+        let mut backtest = Backtest::new(BacktestConfig {
+            initial_balance: 10_000,
+            latency_ms: 1_000,
+            ..Default::default()
+        });
+        backtest.add_trades([
+            trade("2024-01-01T00:00:00Z", 45, "yes"),
+            // Trades through 40¢ before the order arrives: no fill.
+            trade("2024-01-01T00:00:00.500Z", 38, "no"),
+            trade("2024-01-01T00:00:02Z", 39, "no"),
+        ]);
+        backtest.add_settlement(
+            "KXTEST-24JAN01-T1",
+            1_704_067_203_000,
+            SettlementResult::Yes,
+        );
+
+        let mut strategy = BidOnce {
+            sent: false,
+            accepted_at: None,
+        };
+        let report = backtest.run(&mut strategy).await.unwrap();
+
+        assert_eq!(strategy.accepted_at, Some(1_704_067_201_000));
+        assert_eq!(report.contracts_filled, 10);
+        assert_eq!(report.fill_rate(), 1.0);
+        // Bought 10 at 40¢, settled at 100¢, no maker fees by default.
+        assert_eq!(report.pnl, 600);
+        assert_eq!(report.markets.len(), 1);
+        assert_eq!(report.markets[0].realized_pnl, 600);
+        assert_eq!(report.markets[0].net_pnl(), 600);
+        assert!(report.max_drawdown >= 0);
+    }
+
+    #[test]
+    fn test_drawdown_and_sharpe() {
+        let curve = [(0, 110), (1, 90), (2, 120), (3, 100)];
+        assert_eq!(max_drawdown(100, &curve), (20, 20.0 / 110.0));
+
+        let steady = [(DAY_MS, 101), (2 * DAY_MS, 103), (3 * DAY_MS, 104)];
+        assert!(sharpe(100, &steady, DAY_MS).unwrap() > 0.0);
+        assert_eq!(sharpe(100, &[(DAY_MS, 100)], DAY_MS), None);
+    }
+}
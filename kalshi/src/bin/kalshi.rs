@@ -0,0 +1,212 @@
+//! Command-line tools built on the library.
+//!
+//! ```text
+//! kalshi history --series KXHIGHNY [--event TICKER] [--from YYYY-MM-DD] [--to YYYY-MM-DD]
+//!                [--out DIR] [--format csv,parquet] [--period 1|60|1440] [--prod]
+//! kalshi statement [--from YYYY-MM-DD] [--to YYYY-MM-DD] [--period monthly|annual]
+//!                  [--out DIR] [--prod]
+//! ```
+//!
+//! Credentials come from `KALSHI_API_KEY` and `KALSHI_PEM_PATH`. Runs against the demo
+//! environment unless `--prod` is given.
+
+use chrono::NaiveDate;
+use kalshi_rust::{
+    HistoryFormat, HistoryScope, HistorySync, Kalshi, StatementBuilder, StatementPeriod,
+    TradingEnvironment,
+};
+use std::env;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: kalshi history [--series TICKER] [--event TICKER] \
+[--from YYYY-MM-DD] [--to YYYY-MM-DD] [--out DIR] [--format csv,parquet] \
+[--period 1|60|1440] [--prod]
+       kalshi statement [--from YYYY-MM-DD] [--to YYYY-MM-DD] \
+[--period monthly|annual] [--out DIR] [--prod]";
+
+struct HistoryArgs {
+    scope: HistoryScope,
+    out: String,
+    formats: Vec<HistoryFormat>,
+    period: i32,
+    prod: bool,
+}
+
+fn parse_date(s: &str) -> Result<i64, String> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc().timestamp())
+        .ok_or_else(|| format!("invalid date `{}`, expected YYYY-MM-DD", s))
+}
+
+fn parse_history_args(args: &[String]) -> Result<HistoryArgs, String> {
+    let mut parsed = HistoryArgs {
+        scope: HistoryScope::default(),
+        out: "history".to_string(),
+        formats: vec![HistoryFormat::Csv],
+        period: 60,
+        prod: false,
+    };
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        if flag == "--prod" {
+            parsed.prod = true;
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {}", flag))?;
+        match flag.as_str() {
+            "--series" => parsed.scope.series_ticker = Some(value.clone()),
+            "--event" => parsed.scope.event_ticker = Some(value.clone()),
+            "--from" => parsed.scope.min_ts = Some(parse_date(value)?),
+            // Inclusive: the whole `--to` day.
+            "--to" => parsed.scope.max_ts = Some(parse_date(value)? + 86_399),
+            "--out" => parsed.out = value.clone(),
+            "--period" => {
+                parsed.period = value
+                    .parse()
+                    .map_err(|_| format!("invalid period `{}`", value))?
+            }
+            "--format" => {
+                parsed.formats = value
+                    .split(',')
+                    .map(|f| match f.trim() {
+                        "csv" => Ok(HistoryFormat::Csv),
+                        "parquet" => Ok(HistoryFormat::Parquet),
+                        other => Err(format!("unknown format `{}`", other)),
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            other => return Err(format!("unknown option `{}`", other)),
+        }
+    }
+    let scope = &parsed.scope;
+    if scope.series_ticker.is_none() && scope.event_ticker.is_none() && scope.min_ts.is_none() {
+        return Err("give --series, --event or --from".to_string());
+    }
+    Ok(parsed)
+}
+
+async fn connect(prod: bool) -> Result<Kalshi, String> {
+    let key_id = env::var("KALSHI_API_KEY").map_err(|_| "KALSHI_API_KEY is not set")?;
+    let pem_path = env::var("KALSHI_PEM_PATH").map_err(|_| "KALSHI_PEM_PATH is not set")?;
+    let environment = if prod {
+        TradingEnvironment::ProdMode
+    } else {
+        TradingEnvironment::DemoMode
+    };
+    Kalshi::new(environment, &key_id, &pem_path)
+        .await
+        .map_err(|e| format!("authentication failed: {}", e))
+}
+
+async fn run_history(args: &[String]) -> Result<(), String> {
+    let args = parse_history_args(args)?;
+    let kalshi = connect(args.prod).await?;
+
+    let report = HistorySync::new(&args.out)
+        .with_formats(args.formats)
+        .with_period_interval(args.period)
+        .sync(&kalshi, &args.scope)
+        .await
+        .map_err(|e| e.to_string())?;
+    println!(
+        "{}: {} series, {} events, {} markets, {} new trades, {} new candles",
+        args.out, report.series, report.events, report.markets, report.trades, report.candles
+    );
+    Ok(())
+}
+
+struct StatementArgs {
+    from: Option<i64>,
+    to: Option<i64>,
+    period: StatementPeriod,
+    out: String,
+    prod: bool,
+}
+
+fn parse_statement_args(args: &[String]) -> Result<StatementArgs, String> {
+    let mut parsed = StatementArgs {
+        from: None,
+        to: None,
+        period: StatementPeriod::Monthly,
+        out: "statements".to_string(),
+        prod: false,
+    };
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        if flag == "--prod" {
+            parsed.prod = true;
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {}", flag))?;
+        match flag.as_str() {
+            "--from" => parsed.from = Some(parse_date(value)?),
+            "--to" => parsed.to = Some(parse_date(value)? + 86_399),
+            "--out" => parsed.out = value.clone(),
+            "--period" => {
+                parsed.period = match value.as_str() {
+                    "monthly" => StatementPeriod::Monthly,
+                    "annual" => StatementPeriod::Annual,
+                    other => return Err(format!("unknown period `{}`", other)),
+                }
+            }
+            other => return Err(format!("unknown option `{}`", other)),
+        }
+    }
+    Ok(parsed)
+}
+
+async fn run_statement(args: &[String]) -> Result<(), String> {
+    let args = parse_statement_args(args)?;
+    let kalshi = connect(args.prod).await?;
+
+    // Cost basis needs every fill, so fetch all history and filter the periods.
+    let ledger = StatementBuilder::new()
+        .fetch(&kalshi, None, args.to)
+        .await
+        .map_err(|e| e.to_string())?
+        .build();
+    let from = args
+        .from
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+        .map(|t| t.date_naive());
+    for statement in ledger.statements(args.period) {
+        if from.is_some_and(|from| statement.end < from) {
+            continue;
+        }
+        statement.write_csv(&args.out).map_err(|e| e.to_string())?;
+        statement
+            .write_json(format!("{}/statement-{}.json", args.out, statement.period))
+            .map_err(|e| e.to_string())?;
+        println!(
+            "{}: realized {}¢ on {} contracts, fees {}¢",
+            statement.period,
+            statement.total.realized_gain,
+            statement.total.contracts_closed,
+            statement.total.fees
+        );
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("history") => run_history(&args[1..]).await,
+        Some("statement") => run_statement(&args[1..]).await,
+        _ => Err(USAGE.to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
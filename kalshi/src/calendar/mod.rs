@@ -0,0 +1,397 @@
+//! Exchange trading hours and maintenance windows.
+//!
+//! [`ExchangeCalendar`] parses an [`ExchangeSchedule`] from
+//! [`Kalshi::get_exchange_schedule`] and answers when the exchange is open. Daily hours
+//! are given in exchange time (US Eastern); a session whose close time is not after its
+//! open time runs past midnight into the next day, so `00:00`–`00:00` is open all day.
+//! Each [`StandardHours`] entry applies between its `start_time` and `end_time`, and the
+//! exchange is closed during every maintenance window.
+//!
+//! Eastern time is converted with the US daylight-saving rules in force since 2007 (second
+//! Sunday of March to first Sunday of November), which avoids a time zone database.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::ExchangeCalendar;
+//!
+//! let calendar = ExchangeCalendar::load(&kalshi).await?;
+//! if !calendar.is_open(chrono::Utc::now()) {
+//!     println!("closed until {:?}", calendar.next_open());
+//!     calendar.sleep_until_open().await;
+//! }
+//! ```
+//!
+//! [`StandardHours`]: crate::StandardHours
+
+use crate::kalshi_error::*;
+use crate::{ExchangeSchedule, Kalshi};
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday,
+};
+
+/// How far ahead [`ExchangeCalendar::next_open`] and [`ExchangeCalendar::next_close`]
+/// look before giving up.
+pub const CALENDAR_HORIZON_DAYS: i64 = 14;
+
+/// A `[start, end)` interval in UTC.
+type Interval = (DateTime<Utc>, DateTime<Utc>);
+
+/// One [`StandardHours`](crate::StandardHours) entry, parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HoursPeriod {
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    /// Sessions per weekday, Monday first, as exchange-time (open, close).
+    days: [Vec<(NaiveTime, NaiveTime)>; 7],
+}
+
+/// When the exchange is open. See the [module documentation](crate::calendar).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExchangeCalendar {
+    periods: Vec<HoursPeriod>,
+    maintenance: Vec<Interval>,
+}
+
+impl ExchangeCalendar {
+    /// Parses a schedule.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(ExchangeCalendar)`: The calendar.
+    /// - `Err(KalshiError::UserInputError)`: A time or datetime could not be parsed.
+    ///
+    pub fn from_schedule(schedule: &ExchangeSchedule) -> Result<Self, KalshiError> {
+        let mut periods = Vec::new();
+        for hours in &schedule.standard_hours {
+            let mut days: [Vec<(NaiveTime, NaiveTime)>; 7] = Default::default();
+            let by_day = [
+                &hours.monday,
+                &hours.tuesday,
+                &hours.wednesday,
+                &hours.thursday,
+                &hours.friday,
+                &hours.saturday,
+                &hours.sunday,
+            ];
+            for (slot, sessions) in days.iter_mut().zip(by_day) {
+                for s in sessions {
+                    slot.push((parse_time(&s.open_time)?, parse_time(&s.close_time)?));
+                }
+            }
+            periods.push(HoursPeriod {
+                start: parse_optional_datetime(&hours.start_time)?,
+                end: parse_optional_datetime(&hours.end_time)?,
+                days,
+            });
+        }
+        let mut maintenance = Vec::new();
+        for window in &schedule.maintenance_windows {
+            let start = parse_datetime(&window.start_datetime)?;
+            let end = parse_datetime(&window.end_datetime)?;
+            if end > start {
+                maintenance.push((start, end));
+            }
+        }
+        maintenance.sort();
+        Ok(ExchangeCalendar {
+            periods,
+            maintenance,
+        })
+    }
+
+    /// Fetches the schedule and parses it.
+    pub async fn load(kalshi: &Kalshi) -> Result<Self, KalshiError> {
+        Self::from_schedule(&kalshi.get_exchange_schedule().await?)
+    }
+
+    /// Whether trading hours include `at` and no maintenance window covers it.
+    pub fn is_open(&self, at: DateTime<Utc>) -> bool {
+        !self.in_maintenance(at) && self.in_session(at)
+    }
+
+    /// Whether a maintenance window covers `at`.
+    pub fn in_maintenance(&self, at: DateTime<Utc>) -> bool {
+        self.maintenance_at(at).is_some()
+    }
+
+    /// The maintenance window covering `at`, if any.
+    pub fn maintenance_at(&self, at: DateTime<Utc>) -> Option<Interval> {
+        self.maintenance
+            .iter()
+            .copied()
+            .find(|(start, end)| *start <= at && at < *end)
+    }
+
+    /// The first maintenance window starting after `at`.
+    pub fn next_maintenance(&self, at: DateTime<Utc>) -> Option<Interval> {
+        self.maintenance
+            .iter()
+            .copied()
+            .find(|(start, _)| *start > at)
+    }
+
+    /// The next time the exchange is open: now if it is open, otherwise when it opens.
+    /// `None` if it stays closed for [`CALENDAR_HORIZON_DAYS`].
+    pub fn next_open(&self) -> Option<DateTime<Utc>> {
+        self.next_open_after(Utc::now())
+    }
+
+    /// The first instant at or after `at` when the exchange is open.
+    pub fn next_open_after(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.open_intervals(at)
+            .into_iter()
+            .find(|(_, end)| *end > at)
+            .map(|(start, _)| start.max(at))
+    }
+
+    /// The next time the exchange is closed: now if it is closed, otherwise when it
+    /// closes. `None` if it stays open for [`CALENDAR_HORIZON_DAYS`].
+    pub fn next_close(&self) -> Option<DateTime<Utc>> {
+        self.next_close_after(Utc::now())
+    }
+
+    /// The first instant at or after `at` when the exchange is closed.
+    pub fn next_close_after(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let horizon = at + Duration::days(CALENDAR_HORIZON_DAYS);
+        let mut t = at;
+        for (start, end) in self.open_intervals(at) {
+            if end <= t {
+                continue;
+            }
+            if start > t {
+                return Some(t);
+            }
+            t = end;
+        }
+        (t < horizon).then_some(t)
+    }
+
+    /// Sleeps until the exchange is open. Returns at once if it is open.
+    ///
+    /// # Returns
+    ///
+    /// `false` if no opening was found within [`CALENDAR_HORIZON_DAYS`].
+    ///
+    pub async fn sleep_until_open(&self) -> bool {
+        let now = Utc::now();
+        let Some(open) = self.next_open_after(now) else {
+            return false;
+        };
+        if let Ok(wait) = (open - now).to_std() {
+            tokio::time::sleep(wait).await;
+        }
+        true
+    }
+
+    /// Whether trading hours include `at`, ignoring maintenance.
+    fn in_session(&self, at: DateTime<Utc>) -> bool {
+        let date = to_exchange_time(at).date();
+        self.sessions(date - Duration::days(1), date)
+            .iter()
+            .any(|(start, end)| *start <= at && at < *end)
+    }
+
+    /// Sessions opening on exchange dates `from..=to`, unmerged.
+    fn sessions(&self, from: NaiveDate, to: NaiveDate) -> Vec<Interval> {
+        let mut out = Vec::new();
+        let mut date = from;
+        while date <= to {
+            let weekday = date.weekday().num_days_from_monday() as usize;
+            for period in &self.periods {
+                for (open, close) in &period.days[weekday] {
+                    let start = from_exchange_time(date.and_time(*open));
+                    if period.start.is_some_and(|s| start < s)
+                        || period.end.is_some_and(|e| start >= e)
+                    {
+                        continue;
+                    }
+                    let close_date = if close <= open {
+                        date + Duration::days(1)
+                    } else {
+                        date
+                    };
+                    out.push((start, from_exchange_time(close_date.and_time(*close))));
+                }
+            }
+            date += Duration::days(1);
+        }
+        out
+    }
+
+    /// Open intervals overlapping `[at − 1 day, at + horizon]`, merged and with
+    /// maintenance removed, in order.
+    fn open_intervals(&self, at: DateTime<Utc>) -> Vec<Interval> {
+        let date = to_exchange_time(at).date();
+        let mut sessions = self.sessions(
+            date - Duration::days(1),
+            date + Duration::days(CALENDAR_HORIZON_DAYS),
+        );
+        sessions.sort();
+        let mut merged: Vec<Interval> = Vec::new();
+        for (start, end) in sessions {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        let mut open = Vec::new();
+        for (mut start, end) in merged {
+            for &(m_start, m_end) in &self.maintenance {
+                if m_end <= start || m_start >= end {
+                    continue;
+                }
+                if m_start > start {
+                    open.push((start, m_start));
+                }
+                start = start.max(m_end);
+            }
+            if start < end {
+                open.push((start, end));
+            }
+        }
+        open
+    }
+}
+
+/// Converts a UTC instant to US Eastern wall-clock time.
+pub fn to_exchange_time(at: DateTime<Utc>) -> NaiveDateTime {
+    let naive = at.naive_utc();
+    let year = naive.year();
+    // 2:00 EST and 2:00 EDT, in UTC.
+    let dst_start = nth_sunday(year, 3, 2)
+        .and_hms_opt(7, 0, 0)
+        .unwrap_or_default();
+    let dst_end = nth_sunday(year, 11, 1)
+        .and_hms_opt(6, 0, 0)
+        .unwrap_or_default();
+    let offset = if naive >= dst_start && naive < dst_end {
+        4
+    } else {
+        5
+    };
+    naive - Duration::hours(offset)
+}
+
+/// Converts US Eastern wall-clock time to UTC. Times skipped by the spring change are
+/// read as standard time; repeated times in the fall are read as daylight time.
+pub fn from_exchange_time(local: NaiveDateTime) -> DateTime<Utc> {
+    let year = local.year();
+    let dst_start = nth_sunday(year, 3, 2)
+        .and_hms_opt(2, 0, 0)
+        .unwrap_or_default();
+    let dst_end = nth_sunday(year, 11, 1)
+        .and_hms_opt(2, 0, 0)
+        .unwrap_or_default();
+    let offset = if local >= dst_start && local < dst_end {
+        4
+    } else {
+        5
+    };
+    Utc.from_utc_datetime(&(local + Duration::hours(offset)))
+}
+
+/// The `n`th Sunday of `month`.
+fn nth_sunday(year: i32, month: u32, n: u32) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Sun, n as u8).unwrap_or_default()
+}
+
+fn parse_time(s: &str) -> Result<NaiveTime, KalshiError> {
+    NaiveTime::parse_from_str(s, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M:%S"))
+        .map_err(|_| KalshiError::UserInputError(format!("invalid schedule time `{}`", s)))
+}
+
+fn parse_datetime(s: &str) -> Result<DateTime<Utc>, KalshiError> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| KalshiError::UserInputError(format!("invalid schedule datetime `{}`", s)))
+}
+
+/// Like [`parse_datetime`], with an empty string meaning unbounded.
+fn parse_optional_datetime(s: &str) -> Result<Option<DateTime<Utc>>, KalshiError> {
+    if s.is_empty() {
+        Ok(None)
+    } else {
+        parse_datetime(s).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        parse_datetime(s).unwrap()
+    }
+
+    fn calendar() -> ExchangeCalendar {
+        // Weekdays 08:00–03:00 the next morning, closed at weekends, with maintenance
+        // on Thursday 4 July 2024 from 03:00 to 05:00 ET.
+        let weekday = serde_json::json!([{ "open_time": "08:00", "close_time": "03:00" }]);
+        let schedule: ExchangeSchedule = serde_json::from_value(serde_json::json!({
+            "standard_hours": [{
+                "start_time": "2024-01-01T00:00:00Z", "end_time": "",
+                "monday": weekday, "tuesday": weekday, "wednesday": weekday,
+                "thursday": weekday, "friday": weekday
+            }],
+            "maintenance_windows": [{
+                "start_datetime": "2024-07-04T07:00:00Z",
+                "end_datetime": "2024-07-04T09:00:00Z"
+            }]
+        }))
+        .unwrap();
+        ExchangeCalendar::from_schedule(&schedule).unwrap()
+    }
+
+    #[test]
+    fn test_eastern_time_follows_daylight_saving() {
+        let summer = to_exchange_time(utc("2024-07-01T12:00:00Z"));
+        let winter = to_exchange_time(utc("2024-01-15T12:00:00Z"));
+        assert_eq!(summer.time(), NaiveTime::from_hms_opt(8, 0, 0).unwrap());
+        assert_eq!(winter.time(), NaiveTime::from_hms_opt(7, 0, 0).unwrap());
+        // 10 March 2024 is the spring change; 3 November 2024 the fall change.
+        assert_eq!(
+            to_exchange_time(utc("2024-03-10T06:59:00Z")).time(),
+            NaiveTime::from_hms_opt(1, 59, 0).unwrap()
+        );
+        assert_eq!(
+            to_exchange_time(utc("2024-03-10T07:00:00Z")).time(),
+            NaiveTime::from_hms_opt(3, 0, 0).unwrap()
+        );
+        let local = NaiveDate::from_ymd_opt(2024, 11, 3)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        assert_eq!(from_exchange_time(local), utc("2024-11-03T17:00:00Z"));
+    }
+
+    #[test]
+    fn test_sessions_cross_midnight_and_skip_weekends_and_maintenance() {
+        let cal = calendar();
+        // Monday 1 July 2024, 08:00 ET = 12:00 UTC.
+        assert!(!cal.is_open(utc("2024-07-01T11:59:00Z")));
+        assert!(cal.is_open(utc("2024-07-01T12:00:00Z")));
+        // Tuesday 02:30 ET is still Monday's session.
+        assert!(cal.is_open(utc("2024-07-02T06:30:00Z")));
+        assert_eq!(
+            cal.next_close_after(utc("2024-07-02T06:30:00Z")),
+            Some(utc("2024-07-02T07:00:00Z"))
+        );
+
+        // Wednesday's session runs into maintenance on Thursday at 03:00 ET.
+        assert!(cal.in_maintenance(utc("2024-07-04T08:00:00Z")));
+        assert!(!cal.is_open(utc("2024-07-04T08:00:00Z")));
+
+        // Saturday: next open is Monday 08:00 ET.
+        assert_eq!(
+            cal.next_open_after(utc("2024-07-06T15:00:00Z")),
+            Some(utc("2024-07-08T12:00:00Z"))
+        );
+        // Friday's session closes Saturday 03:00 ET.
+        assert_eq!(
+            cal.next_close_after(utc("2024-07-05T20:00:00Z")),
+            Some(utc("2024-07-06T07:00:00Z"))
+        );
+    }
+}
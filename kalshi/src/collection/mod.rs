@@ -1,7 +1,8 @@
 //! collection.rs – wrappers for Kalshi Trade API → collection (multivariate)
-use crate::{kalshi_error::*, Kalshi};
-use serde::Deserialize;
-use serde_json::Value;
+use crate::market::null_to_empty_vec;
+use crate::{kalshi_error::*, Kalshi, Market, Side};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 impl Kalshi {
     /// Retrieves a list of multivariate event collections from the Kalshi exchange.
@@ -80,128 +81,121 @@ impl Kalshi {
         Ok(res.multivariate_event_collection)
     }
 
-    /// Retrieves the lookup history for a multivariate event collection.
+    /// Retrieves the recent lookup history for a multivariate event collection.
     ///
-    /// This method fetches the historical lookup data for a collection, allowing for
-    /// pagination. Lookup history shows how markets within the collection have been
-    /// queried over time.
+    /// This method fetches the market combinations looked up in a collection within
+    /// a recent window, with the market each combination resolved to.
     ///
     /// # Arguments
     ///
     /// * `collection_ticker` - A string slice referencing the collection's unique ticker identifier.
-    /// * `limit` - An optional integer to limit the number of lookup entries returned.
-    /// * `cursor` - An optional string for pagination cursor.
+    /// * `lookback_seconds` - How far back to look; the API accepts 10, 60, 300 or 3600.
     ///
     /// # Returns
     ///
-    /// - `Ok((Option<String>, Vec<LookupEntry>))`: A tuple containing an optional pagination cursor
-    ///   and a vector of `LookupEntry` objects on successful retrieval.
+    /// - `Ok(Vec<LookupEntry>)`: The lookups made within the window on successful retrieval.
     /// - `Err(KalshiError)`: An error if the user is not authenticated or if there is an issue with the request.
     ///
     /// # Example
     ///
     /// ```
     /// // Assuming `kalshi_instance` is an already authenticated instance of `Kalshi`
-    /// let (cursor, lookups) = kalshi_instance.get_collection_lookup_history(
-    ///     "SOME-COLLECTION", Some(50), None
+    /// let lookups = kalshi_instance.get_collection_lookup_history(
+    ///     "SOME-COLLECTION", 300
     /// ).await.unwrap();
     /// ```
     ///
     pub async fn get_collection_lookup_history(
         &self,
         collection_ticker: &str,
-        limit: Option<i64>,
-        cursor: Option<String>,
-    ) -> Result<(Option<String>, Vec<LookupEntry>), KalshiError> {
-        let mut p = Vec::new();
-        add_param!(p, "limit", limit);
-        add_param!(p, "cursor", cursor);
-        let query = if p.is_empty() {
-            String::new()
-        } else {
-            format!("?{}", serde_urlencoded::to_string(&p)?)
-        };
-        let path = format!("/multivariate_event_collections/{collection_ticker}/lookup{query}");
+        lookback_seconds: i64,
+    ) -> Result<Vec<LookupEntry>, KalshiError> {
+        let path = format!(
+            "/multivariate_event_collections/{collection_ticker}/lookup?lookback_seconds={lookback_seconds}"
+        );
         let res: LookupHistoryResponse = self.signed_get(&path).await?;
-        Ok((res.cursor, res.lookups))
+        Ok(res.lookup_points)
     }
 
-    /// Creates a new market within a multivariate event collection.
+    /// Creates the market for a combination of markets within a multivariate event collection.
     ///
-    /// This method adds a new market to an existing collection. The market data
-    /// is provided as a JSON value in the request body.
+    /// This method asks the exchange to create (or return, if it already exists) the
+    /// combo market representing the selected markets and sides.
     ///
     /// # Arguments
     ///
     /// * `collection_ticker` - A string slice referencing the collection's unique ticker identifier.
-    /// * `body` - A reference to a JSON value containing the market data to be added.
+    /// * `request` - The selected markets, one per leg.
     ///
     /// # Returns
     ///
-    /// - `Ok(Collection)`: The updated collection object after adding the market on successful creation.
+    /// - `Ok(CreateMarketInCollectionResponse)`: The combo's event and market tickers on successful creation.
     /// - `Err(KalshiError)`: An error if the user is not authenticated or if there is an issue with the request.
     ///
     /// # Example
     ///
     /// ```
     /// // Assuming `kalshi_instance` is an already authenticated instance of `Kalshi`
-    /// use serde_json::json;
-    /// let market_data = json!({
-    ///     "market_ticker": "NEW-MARKET-2024",
-    ///     "title": "New Market Title"
-    /// });
-    /// let updated_collection = kalshi_instance.create_market_in_collection(
-    ///     "SOME-COLLECTION", &market_data
+    /// use kalshi::{CreateMarketInCollectionRequest, Side, TickerPair};
+    /// let request = CreateMarketInCollectionRequest {
+    ///     selected_markets: vec![
+    ///         TickerPair::new("EVENT-A", "MARKET-A", Side::Yes),
+    ///         TickerPair::new("EVENT-B", "MARKET-B", Side::No),
+    ///     ],
+    ///     with_market_payload: None,
+    /// };
+    /// let created = kalshi_instance.create_market_in_collection(
+    ///     "SOME-COLLECTION", &request
     /// ).await.unwrap();
     /// ```
     ///
     pub async fn create_market_in_collection(
         &self,
         collection_ticker: &str,
-        body: &Value,
-    ) -> Result<Collection, KalshiError> {
+        request: &CreateMarketInCollectionRequest,
+    ) -> Result<CreateMarketInCollectionResponse, KalshiError> {
         let path = format!("/multivariate_event_collections/{collection_ticker}");
-        let res: SingleCollectionResponse = self.signed_post(&path, body).await?;
-        Ok(res.multivariate_event_collection)
+        self.signed_post(&path, request).await
     }
 
-    /// Performs a batch lookup for market tickers within a collection.
+    /// Looks up the market for a combination of markets within a multivariate event collection.
     ///
-    /// This method allows querying multiple market tickers within a collection
-    /// in a single request, returning the updated collection with lookup results.
+    /// Unlike [`create_market_in_collection`](Self::create_market_in_collection), this
+    /// does not create the combo market; it fails if the combination has no market yet.
     ///
     /// # Arguments
     ///
     /// * `collection_ticker` - A string slice referencing the collection's unique ticker identifier.
-    /// * `body` - A reference to a JSON value containing the market tickers to lookup
-    ///   (e.g., `{"market_tickers": ["A", "B", "C"]}`).
+    /// * `request` - The selected markets, one per leg.
     ///
     /// # Returns
     ///
-    /// - `Ok(Collection)`: The updated collection object with lookup results on successful operation.
+    /// - `Ok(LookupTickersResponse)`: The combo's event and market tickers on successful lookup.
     /// - `Err(KalshiError)`: An error if the user is not authenticated or if there is an issue with the request.
     ///
     /// # Example
     ///
     /// ```
     /// // Assuming `kalshi_instance` is an already authenticated instance of `Kalshi`
-    /// use serde_json::json;
-    /// let tickers = json!({
-    ///     "market_tickers": ["MARKET-A", "MARKET-B", "MARKET-C"]
-    /// });
-    /// let result = kalshi_instance.lookup_tickers_for_market(
-    ///     "SOME-COLLECTION", &tickers
+    /// use kalshi::{LookupTickersRequest, Side, TickerPair};
+    /// let request = LookupTickersRequest {
+    ///     selected_markets: vec![
+    ///         TickerPair::new("EVENT-A", "MARKET-A", Side::Yes),
+    ///         TickerPair::new("EVENT-B", "MARKET-B", Side::Yes),
+    ///     ],
+    /// };
+    /// let found = kalshi_instance.lookup_tickers_for_market(
+    ///     "SOME-COLLECTION", &request
     /// ).await.unwrap();
     /// ```
     ///
     pub async fn lookup_tickers_for_market(
         &self,
         collection_ticker: &str,
-        body: &Value,
-    ) -> Result<Collection, KalshiError> {
-        let path = format!("/multivariate_event_collections/{collection_ticker}/tickers");
-        let res: SingleCollectionResponse = self.signed_put(&path, Some(body)).await?;
-        Ok(res.multivariate_event_collection)
+        request: &LookupTickersRequest,
+    ) -> Result<LookupTickersResponse, KalshiError> {
+        let path = format!("/multivariate_event_collections/{collection_ticker}/lookup");
+        self.signed_put(&path, Some(request)).await
     }
 }
 
@@ -209,15 +203,146 @@ impl Kalshi {
 
 /// Represents a multivariate event collection on the Kalshi exchange.
 ///
-/// Collections group related markets together for analysis and management.
-/// The schema is currently untyped as it's not publicly documented.
-pub type Collection = Value;
+/// A collection defines which events can be combined into combo markets, and how
+/// many legs a combination may have.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MultivariateEventCollection {
+    pub collection_ticker: String,
+    #[serde(default)]
+    pub series_ticker: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub open_date: Option<String>,
+    #[serde(default)]
+    pub close_date: Option<String>,
+    /// The events whose markets may be combined.
+    #[serde(default, deserialize_with = "null_to_empty_vec")]
+    pub associated_events: Vec<AssociatedEvent>,
+    /// Tickers of the associated events (older responses list only these).
+    #[serde(default, deserialize_with = "null_to_empty_vec")]
+    pub associated_event_tickers: Vec<String>,
+    /// Whether the order of the legs matters.
+    #[serde(default)]
+    pub is_ordered: bool,
+    /// Whether at most one market per event may be selected.
+    #[serde(default)]
+    pub is_single_market_per_event: bool,
+    /// Whether every leg must be on the YES side.
+    #[serde(default)]
+    pub is_all_yes: bool,
+    /// Fewest legs in a combination.
+    #[serde(default)]
+    pub size_min: i32,
+    /// Most legs in a combination.
+    #[serde(default)]
+    pub size_max: i32,
+    #[serde(default)]
+    pub functional_description: Option<String>,
+    /// Additional fields that may be returned by the API.
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
 
-/// Represents a lookup entry in a collection's history.
-///
-/// Lookup entries track how markets within a collection have been queried.
-/// The schema is currently untyped as it's not publicly documented.
-pub type LookupEntry = Value;
+impl MultivariateEventCollection {
+    /// Tickers of the events whose markets may be combined.
+    pub fn event_tickers(&self) -> Vec<&str> {
+        if self.associated_events.is_empty() {
+            self.associated_event_tickers
+                .iter()
+                .map(String::as_str)
+                .collect()
+        } else {
+            self.associated_events
+                .iter()
+                .map(|e| e.ticker.as_str())
+                .collect()
+        }
+    }
+}
+
+/// Short name for [`MultivariateEventCollection`].
+pub type Collection = MultivariateEventCollection;
+
+/// An event that may be combined within a [`MultivariateEventCollection`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AssociatedEvent {
+    pub ticker: String,
+    /// Whether only YES may be selected for this event's markets.
+    #[serde(default)]
+    pub is_yes_only: bool,
+    /// Fewest markets of this event in a combination.
+    #[serde(default)]
+    pub size_min: Option<i32>,
+    /// Most markets of this event in a combination.
+    #[serde(default)]
+    pub size_max: Option<i32>,
+    #[serde(default, deserialize_with = "null_to_empty_vec")]
+    pub active_quoters: Vec<String>,
+}
+
+/// One leg of a combination: a market and the side selected in it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct TickerPair {
+    pub event_ticker: String,
+    pub market_ticker: String,
+    pub side: Side,
+}
+
+impl TickerPair {
+    pub fn new(event_ticker: &str, market_ticker: &str, side: Side) -> Self {
+        TickerPair {
+            event_ticker: event_ticker.to_string(),
+            market_ticker: market_ticker.to_string(),
+            side,
+        }
+    }
+}
+
+/// A combination looked up in a collection, from [`Kalshi::get_collection_lookup_history`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LookupEntry {
+    pub event_ticker: String,
+    pub market_ticker: String,
+    #[serde(default, deserialize_with = "null_to_empty_vec")]
+    pub selected_markets: Vec<TickerPair>,
+    #[serde(default)]
+    pub last_queried_ts: Option<String>,
+}
+
+/// Request body for [`Kalshi::create_market_in_collection`].
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateMarketInCollectionRequest {
+    pub selected_markets: Vec<TickerPair>,
+    /// Whether to include the created market in the response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub with_market_payload: Option<bool>,
+}
+
+/// Response from [`Kalshi::create_market_in_collection`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateMarketInCollectionResponse {
+    pub event_ticker: String,
+    pub market_ticker: String,
+    /// The created market, when `with_market_payload` was requested.
+    #[serde(default)]
+    pub market: Option<Market>,
+}
+
+/// Request body for [`Kalshi::lookup_tickers_for_market`].
+#[derive(Debug, Clone, Serialize)]
+pub struct LookupTickersRequest {
+    pub selected_markets: Vec<TickerPair>,
+}
+
+/// Response from [`Kalshi::lookup_tickers_for_market`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct LookupTickersResponse {
+    pub event_ticker: String,
+    pub market_ticker: String,
+}
 
 // -------- response wrappers --------
 
@@ -234,6 +359,48 @@ struct SingleCollectionResponse {
 
 #[derive(Debug, Deserialize)]
 struct LookupHistoryResponse {
-    cursor: Option<String>,
-    lookups: Vec<LookupEntry>,
+    #[serde(default, deserialize_with = "null_to_empty_vec")]
+    lookup_points: Vec<LookupEntry>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_collection_and_lookup_models() {
+        let res: SingleCollectionResponse = serde_json::from_value(json!({
+            "multivariate_event_collection": {
+                "collection_ticker": "KXMVESPORTS",
+                "series_ticker": "KXMVESPORTS",
+                "title": "Sports combos",
+                "associated_events": [
+                    { "ticker": "KXNFL-A", "is_yes_only": true, "active_quoters": null },
+                    { "ticker": "KXNFL-B", "is_yes_only": false, "size_max": 2 }
+                ],
+                "is_ordered": false,
+                "is_single_market_per_event": true,
+                "size_min": 2,
+                "size_max": 6,
+                "unrecognized": 1
+            }
+        }))
+        .unwrap();
+        let collection = res.multivariate_event_collection;
+        assert_eq!(collection.event_tickers(), ["KXNFL-A", "KXNFL-B"]);
+        assert!(collection.associated_events[0].is_yes_only);
+        assert_eq!(collection.size_max, 6);
+        assert!(collection.extra.contains_key("unrecognized"));
+
+        let request = LookupTickersRequest {
+            selected_markets: vec![TickerPair::new("KXNFL-A", "KXNFL-A-KC", Side::Yes)],
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({ "selected_markets": [
+                { "event_ticker": "KXNFL-A", "market_ticker": "KXNFL-A-KC", "side": "yes" }
+            ]})
+        );
+    }
 }
@@ -0,0 +1,534 @@
+//! Multivariate combo markets: building selections and resolving them to tickers.
+//!
+//! A [`Combo`] is a parlay-style selection of legs within one multivariate event
+//! collection. A [`ComboCache`] validates combos against the collection's rules, finds
+//! the combo market with `lookup_tickers_for_market` (creating it with
+//! `create_market_in_collection` if it does not exist yet), and remembers the result.
+//! Lookups announced on the [`ComboCache::CHANNELS`] subscription are cached too, so
+//! combos other traders have already created resolve without a request.
+//!
+//! [`value_combo`] and [`price_combo`] estimate a combo's fair value from its legs'
+//! books, assuming independence or using a [`CorrelationModel`], and compare it with the
+//! combo's own book to screen for mispriced parlays.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::{Combo, ComboCache, Side};
+//!
+//! let cache = ComboCache::new();
+//! ws.subscribe(ComboCache::CHANNELS.to_vec(), None, None).await?;
+//!
+//! let combo = Combo::new("KXMVENFLSINGLEGAME")
+//!     .leg("KXNFLGAME-25NOV02KCBUF", "KXNFLGAME-25NOV02KCBUF-KC", Side::Yes)
+//!     .leg("KXNFLTOTAL-25NOV02KCBUF", "KXNFLTOTAL-25NOV02KCBUF-45", Side::No);
+//! let resolved = cache.resolve(&kalshi, &combo).await?;
+//! println!("trade {}", resolved.market_ticker);
+//!
+//! // In the message loop: cache.handle_message(&msg);
+//! ```
+
+use crate::kalshi_error::*;
+use crate::{
+    Channel, Collection, CreateMarketInCollectionRequest, Kalshi, LookupTickersRequest,
+    MultivariateLookupMsg, Side, TickerPair, WebSocketMessage,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+mod pricing;
+
+pub use pricing::*;
+
+/// A selection of legs within a multivariate event collection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Combo {
+    pub collection_ticker: String,
+    pub legs: Vec<TickerPair>,
+}
+
+impl Combo {
+    pub fn new(collection_ticker: &str) -> Self {
+        Combo {
+            collection_ticker: collection_ticker.to_string(),
+            legs: Vec::new(),
+        }
+    }
+
+    /// Adds a leg: `side` of `market_ticker`, a market of `event_ticker`.
+    pub fn leg(mut self, event_ticker: &str, market_ticker: &str, side: Side) -> Self {
+        self.legs
+            .push(TickerPair::new(event_ticker, market_ticker, side));
+        self
+    }
+
+    /// Checks the combo against the collection's rules.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())`: The combo can be created in `collection`.
+    /// - `Err(KalshiError::UserInputError)`: The first rule the combo breaks.
+    ///
+    pub fn validate(&self, collection: &Collection) -> Result<(), KalshiError> {
+        let invalid = |reason: String| {
+            Err(KalshiError::UserInputError(format!(
+                "invalid combo in {}: {}",
+                collection.collection_ticker, reason
+            )))
+        };
+        if self.collection_ticker != collection.collection_ticker {
+            return invalid(format!("combo is for {}", self.collection_ticker));
+        }
+        let size = self.legs.len() as i32;
+        if size == 0 || size < collection.size_min {
+            return invalid(format!(
+                "{} legs, at least {} required",
+                size,
+                collection.size_min.max(1)
+            ));
+        }
+        if collection.size_max > 0 && size > collection.size_max {
+            return invalid(format!(
+                "{} legs, at most {} allowed",
+                size, collection.size_max
+            ));
+        }
+
+        let allowed: HashSet<&str> = collection.event_tickers().into_iter().collect();
+        let mut markets = HashSet::new();
+        let mut per_event: HashMap<&str, i32> = HashMap::new();
+        for leg in &self.legs {
+            if !allowed.contains(leg.event_ticker.as_str()) {
+                return invalid(format!(
+                    "event {} is not in the collection",
+                    leg.event_ticker
+                ));
+            }
+            if !markets.insert(leg.market_ticker.as_str()) {
+                return invalid(format!("market {} selected twice", leg.market_ticker));
+            }
+            *per_event.entry(leg.event_ticker.as_str()).or_insert(0) += 1;
+
+            let event = collection
+                .associated_events
+                .iter()
+                .find(|e| e.ticker == leg.event_ticker);
+            let yes_only = collection.is_all_yes || event.is_some_and(|e| e.is_yes_only);
+            if yes_only && leg.side != Side::Yes {
+                return invalid(format!("{} may only be selected YES", leg.market_ticker));
+            }
+        }
+
+        for (event_ticker, count) in per_event {
+            let event = collection
+                .associated_events
+                .iter()
+                .find(|e| e.ticker == event_ticker);
+            let max = if collection.is_single_market_per_event {
+                Some(1)
+            } else {
+                event.and_then(|e| e.size_max)
+            };
+            if let Some(max) = max.filter(|max| count > *max) {
+                return invalid(format!(
+                    "{} markets of {} selected, at most {} allowed",
+                    count, event_ticker, max
+                ));
+            }
+            if let Some(min) = event.and_then(|e| e.size_min).filter(|min| count < *min) {
+                return invalid(format!(
+                    "{} markets of {} selected, at least {} required",
+                    count, event_ticker, min
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The cache key: legs as (market, side), sorted unless the collection is ordered.
+    fn key(&self, ordered: bool) -> ComboKey {
+        let legs = self
+            .legs
+            .iter()
+            .map(|l| (l.market_ticker.clone(), l.side))
+            .collect();
+        ComboKey::new(&self.collection_ticker, legs, ordered)
+    }
+}
+
+/// The market a [`Combo`] trades as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedCombo {
+    /// The combo's event, when known; lookup messages may omit it.
+    pub event_ticker: Option<String>,
+    pub market_ticker: String,
+    /// Whether this call created the market.
+    pub created: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ComboKey {
+    collection_ticker: String,
+    legs: Vec<(String, Side)>,
+}
+
+impl ComboKey {
+    fn new(collection_ticker: &str, mut legs: Vec<(String, Side)>, ordered: bool) -> Self {
+        if !ordered {
+            legs.sort_by(|a, b| {
+                a.0.cmp(&b.0)
+                    .then((a.1 == Side::No).cmp(&(b.1 == Side::No)))
+            });
+        }
+        ComboKey {
+            collection_ticker: collection_ticker.to_string(),
+            legs,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    collections: HashMap<String, Collection>,
+    combos: HashMap<ComboKey, ResolvedCombo>,
+}
+
+/// Collections and resolved combo markets. See the [module documentation](crate::combo).
+#[derive(Debug)]
+pub struct ComboCache {
+    state: Mutex<CacheState>,
+}
+
+impl Default for ComboCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ComboCache {
+    /// The channel that announces combo lookups.
+    pub const CHANNELS: [Channel; 1] = [Channel::Multivariate];
+
+    pub fn new() -> Self {
+        ComboCache {
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Adds or replaces a collection.
+    pub fn insert_collection(&self, collection: Collection) {
+        self.lock()
+            .collections
+            .insert(collection.collection_ticker.clone(), collection);
+    }
+
+    /// The cached collection, if loaded.
+    pub fn cached_collection(&self, collection_ticker: &str) -> Option<Collection> {
+        self.lock().collections.get(collection_ticker).cloned()
+    }
+
+    /// Returns a collection, fetching and caching it if needed.
+    pub async fn collection(
+        &self,
+        kalshi: &Kalshi,
+        collection_ticker: &str,
+    ) -> Result<Collection, KalshiError> {
+        if let Some(collection) = self.cached_collection(collection_ticker) {
+            return Ok(collection);
+        }
+        let collection = kalshi
+            .get_multivariate_event_collection(collection_ticker)
+            .await?;
+        self.insert_collection(collection.clone());
+        Ok(collection)
+    }
+
+    /// The cached market for a combo, if resolved or announced before.
+    pub fn cached(&self, combo: &Combo) -> Option<ResolvedCombo> {
+        let state = self.lock();
+        let ordered = state
+            .collections
+            .get(&combo.collection_ticker)
+            .is_some_and(|c| c.is_ordered);
+        state
+            .combos
+            .get(&combo.key(ordered))
+            .cloned()
+            .map(|r| ResolvedCombo {
+                created: false,
+                ..r
+            })
+    }
+
+    /// Validates a combo and returns its market, creating the market if the lookup reports
+    /// that it does not exist. Any other lookup error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `kalshi` - Client used for the collection, lookup and create requests.
+    /// * `combo` - The selection to resolve.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(ResolvedCombo)`: The combo's market, from the cache, a lookup or a create.
+    /// - `Err(KalshiError)`: The combo is invalid or a request failed.
+    ///
+    pub async fn resolve(
+        &self,
+        kalshi: &Kalshi,
+        combo: &Combo,
+    ) -> Result<ResolvedCombo, KalshiError> {
+        self.resolve_inner(kalshi, combo, true).await
+    }
+
+    /// Like [`resolve`](Self::resolve), but fails instead of creating a missing market.
+    pub async fn lookup(
+        &self,
+        kalshi: &Kalshi,
+        combo: &Combo,
+    ) -> Result<ResolvedCombo, KalshiError> {
+        self.resolve_inner(kalshi, combo, false).await
+    }
+
+    async fn resolve_inner(
+        &self,
+        kalshi: &Kalshi,
+        combo: &Combo,
+        create: bool,
+    ) -> Result<ResolvedCombo, KalshiError> {
+        let collection = self.collection(kalshi, &combo.collection_ticker).await?;
+        combo.validate(&collection)?;
+        if let Some(resolved) = self.cached(combo) {
+            return Ok(resolved);
+        }
+
+        let request = LookupTickersRequest {
+            selected_markets: combo.legs.clone(),
+        };
+        let resolved = match kalshi
+            .lookup_tickers_for_market(&combo.collection_ticker, &request)
+            .await
+        {
+            Ok(found) => ResolvedCombo {
+                event_ticker: Some(found.event_ticker),
+                market_ticker: found.market_ticker,
+                created: false,
+            },
+            Err(e) if create && e.is_not_found() => {
+                let request = CreateMarketInCollectionRequest {
+                    selected_markets: combo.legs.clone(),
+                    with_market_payload: None,
+                };
+                let created = kalshi
+                    .create_market_in_collection(&combo.collection_ticker, &request)
+                    .await?;
+                ResolvedCombo {
+                    event_ticker: Some(created.event_ticker),
+                    market_ticker: created.market_ticker,
+                    created: true,
+                }
+            }
+            Err(e) => return Err(e),
+        };
+        self.lock().combos.insert(
+            combo.key(collection.is_ordered),
+            ResolvedCombo {
+                created: false,
+                ..resolved.clone()
+            },
+        );
+        Ok(resolved)
+    }
+
+    /// Caches combo lookups from `multivariate` messages. Other messages are ignored.
+    ///
+    /// # Returns
+    ///
+    /// The combo market ticker if the message was cached.
+    ///
+    pub fn handle_message(&self, msg: &WebSocketMessage) -> Option<String> {
+        match msg {
+            WebSocketMessage::MultivariateLookup(lookup) => self.apply_lookup(lookup),
+            _ => None,
+        }
+    }
+
+    /// Caches a combo lookup. Lookups with an unrecognized side are skipped.
+    pub fn apply_lookup(&self, msg: &MultivariateLookupMsg) -> Option<String> {
+        let legs = msg
+            .legs()?
+            .into_iter()
+            .map(|l| (l.market_ticker, l.side))
+            .collect();
+        let mut state = self.lock();
+        let ordered = state
+            .collections
+            .get(&msg.collection_ticker)
+            .is_some_and(|c| c.is_ordered);
+        let key = ComboKey::new(&msg.collection_ticker, legs, ordered);
+        let entry = state.combos.entry(key).or_insert_with(|| ResolvedCombo {
+            event_ticker: None,
+            market_ticker: msg.market_ticker.clone(),
+            created: false,
+        });
+        entry.market_ticker = msg.market_ticker.clone();
+        if msg.event_ticker.is_some() {
+            entry.event_ticker = msg.event_ticker.clone();
+        }
+        Some(msg.market_ticker.clone())
+    }
+
+    /// Number of cached combos.
+    pub fn len(&self) -> usize {
+        self.lock().combos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().combos.is_empty()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl MultivariateLookupMsg {
+    /// The combo's legs, or `None` if a side is unrecognized. `event_ticker` is empty
+    /// for legs the message sent without one.
+    pub fn legs(&self) -> Option<Vec<TickerPair>> {
+        self.selected_markets
+            .iter()
+            .map(|m| {
+                let side = match m.side.as_str() {
+                    "yes" => Side::Yes,
+                    "no" => Side::No,
+                    _ => return None,
+                };
+                let event_ticker = m.event_ticker.as_deref().unwrap_or_default();
+                Some(TickerPair::new(event_ticker, &m.market_ticker, side))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SelectedMarket;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate_and_cache_lookups() {
+        let collection: Collection = serde_json::from_value(json!({
+            "collection_ticker": "KXMVE",
+            "associated_events": [
+                { "ticker": "EV-A", "is_yes_only": true },
+                { "ticker": "EV-B", "is_yes_only": false }
+            ],
+            "is_single_market_per_event": true,
+            "size_min": 2,
+            "size_max": 3
+        }))
+        .unwrap();
+
+        let combo =
+            Combo::new("KXMVE")
+                .leg("EV-B", "EV-B-2", Side::No)
+                .leg("EV-A", "EV-A-1", Side::Yes);
+        assert!(combo.validate(&collection).is_ok());
+        let invalid = [
+            Combo::new("KXMVE").leg("EV-A", "EV-A-1", Side::Yes),
+            Combo::new("KXMVE")
+                .leg("EV-A", "EV-A-1", Side::No)
+                .leg("EV-B", "EV-B-2", Side::Yes),
+            Combo::new("KXMVE")
+                .leg("EV-B", "EV-B-1", Side::Yes)
+                .leg("EV-B", "EV-B-2", Side::Yes),
+            Combo::new("KXMVE")
+                .leg("EV-A", "EV-A-1", Side::Yes)
+                .leg("EV-C", "EV-C-1", Side::Yes),
+        ];
+        for combo in &invalid {
+            assert!(combo.validate(&collection).is_err(), "{:?}", combo);
+        }
+
+        let cache = ComboCache::new();
+        cache.insert_collection(collection);
+        let msg = WebSocketMessage::MultivariateLookup(MultivariateLookupMsg {
+            collection_ticker: "KXMVE".to_string(),
+            event_ticker: None,
+            market_ticker: "KXMVE-S1-ABC".to_string(),
+            selected_markets: vec![
+                SelectedMarket {
+                    event_ticker: None,
+                    market_ticker: "EV-A-1".to_string(),
+                    side: "yes".to_string(),
+                },
+                SelectedMarket {
+                    event_ticker: None,
+                    market_ticker: "EV-B-2".to_string(),
+                    side: "no".to_string(),
+                },
+            ],
+        });
+        assert_eq!(cache.handle_message(&msg).as_deref(), Some("KXMVE-S1-ABC"));
+        // The collection is unordered, so leg order does not matter.
+        let cached = cache.cached(&combo).unwrap();
+        assert_eq!(cached.market_ticker, "KXMVE-S1-ABC");
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_creates_missing_markets() {
+        let collection: Collection = serde_json::from_value(json!({
+            "collection_ticker": "KXMVE",
+            "associated_events": [
+                { "ticker": "EV-A", "is_yes_only": false },
+                { "ticker": "EV-B", "is_yes_only": false }
+            ],
+            "size_min": 2,
+            "size_max": 2
+        }))
+        .unwrap();
+        let combo =
+            Combo::new("KXMVE")
+                .leg("EV-A", "EV-A-1", Side::Yes)
+                .leg("EV-B", "EV-B-2", Side::No);
+        let (url, seen) = crate::test_server(|line| {
+            if line.starts_with("PUT") {
+                (
+                    "404 Not Found",
+                    r#"{"error":{"code":"not_found"}}"#.to_string(),
+                )
+            } else {
+                (
+                    "201 Created",
+                    r#"{"event_ticker":"KXMVE-S1","market_ticker":"KXMVE-S1-ABC"}"#.to_string(),
+                )
+            }
+        })
+        .await;
+        let mut kalshi = Kalshi::test_client();
+        kalshi.base_url = url;
+
+        let lookup_only = ComboCache::new();
+        lookup_only.insert_collection(collection.clone());
+        let err = lookup_only.lookup(&kalshi, &combo).await.unwrap_err();
+        assert!(err.is_not_found(), "{:?}", err);
+
+        let cache = ComboCache::new();
+        cache.insert_collection(collection);
+        let resolved = cache.resolve(&kalshi, &combo).await.unwrap();
+        assert!(resolved.created);
+        assert_eq!(resolved.market_ticker, "KXMVE-S1-ABC");
+        assert!(!cache.resolve(&kalshi, &combo).await.unwrap().created);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                "PUT /multivariate_event_collections/KXMVE/lookup",
+                "PUT /multivariate_event_collections/KXMVE/lookup",
+                "POST /multivariate_event_collections/KXMVE",
+            ]
+        );
+    }
+}
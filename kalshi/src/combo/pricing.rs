@@ -0,0 +1,359 @@
+use crate::kalshi_error::*;
+use crate::positions::best_level;
+use crate::{MarketData, Orderbook, Side, TickerPair};
+use std::collections::HashMap;
+
+/// Integration range and step for [`GaussianCopula`], in standard deviations.
+const COPULA_Z_RANGE: f64 = 8.0;
+const COPULA_Z_STEP: f64 = 0.02;
+
+/// Estimates the probability that every leg of a combo wins.
+///
+/// Closures taking the legs and their marginal probabilities (0 to 1, in leg order) and
+/// returning the joint probability are models.
+pub trait CorrelationModel: Send + Sync {
+    fn joint_probability(&self, legs: &[TickerPair], probabilities: &[f64]) -> f64;
+}
+
+impl<F: Fn(&[TickerPair], &[f64]) -> f64 + Send + Sync> CorrelationModel for F {
+    fn joint_probability(&self, legs: &[TickerPair], probabilities: &[f64]) -> f64 {
+        self(legs, probabilities)
+    }
+}
+
+/// One-factor Gaussian copula: every pair of legs has latent correlation `rho`.
+///
+/// `rho` is clamped to `[0, 0.999]`; zero is the independence assumption, and larger
+/// values make legs more likely to win together, raising the combo's fair value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GaussianCopula {
+    pub rho: f64,
+}
+
+impl CorrelationModel for GaussianCopula {
+    fn joint_probability(&self, _legs: &[TickerPair], probabilities: &[f64]) -> f64 {
+        if probabilities.iter().any(|p| *p <= 0.0) {
+            return 0.0;
+        }
+        let rho = self.rho.clamp(0.0, 0.999);
+        // Legs certain to win do not affect the joint probability.
+        let thresholds: Vec<f64> = probabilities
+            .iter()
+            .filter(|p| **p < 1.0)
+            .map(|p| inverse_normal_cdf(*p))
+            .collect();
+        let (a, b) = (rho.sqrt(), (1.0 - rho).sqrt());
+
+        let steps = (2.0 * COPULA_Z_RANGE / COPULA_Z_STEP) as usize;
+        (0..=steps)
+            .map(|i| {
+                let z = -COPULA_Z_RANGE + i as f64 * COPULA_Z_STEP;
+                let conditional: f64 = thresholds
+                    .iter()
+                    .map(|t| normal_cdf((t - a * z) / b))
+                    .product();
+                let weight = if i == 0 || i == steps { 0.5 } else { 1.0 };
+                weight * normal_pdf(z) * conditional * COPULA_Z_STEP
+            })
+            .sum::<f64>()
+            .clamp(0.0, 1.0)
+    }
+}
+
+/// A leg's quote for the side selected in the combo, in cents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegQuote {
+    pub market_ticker: String,
+    pub side: Side,
+    pub bid: Option<f64>,
+    pub ask: Option<f64>,
+}
+
+impl LegQuote {
+    fn from_book(leg: &TickerPair, book: Option<&Orderbook>) -> Self {
+        let (bid, ask) = book.map_or((None, None), |book| book_quote(book, leg.side));
+        LegQuote {
+            market_ticker: leg.market_ticker.clone(),
+            side: leg.side,
+            bid,
+            ask,
+        }
+    }
+
+    /// Midpoint of a two-sided quote.
+    pub fn mid(&self) -> Option<f64> {
+        self.bid.zip(self.ask).map(|(b, a)| (b + a) / 2.0)
+    }
+}
+
+/// A combo's fair value from its legs' books, next to its own book. Prices are YES
+/// prices in cents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComboValuation {
+    pub market_ticker: String,
+    pub legs: Vec<LegQuote>,
+    /// Product of the leg mids, if every leg has a two-sided book.
+    pub independent: Option<f64>,
+    /// Product of the leg bids; zero if any leg has no bid.
+    pub independent_low: f64,
+    /// Product of the leg asks; a leg with no ask counts as 100.
+    pub independent_high: f64,
+    /// Fair value from the leg mids under the correlation model, if one was given.
+    pub correlated: Option<f64>,
+    pub combo_bid: Option<f64>,
+    pub combo_ask: Option<f64>,
+}
+
+impl ComboValuation {
+    /// The correlated fair value if available, else the independent one.
+    pub fn fair(&self) -> Option<f64> {
+        self.correlated.or(self.independent)
+    }
+
+    /// Cents by which the combo's ask is below fair value; positive means cheap to buy.
+    pub fn buy_edge(&self) -> Option<f64> {
+        Some(self.fair()? - self.combo_ask?)
+    }
+
+    /// Cents by which the combo's bid is above fair value; positive means rich to sell.
+    pub fn sell_edge(&self) -> Option<f64> {
+        Some(self.combo_bid? - self.fair()?)
+    }
+}
+
+/// Values a combo from books at hand, e.g. an [`OrderbookCache`](crate::OrderbookCache).
+///
+/// # Arguments
+///
+/// * `market_ticker` - The combo market, whose book is compared with the estimate.
+/// * `legs` - The combo's legs, e.g. from [`MultivariateLookupMsg::legs`](crate::MultivariateLookupMsg::legs).
+/// * `books` - Returns the book for a ticker, or `None` if it is unknown.
+/// * `model` - Optional correlation model for [`ComboValuation::correlated`].
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use kalshi::{value_combo, GaussianCopula};
+///
+/// let legs = lookup.legs().unwrap_or_default();
+/// let model = GaussianCopula { rho: 0.3 };
+/// let valuation = value_combo(&lookup.market_ticker, &legs, |t| cache.get(t), Some(&model));
+/// if valuation.buy_edge().is_some_and(|edge| edge > 2.0) {
+///     println!("{} looks cheap", valuation.market_ticker);
+/// }
+/// ```
+///
+pub fn value_combo(
+    market_ticker: &str,
+    legs: &[TickerPair],
+    books: impl Fn(&str) -> Option<Orderbook>,
+    model: Option<&dyn CorrelationModel>,
+) -> ComboValuation {
+    let quotes: Vec<LegQuote> = legs
+        .iter()
+        .map(|leg| LegQuote::from_book(leg, books(&leg.market_ticker).as_ref()))
+        .collect();
+    let (combo_bid, combo_ask) =
+        books(market_ticker).map_or((None, None), |book| book_quote(&book, Side::Yes));
+
+    let mids: Option<Vec<f64>> = quotes.iter().map(|q| q.mid().map(|m| m / 100.0)).collect();
+    let independent = mids.as_ref().map(|m| 100.0 * m.iter().product::<f64>());
+    let correlated = mids
+        .as_ref()
+        .zip(model)
+        .map(|(m, model)| 100.0 * model.joint_probability(legs, m));
+    let independent_low = 100.0
+        * quotes
+            .iter()
+            .map(|q| q.bid.unwrap_or(0.0) / 100.0)
+            .product::<f64>();
+    let independent_high = 100.0
+        * quotes
+            .iter()
+            .map(|q| q.ask.unwrap_or(100.0) / 100.0)
+            .product::<f64>();
+
+    ComboValuation {
+        market_ticker: market_ticker.to_string(),
+        legs: quotes,
+        independent,
+        independent_low,
+        independent_high,
+        correlated,
+        combo_bid,
+        combo_ask,
+    }
+}
+
+/// Fetches the combo's and its legs' books and values the combo.
+///
+/// # Returns
+///
+/// - `Ok(ComboValuation)`: The valuation.
+/// - `Err(KalshiError)`: A book could not be fetched.
+///
+pub async fn price_combo<B: MarketData>(
+    backend: &B,
+    market_ticker: &str,
+    legs: &[TickerPair],
+    model: Option<&dyn CorrelationModel>,
+) -> Result<ComboValuation, KalshiError> {
+    let mut books = HashMap::new();
+    for ticker in legs
+        .iter()
+        .map(|l| l.market_ticker.as_str())
+        .chain(std::iter::once(market_ticker))
+    {
+        if !books.contains_key(ticker) {
+            books.insert(
+                ticker.to_string(),
+                backend.get_orderbook(ticker, Some(1)).await?,
+            );
+        }
+    }
+    Ok(value_combo(
+        market_ticker,
+        legs,
+        |t| books.get(t).cloned(),
+        model,
+    ))
+}
+
+/// Best bid and ask in cents for `side`: a side's ask is 100 minus the other side's bid.
+fn book_quote(book: &Orderbook, side: Side) -> (Option<f64>, Option<f64>) {
+    let yes = best_level(book.yes.as_ref());
+    let no = best_level(book.no.as_ref());
+    let (bid, other) = match side {
+        Side::Yes => (yes, no),
+        Side::No => (no, yes),
+    };
+    (bid.map(f64::from), other.map(|p| 100.0 - p as f64))
+}
+
+fn normal_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Standard normal CDF (Abramowitz and Stegun 7.1.26; error below 1e-7).
+fn normal_cdf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs() / std::f64::consts::SQRT_2);
+    let poly = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let erf = 1.0 - poly * (-(x * x) / 2.0).exp();
+    if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+/// Inverse standard normal CDF (Acklam's rational approximation) for `p` in (0, 1).
+fn inverse_normal_cdf(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const P_LOW: f64 = 0.024_25;
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - P_LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(yes: &[(i32, i32)], no: &[(i32, i32)]) -> Orderbook {
+        let levels = |l: &[(i32, i32)]| Some(l.iter().map(|(p, c)| vec![*p, *c]).collect());
+        Orderbook {
+            yes: levels(yes),
+            no: levels(no),
+            yes_dollars: Vec::new(),
+            no_dollars: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_value_combo_from_leg_books() {
+        let legs = vec![
+            TickerPair::new("EV-A", "EV-A-1", Side::Yes),
+            TickerPair::new("EV-B", "EV-B-1", Side::No),
+        ];
+        let books: HashMap<String, Orderbook> = [
+            // EV-A-1 YES 58/62, mid 60.
+            ("EV-A-1", book(&[(58, 10)], &[(38, 10)])),
+            // EV-B-1 NO 48/52, mid 50.
+            ("EV-B-1", book(&[(48, 10)], &[(48, 10), (40, 5)])),
+            // The combo trades 22/25.
+            ("KXMVE-S1", book(&[(22, 3)], &[(75, 3)])),
+        ]
+        .into_iter()
+        .map(|(t, b)| (t.to_string(), b))
+        .collect();
+
+        let independent = value_combo("KXMVE-S1", &legs, |t| books.get(t).cloned(), None);
+        assert!((independent.independent.unwrap() - 30.0).abs() < 1e-9);
+        assert!((independent.independent_low - 0.58 * 0.48 * 100.0).abs() < 1e-9);
+        assert!((independent.buy_edge().unwrap() - 5.0).abs() < 1e-9);
+        assert!(independent.correlated.is_none());
+
+        let copula = GaussianCopula { rho: 0.0 };
+        let uncorrelated = value_combo("KXMVE-S1", &legs, |t| books.get(t).cloned(), Some(&copula));
+        assert!((uncorrelated.correlated.unwrap() - 30.0).abs() < 1e-3);
+
+        let copula = GaussianCopula { rho: 0.5 };
+        let correlated = value_combo("KXMVE-S1", &legs, |t| books.get(t).cloned(), Some(&copula));
+        let fair = correlated.fair().unwrap();
+        assert!(fair > 30.0 && fair < 50.0, "{}", fair);
+
+        // A leg without a book leaves only the bounds.
+        let partial = value_combo(
+            "KXMVE-S1",
+            &legs,
+            |t| (t != "EV-B-1").then(|| books[t].clone()),
+            None,
+        );
+        assert_eq!(partial.independent, None);
+        assert_eq!(partial.independent_low, 0.0);
+        assert_eq!(partial.buy_edge(), None);
+    }
+}
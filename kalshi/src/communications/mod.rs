@@ -603,10 +603,18 @@ pub struct Quote {
     pub id: String,
     /// The RFQ this quote responds to.
     pub rfq_id: Option<String>,
-    /// The quoted yes bid price in dollars.
-    pub yes_bid: Option<String>,
-    /// The quoted no bid price in dollars.
-    pub no_bid: Option<String>,
+    /// The quoted yes bid price in cents.
+    #[serde(default, deserialize_with = "cents_or_dollars")]
+    pub yes_bid: Option<i32>,
+    /// The quoted no bid price in cents.
+    #[serde(default, deserialize_with = "cents_or_dollars")]
+    pub no_bid: Option<i32>,
+    /// The quoted yes bid price in dollars ("0.5600" format).
+    #[serde(default)]
+    pub yes_bid_dollars: Option<String>,
+    /// The quoted no bid price in dollars ("0.5600" format).
+    #[serde(default)]
+    pub no_bid_dollars: Option<String>,
     /// The quoted price in cents (legacy).
     pub price: Option<i32>,
     /// The quoted quantity (legacy).
@@ -628,6 +636,37 @@ pub struct Quote {
     pub extra: std::collections::HashMap<String, serde_json::Value>,
 }
 
+impl Quote {
+    /// The quoted prices in cents, if both sides are present.
+    pub fn price(&self) -> Option<crate::QuotePrice> {
+        Some(crate::QuotePrice {
+            yes_bid: self.yes_bid?,
+            no_bid: self.no_bid?,
+        })
+    }
+}
+
+/// Reads a price given either as integer cents or, as older responses did, as a
+/// dollar string ("0.5600").
+fn cents_or_dollars<'de, D>(d: D) -> Result<Option<i32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+    match Option::<serde_json::Value>::deserialize(d)? {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(serde_json::Value::Number(n)) => n
+            .as_i64()
+            .map(|c| Some(c as i32))
+            .ok_or_else(|| D::Error::custom(format!("invalid cents price {}", n))),
+        Some(serde_json::Value::String(s)) => s
+            .parse::<f64>()
+            .map(|d| Some((d * 100.0).round() as i32))
+            .map_err(|_| D::Error::custom(format!("invalid dollar price `{}`", s))),
+        Some(other) => Err(D::Error::custom(format!("invalid price {}", other))),
+    }
+}
+
 /// Represents a confirmed quote.
 #[derive(Debug, Deserialize, Serialize)]
 pub struct QuoteConfirmed {
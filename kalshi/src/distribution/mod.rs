@@ -0,0 +1,431 @@
+//! Implied distributions of scalar outcomes from strike ladders.
+//!
+//! Markets with `floor_strike`, `cap_strike` and `strike_type` pay on a numeric outcome:
+//! "high temperature above 60°", "CPI between 0.2% and 0.3%". Read together, the markets
+//! of an event price the distribution of that outcome. [`StrikeLadder`] turns them into
+//! points of the cumulative distribution function `F(x) = P(outcome ≤ x)`:
+//!
+//! - **Brackets** (mutually exclusive events): each market covers a range; a `less`
+//!   market is the lower tail and a `greater` market the upper tail. Prices are
+//!   normalized to sum to 1 and accumulated from the lowest bracket, giving `F` at each
+//!   bracket's upper bound.
+//! - **Thresholds** (other events): a `greater` market at `k` prices `1 − F(k)` and a
+//!   `less` market prices `F(k)`.
+//!
+//! Market prices are noisy, so the points are made non-decreasing by pooling adjacent
+//! violators. Between points the CDF is linear, which makes the density piecewise
+//! constant. Strict and non-strict inequalities are treated alike.
+//!
+//! Probabilities come from the Yes mid when a market is quoted on both sides and from the
+//! last price otherwise.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::StrikeLadder;
+//!
+//! let ladder = StrikeLadder::fetch(&kalshi, "KXHIGHNY-24JAN01").await?;
+//! println!("median {:?}, P(≤ 60) = {:.2}", ladder.median(), ladder.cdf(60.0));
+//!
+//! let forecast = kalshi.get_event_forecast_percentile_history("KXHIGHNY-24JAN01").await?;
+//! if let Some(latest) = forecast.history.last() {
+//!     for c in ladder.compare_forecast(latest) {
+//!         println!("p{}: forecast {} market {:?}", c.percentile * 100.0, c.forecast, c.market);
+//!     }
+//! }
+//! ```
+
+use crate::backend::event_with_markets;
+use crate::kalshi_error::*;
+use crate::{Event, ForecastDataPoint, Market, MarketData};
+
+/// A point of the cumulative distribution function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CdfPoint {
+    pub strike: f64,
+    /// `P(outcome ≤ strike)`.
+    pub cdf: f64,
+}
+
+/// Probability mass between two strikes. Unbounded ends are `None`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProbabilityBucket {
+    pub lower: Option<f64>,
+    pub upper: Option<f64>,
+    pub probability: f64,
+}
+
+impl ProbabilityBucket {
+    /// Probability per unit of the outcome, for bounded buckets.
+    pub fn density(&self) -> Option<f64> {
+        let width = self.upper? - self.lower?;
+        (width > 0.0).then(|| self.probability / width)
+    }
+}
+
+/// A forecast percentile next to the market's value at the same percentile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForecastComparison {
+    /// Between 0 and 1.
+    pub percentile: f64,
+    /// Forecast value at the percentile.
+    pub forecast: f64,
+    /// Market-implied value at the percentile, if inside the ladder.
+    pub market: Option<f64>,
+    /// Market-implied probability that the outcome is at most the forecast value.
+    pub market_cdf: f64,
+}
+
+/// Implied CDF of an event's outcome. See the [module documentation](crate::distribution).
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrikeLadder {
+    points: Vec<CdfPoint>,
+}
+
+impl StrikeLadder {
+    /// Builds the ladder from CDF points, in any order.
+    pub fn from_points(mut points: Vec<CdfPoint>) -> Self {
+        points.retain(|p| p.strike.is_finite() && p.cdf.is_finite());
+        points.sort_by(|a, b| a.strike.total_cmp(&b.strike));
+        StrikeLadder {
+            points: make_monotone(points),
+        }
+    }
+
+    /// Builds the ladder from an event's nested markets.
+    pub fn from_event(event: &Event) -> Self {
+        let markets: Vec<&Market> = event.markets.iter().flatten().collect();
+        Self::from_markets(&markets, event.mutually_exclusive)
+    }
+
+    /// Builds the ladder from markets of one event. `mutually_exclusive` selects bracket
+    /// or threshold interpretation. Markets without strikes are skipped.
+    pub fn from_markets(markets: &[&Market], mutually_exclusive: bool) -> Self {
+        let points = if mutually_exclusive {
+            bracket_points(markets)
+        } else {
+            markets
+                .iter()
+                .filter_map(|m| {
+                    let p = probability(m)?;
+                    match strike_range(m)? {
+                        (Some(floor), None) => Some(CdfPoint {
+                            strike: floor,
+                            cdf: 1.0 - p,
+                        }),
+                        (None, Some(cap)) => Some(CdfPoint {
+                            strike: cap,
+                            cdf: p,
+                        }),
+                        _ => None,
+                    }
+                })
+                .collect()
+        };
+        Self::from_points(points)
+    }
+
+    /// Fetches `event_ticker` and its markets and builds the ladder.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - Where to read the event and markets from.
+    /// * `event_ticker` - The event to build the ladder for.
+    ///
+    pub async fn fetch<B: MarketData>(
+        backend: &B,
+        event_ticker: &str,
+    ) -> Result<StrikeLadder, KalshiError> {
+        Ok(Self::from_event(
+            &event_with_markets(backend, event_ticker).await?,
+        ))
+    }
+
+    /// The CDF points, by increasing strike.
+    pub fn points(&self) -> &[CdfPoint] {
+        &self.points
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// `P(outcome ≤ x)`, interpolated linearly between strikes. Outside the ladder this is
+    /// the CDF at the nearest end.
+    pub fn cdf(&self, x: f64) -> f64 {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return 0.0;
+        };
+        if x <= first.strike {
+            return first.cdf;
+        }
+        if x >= last.strike {
+            return last.cdf;
+        }
+        let i = self.points.partition_point(|p| p.strike <= x);
+        let (a, b) = (self.points[i - 1], self.points[i]);
+        a.cdf + (b.cdf - a.cdf) * (x - a.strike) / (b.strike - a.strike)
+    }
+
+    /// The outcome value with `P(outcome ≤ value) = q`, or `None` when `q` falls in a tail
+    /// beyond the ladder's strikes.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let first = self.points.first()?;
+        let last = self.points.last()?;
+        if q < first.cdf || q > last.cdf {
+            return None;
+        }
+        let i = self.points.partition_point(|p| p.cdf < q);
+        let b = self.points[i];
+        if i == 0 || b.cdf == q {
+            return Some(b.strike);
+        }
+        let a = self.points[i - 1];
+        Some(a.strike + (b.strike - a.strike) * (q - a.cdf) / (b.cdf - a.cdf))
+    }
+
+    pub fn median(&self) -> Option<f64> {
+        self.quantile(0.5)
+    }
+
+    /// Probability mass between consecutive strikes, with an unbounded bucket at each end.
+    pub fn pdf(&self) -> Vec<ProbabilityBucket> {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return Vec::new();
+        };
+        let mut buckets = vec![ProbabilityBucket {
+            lower: None,
+            upper: Some(first.strike),
+            probability: first.cdf,
+        }];
+        for pair in self.points.windows(2) {
+            buckets.push(ProbabilityBucket {
+                lower: Some(pair[0].strike),
+                upper: Some(pair[1].strike),
+                probability: pair[1].cdf - pair[0].cdf,
+            });
+        }
+        buckets.push(ProbabilityBucket {
+            lower: Some(last.strike),
+            upper: None,
+            probability: 1.0 - last.cdf,
+        });
+        buckets
+    }
+
+    /// Approximate mean: bounded buckets at their midpoint, tails at their finite bound.
+    pub fn mean(&self) -> Option<f64> {
+        if self.points.is_empty() {
+            return None;
+        }
+        let mean = self
+            .pdf()
+            .iter()
+            .map(|b| {
+                let at = match (b.lower, b.upper) {
+                    (Some(lo), Some(hi)) => (lo + hi) / 2.0,
+                    (Some(bound), None) | (None, Some(bound)) => bound,
+                    (None, None) => 0.0,
+                };
+                at * b.probability
+            })
+            .sum();
+        Some(mean)
+    }
+
+    /// Compares a point of `get_event_forecast_percentile_history` with the ladder.
+    ///
+    /// Percentile keys are read as percentages (`"10"`, `"p90"`, `"50.0"`); keys without
+    /// a number are skipped. Results are sorted by percentile.
+    pub fn compare_forecast(&self, forecast: &ForecastDataPoint) -> Vec<ForecastComparison> {
+        let mut out: Vec<ForecastComparison> = forecast
+            .percentiles
+            .iter()
+            .filter_map(|(key, value)| {
+                let digits: String = key
+                    .chars()
+                    .filter(|c| c.is_ascii_digit() || *c == '.')
+                    .collect();
+                let percentile = digits.parse::<f64>().ok()? / 100.0;
+                Some(ForecastComparison {
+                    percentile,
+                    forecast: *value,
+                    market: self.quantile(percentile),
+                    market_cdf: self.cdf(*value),
+                })
+            })
+            .collect();
+        out.sort_by(|a, b| a.percentile.total_cmp(&b.percentile));
+        out
+    }
+}
+
+/// CDF points of mutually exclusive brackets, accumulated from the lowest.
+fn bracket_points(markets: &[&Market]) -> Vec<CdfPoint> {
+    let mut brackets: Vec<(Option<f64>, Option<f64>, f64)> = markets
+        .iter()
+        .filter_map(|m| {
+            let (lower, upper) = strike_range(m)?;
+            Some((lower, upper, probability(m)?))
+        })
+        .collect();
+    let total: f64 = brackets.iter().map(|b| b.2).sum();
+    if total <= 0.0 {
+        return Vec::new();
+    }
+    // Lower tail first, then by lower bound.
+    brackets.sort_by(|a, b| {
+        let key = |r: &(Option<f64>, Option<f64>, f64)| r.0.unwrap_or(f64::NEG_INFINITY);
+        key(a).total_cmp(&key(b))
+    });
+    let mut points = Vec::new();
+    if let Some(lower) = brackets.first().and_then(|b| b.0) {
+        // No lower tail market: no mass below the lowest bracket.
+        points.push(CdfPoint {
+            strike: lower,
+            cdf: 0.0,
+        });
+    }
+    let mut cumulative = 0.0;
+    for (_, upper, p) in brackets {
+        cumulative += p / total;
+        if let Some(upper) = upper {
+            points.push(CdfPoint {
+                strike: upper,
+                cdf: cumulative,
+            });
+        }
+    }
+    points
+}
+
+/// `(lower, upper)` bounds of the outcomes a market's Yes covers.
+fn strike_range(m: &Market) -> Option<(Option<f64>, Option<f64>)> {
+    let kind = m
+        .strike_type
+        .as_deref()
+        .unwrap_or(match (m.floor_strike, m.cap_strike) {
+            (Some(_), Some(_)) => "between",
+            (Some(_), None) => "greater",
+            (None, Some(_)) => "less",
+            (None, None) => return None,
+        });
+    match kind {
+        "greater" | "greater_or_equal" => Some((Some(m.floor_strike?), None)),
+        "less" | "less_or_equal" => Some((None, Some(m.cap_strike?))),
+        "between" => Some((Some(m.floor_strike?), Some(m.cap_strike?))),
+        _ => None,
+    }
+}
+
+/// Yes probability from the mid when quoted on both sides, else the last price.
+fn probability(m: &Market) -> Option<f64> {
+    let cents = if m.yes_bid > 0 && m.yes_ask > 0 && m.yes_ask < 100 {
+        (m.yes_bid + m.yes_ask) as f64 / 2.0
+    } else if m.last_price > 0 {
+        m.last_price as f64
+    } else {
+        return None;
+    };
+    Some((cents / 100.0).clamp(0.0, 1.0))
+}
+
+/// Pool-adjacent-violators: the closest non-decreasing sequence of CDF values, with equal
+/// strikes merged.
+fn make_monotone(points: Vec<CdfPoint>) -> Vec<CdfPoint> {
+    // (strike of each member, pooled cdf, weight)
+    let mut blocks: Vec<(Vec<f64>, f64, f64)> = Vec::new();
+    for p in points {
+        let cdf = p.cdf.clamp(0.0, 1.0);
+        match blocks.last_mut() {
+            Some(last) if last.0.last() == Some(&p.strike) => {
+                last.1 = (last.1 * last.2 + cdf) / (last.2 + 1.0);
+                last.2 += 1.0;
+            }
+            _ => blocks.push((vec![p.strike], cdf, 1.0)),
+        }
+        while blocks.len() > 1 && blocks[blocks.len() - 2].1 > blocks[blocks.len() - 1].1 {
+            let (strikes, cdf, weight) = blocks.pop().unwrap_or_default();
+            let prev = blocks.last_mut().unwrap();
+            prev.1 = (prev.1 * prev.2 + cdf * weight) / (prev.2 + weight);
+            prev.2 += weight;
+            prev.0.extend(strikes);
+        }
+    }
+    blocks
+        .into_iter()
+        .flat_map(|(strikes, cdf, _)| {
+            strikes
+                .into_iter()
+                .map(move |strike| CdfPoint { strike, cdf })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn market(
+        strike_type: &str,
+        floor: Option<f64>,
+        cap: Option<f64>,
+        bid: i64,
+        ask: i64,
+    ) -> Market {
+        crate::test_market(serde_json::json!({
+            "ticker": "KXHIGHNY-24JAN01-X", "event_ticker": "KXHIGHNY-24JAN01",
+            "yes_bid": bid, "yes_ask": ask, "no_bid": 100 - ask, "no_ask": 100 - bid,
+            "last_price": bid, "strike_type": strike_type, "floor_strike": floor,
+            "cap_strike": cap
+        }))
+    }
+
+    #[test]
+    fn test_brackets_give_cdf_quantiles_and_forecast_comparison() {
+        // ≤50: 10%, 50–55: 40%, 55–60: 40%, >60: 10%.
+        let markets = [
+            market("less", None, Some(50.0), 9, 11),
+            market("between", Some(50.0), Some(55.0), 39, 41),
+            market("between", Some(55.0), Some(60.0), 39, 41),
+            market("greater", Some(60.0), None, 9, 11),
+        ];
+        let refs: Vec<&Market> = markets.iter().collect();
+        let ladder = StrikeLadder::from_markets(&refs, true);
+
+        assert_eq!(ladder.points().len(), 3);
+        assert!((ladder.cdf(55.0) - 0.5).abs() < 1e-9);
+        assert!((ladder.median().unwrap() - 55.0).abs() < 1e-9);
+        assert!((ladder.quantile(0.3).unwrap() - 52.5).abs() < 1e-9);
+        assert_eq!(ladder.quantile(0.05), None);
+        let total: f64 = ladder.pdf().iter().map(|b| b.probability).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!((ladder.pdf()[1].density().unwrap() - 0.08).abs() < 1e-9);
+
+        let forecast = ForecastDataPoint {
+            ts: "2024-01-01T00:00:00Z".to_string(),
+            percentiles: HashMap::from([("p50".to_string(), 56.0), ("90".to_string(), 59.0)]),
+        };
+        let cmp = ladder.compare_forecast(&forecast);
+        assert_eq!(cmp[0].percentile, 0.5);
+        assert!((cmp[0].market_cdf - 0.58).abs() < 1e-9);
+        assert!((cmp[1].market.unwrap() - 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_thresholds_are_made_monotone() {
+        // P(> 2) = 0.8, P(> 3) = 0.3, P(> 4) = 0.4 (inconsistent with > 3).
+        let markets = [
+            market("greater", Some(2.0), None, 79, 81),
+            market("greater", Some(3.0), None, 29, 31),
+            market("greater", Some(4.0), None, 39, 41),
+        ];
+        let refs: Vec<&Market> = markets.iter().collect();
+        let ladder = StrikeLadder::from_markets(&refs, false);
+        let cdfs: Vec<f64> = ladder.points().iter().map(|p| p.cdf).collect();
+        assert!((cdfs[0] - 0.2).abs() < 1e-9);
+        assert!((cdfs[1] - 0.65).abs() < 1e-9);
+        assert!((cdfs[2] - 0.65).abs() < 1e-9);
+    }
+}
@@ -280,7 +280,7 @@ struct CandlestickResponse {
 // -------- Public models --------
 
 /// Represents candlestick data for event-level aggregated trading.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Candlestick {
     /// The timestamp for this candlestick period.
     pub ts: String,
@@ -297,7 +297,7 @@ pub struct Candlestick {
 }
 
 /// Represents additional metadata for an event.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct EventMetadata {
     /// Metadata fields as key-value pairs.
     #[serde(flatten)]
@@ -305,14 +305,14 @@ pub struct EventMetadata {
 }
 
 /// Represents forecast percentile history for an event.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ForecastPercentileHistory {
     /// Historical forecast data points.
     pub history: Vec<ForecastDataPoint>,
 }
 
 /// Represents a single forecast data point.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ForecastDataPoint {
     /// The timestamp for this forecast.
     pub ts: String,
@@ -1,5 +1,6 @@
 use super::Kalshi;
 use crate::kalshi_error::*;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 impl Kalshi {
@@ -126,7 +127,8 @@ impl Kalshi {
     ///
     /// This method attempts to verify that the exchange is operational and trading is active.
     /// If the exchange is not active, it will retry with exponential backoff delays.
-    /// After all attempts are exhausted, the program will exit with a non-zero status code.
+    /// For schedule-aware waiting, cancellation and pausing order paths, see
+    /// [`ExchangeGate`](crate::ExchangeGate).
     ///
     /// # Arguments
     ///
@@ -137,17 +139,17 @@ impl Kalshi {
     /// # Returns
     ///
     /// - `Ok(())`: If the exchange becomes active within the retry attempts
-    /// - `Err(KalshiError)`: If there's an unrecoverable error during the process
+    /// - `Err(KalshiError::ExchangeUnavailable)`: If it is still inactive, or the status could
+    ///   not be fetched, after all attempts
     ///
     /// # Example
     ///
     /// ```
     /// // Assuming `kalshi_instance` is an instance of `Kalshi`
-    /// // This will exit the program if exchange doesn't become active after retries
-    /// kalshi_instance.check_exchange_active_with_backoff().await.unwrap();
-    ///
-    /// // Custom configuration
-    /// kalshi_instance.check_exchange_active_with_backoff(3, 60.0, 600.0).await.unwrap();
+    /// match kalshi_instance.check_exchange_active_with_backoff(3, 60.0, 600.0).await {
+    ///     Ok(()) => println!("Exchange is active"),
+    ///     Err(e) => eprintln!("Giving up: {}", e),
+    /// }
     /// ```
     ///
     pub async fn check_exchange_active_with_backoff(
@@ -156,50 +158,33 @@ impl Kalshi {
         base_delay_secs: f64,
         max_delay_secs: f64,
     ) -> Result<(), KalshiError> {
-        use std::process;
         use tokio::time::{sleep, Duration};
 
+        let mut last_status = None;
+        let mut last_error = None;
         for attempt in 1..=max_attempts {
             match self.get_exchange_status().await {
+                Ok(status) if status.trading_active && status.exchange_active => return Ok(()),
                 Ok(status) => {
-                    if status.trading_active && status.exchange_active {
-                        println!("Exchange is active (attempt {}/{}", attempt, max_attempts);
-                        return Ok(());
-                    }
-
-                    if attempt < max_attempts {
-                        let delay_secs = (base_delay_secs * (2.0_f64.powi((attempt - 1) as i32)))
-                            .min(max_delay_secs);
-                        println!(
-                            "Exchange not active (attempt {}/{}). Waiting {:.1} seconds before retry...",
-                            attempt, max_attempts, delay_secs
-                        );
-                        sleep(Duration::from_secs_f64(delay_secs)).await;
-                    } else {
-                        println!("Exchange not active after {} attempts", max_attempts);
-                    }
-                }
-                Err(e) => {
-                    if attempt < max_attempts {
-                        let delay_secs = (base_delay_secs * (2.0_f64.powi((attempt - 1) as i32)))
-                            .min(max_delay_secs);
-                        println!(
-                            "Error checking exchange status (attempt {}/{}): {}. Waiting {:.1} seconds before retry...",
-                            attempt, max_attempts, e, delay_secs
-                        );
-                        sleep(Duration::from_secs_f64(delay_secs)).await;
-                    } else {
-                        println!(
-                            "Failed to check exchange status after {} attempts: {}",
-                            max_attempts, e
-                        );
-                    }
+                    last_status = Some(status);
+                    last_error = None;
                 }
+                Err(e) => last_error = Some(e.to_string()),
+            }
+            if attempt < max_attempts {
+                let delay_secs =
+                    (base_delay_secs * (2.0_f64.powi((attempt - 1) as i32))).min(max_delay_secs);
+                sleep(Duration::from_secs_f64(delay_secs)).await;
             }
         }
 
-        println!("Exiting as exchange is not active after all retry attempts");
-        process::exit(1);
+        Err(KalshiError::ExchangeUnavailable(
+            crate::ExchangeUnavailable::TimedOut {
+                attempts: max_attempts,
+                last_status,
+                last_error,
+            },
+        ))
     }
 
     /// Convenience method to check exchange status with default backoff settings.
@@ -212,7 +197,7 @@ impl Kalshi {
     /// # Returns
     ///
     /// - `Ok(())`: If the exchange becomes active within the retry attempts
-    /// - `Err(KalshiError)`: If there's an unrecoverable error during the process
+    /// - `Err(KalshiError::ExchangeUnavailable)`: If it is not active after all attempts
     ///
     /// # Example
     ///
@@ -271,7 +256,7 @@ impl Kalshi {
 ///
 /// This struct provides simple boolean flags indicating whether the exchange
 /// platform and trading engine are currently active and operational.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ExchangeStatus {
     /// Indicates whether the trading engine is currently active and accepting orders.
     pub trading_active: bool,
@@ -296,26 +281,71 @@ pub struct ExchangeSchedule {
 /// Announcements provide important information about exchange updates,
 /// maintenance schedules, new features, or other relevant information
 /// that users need to be aware of.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ExchangeAnnouncement {
     /// The announcement message content.
     pub message: String,
     /// The timestamp when the announcement was created (seconds since epoch, ISO string, or RFC3339).
+    /// Use [`timestamp`](Self::timestamp) to parse it.
+    #[serde(alias = "delivery_time")]
     pub ts: String,
-    /// The current status of the announcement (e.g., "active" or "expired").
-    pub status: String,
+    /// The current status of the announcement.
+    pub status: AnnouncementStatus,
+}
+
+impl ExchangeAnnouncement {
+    /// The creation time, if `ts` could be parsed.
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        parse_exchange_ts(&self.ts)
+    }
+}
+
+/// Whether an announcement is still in effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnouncementStatus {
+    /// The announcement is current.
+    Active,
+    /// The announcement is no longer current.
+    Inactive,
+    /// The announcement has expired.
+    Expired,
+    /// A status this version of the crate does not know.
+    #[serde(other)]
+    Unknown,
 }
 
 /// Represents the timestamp of the last portfolio data refresh.
 ///
 /// This struct provides information about when user portfolio data
 /// was last updated, allowing users to determine data freshness.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct UserDataTimestamp {
     /// The timestamp of the last portfolio data validation/refresh.
     pub last_validated_ts: String,
 }
 
+impl UserDataTimestamp {
+    /// The validation time, if `last_validated_ts` could be parsed.
+    pub fn last_validated(&self) -> Option<DateTime<Utc>> {
+        parse_exchange_ts(&self.last_validated_ts)
+    }
+}
+
+/// Parses an exchange timestamp given as RFC3339, a naive ISO datetime (read as UTC), or
+/// seconds since the epoch.
+fn parse_exchange_ts(ts: &str) -> Option<DateTime<Utc>> {
+    let ts = ts.trim();
+    if let Ok(t) = DateTime::parse_from_rfc3339(ts) {
+        return Some(t.with_timezone(&Utc));
+    }
+    if let Ok(t) = chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Some(t.and_utc());
+    }
+    let secs: f64 = ts.parse().ok()?;
+    DateTime::from_timestamp_millis((secs * 1000.0) as i64)
+}
+
 /// Represents a scheduled maintenance window for the Kalshi exchange.
 ///
 /// Maintenance windows indicate periods when the exchange may be unavailable
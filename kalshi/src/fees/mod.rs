@@ -0,0 +1,262 @@
+//! Expected trading fees.
+//!
+//! Kalshi charges fees on the expected earnings of a trade:
+//!
+//! ```text
+//! fee = round_up(multiplier × count × P × (1 − P))
+//! ```
+//!
+//! where `P` is the contract price in dollars and the result is rounded up to the next
+//! cent. Takers pay the taker multiplier (0.07 by default). Makers pay nothing except in
+//! series that charge maker fees (0.0175 by default).
+//!
+//! [`FeeCalculator`] applies the schedule in force for each series on a given date, loaded
+//! from [`Kalshi::get_series_fee_changes`]. Use it to estimate fees for an
+//! [`OrderCreationField`] before sending it, or attach it to a [`PositionBook`] so fills
+//! are booked net of fees.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::{FeeCalculator, FeeRole};
+//!
+//! let fees = FeeCalculator::load(&kalshi, None).await?;
+//! let fee = fees.fee_for("KXHIGHNY-24JAN01-T60", 10, 45, FeeRole::Taker, chrono::Utc::now());
+//! let edge = fees.edge_after_fees("KXHIGHNY-24JAN01-T60", 10, 45, 52.0, FeeRole::Taker, chrono::Utc::now());
+//! ```
+
+use crate::kalshi_error::*;
+use crate::risk::{series_ticker_of, side_price};
+use crate::{Kalshi, OrderCreationField, SeriesFeeChange};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+
+/// Standard taker fee multiplier.
+pub const DEFAULT_TAKER_MULTIPLIER: f64 = 0.07;
+
+/// Maker fee multiplier for series that charge maker fees.
+pub const DEFAULT_MAKER_MULTIPLIER: f64 = 0.0175;
+
+/// Whether an order takes or provides liquidity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeRole {
+    Taker,
+    Maker,
+}
+
+/// Fee multipliers in force for a series.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeSchedule {
+    /// Multiplier applied to taker fills.
+    pub taker_multiplier: f64,
+    /// Multiplier applied to maker fills; zero when the series has no maker fees.
+    pub maker_multiplier: f64,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        FeeSchedule {
+            taker_multiplier: DEFAULT_TAKER_MULTIPLIER,
+            maker_multiplier: 0.0,
+        }
+    }
+}
+
+impl FeeSchedule {
+    /// The default schedule with maker fees enabled.
+    pub fn with_maker_fees() -> Self {
+        FeeSchedule {
+            maker_multiplier: DEFAULT_MAKER_MULTIPLIER,
+            ..Default::default()
+        }
+    }
+
+    /// Fee in cents for `count` contracts at `price` cents.
+    pub fn fee(&self, count: i32, price: i32, role: FeeRole) -> i64 {
+        let multiplier = match role {
+            FeeRole::Taker => self.taker_multiplier,
+            FeeRole::Maker => self.maker_multiplier,
+        };
+        fee_cents(multiplier, count, price)
+    }
+}
+
+/// Computes `round_up(multiplier × count × P × (1 − P))` in cents for a price in cents.
+pub fn fee_cents(multiplier: f64, count: i32, price: i32) -> i64 {
+    if multiplier <= 0.0 || count <= 0 {
+        return 0;
+    }
+    let price = price.clamp(0, 100) as f64;
+    let cents = multiplier * count as f64 * price * (100.0 - price) / 100.0;
+    // Guard against float noise pushing an exact cent amount over the boundary.
+    (cents - 1e-9).ceil().max(0.0) as i64
+}
+
+/// Applies per-series fee schedules, including scheduled changes.
+#[derive(Debug, Clone, Default)]
+pub struct FeeCalculator {
+    default: FeeSchedule,
+    /// Changes per series, sorted by effective date.
+    changes: HashMap<String, Vec<(DateTime<Utc>, FeeSchedule)>>,
+}
+
+impl FeeCalculator {
+    /// Creates a calculator that applies `default` to every series.
+    pub fn new(default: FeeSchedule) -> Self {
+        FeeCalculator {
+            default,
+            changes: HashMap::new(),
+        }
+    }
+
+    /// Creates a calculator from the exchange's fee change history.
+    ///
+    /// # Arguments
+    ///
+    /// * `kalshi` - Client used to call `get_series_fee_changes`.
+    /// * `series_ticker` - Restrict to one series, or `None` for all.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(FeeCalculator)`: A calculator using the default schedule plus the changes.
+    /// - `Err(KalshiError)`: An error if the request failed.
+    ///
+    pub async fn load(kalshi: &Kalshi, series_ticker: Option<String>) -> Result<Self, KalshiError> {
+        let mut calc = FeeCalculator::default();
+        calc.apply_changes(&kalshi.get_series_fee_changes(series_ticker).await?);
+        Ok(calc)
+    }
+
+    /// Records fee changes. `new_fee` is read as the series' taker multiplier.
+    ///
+    /// Changes with an unparseable `effective_date` are skipped.
+    pub fn apply_changes(&mut self, changes: &[SeriesFeeChange]) {
+        for change in changes {
+            let Some(at) = parse_effective_date(&change.effective_date) else {
+                continue;
+            };
+            let schedule = FeeSchedule {
+                taker_multiplier: change.new_fee,
+                ..self.default
+            };
+            self.set_schedule(&change.series_ticker, at, schedule);
+        }
+    }
+
+    /// Sets the schedule for `series_ticker` from `effective` onwards.
+    pub fn set_schedule(
+        &mut self,
+        series_ticker: &str,
+        effective: DateTime<Utc>,
+        schedule: FeeSchedule,
+    ) {
+        let entries = self.changes.entry(series_ticker.to_string()).or_default();
+        entries.retain(|(at, _)| *at != effective);
+        entries.push((effective, schedule));
+        entries.sort_by_key(|(at, _)| *at);
+    }
+
+    /// Returns the schedule in force for a series at `at`.
+    pub fn schedule_for(&self, series_ticker: &str, at: DateTime<Utc>) -> FeeSchedule {
+        self.changes
+            .get(series_ticker)
+            .and_then(|entries| entries.iter().rev().find(|(from, _)| *from <= at))
+            .map(|(_, schedule)| *schedule)
+            .unwrap_or(self.default)
+    }
+
+    /// Expected fee in cents for trading `count` contracts of `ticker` at `price` cents.
+    pub fn fee_for(
+        &self,
+        ticker: &str,
+        count: i32,
+        price: i32,
+        role: FeeRole,
+        at: DateTime<Utc>,
+    ) -> i64 {
+        self.schedule_for(&series_ticker_of(ticker), at)
+            .fee(count, price, role)
+    }
+
+    /// Expected profit in cents of buying `count` contracts at `price` when they are worth
+    /// `fair_value` cents, after fees.
+    pub fn edge_after_fees(
+        &self,
+        ticker: &str,
+        count: i32,
+        price: i32,
+        fair_value: f64,
+        role: FeeRole,
+        at: DateTime<Utc>,
+    ) -> f64 {
+        let gross = (fair_value - price as f64) * count as f64;
+        gross - self.fee_for(ticker, count, price, role, at) as f64
+    }
+}
+
+impl OrderCreationField {
+    /// Expected fee in cents if this order fills completely now.
+    ///
+    /// Returns `None` for orders without a limit price.
+    pub fn estimated_fee(&self, fees: &FeeCalculator, role: FeeRole) -> Option<i64> {
+        let price = side_price(
+            self.side,
+            self.yes_price,
+            self.no_price,
+            self.yes_price_dollars.as_deref(),
+            self.no_price_dollars.as_deref(),
+        )?;
+        Some(fees.fee_for(&self.ticker, self.count, price, role, Utc::now()))
+    }
+}
+
+/// Parses an RFC 3339 timestamp or a plain `YYYY-MM-DD` date (midnight UTC).
+fn parse_effective_date(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(s) {
+        return Some(ts.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_rounds_up_to_next_cent() {
+        // 0.07 × 100 × 0.50 × 0.50 = $1.75 exactly.
+        assert_eq!(fee_cents(0.07, 100, 50), 175);
+        // 0.07 × 1 × 0.50 × 0.50 = $0.0175 → 2¢.
+        assert_eq!(fee_cents(0.07, 1, 50), 2);
+        assert_eq!(fee_cents(0.07, 10, 99), 1);
+        assert_eq!(FeeSchedule::default().fee(10, 40, FeeRole::Maker), 0);
+    }
+
+    #[test]
+    fn test_schedule_follows_effective_date() {
+        let mut fees = FeeCalculator::default();
+        fees.apply_changes(&[SeriesFeeChange {
+            series_ticker: "INX".to_string(),
+            old_fee: Some(0.07),
+            new_fee: 0.035,
+            effective_date: "2025-01-01".to_string(),
+        }]);
+        let before = "2024-12-31T00:00:00Z".parse().unwrap();
+        let after = "2025-06-01T00:00:00Z".parse().unwrap();
+        assert_eq!(
+            fees.fee_for("INX-25JUN01-B5000", 100, 50, FeeRole::Taker, before),
+            175
+        );
+        assert_eq!(
+            fees.fee_for("INX-25JUN01-B5000", 100, 50, FeeRole::Taker, after),
+            88
+        );
+        assert_eq!(
+            fees.fee_for("KXOTHER-1", 100, 50, FeeRole::Taker, after),
+            175
+        );
+    }
+}
@@ -0,0 +1,519 @@
+//! Waiting for the exchange to trade, and pausing order paths while it does not.
+//!
+//! [`ExchangeGate`] polls [`Kalshi::get_exchange_status`]. When given an
+//! [`ExchangeCalendar`] it sleeps through scheduled closures and maintenance instead of
+//! backing off blindly, and only falls back to exponential backoff when the exchange is
+//! unexpectedly halted during trading hours.
+//!
+//! - [`ExchangeGate::wait_until_active`] waits for `trading_active` and returns a typed
+//!   result; [`ExchangeGate::wait_until_active_or`] also stops when a cancellation future
+//!   completes.
+//! - [`ExchangeGate::watch`] runs a background task that keeps the status current,
+//!   publishes [`GateEvent`]s and optionally cancels resting orders when trading stops.
+//! - A gate attached with [`Kalshi::with_exchange_gate`] makes `create_order`,
+//!   `batch_create_order` and `amend_order` fail with
+//!   [`ExchangeUnavailable::Paused`] while the last known status is inactive. Cancels are
+//!   never blocked.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::{ExchangeCalendar, ExchangeGate, OrderFilter};
+//! use std::sync::Arc;
+//!
+//! let calendar = ExchangeCalendar::load(&kalshi).await?;
+//! let gate = Arc::new(
+//!     ExchangeGate::new(kalshi.clone())
+//!         .with_calendar(calendar)
+//!         .with_cancel_on_pause(OrderFilter::default()),
+//! );
+//! gate.wait_until_active_or(tokio::signal::ctrl_c()).await?;
+//! let watcher = gate.watch();
+//! let kalshi = kalshi.with_exchange_gate(gate.clone());
+//! ```
+
+use crate::kalshi_error::*;
+use crate::{ExchangeCalendar, ExchangeStatus, Kalshi, OrderFilter};
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Capacity of the broadcast channel carrying [`GateEvent`]s.
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// Why the exchange could not be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExchangeUnavailable {
+    /// The last status check reported trading inactive; the order was not sent.
+    Paused(ExchangeStatus),
+    /// The wait was cancelled before the exchange became active.
+    Cancelled,
+    /// The exchange did not become active within the allowed attempts or time.
+    TimedOut {
+        /// Status checks made.
+        attempts: u32,
+        /// The last status received, if any.
+        last_status: Option<ExchangeStatus>,
+        /// The last request error, if the last check failed.
+        last_error: Option<String>,
+    },
+}
+
+impl fmt::Display for ExchangeUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExchangeUnavailable::Paused(status) => write!(
+                f,
+                "trading paused (trading_active: {}, exchange_active: {})",
+                status.trading_active, status.exchange_active
+            ),
+            ExchangeUnavailable::Cancelled => write!(f, "wait for exchange cancelled"),
+            ExchangeUnavailable::TimedOut {
+                attempts,
+                last_error: Some(e),
+                ..
+            } => write!(f, "exchange not active after {} checks: {}", attempts, e),
+            ExchangeUnavailable::TimedOut { attempts, .. } => {
+                write!(f, "exchange not active after {} checks", attempts)
+            }
+        }
+    }
+}
+
+/// A change observed by an [`ExchangeGate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GateEvent {
+    /// Trading stopped; order paths are paused.
+    Paused(ExchangeStatus),
+    /// Trading resumed; order paths are open again.
+    Resumed(ExchangeStatus),
+    /// Resting orders were cancelled after a pause.
+    OrdersCancelled {
+        /// Orders cancelled.
+        cancelled: usize,
+        /// Orders that could not be cancelled.
+        failed: usize,
+    },
+    /// A status check failed; the last known status is kept.
+    CheckFailed(String),
+}
+
+#[derive(Debug, Default)]
+struct GateState {
+    status: Option<ExchangeStatus>,
+    checked_at: Option<DateTime<Utc>>,
+}
+
+/// Tracks whether the exchange is trading. See the [module documentation](crate::gate).
+#[derive(Debug)]
+pub struct ExchangeGate {
+    kalshi: Kalshi,
+    calendar: Option<ExchangeCalendar>,
+    poll_interval: Duration,
+    base_delay: Duration,
+    max_delay: Duration,
+    max_wait: Option<Duration>,
+    cancel_on_pause: Option<OrderFilter>,
+    state: Mutex<GateState>,
+    events: broadcast::Sender<GateEvent>,
+}
+
+impl ExchangeGate {
+    /// Creates a gate polling every 30 seconds, backing off from 5 seconds to 5 minutes
+    /// while halted, with no wait limit.
+    pub fn new(kalshi: Kalshi) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        ExchangeGate {
+            kalshi,
+            calendar: None,
+            poll_interval: Duration::from_secs(30),
+            base_delay: Duration::from_secs(5),
+            max_delay: Duration::from_secs(300),
+            max_wait: None,
+            cancel_on_pause: None,
+            state: Mutex::new(GateState::default()),
+            events,
+        }
+    }
+
+    /// Sleeps through the calendar's closures instead of polling them.
+    pub fn with_calendar(mut self, calendar: ExchangeCalendar) -> Self {
+        self.calendar = Some(calendar);
+        self
+    }
+
+    /// How often [`watch`](Self::watch) checks the status while trading is active.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Backoff between checks while trading is halted during scheduled hours, or while
+    /// checks fail. The delay doubles from `base` up to `max`.
+    pub fn with_backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.base_delay = base;
+        self.max_delay = max;
+        self
+    }
+
+    /// Gives up waiting after `max_wait` with [`ExchangeUnavailable::TimedOut`].
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = Some(max_wait);
+        self
+    }
+
+    /// Makes [`watch`](Self::watch) cancel resting orders matching `filter` when trading
+    /// stops.
+    pub fn with_cancel_on_pause(mut self, filter: OrderFilter) -> Self {
+        self.cancel_on_pause = Some(filter);
+        self
+    }
+
+    /// The calendar, if one was given.
+    pub fn calendar(&self) -> Option<&ExchangeCalendar> {
+        self.calendar.as_ref()
+    }
+
+    /// Receives [`GateEvent`]s.
+    pub fn subscribe(&self) -> broadcast::Receiver<GateEvent> {
+        self.events.subscribe()
+    }
+
+    /// The last status received and when.
+    pub fn status(&self) -> Option<(ExchangeStatus, DateTime<Utc>)> {
+        let state = self.lock();
+        Some((state.status.clone()?, state.checked_at?))
+    }
+
+    /// Whether order paths are paused: the last status reported trading inactive. A gate
+    /// that has not checked yet is not paused.
+    pub fn is_paused(&self) -> bool {
+        self.lock().status.as_ref().is_some_and(|s| !is_active(s))
+    }
+
+    /// Fails with [`ExchangeUnavailable::Paused`] if order paths are paused.
+    pub fn ensure_active(&self) -> Result<(), KalshiError> {
+        match &self.lock().status {
+            Some(status) if !is_active(status) => Err(KalshiError::ExchangeUnavailable(
+                ExchangeUnavailable::Paused(status.clone()),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fetches the status and records it.
+    pub async fn check(&self) -> Result<ExchangeStatus, KalshiError> {
+        let status = self.kalshi.get_exchange_status().await?;
+        self.record_status(status.clone());
+        Ok(status)
+    }
+
+    /// Records a status obtained elsewhere.
+    ///
+    /// # Returns
+    ///
+    /// [`GateEvent::Paused`] or [`GateEvent::Resumed`] if this changes whether order paths
+    /// are paused. The event is also published to subscribers.
+    ///
+    pub fn record_status(&self, status: ExchangeStatus) -> Option<GateEvent> {
+        let event = {
+            let mut state = self.lock();
+            let was_active = state.status.as_ref().is_none_or(is_active);
+            let now_active = is_active(&status);
+            state.status = Some(status.clone());
+            state.checked_at = Some(Utc::now());
+            match (was_active, now_active) {
+                (true, false) => Some(GateEvent::Paused(status)),
+                (false, true) => Some(GateEvent::Resumed(status)),
+                _ => None,
+            }
+        };
+        if let Some(event) = &event {
+            let _ = self.events.send(event.clone());
+        }
+        event
+    }
+
+    /// Waits until trading is active.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(ExchangeStatus)`: The active status.
+    /// - `Err(KalshiError::ExchangeUnavailable)`: The wait exceeded
+    ///   [`with_max_wait`](Self::with_max_wait).
+    ///
+    pub async fn wait_until_active(&self) -> Result<ExchangeStatus, KalshiError> {
+        self.wait_until_active_or(std::future::pending::<()>())
+            .await
+    }
+
+    /// Waits until trading is active or `cancel` completes.
+    ///
+    /// Closures known to the calendar are slept through in one step; otherwise checks
+    /// back off exponentially. Failed checks are retried. With
+    /// [`with_max_wait`](Self::with_max_wait), the last sleep is cut short at the deadline
+    /// and the status is checked once more before giving up.
+    ///
+    /// # Arguments
+    ///
+    /// * `cancel` - A future that stops the wait when it completes, e.g.
+    ///   `tokio::signal::ctrl_c()` or a oneshot receiver.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(ExchangeStatus)`: The active status.
+    /// - `Err(KalshiError::ExchangeUnavailable)`: The wait was cancelled or timed out.
+    ///
+    pub async fn wait_until_active_or<F: Future>(
+        &self,
+        cancel: F,
+    ) -> Result<ExchangeStatus, KalshiError> {
+        tokio::pin!(cancel);
+        let deadline = self.max_wait.map(|w| tokio::time::Instant::now() + w);
+        let mut attempts = 0u32;
+        let mut failures = 0u32;
+        loop {
+            attempts += 1;
+            let (last_status, last_error) = match self.check().await {
+                Ok(status) if is_active(&status) => return Ok(status),
+                Ok(status) => (Some(status), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            let now = tokio::time::Instant::now();
+            if deadline.is_some_and(|d| now >= d) {
+                return Err(KalshiError::ExchangeUnavailable(
+                    ExchangeUnavailable::TimedOut {
+                        attempts,
+                        last_status: last_status.or_else(|| self.lock().status.clone()),
+                        last_error,
+                    },
+                ));
+            }
+            let delay = self.retry_delay(Utc::now(), failures);
+            failures += 1;
+            let wake = deadline.map_or(now + delay, |d| d.min(now + delay));
+            tokio::select! {
+                _ = &mut cancel => {
+                    return Err(KalshiError::ExchangeUnavailable(ExchangeUnavailable::Cancelled));
+                }
+                _ = tokio::time::sleep_until(wake) => {}
+            }
+        }
+    }
+
+    /// Spawns a task that keeps the status current until the handle is aborted.
+    ///
+    /// While trading is active the status is checked every poll interval, and at the
+    /// calendar's next close if that is sooner. While halted, the task sleeps until the
+    /// calendar's next open or backs off as in
+    /// [`wait_until_active_or`](Self::wait_until_active_or). On a pause, resting orders
+    /// are cancelled if [`with_cancel_on_pause`](Self::with_cancel_on_pause) was set.
+    /// Failed checks are retried with the same bounded backoff.
+    pub fn watch(self: &Arc<Self>) -> JoinHandle<()> {
+        let gate = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut failures = 0u32;
+            loop {
+                let delay = match gate.kalshi.get_exchange_status().await {
+                    Ok(status) => {
+                        let active = is_active(&status);
+                        if let Some(GateEvent::Paused(_)) = gate.record_status(status) {
+                            gate.cancel_resting().await;
+                        }
+                        if active {
+                            failures = 0;
+                            gate.poll_delay(Utc::now())
+                        } else {
+                            let delay = gate.retry_delay(Utc::now(), failures);
+                            failures += 1;
+                            delay
+                        }
+                    }
+                    Err(e) => {
+                        let _ = gate.events.send(GateEvent::CheckFailed(e.to_string()));
+                        let delay = gate.backoff(failures);
+                        failures += 1;
+                        delay
+                    }
+                };
+                tokio::time::sleep(delay).await;
+            }
+        })
+    }
+
+    /// Cancels resting orders if configured. Called by the watcher on a pause.
+    async fn cancel_resting(&self) {
+        let Some(filter) = &self.cancel_on_pause else {
+            return;
+        };
+        match self.kalshi.cancel_all_orders(filter).await {
+            Ok(report) => {
+                let _ = self.events.send(GateEvent::OrdersCancelled {
+                    cancelled: report.cancelled.len(),
+                    failed: report.failed.len(),
+                });
+            }
+            Err(e) => {
+                let _ = self.events.send(GateEvent::CheckFailed(e.to_string()));
+            }
+        }
+    }
+
+    /// Delay before the next check while trading is active.
+    fn poll_delay(&self, now: DateTime<Utc>) -> Duration {
+        let until_close = self
+            .calendar
+            .as_ref()
+            .and_then(|c| c.next_close_after(now))
+            .and_then(|t| (t - now).to_std().ok())
+            .filter(|d| !d.is_zero());
+        until_close.map_or(self.poll_interval, |d| d.min(self.poll_interval))
+    }
+
+    /// Delay before the next check while trading is halted, after `failures` earlier
+    /// retries.
+    fn retry_delay(&self, now: DateTime<Utc>, failures: u32) -> Duration {
+        let until_open = self
+            .calendar
+            .as_ref()
+            .and_then(|c| c.next_open_after(now))
+            .and_then(|t| (t - now).to_std().ok())
+            .filter(|d| !d.is_zero());
+        until_open.unwrap_or_else(|| self.backoff(failures))
+    }
+
+    /// Exponential backoff after `failures` earlier retries, capped at the maximum delay.
+    fn backoff(&self, failures: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(failures))
+            .min(self.max_delay)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, GateState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Whether the status allows trading.
+pub(crate) fn is_active(status: &ExchangeStatus) -> bool {
+    status.trading_active && status.exchange_active
+}
+
+impl Kalshi {
+    /// Attaches an [`ExchangeGate`] that order paths consult before sending.
+    pub fn with_exchange_gate(mut self, gate: Arc<ExchangeGate>) -> Self {
+        self.exchange_gate = Some(gate);
+        self
+    }
+
+    /// Returns the attached [`ExchangeGate`], if any.
+    pub fn exchange_gate(&self) -> Option<&Arc<ExchangeGate>> {
+        self.exchange_gate.as_ref()
+    }
+
+    pub(crate) fn gate_check(&self) -> Result<(), KalshiError> {
+        match &self.exchange_gate {
+            Some(gate) => gate.ensure_active(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, OrderType, Side};
+
+    fn status(trading_active: bool) -> ExchangeStatus {
+        ExchangeStatus {
+            trading_active,
+            exchange_active: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_paused_gate_blocks_orders_and_reports_transitions() {
+        let gate = Arc::new(ExchangeGate::new(Kalshi::test_client()));
+        let mut events = gate.subscribe();
+        let kalshi = Kalshi::test_client().with_exchange_gate(gate.clone());
+        assert!(!gate.is_paused());
+
+        assert_eq!(
+            gate.record_status(status(false)),
+            Some(GateEvent::Paused(status(false)))
+        );
+        assert_eq!(gate.record_status(status(false)), None);
+        let err = kalshi
+            .create_order(
+                Action::Buy,
+                None,
+                1,
+                Side::Yes,
+                "KXTEST-1".to_string(),
+                OrderType::Limit,
+                None,
+                None,
+                Some(50),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            KalshiError::ExchangeUnavailable(ExchangeUnavailable::Paused(_))
+        ));
+
+        gate.record_status(status(true));
+        assert!(gate.ensure_active().is_ok());
+        assert_eq!(events.try_recv().unwrap(), GateEvent::Paused(status(false)));
+        assert_eq!(events.try_recv().unwrap(), GateEvent::Resumed(status(true)));
+    }
+
+    #[test]
+    fn test_retry_delay_sleeps_until_calendar_open() {
+        let schedule: crate::ExchangeSchedule = serde_json::from_value(serde_json::json!({
+            "standard_hours": [{
+                "start_time": "", "end_time": "",
+                "monday": [{ "open_time": "08:00", "close_time": "17:00" }],
+                "tuesday": [], "wednesday": [], "thursday": [], "friday": [],
+                "saturday": [], "sunday": []
+            }],
+            "maintenance_windows": []
+        }))
+        .unwrap();
+        let gate = ExchangeGate::new(Kalshi::test_client())
+            .with_calendar(ExchangeCalendar::from_schedule(&schedule).unwrap());
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+
+        // Monday 1 July 2024 07:00 ET: an hour before the open.
+        assert_eq!(
+            gate.retry_delay(at("2024-07-01T11:00:00Z"), 3),
+            Duration::from_secs(3600)
+        );
+        // Halted during hours: exponential backoff.
+        assert_eq!(
+            gate.retry_delay(at("2024-07-01T13:00:00Z"), 2),
+            Duration::from_secs(20)
+        );
+        // Backoff is capped.
+        assert_eq!(gate.backoff(20), Duration::from_secs(300));
+        // Polling wakes at the close.
+        assert_eq!(
+            gate.poll_delay(at("2024-07-01T20:59:50Z")),
+            Duration::from_secs(10)
+        );
+    }
+}
@@ -0,0 +1,1098 @@
+//! Bulk historical data downloads with incremental sync.
+//!
+//! [`HistorySync`] downloads the series, events, markets, public trades and candlesticks
+//! in a [`HistoryScope`] into a directory, as CSV and (with the `parquet` feature) Parquet.
+//! Each table has a fixed column layout, listed in [`SERIES_SCHEMA`], [`EVENT_SCHEMA`],
+//! [`MARKET_SCHEMA`], [`TRADE_SCHEMA`] and [`CANDLE_SCHEMA`].
+//!
+//! ## Directory layout
+//!
+//! ```text
+//! <dir>/sync_state.json             last stored trade and candle timestamps per market
+//! <dir>/series.csv                  rewritten on every sync
+//! <dir>/events.csv                  rewritten on every sync
+//! <dir>/markets.csv                 rewritten on every sync
+//! <dir>/trades.csv                  appended
+//! <dir>/candlesticks.csv            appended
+//! <dir>/trades/part-<key>.parquet   one file per append, named after its first row
+//! <dir>/candlesticks/part-<key>.parquet
+//! ```
+//!
+//! Reference tables (series, events, markets) are rewritten for the scope, so use one
+//! directory per scope.
+//!
+//! ## Incremental sync
+//!
+//! The newest stored trade timestamp and trade IDs, and the end of the newest stored
+//! candle, are kept per market in `sync_state.json`. The next sync starts from there, so
+//! rerunning after an interruption only fetches what is missing. State is saved after each
+//! market's trades and after each candlestick batch.
+//!
+//! Appended data and state are kept consistent: the state also records the committed
+//! length of each appended CSV file, and a sync first truncates rows written after the
+//! last saved state. Parquet parts are named after their first row, so a rewritten part
+//! replaces the one left by an interrupted sync.
+//!
+//! Markets are selected by close time: [`HistoryScope::min_ts`] and
+//! [`HistoryScope::max_ts`] are passed to `get_markets` as `min_close_ts` and
+//! `max_close_ts`, and bound trades and candles by their own timestamps.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::{HistoryFormat, HistoryScope, HistorySync};
+//!
+//! let sync = HistorySync::new("data/KXHIGHNY")
+//!     .with_formats(vec![HistoryFormat::Csv, HistoryFormat::Parquet])
+//!     .with_period_interval(60);
+//! let report = sync.sync(&kalshi, &HistoryScope::series("KXHIGHNY")).await?;
+//! println!("{} new trades, {} new candles", report.trades, report.candles);
+//! ```
+
+use crate::kalshi_error::*;
+use crate::utils::parse_ts;
+use crate::{Candle, Event, Market, MarketData, Series, Trade};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Page size used for paginated listings.
+const PAGE_SIZE: i64 = 1000;
+
+/// Most tickers `batch_get_market_candlesticks` accepts per request.
+const MAX_CANDLE_TICKERS: usize = 100;
+
+/// Most candles requested at once across all tickers of a batch.
+const MAX_CANDLES_PER_REQUEST: i64 = 10_000;
+
+const STATE_FILE: &str = "sync_state.json";
+
+/// Data type of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryColumnType {
+    Utf8,
+    Int64,
+    Float64,
+    Bool,
+}
+
+/// A column of a history table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryColumn {
+    pub name: &'static str,
+    pub kind: HistoryColumnType,
+}
+
+pub(crate) const fn col(name: &'static str, kind: HistoryColumnType) -> HistoryColumn {
+    HistoryColumn { name, kind }
+}
+
+use HistoryColumnType::{Bool, Float64, Int64, Utf8};
+
+/// Columns of `series.csv`. `tags` is `;`-separated.
+pub const SERIES_SCHEMA: &[HistoryColumn] = &[
+    col("ticker", Utf8),
+    col("title", Utf8),
+    col("frequency", Utf8),
+    col("category", Utf8),
+    col("tags", Utf8),
+    col("contract_url", Utf8),
+];
+
+/// Columns of `events.csv`.
+pub const EVENT_SCHEMA: &[HistoryColumn] = &[
+    col("event_ticker", Utf8),
+    col("series_ticker", Utf8),
+    col("title", Utf8),
+    col("sub_title", Utf8),
+    col("category", Utf8),
+    col("mutually_exclusive", Bool),
+    col("strike_date", Utf8),
+    col("strike_period", Utf8),
+];
+
+/// Columns of `markets.csv`. Prices are in cents.
+pub const MARKET_SCHEMA: &[HistoryColumn] = &[
+    col("ticker", Utf8),
+    col("event_ticker", Utf8),
+    col("market_type", Utf8),
+    col("title", Utf8),
+    col("subtitle", Utf8),
+    col("yes_sub_title", Utf8),
+    col("no_sub_title", Utf8),
+    col("category", Utf8),
+    col("status", Utf8),
+    col("open_time", Utf8),
+    col("close_time", Utf8),
+    col("expiration_time", Utf8),
+    col("result", Utf8),
+    col("settlement_value", Utf8),
+    col("strike_type", Utf8),
+    col("floor_strike", Float64),
+    col("cap_strike", Float64),
+    col("yes_bid", Int64),
+    col("yes_ask", Int64),
+    col("no_bid", Int64),
+    col("no_ask", Int64),
+    col("last_price", Int64),
+    col("volume", Int64),
+    col("volume_24h", Int64),
+    col("open_interest", Int64),
+    col("liquidity", Int64),
+];
+
+/// Columns of `trades.csv`. `ts` is Unix seconds; prices are in cents.
+pub const TRADE_SCHEMA: &[HistoryColumn] = &[
+    col("trade_id", Utf8),
+    col("ticker", Utf8),
+    col("created_time", Utf8),
+    col("ts", Int64),
+    col("yes_price", Int64),
+    col("no_price", Int64),
+    col("count", Int64),
+    col("taker_side", Utf8),
+];
+
+/// Columns of `candlesticks.csv`. Timestamps are Unix seconds; `period_interval` is in
+/// minutes; prices are in cents.
+pub const CANDLE_SCHEMA: &[HistoryColumn] = &[
+    col("ticker", Utf8),
+    col("period_interval", Int64),
+    col("start_ts", Int64),
+    col("end_ts", Int64),
+    col("yes_open", Int64),
+    col("yes_high", Int64),
+    col("yes_low", Int64),
+    col("yes_close", Int64),
+    col("no_open", Int64),
+    col("no_high", Int64),
+    col("no_low", Int64),
+    col("no_close", Int64),
+    col("volume", Int64),
+    col("open_interest", Int64),
+];
+
+/// A single cell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HistoryValue {
+    Null,
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl From<&str> for HistoryValue {
+    fn from(s: &str) -> Self {
+        HistoryValue::Str(s.to_string())
+    }
+}
+
+impl From<String> for HistoryValue {
+    fn from(s: String) -> Self {
+        HistoryValue::Str(s)
+    }
+}
+
+impl From<i64> for HistoryValue {
+    fn from(v: i64) -> Self {
+        HistoryValue::Int(v)
+    }
+}
+
+impl From<i32> for HistoryValue {
+    fn from(v: i32) -> Self {
+        HistoryValue::Int(v as i64)
+    }
+}
+
+impl From<bool> for HistoryValue {
+    fn from(v: bool) -> Self {
+        HistoryValue::Bool(v)
+    }
+}
+
+impl<T: Into<HistoryValue>> From<Option<T>> for HistoryValue {
+    fn from(v: Option<T>) -> Self {
+        v.map(Into::into).unwrap_or(HistoryValue::Null)
+    }
+}
+
+impl From<f64> for HistoryValue {
+    fn from(v: f64) -> Self {
+        HistoryValue::Float(v)
+    }
+}
+
+/// Rows laid out by one of the history schemas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryTable {
+    pub columns: &'static [HistoryColumn],
+    pub rows: Vec<Vec<HistoryValue>>,
+}
+
+impl HistoryTable {
+    pub fn new(columns: &'static [HistoryColumn]) -> Self {
+        HistoryTable {
+            columns,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn series<'a>(series: impl IntoIterator<Item = &'a Series>) -> Self {
+        let mut table = HistoryTable::new(SERIES_SCHEMA);
+        for s in series {
+            table.rows.push(vec![
+                s.ticker.clone().into(),
+                s.title.clone().into(),
+                s.frequency.clone().into(),
+                s.category.clone().into(),
+                s.tags.join(";").into(),
+                s.contract_url.clone().into(),
+            ]);
+        }
+        table
+    }
+
+    pub fn events<'a>(events: impl IntoIterator<Item = &'a Event>) -> Self {
+        let mut table = HistoryTable::new(EVENT_SCHEMA);
+        for e in events {
+            table.rows.push(vec![
+                e.event_ticker.as_str().into(),
+                e.series_ticker.as_str().into(),
+                e.title.as_str().into(),
+                e.sub_title.as_str().into(),
+                e.category.as_str().into(),
+                e.mutually_exclusive.into(),
+                e.strike_date.clone().into(),
+                e.strike_period.clone().into(),
+            ]);
+        }
+        table
+    }
+
+    pub fn markets<'a>(markets: impl IntoIterator<Item = &'a Market>) -> Self {
+        let mut table = HistoryTable::new(MARKET_SCHEMA);
+        for m in markets {
+            let result = serde_json::to_value(m.result)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string));
+            table.rows.push(vec![
+                m.ticker.as_str().into(),
+                m.event_ticker.as_str().into(),
+                m.market_type.as_str().into(),
+                m.title.as_str().into(),
+                m.subtitle.as_str().into(),
+                m.yes_sub_title.as_str().into(),
+                m.no_sub_title.as_str().into(),
+                m.category.as_str().into(),
+                m.status.as_str().into(),
+                m.open_time.as_str().into(),
+                m.close_time.as_str().into(),
+                m.expiration_time.clone().into(),
+                result.into(),
+                m.settlement_value.clone().into(),
+                m.strike_type.clone().into(),
+                m.floor_strike.into(),
+                m.cap_strike.into(),
+                m.yes_bid.into(),
+                m.yes_ask.into(),
+                m.no_bid.into(),
+                m.no_ask.into(),
+                m.last_price.into(),
+                m.volume.into(),
+                m.volume_24h.into(),
+                m.open_interest.into(),
+                m.liquidity.into(),
+            ]);
+        }
+        table
+    }
+
+    pub fn trades<'a>(trades: impl IntoIterator<Item = &'a Trade>) -> Self {
+        let mut table = HistoryTable::new(TRADE_SCHEMA);
+        for t in trades {
+            table.rows.push(vec![
+                t.trade_id.as_str().into(),
+                t.ticker.as_str().into(),
+                t.created_time.as_str().into(),
+                parse_ts(&t.created_time).into(),
+                t.yes_price.into(),
+                t.no_price.into(),
+                t.count.into(),
+                t.taker_side.as_str().into(),
+            ]);
+        }
+        table
+    }
+
+    pub fn candles<'a>(
+        period_interval: i32,
+        candles: impl IntoIterator<Item = (&'a str, &'a Candle)>,
+    ) -> Self {
+        let mut table = HistoryTable::new(CANDLE_SCHEMA);
+        for (ticker, c) in candles {
+            table.rows.push(vec![
+                ticker.into(),
+                period_interval.into(),
+                c.start_ts.into(),
+                c.end_ts.into(),
+                c.yes_open.into(),
+                c.yes_high.into(),
+                c.yes_low.into(),
+                c.yes_close.into(),
+                c.no_open.into(),
+                c.no_high.into(),
+                c.no_low.into(),
+                c.no_close.into(),
+                c.volume.into(),
+                c.open_interest.into(),
+            ]);
+        }
+        table
+    }
+
+    /// Writes the table as CSV. When `append` is set and the file exists, rows are added
+    /// without repeating the header.
+    pub fn write_csv(&self, path: &Path, append: bool) -> Result<(), KalshiError> {
+        let exists = append && path.exists();
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(exists)
+            .truncate(!exists)
+            .open(path)
+            .map_err(|e| io_error(path, e))?;
+        let mut out = String::new();
+        if !exists {
+            let header: Vec<&str> = self.columns.iter().map(|c| c.name).collect();
+            out.push_str(&header.join(","));
+            out.push('\n');
+        }
+        for row in &self.rows {
+            let cells: Vec<String> = row.iter().map(csv_cell).collect();
+            out.push_str(&cells.join(","));
+            out.push('\n');
+        }
+        file.write_all(out.as_bytes())
+            .map_err(|e| io_error(path, e))
+    }
+
+    /// Writes the table as a Parquet file, replacing any existing file.
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet(&self, path: &Path) -> Result<(), KalshiError> {
+        use arrow_array::builder::{BooleanBuilder, Float64Builder, Int64Builder, StringBuilder};
+        use arrow_array::{ArrayRef, RecordBatch};
+        use arrow_schema::{DataType, Field, Schema};
+        use std::sync::Arc;
+
+        let fields: Vec<Field> = self
+            .columns
+            .iter()
+            .map(|c| {
+                let dt = match c.kind {
+                    Utf8 => DataType::Utf8,
+                    Int64 => DataType::Int64,
+                    Float64 => DataType::Float64,
+                    Bool => DataType::Boolean,
+                };
+                Field::new(c.name, dt, true)
+            })
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+
+        let arrays: Vec<ArrayRef> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| -> ArrayRef {
+                let cells = self
+                    .rows
+                    .iter()
+                    .map(|r| r.get(i).unwrap_or(&HistoryValue::Null));
+                match c.kind {
+                    Utf8 => {
+                        let mut b = StringBuilder::new();
+                        for v in cells {
+                            match v {
+                                HistoryValue::Null => b.append_null(),
+                                v => b.append_value(csv_text(v)),
+                            }
+                        }
+                        Arc::new(b.finish())
+                    }
+                    Int64 => {
+                        let mut b = Int64Builder::new();
+                        for v in cells {
+                            b.append_option(match v {
+                                HistoryValue::Int(n) => Some(*n),
+                                _ => None,
+                            });
+                        }
+                        Arc::new(b.finish())
+                    }
+                    Float64 => {
+                        let mut b = Float64Builder::new();
+                        for v in cells {
+                            b.append_option(match v {
+                                HistoryValue::Float(f) => Some(*f),
+                                HistoryValue::Int(n) => Some(*n as f64),
+                                _ => None,
+                            });
+                        }
+                        Arc::new(b.finish())
+                    }
+                    Bool => {
+                        let mut b = BooleanBuilder::new();
+                        for v in cells {
+                            b.append_option(match v {
+                                HistoryValue::Bool(x) => Some(*x),
+                                _ => None,
+                            });
+                        }
+                        Arc::new(b.finish())
+                    }
+                }
+            })
+            .collect();
+
+        let batch = RecordBatch::try_new(schema.clone(), arrays)
+            .map_err(|e| KalshiError::InternalError(format!("parquet batch: {}", e)))?;
+        let file = fs::File::create(path).map_err(|e| io_error(path, e))?;
+        let mut writer = parquet::arrow::ArrowWriter::try_new(file, schema, None)
+            .map_err(|e| KalshiError::InternalError(format!("parquet writer: {}", e)))?;
+        writer
+            .write(&batch)
+            .and_then(|_| writer.close().map(|_| ()))
+            .map_err(|e| KalshiError::InternalError(format!("parquet write: {}", e)))
+    }
+
+    /// Writes the table as a Parquet file. This build lacks the `parquet` feature.
+    #[cfg(not(feature = "parquet"))]
+    pub fn write_parquet(&self, _path: &Path) -> Result<(), KalshiError> {
+        Err(KalshiError::UserInputError(
+            "Parquet output requires the `parquet` feature".to_string(),
+        ))
+    }
+}
+
+/// Output file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryFormat {
+    Csv,
+    /// Requires the `parquet` feature.
+    Parquet,
+}
+
+/// What to download.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryScope {
+    /// Only this series.
+    pub series_ticker: Option<String>,
+    /// Only this event.
+    pub event_ticker: Option<String>,
+    /// Markets closing at or after this time, and trades/candles from it (Unix seconds).
+    pub min_ts: Option<i64>,
+    /// Markets closing at or before this time, and trades/candles up to it (Unix seconds).
+    pub max_ts: Option<i64>,
+}
+
+impl HistoryScope {
+    /// Everything in one series.
+    pub fn series(series_ticker: &str) -> Self {
+        HistoryScope {
+            series_ticker: Some(series_ticker.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Limits the scope to `[min_ts, max_ts]`: markets closing in the window, and their
+    /// trades and candles within it.
+    pub fn between(mut self, min_ts: i64, max_ts: i64) -> Self {
+        self.min_ts = Some(min_ts);
+        self.max_ts = Some(max_ts);
+        self
+    }
+}
+
+/// Counts of rows written by [`HistorySync::sync`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub series: usize,
+    pub events: usize,
+    pub markets: usize,
+    /// New trades appended.
+    pub trades: usize,
+    /// New candles appended.
+    pub candles: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncState {
+    #[serde(default)]
+    trades: BTreeMap<String, TradeMark>,
+    /// End of the newest stored candle, per market.
+    #[serde(default)]
+    candles: BTreeMap<String, i64>,
+    /// Length in bytes of each appended CSV file as of this state, by file name.
+    #[serde(default)]
+    committed: BTreeMap<String, u64>,
+}
+
+/// Newest stored trade time of a market, and the IDs stored at that second, so the next
+/// sync can start at the same second without duplicating trades.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TradeMark {
+    last_ts: i64,
+    ids_at_last_ts: BTreeSet<String>,
+}
+
+/// Downloads history into a directory. See the [module documentation](crate::history).
+#[derive(Debug, Clone)]
+pub struct HistorySync {
+    dir: PathBuf,
+    formats: Vec<HistoryFormat>,
+    period_interval: i32,
+}
+
+impl HistorySync {
+    /// Writes CSV with hourly candles into `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        HistorySync {
+            dir: dir.into(),
+            formats: vec![HistoryFormat::Csv],
+            period_interval: 60,
+        }
+    }
+
+    /// Sets the output formats.
+    pub fn with_formats(mut self, formats: Vec<HistoryFormat>) -> Self {
+        self.formats = formats;
+        self
+    }
+
+    /// Sets the candle period in minutes (1, 60 or 1440).
+    pub fn with_period_interval(mut self, period_interval: i32) -> Self {
+        self.period_interval = period_interval;
+        self
+    }
+
+    /// Downloads everything in `scope` that is not stored yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - Source of market data, usually a [`Kalshi`](crate::Kalshi).
+    /// * `scope` - Series, event and time window to download.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(SyncReport)`: Row counts written.
+    /// - `Err(KalshiError)`: A request or file error. Data and state saved before the
+    ///   error are kept, so rerunning resumes from there.
+    ///
+    pub async fn sync<B: MarketData>(
+        &self,
+        backend: &B,
+        scope: &HistoryScope,
+    ) -> Result<SyncReport, KalshiError> {
+        if self.formats.contains(&HistoryFormat::Parquet) && !cfg!(feature = "parquet") {
+            return Err(KalshiError::UserInputError(
+                "Parquet output requires the `parquet` feature".to_string(),
+            ));
+        }
+        fs::create_dir_all(&self.dir).map_err(|e| io_error(&self.dir, e))?;
+        let mut state = self.load_state()?;
+        self.truncate_uncommitted(&state)?;
+        let mut report = SyncReport::default();
+
+        let markets = fetch_markets(backend, scope).await?;
+        let events = fetch_events(backend, scope, &markets).await?;
+        let series = fetch_series(backend, scope, &events).await?;
+        report.markets = markets.len();
+        report.events = events.len();
+        report.series = series.len();
+        self.write("series", &HistoryTable::series(&series))?;
+        self.write("events", &HistoryTable::events(&events))?;
+        self.write("markets", &HistoryTable::markets(&markets))?;
+
+        for market in &markets {
+            report.trades += self.sync_trades(backend, scope, market, &mut state).await?;
+        }
+        report.candles = self
+            .sync_candles(backend, scope, &markets, &mut state)
+            .await?;
+        Ok(report)
+    }
+
+    async fn sync_trades<B: MarketData>(
+        &self,
+        backend: &B,
+        scope: &HistoryScope,
+        market: &Market,
+        state: &mut SyncState,
+    ) -> Result<usize, KalshiError> {
+        let mark = state.trades.get(&market.ticker);
+        let min_ts = match (mark.map(|m| m.last_ts), scope.min_ts) {
+            (Some(last), Some(min)) => Some(last.max(min)),
+            (last, min) => last.or(min),
+        };
+        let mut trades = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (next, page) = backend
+                .get_trades(
+                    Some(PAGE_SIZE),
+                    cursor,
+                    Some(market.ticker.clone()),
+                    min_ts,
+                    scope.max_ts,
+                )
+                .await?;
+            let done = page.is_empty();
+            trades.extend(page);
+            match next {
+                Some(c) if !c.is_empty() && !done => cursor = Some(c),
+                _ => break,
+            }
+        }
+
+        // Pages come newest first; store oldest first and skip what was stored before.
+        let mut trades: Vec<(i64, Trade)> = trades
+            .into_iter()
+            .filter_map(|t| parse_ts(&t.created_time).map(|ts| (ts, t)))
+            .filter(|(ts, t)| {
+                mark.is_none_or(|m| {
+                    *ts > m.last_ts || (*ts == m.last_ts && !m.ids_at_last_ts.contains(&t.trade_id))
+                })
+            })
+            .collect();
+        if trades.is_empty() {
+            return Ok(0);
+        }
+        trades.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.trade_id.cmp(&b.1.trade_id)));
+        let part = format!("{}-{}-{}", market.ticker, trades[0].0, trades[0].1.trade_id);
+        self.append(
+            "trades",
+            &part,
+            &HistoryTable::trades(trades.iter().map(|(_, t)| t)),
+            state,
+        )?;
+
+        let last_ts = trades.last().map(|(ts, _)| *ts).unwrap_or_default();
+        let entry = state.trades.entry(market.ticker.clone()).or_default();
+        if entry.last_ts != last_ts {
+            entry.ids_at_last_ts.clear();
+        }
+        entry.last_ts = last_ts;
+        entry.ids_at_last_ts.extend(
+            trades
+                .iter()
+                .filter(|(ts, _)| *ts == last_ts)
+                .map(|(_, t)| t.trade_id.clone()),
+        );
+        self.commit(state)?;
+        Ok(trades.len())
+    }
+
+    async fn sync_candles<B: MarketData>(
+        &self,
+        backend: &B,
+        scope: &HistoryScope,
+        markets: &[Market],
+        state: &mut SyncState,
+    ) -> Result<usize, KalshiError> {
+        let now = Utc::now().timestamp();
+        let period_secs = self.period_interval.max(1) as i64 * 60;
+        // Per market: first candle end still missing, and the last one wanted.
+        let windows: Vec<(&str, i64, i64)> = markets
+            .iter()
+            .filter_map(|m| {
+                let open = parse_ts(&m.open_time).unwrap_or(0);
+                let close = parse_ts(&m.close_time).unwrap_or(now);
+                let start = state
+                    .candles
+                    .get(&m.ticker)
+                    .copied()
+                    .into_iter()
+                    .chain(scope.min_ts)
+                    .chain(Some(open))
+                    .max()?;
+                let end = close.min(now).min(scope.max_ts.unwrap_or(i64::MAX));
+                (start < end).then_some((m.ticker.as_str(), start, end))
+            })
+            .collect();
+
+        let mut added = 0;
+        for chunk in windows.chunks(MAX_CANDLE_TICKERS) {
+            let start = chunk.iter().map(|w| w.1).min().unwrap_or_default();
+            let end = chunk.iter().map(|w| w.2).max().unwrap_or_default();
+            let span = (MAX_CANDLES_PER_REQUEST / chunk.len() as i64).max(1) * period_secs;
+            let tickers: Vec<String> = chunk.iter().map(|w| w.0.to_string()).collect();
+            let mut from = start;
+            while from < end {
+                let to = (from + span).min(end);
+                let batch = backend
+                    .batch_get_market_candlesticks(
+                        tickers.clone(),
+                        from,
+                        to,
+                        self.period_interval,
+                        None,
+                    )
+                    .await?;
+                let mut rows: Vec<(&str, &Candle)> = Vec::new();
+                for market in &batch {
+                    let Some(&(ticker, after, until)) = chunk.iter().find(|w| w.0 == market.ticker)
+                    else {
+                        continue;
+                    };
+                    rows.extend(
+                        market
+                            .candlesticks
+                            .iter()
+                            .filter(|c| c.end_ts > after && c.end_ts <= until)
+                            .map(|c| (ticker, c)),
+                    );
+                }
+                rows.sort_by_key(|(ticker, c)| (*ticker, c.end_ts));
+                rows.dedup_by_key(|(ticker, c)| (*ticker, c.end_ts));
+                if let Some((first_ticker, first)) = rows.first() {
+                    let part = format!("{}-{}", first_ticker, first.end_ts);
+                    self.append(
+                        "candlesticks",
+                        &part,
+                        &HistoryTable::candles(self.period_interval, rows.iter().copied()),
+                        state,
+                    )?;
+                    for (ticker, c) in &rows {
+                        let last = state.candles.entry(ticker.to_string()).or_default();
+                        *last = (*last).max(c.end_ts);
+                    }
+                    self.commit(state)?;
+                    added += rows.len();
+                }
+                from = to;
+            }
+        }
+        Ok(added)
+    }
+
+    /// Replaces a reference table in every configured format.
+    fn write(&self, name: &str, table: &HistoryTable) -> Result<(), KalshiError> {
+        for format in &self.formats {
+            match format {
+                HistoryFormat::Csv => {
+                    table.write_csv(&self.dir.join(format!("{}.csv", name)), false)?
+                }
+                HistoryFormat::Parquet => {
+                    table.write_parquet(&self.dir.join(format!("{}.parquet", name)))?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends `table` in every configured format: to one growing CSV file, and as the
+    /// Parquet part `part-<part>.parquet`. The rows are only kept once [`commit`] saves
+    /// the state; a CSV file seen for the first time is committed at its current length
+    /// before anything is appended.
+    ///
+    /// [`commit`]: Self::commit
+    fn append(
+        &self,
+        name: &str,
+        part: &str,
+        table: &HistoryTable,
+        state: &mut SyncState,
+    ) -> Result<(), KalshiError> {
+        for format in &self.formats {
+            match format {
+                HistoryFormat::Csv => {
+                    let file = format!("{}.csv", name);
+                    let path = self.dir.join(&file);
+                    if let Entry::Vacant(entry) = state.committed.entry(file) {
+                        entry.insert(file_len(&path)?);
+                        self.save_state(state)?;
+                    }
+                    table.write_csv(&path, true)?
+                }
+                HistoryFormat::Parquet => {
+                    let dir = self.dir.join(name);
+                    fs::create_dir_all(&dir).map_err(|e| io_error(&dir, e))?;
+                    table.write_parquet(&dir.join(format!("part-{}.parquet", part)))?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the current length of every appended CSV file and saves the state.
+    fn commit(&self, state: &mut SyncState) -> Result<(), KalshiError> {
+        for (file, len) in state.committed.iter_mut() {
+            *len = file_len(&self.dir.join(file))?;
+        }
+        self.save_state(state)
+    }
+
+    /// Drops rows appended to CSV files after the state was last saved.
+    fn truncate_uncommitted(&self, state: &SyncState) -> Result<(), KalshiError> {
+        for (file, &len) in &state.committed {
+            let path = self.dir.join(file);
+            if file_len(&path)? > len {
+                fs::OpenOptions::new()
+                    .write(true)
+                    .open(&path)
+                    .and_then(|f| f.set_len(len))
+                    .map_err(|e| io_error(&path, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn load_state(&self) -> Result<SyncState, KalshiError> {
+        let path = self.dir.join(STATE_FILE);
+        if !path.exists() {
+            return Ok(SyncState::default());
+        }
+        let text = fs::read_to_string(&path).map_err(|e| io_error(&path, e))?;
+        serde_json::from_str(&text)
+            .map_err(|e| KalshiError::UserInputError(format!("corrupt {}: {}", path.display(), e)))
+    }
+
+    /// Saves state through a temporary file so an interrupted write never corrupts it.
+    fn save_state(&self, state: &SyncState) -> Result<(), KalshiError> {
+        let path = self.dir.join(STATE_FILE);
+        let tmp = self.dir.join(format!("{}.tmp", STATE_FILE));
+        let text = serde_json::to_string_pretty(state)
+            .map_err(|e| KalshiError::InternalError(e.to_string()))?;
+        fs::write(&tmp, text).map_err(|e| io_error(&tmp, e))?;
+        fs::rename(&tmp, &path).map_err(|e| io_error(&path, e))
+    }
+}
+
+async fn fetch_markets<B: MarketData>(
+    backend: &B,
+    scope: &HistoryScope,
+) -> Result<Vec<Market>, KalshiError> {
+    let mut markets = Vec::new();
+    let mut cursor = None;
+    loop {
+        let (next, page) = backend
+            .get_markets(
+                Some(PAGE_SIZE),
+                cursor,
+                scope.event_ticker.clone(),
+                scope.series_ticker.clone(),
+                None,
+                None,
+                scope.min_ts,
+                scope.max_ts,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await?;
+        let done = page.is_empty();
+        markets.extend(page);
+        match next {
+            Some(c) if !c.is_empty() && !done => cursor = Some(c),
+            _ => break,
+        }
+    }
+    markets.sort_by(|a, b| a.ticker.cmp(&b.ticker));
+    Ok(markets)
+}
+
+/// Events of the scope's series, or of the downloaded markets when there is no series.
+async fn fetch_events<B: MarketData>(
+    backend: &B,
+    scope: &HistoryScope,
+    markets: &[Market],
+) -> Result<Vec<Event>, KalshiError> {
+    let wanted: BTreeSet<&str> = markets.iter().map(|m| m.event_ticker.as_str()).collect();
+    let mut events = Vec::new();
+    if scope.series_ticker.is_some() && scope.event_ticker.is_none() {
+        let mut cursor = None;
+        loop {
+            let (next, page) = backend
+                .get_events(
+                    Some(200),
+                    cursor,
+                    None,
+                    scope.series_ticker.clone(),
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+            let done = page.is_empty();
+            events.extend(
+                page.into_iter()
+                    .filter(|e| wanted.contains(e.event_ticker.as_str())),
+            );
+            match next {
+                Some(c) if !c.is_empty() && !done => cursor = Some(c),
+                _ => break,
+            }
+        }
+    } else {
+        for event_ticker in wanted {
+            let mut event = backend.get_event(event_ticker).await?;
+            event.markets = None;
+            events.push(event);
+        }
+    }
+    events.sort_by(|a, b| a.event_ticker.cmp(&b.event_ticker));
+    Ok(events)
+}
+
+async fn fetch_series<B: MarketData>(
+    backend: &B,
+    scope: &HistoryScope,
+    events: &[Event],
+) -> Result<Vec<Series>, KalshiError> {
+    let mut tickers: BTreeSet<&str> = events.iter().map(|e| e.series_ticker.as_str()).collect();
+    tickers.extend(scope.series_ticker.as_deref());
+    let mut series = Vec::new();
+    for ticker in tickers {
+        series.push(backend.get_series(ticker).await?);
+    }
+    Ok(series)
+}
+
+fn csv_text(v: &HistoryValue) -> String {
+    match v {
+        HistoryValue::Null => String::new(),
+        HistoryValue::Str(s) => s.clone(),
+        HistoryValue::Int(n) => n.to_string(),
+        HistoryValue::Float(f) => f.to_string(),
+        HistoryValue::Bool(b) => b.to_string(),
+    }
+}
+
+/// Formats a cell, quoting it when it contains a separator, quote or line break.
+fn csv_cell(v: &HistoryValue) -> String {
+    let text = csv_text(v);
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+/// File errors come from the caller's directory or disk, so they are reported as input
+/// errors.
+pub(crate) fn io_error(path: &Path, e: std::io::Error) -> KalshiError {
+    KalshiError::UserInputError(format!("{}: {}", path.display(), e))
+}
+
+/// Length of a file in bytes, or 0 if it does not exist.
+fn file_len(path: &Path) -> Result<u64, KalshiError> {
+    match fs::metadata(path) {
+        Ok(meta) => Ok(meta.len()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(io_error(path, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InMemoryKalshi;
+
+    fn trade(id: &str, created_time: &str) -> Trade {
+        Trade {
+            trade_id: id.to_string(),
+            taker_side: "yes".to_string(),
+            ticker: "KXTEST-24JAN01-T1".to_string(),
+            count: 1,
+            yes_price: 40,
+            no_price: 60,
+            created_time: created_time.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_csv_cells_are_quoted() {
+        assert_eq!(csv_cell(&HistoryValue::from("a,b")), "\"a,b\"");
+        assert_eq!(
+            csv_cell(&HistoryValue::from("say \"hi\"")),
+            "\"say \"\"hi\"\"\""
+        );
+        assert_eq!(csv_cell(&HistoryValue::Null), "");
+        assert_eq!(csv_cell(&HistoryValue::from(Some(3i64))), "3");
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_round_trip() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let path = std::env::temp_dir().join(format!("kalshi-{}.parquet", uuid::Uuid::new_v4()));
+        let trades = [trade("a", "2024-01-01T00:00:05Z"), trade("b", "bad")];
+        HistoryTable::trades(&trades).write_parquet(&path).unwrap();
+        let reader = SerializedFileReader::new(fs::File::open(&path).unwrap()).unwrap();
+        let meta = reader.metadata();
+        assert_eq!(meta.file_metadata().num_rows(), 2);
+        assert_eq!(
+            meta.file_metadata().schema_descr().num_columns(),
+            TRADE_SCHEMA.len()
+        );
+        fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_trades_resume_without_duplicates() {
+        let dir = std::env::temp_dir().join(format!("kalshi-history-{}", uuid::Uuid::new_v4()));
+        let backend = InMemoryKalshi::new(0);
+        let market = crate::test_market(serde_json::json!({
+            "open_time": "2024-01-01T00:00:00Z", "close_time": "2024-01-02T00:00:00Z",
+            "status": "settled", "result": "yes"
+        }));
+        let scope = HistoryScope::default();
+        fs::create_dir_all(&dir).unwrap();
+        let sync = HistorySync::new(&dir);
+        let mut state = SyncState::default();
+
+        backend.push_trade(trade("a", "2024-01-01T00:00:05Z"));
+        backend.push_trade(trade("b", "2024-01-01T00:00:10Z"));
+        let first = sync
+            .sync_trades(&backend, &scope, &market, &mut state)
+            .await;
+        assert_eq!(first.unwrap(), 2);
+
+        // A late trade in the same second as the last stored one, and a newer one.
+        backend.push_trade(trade("c", "2024-01-01T00:00:10Z"));
+        backend.push_trade(trade("d", "2024-01-01T00:00:20Z"));
+        let second = sync
+            .sync_trades(&backend, &scope, &market, &mut state)
+            .await;
+        assert_eq!(second.unwrap(), 2);
+
+        let csv = fs::read_to_string(dir.join("trades.csv")).unwrap();
+        let ids: Vec<&str> = csv
+            .lines()
+            .skip(1)
+            .map(|l| l.split(',').next().unwrap())
+            .collect();
+        assert_eq!(ids, ["a", "b", "c", "d"]);
+        let saved = sync.load_state().unwrap();
+        assert!(saved.trades.contains_key("KXTEST-24JAN01-T1"));
+
+        // Rows appended after the last saved state are dropped before the next sync.
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(dir.join("trades.csv"))
+            .unwrap();
+        file.write_all(b"e,uncommitted\n").unwrap();
+        sync.truncate_uncommitted(&saved).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("trades.csv")).unwrap(), csv);
+        fs::remove_dir_all(&dir).ok();
+    }
+}
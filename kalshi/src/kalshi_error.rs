@@ -1,4 +1,5 @@
 use core::fmt;
+use serde::{Deserialize, Serialize};
 use std::error::Error;
 // CUSTOM ERROR STRUCTS + ENUMS
 // -----------------------------------------------
@@ -16,20 +17,52 @@ pub enum KalshiError {
     RequestError(RequestError),
     /// Errors caused by incorrect or invalid user input.
     UserInputError(String),
+    /// The exchange rejected a request with a 4xx status other than 401.
+    HttpError {
+        /// The response status.
+        status: reqwest::StatusCode,
+        /// The response body.
+        body: String,
+    },
     /// Errors representing unexpected internal issues or situations that are not supposed to happen.
     InternalError(String),
     /// Authentication errors, such as missing credentials or invalid keys.
     Auth(String),
+    /// A per-item error reported by the exchange inside a batch response.
+    ApiError(ApiError),
+    /// An order was blocked by the client-side [`RiskGuard`](crate::RiskGuard) before being sent.
+    RiskViolation(crate::risk::RiskViolation),
+    /// The exchange is not trading, or waiting for it to trade was cancelled or timed out.
+    /// See [`ExchangeGate`](crate::ExchangeGate).
+    ExchangeUnavailable(crate::gate::ExchangeUnavailable),
     // TODO: add error type specifically for joining threads together.
 }
 
+impl KalshiError {
+    /// True if the exchange (or a local backend) reported that the requested resource
+    /// does not exist: a 404 response or an `ApiError` with code `not_found`.
+    pub(crate) fn is_not_found(&self) -> bool {
+        match self {
+            KalshiError::HttpError { status, .. } => *status == reqwest::StatusCode::NOT_FOUND,
+            KalshiError::ApiError(e) => e.code.as_deref() == Some("not_found"),
+            _ => false,
+        }
+    }
+}
+
 impl fmt::Display for KalshiError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             KalshiError::RequestError(e) => write!(f, "HTTP Error: {}", e),
             KalshiError::UserInputError(e) => write!(f, "User Input Error: {}", e),
+            KalshiError::HttpError { status, body } => {
+                write!(f, "Request failed with status {}: {}", status, body)
+            }
             KalshiError::InternalError(e) => write!(f, "INTERNAL ERROR, PLEASE EMAIL DEVELOPER OR MAKE A NEW ISSUE ON THE CRATE'S REPOSITORY: https://github.com/dpeachpeach/kalshi-rust. Specific Error: {}", e),
-            KalshiError::Auth(e) => write!(f, "Authentication Error: {}", e)
+            KalshiError::Auth(e) => write!(f, "Authentication Error: {}", e),
+            KalshiError::ApiError(e) => write!(f, "API Error: {}", e),
+            KalshiError::RiskViolation(e) => write!(f, "Risk Check Failed: {}", e),
+            KalshiError::ExchangeUnavailable(e) => write!(f, "Exchange Unavailable: {}", e),
         }
     }
 }
@@ -39,8 +72,12 @@ impl Error for KalshiError {
         match self {
             KalshiError::RequestError(e) => Some(e),
             KalshiError::UserInputError(_) => None,
+            KalshiError::HttpError { .. } => None,
             KalshiError::InternalError(_) => None,
             KalshiError::Auth(_) => None,
+            KalshiError::ApiError(_) => None,
+            KalshiError::RiskViolation(_) => None,
+            KalshiError::ExchangeUnavailable(_) => None,
         }
     }
 }
@@ -73,6 +110,34 @@ impl From<reqwest::Error> for KalshiError {
     }
 }
 
+/// An error object returned by the exchange, e.g. for one item of a batch request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiError {
+    /// Machine-readable error code, such as `insufficient_balance`.
+    #[serde(default)]
+    pub code: Option<String>,
+    /// Human-readable description.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Additional details, when provided.
+    #[serde(default)]
+    pub details: Option<String>,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = self.message.as_deref().unwrap_or("unknown error");
+        match &self.code {
+            Some(code) => write!(f, "[{}] {}", code, message)?,
+            None => write!(f, "{}", message)?,
+        }
+        if let Some(details) = &self.details {
+            write!(f, " ({})", details)?;
+        }
+        Ok(())
+    }
+}
+
 /// Specific kinds of HTTP request errors encountered in the Kalshi module.
 ///
 /// This enum categorizes errors related to HTTP requests, including serialization errors, client-side errors,
@@ -161,3 +226,28 @@ impl From<serde_json::Error> for KalshiError {
         KalshiError::InternalError(format!("JSON Error: {}", err))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_not_found_uses_the_status() {
+        let error = |status| KalshiError::HttpError {
+            status,
+            body: "{}".to_string(),
+        };
+        assert!(error(reqwest::StatusCode::NOT_FOUND).is_not_found());
+        assert!(!error(reqwest::StatusCode::BAD_REQUEST).is_not_found());
+        assert!(!KalshiError::InternalError("Server error 503".into()).is_not_found());
+        let api = |code: &str| {
+            KalshiError::ApiError(ApiError {
+                code: Some(code.to_string()),
+                message: None,
+                details: None,
+            })
+        };
+        assert!(api("not_found").is_not_found());
+        assert!(!api("market_closed").is_not_found());
+    }
+}
@@ -0,0 +1,351 @@
+//! Kill switch: cancel every resting order and flatten every position.
+//!
+//! [`Kalshi::cancel_all_orders`] pages through resting orders, keeps those matching an
+//! [`OrderFilter`], and cancels them in batches of 20. [`Kalshi::flatten_positions`] closes
+//! each matching position with a reduce-only immediate-or-cancel order.
+//!
+//! Both calls keep going when individual items fail and report every failure, so a single
+//! rejected cancel never leaves the rest of the book untouched.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::OrderFilter;
+//!
+//! let report = kalshi.cancel_all_orders(&OrderFilter::default()).await?;
+//! if !report.failed.is_empty() {
+//!     eprintln!("{} orders could not be cancelled", report.failed.len());
+//! }
+//! let flattened = kalshi.flatten_positions(&OrderFilter::default()).await?;
+//! ```
+
+use crate::kalshi_error::*;
+use crate::risk::series_ticker_of;
+use crate::{
+    Action, Kalshi, MarketPosition, Order, OrderIntent, OrderStatus, OrderType, Side, TimeInForce,
+};
+
+/// Page size used when listing orders and positions.
+const PAGE_SIZE: i32 = 200;
+
+/// Selects which orders or positions a kill-switch call acts on.
+///
+/// All set fields must match; the default filter matches everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrderFilter {
+    /// Only this market.
+    pub ticker: Option<String>,
+    /// Only markets of this event.
+    pub event_ticker: Option<String>,
+    /// Only markets of this series.
+    pub series_ticker: Option<String>,
+    /// Only orders in this order group. Ignored by `flatten_positions`.
+    pub order_group_id: Option<String>,
+}
+
+impl OrderFilter {
+    /// Matches a single market.
+    pub fn ticker(ticker: &str) -> Self {
+        OrderFilter {
+            ticker: Some(ticker.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Matches every market of an event.
+    pub fn event(event_ticker: &str) -> Self {
+        OrderFilter {
+            event_ticker: Some(event_ticker.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Matches every market of a series.
+    pub fn series(series_ticker: &str) -> Self {
+        OrderFilter {
+            series_ticker: Some(series_ticker.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Matches every order of an order group.
+    pub fn order_group(order_group_id: &str) -> Self {
+        OrderFilter {
+            order_group_id: Some(order_group_id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if `order` passes the filter.
+    pub fn matches_order(&self, order: &Order) -> bool {
+        self.matches_ticker(&order.ticker)
+            && self
+                .order_group_id
+                .as_ref()
+                .is_none_or(|g| order.order_group_id.as_ref() == Some(g))
+    }
+
+    fn matches_ticker(&self, ticker: &str) -> bool {
+        self.ticker.as_deref().is_none_or(|t| t == ticker)
+            && self
+                .event_ticker
+                .as_deref()
+                .is_none_or(|e| ticker.starts_with(&format!("{}-", e)))
+            && self
+                .series_ticker
+                .as_deref()
+                .is_none_or(|s| series_ticker_of(ticker) == s)
+    }
+}
+
+/// Outcome of [`Kalshi::cancel_all_orders`].
+#[derive(Debug, Default)]
+pub struct CancelAllReport {
+    /// Cancelled orders with the number of contracts removed.
+    pub cancelled: Vec<(Order, i32)>,
+    /// Order IDs that could not be cancelled, with the reason.
+    pub failed: Vec<(String, KalshiError)>,
+}
+
+impl CancelAllReport {
+    /// True if every matching order was cancelled.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Outcome of [`Kalshi::flatten_positions`].
+#[derive(Debug, Default)]
+pub struct FlattenReport {
+    /// Closing orders accepted by the exchange.
+    pub submitted: Vec<Order>,
+    /// Market tickers whose closing order failed, with the reason.
+    pub failed: Vec<(String, KalshiError)>,
+}
+
+impl FlattenReport {
+    /// True if a closing order was accepted for every matching position.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+impl Kalshi {
+    /// Cancels every resting order matching `filter`.
+    ///
+    /// All matching order IDs are collected first, so cancellations don't disturb the
+    /// pagination, then cancelled in batches of 20, one batch at a time. A failed batch is
+    /// reported against each of its order IDs and the remaining batches still run.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Which orders to cancel. `OrderFilter::default()` cancels everything.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(CancelAllReport)`: Cancelled orders and per-order failures.
+    /// - `Err(KalshiError)`: An error if the resting orders could not be listed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // Cancel everything in one event
+    /// let report = kalshi_instance
+    ///     .cancel_all_orders(&OrderFilter::event("KXHIGHNY-24JAN01"))
+    ///     .await?;
+    /// ```
+    ///
+    pub async fn cancel_all_orders(
+        &self,
+        filter: &OrderFilter,
+    ) -> Result<CancelAllReport, KalshiError> {
+        let mut ids = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (next, orders) = self
+                .get_orders(
+                    filter.ticker.clone(),
+                    filter.event_ticker.clone(),
+                    None,
+                    None,
+                    Some(OrderStatus::Resting),
+                    Some(PAGE_SIZE),
+                    cursor,
+                )
+                .await?;
+            ids.extend(
+                orders
+                    .iter()
+                    .filter(|o| filter.matches_order(o))
+                    .map(|o| o.order_id.clone()),
+            );
+            match next {
+                Some(c) if !c.is_empty() && !orders.is_empty() => cursor = Some(c),
+                _ => break,
+            }
+        }
+
+        let mut report = CancelAllReport::default();
+        let results = self.batch_cancel_order_chunked(ids.clone(), 1).await;
+        for (id, result) in ids.into_iter().zip(results) {
+            match result {
+                Ok(cancelled) => report.cancelled.push(cancelled),
+                Err(e) => report.failed.push((id, e)),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Closes every open position matching `filter`.
+    ///
+    /// Each position is closed with a reduce-only, immediate-or-cancel limit order that
+    /// sells the held side down to 1¢, so it takes whatever liquidity is on the book and
+    /// can never open a new position. Anything not filled is cancelled by the exchange;
+    /// check the returned orders' `remaining_count` and call again if needed.
+    ///
+    /// Resting orders are not touched. Call [`Kalshi::cancel_all_orders`] first for a
+    /// full kill switch.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Which positions to close. `order_group_id` is ignored.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(FlattenReport)`: Submitted closing orders and per-market failures.
+    /// - `Err(KalshiError)`: An error if the positions could not be listed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let report = kalshi_instance.flatten_positions(&OrderFilter::default()).await?;
+    /// ```
+    ///
+    pub async fn flatten_positions(
+        &self,
+        filter: &OrderFilter,
+    ) -> Result<FlattenReport, KalshiError> {
+        let mut positions: Vec<MarketPosition> = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (next, _events, markets) = self
+                .get_positions(
+                    Some(PAGE_SIZE as i64),
+                    cursor,
+                    Some("unsettled".to_string()),
+                    filter.ticker.clone(),
+                    filter.event_ticker.clone(),
+                    Some("position".to_string()),
+                )
+                .await?;
+            let done = markets.is_empty();
+            positions.extend(
+                markets
+                    .into_iter()
+                    .filter(|p| p.position != 0 && filter.matches_ticker(&p.ticker)),
+            );
+            match next {
+                Some(c) if !c.is_empty() && !done => cursor = Some(c),
+                _ => break,
+            }
+        }
+
+        let mut report = FlattenReport::default();
+        for pos in positions {
+            let intent = flatten_intent(&pos.ticker, pos.position);
+            let price = intent.price.map(i64::from);
+            let (yes_price, no_price) = match intent.side {
+                Side::Yes => (price, None),
+                Side::No => (None, price),
+            };
+            let result = self
+                .create_order(
+                    intent.action,
+                    None,
+                    intent.count,
+                    intent.side,
+                    intent.ticker,
+                    OrderType::Limit,
+                    None,
+                    None,
+                    yes_price,
+                    no_price,
+                    None,
+                    None,
+                    None,
+                    Some(TimeInForce::ImmediateOrCancel),
+                    None,
+                    Some(intent.reduce_only),
+                    None,
+                    None,
+                    None,
+                )
+                .await;
+            match result {
+                Ok(order) => report.submitted.push(order),
+                Err(e) => report.failed.push((pos.ticker, e)),
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// The closing order for a position of `position` contracts (negative for NO): a
+/// reduce-only sell of the held side down to 1¢.
+fn flatten_intent(ticker: &str, position: i32) -> OrderIntent {
+    let (side, count) = if position > 0 {
+        (Side::Yes, position)
+    } else {
+        (Side::No, -position)
+    };
+    OrderIntent {
+        ticker: ticker.to_string(),
+        side,
+        action: Action::Sell,
+        count,
+        price: Some(1),
+        buy_max_cost: None,
+        reduce_only: true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_matches_event_and_series_by_prefix() {
+        let event = OrderFilter::event("KXHIGHNY-24JAN01");
+        assert!(event.matches_ticker("KXHIGHNY-24JAN01-T60"));
+        assert!(!event.matches_ticker("KXHIGHNY-24JAN012-T60"));
+
+        let series = OrderFilter::series("KXHIGHNY");
+        assert!(series.matches_ticker("KXHIGHNY-24JAN01-T60"));
+        assert!(!series.matches_ticker("KXHIGHNYC-24JAN01-T60"));
+
+        assert!(OrderFilter::default().matches_ticker("ANY-THING"));
+    }
+
+    #[test]
+    fn test_flatten_orders_pass_price_collar() {
+        let guard = std::sync::Arc::new(crate::RiskGuard::new(crate::RiskLimits {
+            price_collar: Some(5),
+            max_open_orders: Some(0),
+            daily_loss_limit: Some(100),
+            ..Default::default()
+        }));
+        guard.set_mid("KXHIGHNY-24JAN01-T60", 60.0);
+        guard.record_pnl(-500);
+        let kalshi = Kalshi::test_client().with_risk_guard(guard);
+
+        for position in [25, -25] {
+            let intent = flatten_intent("KXHIGHNY-24JAN01-T60", position);
+            assert_eq!(intent.count, 25);
+            assert!(kalshi.risk_check(&[intent]).is_ok());
+        }
+        // The same order without reduce-only is collared.
+        let mut opening = flatten_intent("KXHIGHNY-24JAN01-T60", 25);
+        opening.reduce_only = false;
+        assert!(kalshi.risk_check(&[opening]).is_err());
+    }
+}
@@ -112,43 +112,88 @@
 #[macro_use]
 mod utils;
 mod api_keys;
+mod arbitrage;
 mod auth;
+mod backend;
+mod backtest;
+mod calendar;
 mod collection;
+mod combo;
 mod communications;
+mod distribution;
 mod events;
 mod exchange;
 mod fcm;
+mod fees;
+mod gate;
+mod history;
 mod incentive_programs;
 mod kalshi_error;
+mod kill_switch;
 mod live_data;
 mod market;
 mod milestone;
+mod oms;
+mod paper;
 mod portfolio;
+mod positions;
+mod registry;
+mod rfq;
+mod risk;
+mod scanner;
 mod search;
+mod settlement;
+mod statement;
+#[cfg(feature = "storage")]
+mod storage;
 mod structured_targets;
+mod watcher;
 mod websocket;
 
 // pub use auth::*;  // Unused import
 pub use api_keys::*;
+pub use arbitrage::*;
+pub use backend::*;
+pub use backtest::*;
+pub use calendar::*;
 pub use collection::*;
+pub use combo::*;
 pub use communications::*;
+pub use distribution::*;
 pub use events::*;
 pub use exchange::*;
 pub use fcm::FcmPosition; // Only export the specific type, not all
+pub use fees::*;
+pub use gate::*;
+pub use history::*;
 pub use incentive_programs::*;
 pub use kalshi_error::*;
+pub use kill_switch::*;
 pub use live_data::*;
 pub use market::*;
 pub use milestone::*;
+pub use oms::*;
+pub use paper::*;
 pub use portfolio::*;
+pub use positions::*;
+pub use registry::*;
+pub use rfq::*;
+pub use risk::*;
+pub use scanner::*;
 pub use search::*;
+pub use settlement::*;
+pub use statement::*;
+#[cfg(feature = "storage")]
+pub use storage::*;
 pub use structured_targets::*;
+pub use watcher::*;
 pub use websocket::*;
 
 // imports
 use openssl::pkey::{PKey, Private};
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
 
 /// The Kalshi struct is the core of the kalshi-crate. It acts as the interface
 /// between the user and the market, abstracting away the meat of requests
@@ -175,6 +220,10 @@ pub struct Kalshi {
     private_key: PKey<Private>,
     /// - `client`: The HTTP client used for making requests to the marketplace.
     client: reqwest::Client,
+    /// - `risk_guard`: Optional pre-trade checks applied to every order path.
+    risk_guard: Option<Arc<RiskGuard>>,
+    /// - `exchange_gate`: Optional exchange status gate that pauses order paths.
+    exchange_gate: Option<Arc<ExchangeGate>>,
 }
 
 impl Kalshi {
@@ -237,6 +286,8 @@ impl Kalshi {
             key_id: key_id.to_string(),
             private_key,
             client: reqwest::Client::new(),
+            risk_guard: None,
+            exchange_gate: None,
         };
 
         // Verify authentication by hitting an authenticated endpoint (balance)
@@ -272,6 +323,82 @@ impl Kalshi {
     }
 }
 
+#[cfg(test)]
+impl Kalshi {
+    /// Builds a client with a throwaway key for unit tests that never hit the network.
+    pub(crate) fn test_client() -> Self {
+        let rsa = openssl::rsa::Rsa::generate(1024).expect("generate test key");
+        Self {
+            base_url: utils::build_base_url(TradingEnvironment::DemoMode).to_string(),
+            key_id: "test-key".to_string(),
+            private_key: PKey::from_rsa(rsa).expect("wrap test key"),
+            client: reqwest::Client::new(),
+            risk_guard: None,
+            exchange_gate: None,
+        }
+    }
+}
+
+/// Serves canned HTTP responses on a local port for unit tests. `respond` maps each
+/// request line, e.g. `PUT /path`, to a status such as `200 OK` and a JSON body. Returns
+/// the base URL and the request lines received, in order.
+#[cfg(test)]
+pub(crate) async fn test_server(
+    respond: impl Fn(&str) -> (&'static str, String) + Send + 'static,
+) -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = seen.clone();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match socket.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let text = String::from_utf8_lossy(&request);
+            let line = text.lines().next().unwrap_or_default();
+            let line = line.rsplit_once(' ').map_or(line, |(l, _)| l).to_string();
+            let (status, body) = respond(&line);
+            log.lock().unwrap().push(line);
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+    (url, seen)
+}
+
+/// Builds an open binary `Market` for unit tests. Every required field gets a neutral
+/// default; `fields` is a JSON object whose entries override or add to them.
+#[cfg(test)]
+pub(crate) fn test_market(fields: serde_json::Value) -> Market {
+    let mut market = serde_json::json!({
+        "ticker": "KXTEST-24JAN01-T1", "event_ticker": "KXTEST-24JAN01",
+        "market_type": "binary", "title": "", "subtitle": "", "yes_sub_title": "",
+        "no_sub_title": "", "open_time": "", "close_time": "", "latest_expiration_time": "",
+        "settlement_timer_seconds": 0, "status": "open", "response_price_units": "",
+        "notional_value": 100, "tick_size": 1, "yes_bid": 0, "yes_ask": 0, "no_bid": 0,
+        "no_ask": 0, "last_price": 0, "previous_yes_bid": 0, "previous_yes_ask": 0,
+        "previous_price": 0, "volume": 0, "volume_24h": 0, "liquidity": 0,
+        "open_interest": 0, "result": "", "can_close_early": false, "expiration_value": "",
+        "category": "", "risk_limit_cents": 0, "rules_primary": "", "rules_secondary": ""
+    });
+    if let (Some(market), serde_json::Value::Object(fields)) = (market.as_object_mut(), fields) {
+        market.extend(fields);
+    }
+    serde_json::from_value(market).expect("valid test market")
+}
+
 // GENERAL ENUMS
 // -----------------------------------------------
 
@@ -1,6 +1,8 @@
 use super::Kalshi;
 use crate::kalshi_error::*;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 impl Kalshi {
     /// Retrieves live data for a specific milestone.
@@ -31,7 +33,8 @@ impl Kalshi {
         milestone_id: &str,
     ) -> Result<LiveData, KalshiError> {
         let path = format!("/live_data/{}/milestone/{}", data_type, milestone_id);
-        self.signed_get(&path).await
+        let res: LiveDataResponse = self.signed_get(&path).await?;
+        Ok(res.live_data)
     }
 
     /// Retrieves live data for multiple milestones at once.
@@ -77,6 +80,11 @@ impl Kalshi {
 
 // -------- Response wrappers --------
 
+#[derive(Debug, Deserialize)]
+struct LiveDataResponse {
+    live_data: LiveData,
+}
+
 #[derive(Debug, Deserialize)]
 struct LiveDataBatchResponse {
     live_datas: Vec<LiveData>,
@@ -85,15 +93,33 @@ struct LiveDataBatchResponse {
 // -------- Public models --------
 
 /// Represents real-time live data for a milestone.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct LiveData {
     /// The type of live data.
     #[serde(rename = "type")]
     pub data_type: String,
     /// The milestone ID this data is associated with.
     pub milestone_id: Option<String>,
-    /// Detailed data fields.
-    pub details: serde_json::Value,
+    /// Detailed data fields. Their names and types depend on `data_type` and the API
+    /// reference does not fix a schema for them, so they are kept open; read them with
+    /// [`LiveData::detail`].
+    #[serde(default)]
+    pub details: HashMap<String, serde_json::Value>,
     /// The timestamp when this data was last updated.
     pub last_updated_ts: Option<String>,
 }
+
+impl LiveData {
+    /// Reads a detail field as `T`.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(T)`: The field is present and has the expected type.
+    /// - `None`: The field is missing, null, or of another type.
+    pub fn detail<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.details
+            .get(key)
+            .filter(|v| !v.is_null())
+            .and_then(|v| T::deserialize(v).ok())
+    }
+}
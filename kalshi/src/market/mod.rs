@@ -782,7 +782,7 @@ impl Kalshi {
 }
 
 /// When the API gives `"field": null` treat it as an empty Vec.
-fn null_to_empty_vec<'de, D, T>(d: D) -> Result<Vec<T>, D::Error>
+pub(crate) fn null_to_empty_vec<'de, D, T>(d: D) -> Result<Vec<T>, D::Error>
 where
     D: serde::Deserializer<'de>,
     T: serde::Deserialize<'de>,
@@ -848,7 +848,7 @@ where
 ///
 /// An event is a prediction market that contains multiple markets for trading.
 /// Events can have various statuses and may include nested markets.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Event {
     pub event_ticker: String,
     pub series_ticker: String,
@@ -865,7 +865,7 @@ pub struct Event {
 ///
 /// A market is a specific trading instrument within an event, representing
 /// a binary outcome that users can trade on (Yes/No).
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Market {
     pub ticker: String,
     pub event_ticker: String,
@@ -914,7 +914,7 @@ pub struct Market {
 ///
 /// A series is a collection of related events and markets, typically
 /// organized around a common theme or category.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Series {
     #[serde(default)]
     pub ticker: Option<String>,
@@ -934,29 +934,11 @@ pub struct Series {
     pub extra: HashMap<String, serde_json::Value>,
 }
 
-/// Represents a multivariate event collection on the Kalshi exchange.
-///
-/// A multivariate event collection contains multiple related markets
-/// that are analyzed together as a group.
-#[derive(Debug, Deserialize, Serialize)]
-pub struct MultivariateEventCollection {
-    pub collection_ticker: String,
-    pub title: String,
-    pub description: String,
-    pub category: String,
-    #[serde(default, deserialize_with = "null_to_empty_vec")]
-    pub tags: Vec<String>,
-    #[serde(default, deserialize_with = "null_to_empty_vec")]
-    pub markets: Vec<Market>,
-    pub created_time: String,
-    pub updated_time: String,
-}
-
 /// Represents a candlestick data point for market analysis.
 ///
 /// Candlesticks provide historical price data including open, high, low, and close
 /// prices for both Yes and No sides of a market over a specific time period.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Candle {
     pub start_ts: i64,
     pub end_ts: i64,
@@ -1012,7 +994,7 @@ pub struct Snapshot {
 ///
 /// A trade represents a completed transaction between a buyer and seller,
 /// including the price, quantity, and timing of the execution.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Trade {
     pub trade_id: String,
     pub taker_side: String,
@@ -1027,7 +1009,7 @@ pub struct Trade {
 ///
 /// Markets can settle in various ways depending on the outcome of the event
 /// and the specific market rules.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SettlementResult {
     Yes,
@@ -1055,7 +1037,7 @@ pub enum MarketStatus {
 ///
 /// This enum allows filtering markets based on whether they belong to
 /// multivariate event collections.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum MveFilter {
     /// Only include markets that are part of multivariate events
@@ -1076,7 +1058,7 @@ impl std::fmt::Display for MveFilter {
 /// Represents candlestick data for a specific market in batch responses.
 ///
 /// Contains the market ticker and its associated candlestick data.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MarketCandlesticks {
     /// The market ticker identifier
     pub ticker: String,
@@ -1088,7 +1070,7 @@ pub struct MarketCandlesticks {
 ///
 /// Settlement sources provide the data or methodology used to determine
 /// the final outcome of markets in a series.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SettlementSource {
     #[serde(default)]
     pub url: Option<String>,
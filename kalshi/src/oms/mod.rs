@@ -0,0 +1,952 @@
+//! Order management: a client-side book of every order submitted through the crate.
+//!
+//! The [`OrderManager`] wraps a [`Kalshi`] client and routes `create_order`,
+//! `batch_create_order`, `amend_order`, `decrease_order` and `cancel_order` through a
+//! local registry keyed by `client_order_id`. Order state is then kept current from
+//! two sources:
+//!
+//! - **WebSocket fills**: feed [`FillMsg`] (or any [`WebSocketMessage`]) into
+//!   [`OrderManager::handle_message`] as they arrive.
+//! - **REST reconciliation**: call [`OrderManager::reconcile`] periodically to page
+//!   `get_orders` and correct anything the stream missed.
+//!
+//! Every state change is published as an [`OrderEvent`] on a broadcast channel obtained
+//! from [`OrderManager::subscribe`].
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::{Action, OrderCreationField, OrderEvent, OrderManager, OrderType, Side};
+//!
+//! # async fn example(kalshi: kalshi::Kalshi) -> Result<(), kalshi::KalshiError> {
+//! let oms = OrderManager::new(kalshi);
+//! let mut events = oms.subscribe();
+//!
+//! let order = oms.create_order(OrderCreationField {
+//!     action: Action::Buy,
+//!     client_order_id: None,
+//!     count: 10,
+//!     side: Side::Yes,
+//!     ticker: "HIGHNY-24JAN15-T50".to_string(),
+//!     input_type: OrderType::Limit,
+//!     yes_price: Some(55),
+//!     # buy_max_cost: None, expiration_ts: None, no_price: None, sell_position_floor: None,
+//!     # yes_price_dollars: None, no_price_dollars: None, time_in_force: None, post_only: None,
+//!     # reduce_only: None, self_trade_prevention_type: None, order_group_id: None,
+//!     # cancel_order_on_pause: None,
+//! }).await?;
+//!
+//! while let Ok(event) = events.recv().await {
+//!     if let OrderEvent::Filled(o) = event {
+//!         println!("{} filled at {:?}", o.client_order_id, o.avg_fill_price);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::kalshi_error::*;
+use crate::{
+    Action, FillMsg, Kalshi, Order, OrderCreationField, OrderStatus, Side, WebSocketMessage,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Capacity of the broadcast channel carrying [`OrderEvent`]s.
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Page size used when reconciling against `get_orders`.
+const RECONCILE_PAGE_SIZE: i32 = 200;
+
+/// Client-side view of an order's lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManagedOrderState {
+    /// Submitted but not yet acknowledged by the exchange.
+    PendingNew,
+    /// Acknowledged and resting with no fills.
+    Acked,
+    /// Resting with some contracts filled.
+    PartiallyFilled,
+    /// Completely filled.
+    Filled,
+    /// Canceled (fully, or the unfilled remainder).
+    Cancelled,
+    /// Refused by the exchange or by client-side validation.
+    Rejected,
+}
+
+impl ManagedOrderState {
+    /// Returns true if the order can still receive fills.
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self,
+            ManagedOrderState::PendingNew
+                | ManagedOrderState::Acked
+                | ManagedOrderState::PartiallyFilled
+        )
+    }
+}
+
+/// An order tracked by the [`OrderManager`].
+#[derive(Debug, Clone)]
+pub struct ManagedOrder {
+    /// Client-side identifier; the key in the manager.
+    pub client_order_id: String,
+    /// Exchange identifier, known once the order is acknowledged.
+    pub order_id: Option<String>,
+    /// Market ticker.
+    pub ticker: String,
+    /// Yes/No side.
+    pub side: Side,
+    /// Buy/Sell action.
+    pub action: Action,
+    /// Limit price in cents on the order's own side, if any.
+    pub limit_price: Option<i32>,
+    /// Contracts originally requested.
+    pub count: i32,
+    /// Contracts filled so far.
+    pub filled_count: i32,
+    /// Contracts still working on the book.
+    pub remaining_count: i32,
+    /// Volume-weighted average fill price in cents on the order's side.
+    pub avg_fill_price: Option<f64>,
+    /// Current lifecycle state.
+    pub state: ManagedOrderState,
+    /// Rejection reason, when `state` is `Rejected`.
+    pub reject_reason: Option<String>,
+    /// Total fill cost in cents used to compute `avg_fill_price`.
+    fill_cost: f64,
+    /// Contracts seen on the fill stream, which may lag fills already counted from a
+    /// REST snapshot.
+    stream_filled: i32,
+}
+
+impl ManagedOrder {
+    fn from_field(field: &OrderCreationField, client_order_id: String) -> Self {
+        let limit_price = match field.side {
+            Side::Yes => field.yes_price.or_else(|| field.no_price.map(|p| 100 - p)),
+            Side::No => field.no_price.or_else(|| field.yes_price.map(|p| 100 - p)),
+        }
+        .map(|p| p as i32);
+        ManagedOrder {
+            client_order_id,
+            order_id: None,
+            ticker: field.ticker.clone(),
+            side: field.side,
+            action: field.action,
+            limit_price,
+            count: field.count,
+            filled_count: 0,
+            remaining_count: field.count,
+            avg_fill_price: None,
+            state: ManagedOrderState::PendingNew,
+            reject_reason: None,
+            fill_cost: 0.0,
+            stream_filled: 0,
+        }
+    }
+
+    fn from_order(order: &Order) -> Self {
+        let mut managed = ManagedOrder {
+            client_order_id: order.client_order_id.clone(),
+            order_id: None,
+            ticker: order.ticker.clone(),
+            side: order.side,
+            action: order.action,
+            limit_price: None,
+            count: 0,
+            filled_count: 0,
+            remaining_count: 0,
+            avg_fill_price: None,
+            state: ManagedOrderState::PendingNew,
+            reject_reason: None,
+            fill_cost: 0.0,
+            stream_filled: 0,
+        };
+        managed.apply_snapshot(order);
+        managed
+    }
+
+    /// Overwrites counts and state from an exchange `Order` snapshot.
+    fn apply_snapshot(&mut self, order: &Order) {
+        self.order_id = Some(order.order_id.clone());
+        let side_price = match order.side {
+            Side::Yes => order.yes_price,
+            Side::No => order.no_price,
+        };
+        if side_price.is_some() {
+            self.limit_price = side_price;
+        }
+
+        let filled = order.fill_count.unwrap_or(self.filled_count);
+        let remaining = order.remaining_count.unwrap_or(self.remaining_count);
+        if filled > self.filled_count {
+            // Fills we never saw on the stream: price them from the reported fill cost when
+            // available, otherwise at the limit price.
+            let reported_cost =
+                order.taker_fill_cost.unwrap_or(0) + order.maker_fill_cost.unwrap_or(0);
+            if reported_cost > 0 {
+                self.fill_cost = reported_cost as f64;
+            } else if let Some(px) = self.limit_price {
+                self.fill_cost += (filled - self.filled_count) as f64 * px as f64;
+            }
+            self.filled_count = filled;
+        }
+        self.remaining_count = remaining;
+        self.count = order.initial_count.or(order.count).unwrap_or(self.count);
+        self.update_avg();
+
+        self.state = match order.status {
+            OrderStatus::Pending => ManagedOrderState::PendingNew,
+            OrderStatus::Resting if self.filled_count > 0 => ManagedOrderState::PartiallyFilled,
+            OrderStatus::Resting => ManagedOrderState::Acked,
+            OrderStatus::Executed => ManagedOrderState::Filled,
+            OrderStatus::Canceled => ManagedOrderState::Cancelled,
+        };
+    }
+
+    fn update_avg(&mut self) {
+        self.avg_fill_price = if self.filled_count > 0 {
+            Some(self.fill_cost / self.filled_count as f64)
+        } else {
+            None
+        };
+    }
+}
+
+/// A state transition published by the [`OrderManager`].
+///
+/// Each variant carries a snapshot of the order after the transition.
+#[derive(Debug, Clone)]
+pub enum OrderEvent {
+    /// The exchange acknowledged the order.
+    Acked(ManagedOrder),
+    /// Some, but not all, contracts filled.
+    PartiallyFilled(ManagedOrder),
+    /// All contracts filled.
+    Filled(ManagedOrder),
+    /// The order (or its remainder) was canceled.
+    Cancelled(ManagedOrder),
+    /// The order was rejected; see `reject_reason`.
+    Rejected(ManagedOrder),
+    /// Price or size changed through `amend_order`/`decrease_order` without a state change.
+    Amended(ManagedOrder),
+}
+
+#[derive(Debug, Default)]
+struct OmsState {
+    orders: HashMap<String, ManagedOrder>,
+    /// Exchange order id -> client order id.
+    by_order_id: HashMap<String, String>,
+    /// Trade ids already applied, so replays and REST/WS overlap don't double count.
+    seen_trades: HashSet<String>,
+}
+
+impl OmsState {
+    fn index(&mut self, order: &ManagedOrder) {
+        if let Some(id) = &order.order_id {
+            self.by_order_id
+                .insert(id.clone(), order.client_order_id.clone());
+        }
+    }
+
+    fn client_id_for(&self, order_id: &str) -> Option<String> {
+        self.by_order_id.get(order_id).cloned()
+    }
+
+    /// Moves an order to a new client id and exchange id, pointing both the old and new
+    /// exchange ids at the new key.
+    fn rekey(&mut self, old_client_id: &str, new_client_id: &str, order_id: &str) {
+        let Some(mut moved) = self.orders.remove(old_client_id) else {
+            return;
+        };
+        moved.client_order_id = new_client_id.to_string();
+        if let Some(old_id) = moved.order_id.replace(order_id.to_string()) {
+            self.by_order_id.insert(old_id, new_client_id.to_string());
+        }
+        self.index(&moved);
+        self.orders.insert(new_client_id.to_string(), moved);
+    }
+}
+
+/// Tracks orders submitted through it and keeps their state in sync with the exchange.
+///
+/// See the [module documentation](crate::oms) for an overview.
+#[derive(Debug)]
+pub struct OrderManager {
+    kalshi: Kalshi,
+    state: Mutex<OmsState>,
+    events: broadcast::Sender<OrderEvent>,
+}
+
+impl OrderManager {
+    /// Creates a manager that submits orders through `kalshi`.
+    pub fn new(kalshi: Kalshi) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        OrderManager {
+            kalshi,
+            state: Mutex::new(OmsState::default()),
+            events,
+        }
+    }
+
+    /// Returns the underlying client.
+    pub fn client(&self) -> &Kalshi {
+        &self.kalshi
+    }
+
+    /// Subscribes to order state transitions.
+    pub fn subscribe(&self) -> broadcast::Receiver<OrderEvent> {
+        self.events.subscribe()
+    }
+
+    /// Returns a snapshot of the order with the given client order id.
+    pub fn get(&self, client_order_id: &str) -> Option<ManagedOrder> {
+        self.lock().orders.get(client_order_id).cloned()
+    }
+
+    /// Returns a snapshot of the order with the given exchange order id.
+    pub fn get_by_order_id(&self, order_id: &str) -> Option<ManagedOrder> {
+        let state = self.lock();
+        let client_id = state.client_id_for(order_id)?;
+        state.orders.get(&client_id).cloned()
+    }
+
+    /// Returns a snapshot of every tracked order.
+    pub fn orders(&self) -> Vec<ManagedOrder> {
+        self.lock().orders.values().cloned().collect()
+    }
+
+    /// Returns a snapshot of every order that can still receive fills.
+    pub fn open_orders(&self) -> Vec<ManagedOrder> {
+        self.lock()
+            .orders
+            .values()
+            .filter(|o| o.state.is_open())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the open orders resting in `ticker`.
+    pub fn open_orders_for(&self, ticker: &str) -> Vec<ManagedOrder> {
+        self.lock()
+            .orders
+            .values()
+            .filter(|o| o.state.is_open() && o.ticker == ticker)
+            .cloned()
+            .collect()
+    }
+
+    /// Submits an order through [`Kalshi::create_order`] and starts tracking it.
+    ///
+    /// A `client_order_id` is generated when the field does not provide one, so the
+    /// order is tracked even if the request fails. A failed request leaves the order in
+    /// the `Rejected` state and publishes [`OrderEvent::Rejected`].
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(ManagedOrder)`: The tracked order after the exchange acknowledged it.
+    /// - `Err(KalshiError)`: The error returned by the exchange.
+    ///
+    pub async fn create_order(
+        &self,
+        mut field: OrderCreationField,
+    ) -> Result<ManagedOrder, KalshiError> {
+        let client_order_id = field
+            .client_order_id
+            .get_or_insert_with(|| Uuid::new_v4().to_string())
+            .clone();
+        self.track_new(&field, &client_order_id)?;
+
+        let f = field;
+        let result = self
+            .kalshi
+            .create_order(
+                f.action,
+                f.client_order_id,
+                f.count,
+                f.side,
+                f.ticker,
+                f.input_type,
+                f.buy_max_cost,
+                f.expiration_ts,
+                f.yes_price,
+                f.no_price,
+                f.sell_position_floor,
+                f.yes_price_dollars,
+                f.no_price_dollars,
+                f.time_in_force,
+                f.post_only,
+                f.reduce_only,
+                f.self_trade_prevention_type,
+                f.order_group_id,
+                f.cancel_order_on_pause,
+            )
+            .await;
+
+        match result {
+            Ok(order) => Ok(self.apply_order(&order)),
+            Err(e) => {
+                self.reject(&client_order_id, e.to_string());
+                Err(e)
+            }
+        }
+    }
+
+    /// Submits orders through [`Kalshi::batch_create_order`] and starts tracking them.
+    ///
+    /// Results are aligned with the input; per-order failures are tracked as `Rejected`.
+    ///
+    pub async fn batch_create_order(
+        &self,
+        mut batch: Vec<OrderCreationField>,
+    ) -> Result<Vec<Result<ManagedOrder, KalshiError>>, KalshiError> {
+        let mut ids = Vec::with_capacity(batch.len());
+        for field in batch.iter_mut() {
+            let id = field
+                .client_order_id
+                .get_or_insert_with(|| Uuid::new_v4().to_string())
+                .clone();
+            self.track_new(field, &id)?;
+            ids.push(id);
+        }
+
+        let results = match self.kalshi.batch_create_order(batch).await {
+            Ok(results) => results,
+            Err(e) => {
+                for id in &ids {
+                    self.reject(id, e.to_string());
+                }
+                return Err(e);
+            }
+        };
+
+        let mut out = Vec::with_capacity(results.len());
+        for (id, result) in ids.iter().zip(results) {
+            match result {
+                Ok(order) => out.push(Ok(self.apply_order(&order))),
+                Err(e) => {
+                    self.reject(id, e.to_string());
+                    out.push(Err(e));
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Cancels a tracked order by client order id.
+    pub async fn cancel_order(&self, client_order_id: &str) -> Result<ManagedOrder, KalshiError> {
+        let order_id = self.order_id_of(client_order_id)?;
+        let (order, _reduced_by) = self.kalshi.cancel_order(&order_id).await?;
+        Ok(self.apply_order(&order))
+    }
+
+    /// Decreases a tracked order by client order id. Exactly one of `reduce_by` and
+    /// `reduce_to` must be provided, as with [`Kalshi::decrease_order`].
+    pub async fn decrease_order(
+        &self,
+        client_order_id: &str,
+        reduce_by: Option<i32>,
+        reduce_to: Option<i32>,
+    ) -> Result<ManagedOrder, KalshiError> {
+        let order_id = self.order_id_of(client_order_id)?;
+        let order = self
+            .kalshi
+            .decrease_order(&order_id, reduce_by, reduce_to)
+            .await?;
+        Ok(self.apply_order(&order))
+    }
+
+    /// Amends the price and/or size of a tracked order.
+    ///
+    /// The amended order is re-keyed under a freshly generated client order id, which is
+    /// returned in the resulting [`ManagedOrder`]; the old key is removed. The response's
+    /// `old_order` is not applied, since it describes the order before the amend.
+    ///
+    pub async fn amend_order(
+        &self,
+        client_order_id: &str,
+        yes_price: Option<i32>,
+        no_price: Option<i32>,
+        count: Option<i32>,
+    ) -> Result<ManagedOrder, KalshiError> {
+        let current = self.get(client_order_id).ok_or_else(|| {
+            KalshiError::UserInputError(format!("Unknown client_order_id: {}", client_order_id))
+        })?;
+        let order_id = current.order_id.clone().ok_or_else(|| {
+            KalshiError::UserInputError(format!(
+                "Order {} has not been acknowledged yet",
+                client_order_id
+            ))
+        })?;
+        let updated_client_order_id = Uuid::new_v4().to_string();
+
+        let response = self
+            .kalshi
+            .amend_order(
+                &order_id,
+                &current.ticker,
+                current.side,
+                current.action,
+                client_order_id,
+                &updated_client_order_id,
+                yes_price,
+                no_price,
+                None,
+                None,
+                count,
+            )
+            .await?;
+
+        self.lock().rekey(
+            client_order_id,
+            &response.order.client_order_id,
+            &response.order.order_id,
+        );
+        Ok(self.apply_order(&response.order))
+    }
+
+    /// Applies any relevant WebSocket message. Currently only fills affect order state.
+    pub fn handle_message(&self, msg: &WebSocketMessage) {
+        if let WebSocketMessage::Fill(fill) = msg {
+            self.apply_fill(fill);
+        }
+    }
+
+    /// Applies a fill from the `fill` channel.
+    ///
+    /// Fills are deduplicated by `trade_id`, and reconciled against counts taken from REST
+    /// snapshots: a fill only adds the contracts that take the stream total above the
+    /// order's `filled_count`, so fills already reported by a snapshot are not counted
+    /// twice. A fill on a cancelled or rejected order is recorded without reopening it.
+    /// Fills for orders the manager does not know about, or that add nothing, are ignored
+    /// and `false` is returned.
+    ///
+    pub fn apply_fill(&self, fill: &FillMsg) -> bool {
+        let event = {
+            let mut state = self.lock();
+            if state.seen_trades.contains(&fill.trade_id) {
+                return false;
+            }
+            let client_id = state
+                .client_id_for(&fill.order_id)
+                .or_else(|| fill.client_order_id.clone());
+            let Some(client_id) = client_id else {
+                return false;
+            };
+            let Some(order) = state.orders.get_mut(&client_id) else {
+                return false;
+            };
+            if order.order_id.is_none() {
+                order.order_id = Some(fill.order_id.clone());
+            }
+
+            let before = order.clone();
+            let price = match order.side {
+                Side::Yes => fill.yes_price.or(fill.no_price.map(|p| 100 - p)),
+                Side::No => fill.no_price.or(fill.yes_price.map(|p| 100 - p)),
+            }
+            .or(order.limit_price)
+            .unwrap_or(0);
+            order.stream_filled += fill.count;
+            let added = (order.stream_filled - order.filled_count).clamp(0, fill.count);
+            if added == 0 {
+                state.seen_trades.insert(fill.trade_id.clone());
+                return false;
+            }
+            order.fill_cost += added as f64 * price as f64;
+            order.filled_count += added;
+            order.remaining_count = (order.remaining_count - added).max(0);
+            order.update_avg();
+            order.state = match order.state {
+                ManagedOrderState::Cancelled | ManagedOrderState::Rejected => order.state,
+                _ if order.remaining_count == 0 => ManagedOrderState::Filled,
+                _ => ManagedOrderState::PartiallyFilled,
+            };
+            let snapshot = order.clone();
+            state.seen_trades.insert(fill.trade_id.clone());
+            state.index(&snapshot);
+            transition_event(Some(&before), &snapshot)
+        };
+        if let Some(event) = event {
+            let _ = self.events.send(event);
+        }
+        true
+    }
+
+    /// Applies an exchange `Order` snapshot, adopting the order if it is unknown.
+    ///
+    /// Returns the tracked order after the update and publishes an event if its state,
+    /// fill count or size changed.
+    ///
+    pub fn apply_order(&self, order: &Order) -> ManagedOrder {
+        let (snapshot, event) = {
+            let mut state = self.lock();
+            let key = state
+                .client_id_for(&order.order_id)
+                .unwrap_or_else(|| order.client_order_id.clone());
+            let (before, after) = match state.orders.get_mut(&key) {
+                Some(existing) => {
+                    let before = existing.clone();
+                    existing.apply_snapshot(order);
+                    (Some(before), existing.clone())
+                }
+                None => {
+                    let adopted = ManagedOrder::from_order(order);
+                    state.orders.insert(key.clone(), adopted.clone());
+                    (None, adopted)
+                }
+            };
+            state.index(&after);
+            let event = transition_event(before.as_ref(), &after);
+            (after, event)
+        };
+        if let Some(event) = event {
+            let _ = self.events.send(event);
+        }
+        snapshot
+    }
+
+    /// Reconciles tracked orders against the exchange.
+    ///
+    /// Pages through all resting orders with `get_orders`, applying each snapshot, then
+    /// fetches every tracked open order that was not in the resting set with
+    /// `get_single_order` to learn its terminal state.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(usize)`: The number of order snapshots applied.
+    /// - `Err(KalshiError)`: An error if any request failed.
+    ///
+    pub async fn reconcile(&self) -> Result<usize, KalshiError> {
+        let mut applied = 0;
+        let mut resting = HashSet::new();
+        let mut cursor = None;
+        loop {
+            let (next, orders) = self
+                .kalshi
+                .get_orders(
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some(OrderStatus::Resting),
+                    Some(RECONCILE_PAGE_SIZE),
+                    cursor,
+                )
+                .await?;
+            for order in &orders {
+                resting.insert(order.order_id.clone());
+                self.apply_order(order);
+                applied += 1;
+            }
+            match next {
+                Some(c) if !c.is_empty() && !orders.is_empty() => cursor = Some(c),
+                _ => break,
+            }
+        }
+
+        let stale: Vec<String> = self
+            .open_orders()
+            .into_iter()
+            .filter_map(|o| o.order_id)
+            .filter(|id| !resting.contains(id))
+            .collect();
+        for order_id in stale {
+            let order = self.kalshi.get_single_order(&order_id).await?;
+            self.apply_order(&order);
+            applied += 1;
+        }
+        Ok(applied)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, OmsState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn track_new(
+        &self,
+        field: &OrderCreationField,
+        client_order_id: &str,
+    ) -> Result<(), KalshiError> {
+        let mut state = self.lock();
+        if state.orders.contains_key(client_order_id) {
+            return Err(KalshiError::UserInputError(format!(
+                "Duplicate client_order_id: {}",
+                client_order_id
+            )));
+        }
+        state.orders.insert(
+            client_order_id.to_string(),
+            ManagedOrder::from_field(field, client_order_id.to_string()),
+        );
+        Ok(())
+    }
+
+    fn reject(&self, client_order_id: &str, reason: String) {
+        let snapshot = {
+            let mut state = self.lock();
+            let Some(order) = state.orders.get_mut(client_order_id) else {
+                return;
+            };
+            order.state = ManagedOrderState::Rejected;
+            order.remaining_count = 0;
+            order.reject_reason = Some(reason);
+            order.clone()
+        };
+        let _ = self.events.send(OrderEvent::Rejected(snapshot));
+    }
+
+    fn order_id_of(&self, client_order_id: &str) -> Result<String, KalshiError> {
+        let state = self.lock();
+        let order = state.orders.get(client_order_id).ok_or_else(|| {
+            KalshiError::UserInputError(format!("Unknown client_order_id: {}", client_order_id))
+        })?;
+        order.order_id.clone().ok_or_else(|| {
+            KalshiError::UserInputError(format!(
+                "Order {} has not been acknowledged yet",
+                client_order_id
+            ))
+        })
+    }
+}
+
+/// Picks the event describing the move from `before` to `after`, if any.
+fn transition_event(before: Option<&ManagedOrder>, after: &ManagedOrder) -> Option<OrderEvent> {
+    let changed_state = before.map(|b| b.state) != Some(after.state);
+    let changed_fills = before.map(|b| b.filled_count) != Some(after.filled_count);
+    if !changed_state && !changed_fills {
+        let resized = before
+            .map(|b| {
+                b.count != after.count
+                    || b.remaining_count != after.remaining_count
+                    || b.limit_price != after.limit_price
+            })
+            .unwrap_or(false);
+        return resized.then(|| OrderEvent::Amended(after.clone()));
+    }
+    let snapshot = after.clone();
+    Some(match after.state {
+        ManagedOrderState::PendingNew => return None,
+        ManagedOrderState::Acked => OrderEvent::Acked(snapshot),
+        ManagedOrderState::PartiallyFilled => OrderEvent::PartiallyFilled(snapshot),
+        ManagedOrderState::Filled => OrderEvent::Filled(snapshot),
+        ManagedOrderState::Cancelled => OrderEvent::Cancelled(snapshot),
+        ManagedOrderState::Rejected => OrderEvent::Rejected(snapshot),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OrderType;
+
+    fn field(count: i32, yes_price: i64) -> OrderCreationField {
+        OrderCreationField {
+            action: Action::Buy,
+            client_order_id: None,
+            count,
+            side: Side::Yes,
+            ticker: "TEST-MKT".to_string(),
+            input_type: OrderType::Limit,
+            buy_max_cost: None,
+            expiration_ts: None,
+            yes_price: Some(yes_price),
+            no_price: None,
+            sell_position_floor: None,
+            yes_price_dollars: None,
+            no_price_dollars: None,
+            time_in_force: None,
+            post_only: None,
+            reduce_only: None,
+            self_trade_prevention_type: None,
+            order_group_id: None,
+            cancel_order_on_pause: None,
+        }
+    }
+
+    fn fill(trade_id: &str, order_id: &str, count: i32, yes_price: i32) -> FillMsg {
+        FillMsg {
+            trade_id: trade_id.to_string(),
+            order_id: order_id.to_string(),
+            market_ticker: "TEST-MKT".to_string(),
+            side: Side::Yes,
+            action: Action::Buy,
+            count,
+            post_position: count,
+            yes_price: Some(yes_price),
+            no_price: Some(100 - yes_price),
+            is_taker: Some(true),
+            client_order_id: None,
+            ts: None,
+        }
+    }
+
+    fn ack(order: &mut ManagedOrder, order_id: &str) {
+        order.order_id = Some(order_id.to_string());
+        order.state = ManagedOrderState::Acked;
+    }
+
+    fn manager_with(order: ManagedOrder) -> (OmsState, ManagedOrder) {
+        let mut state = OmsState::default();
+        state.index(&order);
+        state
+            .orders
+            .insert(order.client_order_id.clone(), order.clone());
+        (state, order)
+    }
+
+    #[test]
+    fn test_fills_update_state_and_average_price() {
+        let mut order = ManagedOrder::from_field(&field(10, 55), "c1".to_string());
+        ack(&mut order, "o1");
+        let (state, _) = manager_with(order);
+        let (events, mut rx) = broadcast::channel(16);
+        let oms = OrderManager {
+            kalshi: Kalshi::test_client(),
+            state: Mutex::new(state),
+            events,
+        };
+
+        assert!(oms.apply_fill(&fill("t1", "o1", 4, 50)));
+        assert!(
+            !oms.apply_fill(&fill("t1", "o1", 4, 50)),
+            "duplicate trade ignored"
+        );
+        let o = oms.get("c1").unwrap();
+        assert_eq!(o.state, ManagedOrderState::PartiallyFilled);
+        assert_eq!(o.remaining_count, 6);
+        assert!(matches!(rx.try_recv(), Ok(OrderEvent::PartiallyFilled(_))));
+
+        assert!(oms.apply_fill(&fill("t2", "o1", 6, 55)));
+        let o = oms.get("c1").unwrap();
+        assert_eq!(o.state, ManagedOrderState::Filled);
+        assert_eq!(o.filled_count, 10);
+        assert!((o.avg_fill_price.unwrap() - 53.0).abs() < 1e-9);
+        assert!(matches!(rx.try_recv(), Ok(OrderEvent::Filled(_))));
+        assert!(oms.open_orders().is_empty());
+    }
+
+    #[test]
+    fn test_amend_rekeys_order_and_exchange_id() {
+        let mut order = ManagedOrder::from_field(&field(10, 55), "c1".to_string());
+        ack(&mut order, "o1");
+        let (mut state, _) = manager_with(order);
+        state.rekey("c1", "c2", "o2");
+        let (events, _rx) = broadcast::channel(16);
+        let oms = OrderManager {
+            kalshi: Kalshi::test_client(),
+            state: Mutex::new(state),
+            events,
+        };
+
+        let amended: Order = serde_json::from_value(serde_json::json!({
+            "order_id": "o2",
+            "client_order_id": "c2",
+            "ticker": "TEST-MKT",
+            "status": "resting",
+            "action": "buy",
+            "side": "yes",
+            "type": "limit",
+            "yes_price": 57,
+            "fill_count": 0,
+            "remaining_count": 8,
+            "initial_count": 8,
+        }))
+        .unwrap();
+        let o = oms.apply_order(&amended);
+        assert_eq!(o.client_order_id, "c2");
+        assert_eq!(o.limit_price, Some(57));
+        assert_eq!(o.remaining_count, 8);
+
+        assert!(oms.get("c1").is_none());
+        assert_eq!(oms.orders().len(), 1);
+        assert_eq!(oms.get_by_order_id("o1").unwrap().client_order_id, "c2");
+        assert_eq!(oms.get_by_order_id("o2").unwrap().client_order_id, "c2");
+
+        // A stream fill carrying the pre-amend exchange id lands on the amended order.
+        assert!(oms.apply_fill(&fill("t1", "o1", 3, 57)));
+        assert_eq!(oms.get("c2").unwrap().filled_count, 3);
+    }
+
+    #[test]
+    fn test_stream_fills_reconcile_with_snapshot_counts() {
+        let mut order = ManagedOrder::from_field(&field(10, 55), "c1".to_string());
+        ack(&mut order, "o1");
+        let (state, _) = manager_with(order);
+        let (events, _rx) = broadcast::channel(16);
+        let oms = OrderManager {
+            kalshi: Kalshi::test_client(),
+            state: Mutex::new(state),
+            events,
+        };
+
+        // A REST snapshot reports 4 filled before the stream delivers the fill.
+        let snapshot: Order = serde_json::from_value(serde_json::json!({
+            "order_id": "o1",
+            "client_order_id": "c1",
+            "ticker": "TEST-MKT",
+            "status": "resting",
+            "action": "buy",
+            "side": "yes",
+            "type": "limit",
+            "yes_price": 55,
+            "fill_count": 4,
+            "remaining_count": 6,
+            "initial_count": 10,
+        }))
+        .unwrap();
+        oms.apply_order(&snapshot);
+
+        assert!(!oms.apply_fill(&fill("t1", "o1", 4, 55)), "already counted");
+        let o = oms.get("c1").unwrap();
+        assert_eq!(o.filled_count, 4);
+        assert_eq!(o.remaining_count, 6);
+
+        assert!(oms.apply_fill(&fill("t2", "o1", 6, 55)));
+        let o = oms.get("c1").unwrap();
+        assert_eq!(o.filled_count, 10);
+        assert_eq!(o.state, ManagedOrderState::Filled);
+    }
+
+    #[test]
+    fn test_fill_after_cancel_keeps_order_cancelled() {
+        let mut order = ManagedOrder::from_field(&field(10, 55), "c1".to_string());
+        ack(&mut order, "o1");
+        let (state, _) = manager_with(order);
+        let (events, mut rx) = broadcast::channel(16);
+        let oms = OrderManager {
+            kalshi: Kalshi::test_client(),
+            state: Mutex::new(state),
+            events,
+        };
+
+        let cancelled: Order = serde_json::from_value(serde_json::json!({
+            "order_id": "o1",
+            "client_order_id": "c1",
+            "ticker": "TEST-MKT",
+            "status": "canceled",
+            "action": "buy",
+            "side": "yes",
+            "type": "limit",
+            "yes_price": 55,
+            "fill_count": 0,
+            "remaining_count": 0,
+            "initial_count": 10,
+        }))
+        .unwrap();
+        oms.apply_order(&cancelled);
+        assert!(matches!(rx.try_recv(), Ok(OrderEvent::Cancelled(_))));
+
+        // The fill raced the cancel: it is recorded, but the order stays dead.
+        assert!(oms.apply_fill(&fill("t1", "o1", 3, 55)));
+        let o = oms.get("c1").unwrap();
+        assert_eq!(o.state, ManagedOrderState::Cancelled);
+        assert_eq!(o.filled_count, 3);
+        assert_eq!(o.remaining_count, 0);
+        assert!(matches!(rx.try_recv(), Ok(OrderEvent::Cancelled(_))));
+        assert!(oms.open_orders().is_empty());
+    }
+}
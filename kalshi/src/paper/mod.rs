@@ -0,0 +1,1601 @@
+//! Paper trading against live or replayed market data.
+//!
+//! [`PaperKalshi`] mirrors the trading methods of [`Kalshi`](crate::Kalshi) (`create_order`,
+//! `cancel_order`, `amend_order`, `decrease_order`, the batch calls, `get_orders`,
+//! `get_positions`, `get_balance`, `get_fills`, `get_settlements`, `get_queue_positions`,
+//! `get_total_resting_order_value`, `get_order_groups`, `get_order_group`) with the same
+//! arguments and return types, but matches orders locally instead of sending them to the
+//! exchange. Order groups are not simulated.
+//! It implements the [`Trading`](crate::Trading) and [`Portfolio`](crate::Portfolio) traits.
+//!
+//! ## Matching model
+//!
+//! - The simulator keeps a copy of each market's book, fed from REST snapshots
+//!   ([`PaperKalshi::apply_orderbook`]) or WebSocket messages ([`PaperKalshi::handle_message`]).
+//!   Recorded messages can be replayed through the same method.
+//! - Incoming orders first take displayed liquidity, best price first. Liquidity taken is
+//!   removed from the local book until the next snapshot or delta replaces it.
+//! - The rest of a limit order rests with price-time priority. Its queue position starts
+//!   behind everything displayed at its price (including earlier paper orders) and moves
+//!   up as public trades print at that price or the level shrinks.
+//! - Resting orders fill when public trades reach them, trade through them, or when a
+//!   book update crosses them.
+//! - Fees come from a [`FeeCalculator`]; taker fills pay taker fees, maker fills maker fees.
+//! - [`PaperKalshi::settle`] pays out positions and cancels resting orders.
+//!
+//! Every fill is published as a [`FillMsg`], the same type the `fill` WebSocket channel
+//! produces, so code consuming live fills can run unchanged.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::{Action, OrderType, PaperKalshi, Side};
+//!
+//! let paper = PaperKalshi::new(100_000); // $1,000.00
+//! let mut fills = paper.subscribe();
+//! paper.apply_orderbook("KXHIGHNY-24JAN01-T60", &kalshi.get_orderbook("KXHIGHNY-24JAN01-T60", None).await?);
+//!
+//! let order = paper.create_order(
+//!     Action::Buy, None, 10, Side::Yes, "KXHIGHNY-24JAN01-T60".to_string(), OrderType::Limit,
+//!     None, None, Some(45), None, None, None, None, None, None, None, None, None, None,
+//! ).await?;
+//!
+//! // Feed live data as it arrives; resting orders fill as the market trades.
+//! while let Some(msg) = stream.next().await {
+//!     paper.handle_message(&msg?);
+//! }
+//! ```
+
+use crate::fees::{FeeCalculator, FeeRole};
+use crate::kalshi_error::*;
+use crate::positions::{event_ticker_of, yes_delta, PositionEntry};
+use crate::risk::side_price;
+use crate::utils::parse_ts;
+use crate::{
+    Action, AmendOrderResponse, EventPosition, Fill, FillMsg, MarketPosition, Order,
+    OrderCreationField, OrderGroup, OrderQueuePosition, OrderStatus, OrderType, Orderbook,
+    SelfTradePreventionType, Settlement, SettlementResult, Side, TimeInForce, WebSocketMessage,
+};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Capacity of the broadcast channel carrying simulated fills.
+const FILL_CHANNEL_CAPACITY: usize = 1024;
+
+/// Displayed bids for one market, by price in cents.
+#[derive(Debug, Default, Clone)]
+struct SimBook {
+    yes: BTreeMap<i32, i64>,
+    no: BTreeMap<i32, i64>,
+}
+
+impl SimBook {
+    fn side(&self, side: Side) -> &BTreeMap<i32, i64> {
+        match side {
+            Side::Yes => &self.yes,
+            Side::No => &self.no,
+        }
+    }
+
+    fn side_mut(&mut self, side: Side) -> &mut BTreeMap<i32, i64> {
+        match side {
+            Side::Yes => &mut self.yes,
+            Side::No => &mut self.no,
+        }
+    }
+
+    fn replace(&mut self, yes: &[Vec<i32>], no: &[Vec<i32>]) {
+        self.yes = levels_to_map(yes);
+        self.no = levels_to_map(no);
+    }
+
+    fn apply_delta(&mut self, side: Side, price: i32, delta: i64) {
+        let levels = self.side_mut(side);
+        let qty = levels.entry(price).or_insert(0);
+        *qty += delta;
+        if *qty <= 0 {
+            levels.remove(&price);
+        }
+    }
+
+    fn level(&self, side: Side, price: i32) -> i64 {
+        self.side(side).get(&price).copied().unwrap_or(0)
+    }
+
+    fn take(&mut self, side: Side, price: i32, qty: i64) {
+        self.apply_delta(side, price, -qty);
+    }
+}
+
+fn levels_to_map(levels: &[Vec<i32>]) -> BTreeMap<i32, i64> {
+    levels
+        .iter()
+        .filter(|l| l.len() >= 2 && l[1] > 0)
+        .map(|l| (l[0], l[1] as i64))
+        .collect()
+}
+
+/// A simulated order.
+#[derive(Debug, Clone)]
+struct PaperOrder {
+    order_id: String,
+    client_order_id: String,
+    ticker: String,
+    side: Side,
+    action: Action,
+    order_type: OrderType,
+    /// Limit price in cents on `side`.
+    price: i32,
+    count: i32,
+    remaining: i32,
+    status: OrderStatus,
+    expiration_ts: Option<i64>,
+    order_group_id: Option<String>,
+    self_trade_prevention_type: Option<SelfTradePreventionType>,
+    /// Contracts displayed ahead of this order at its price.
+    queue_ahead: i64,
+    seq: u64,
+    created: DateTime<Utc>,
+    updated: DateTime<Utc>,
+    taker_fill_count: i32,
+    maker_fill_count: i32,
+    taker_fees: i64,
+    maker_fees: i64,
+    taker_fill_cost: i64,
+    maker_fill_cost: i64,
+    decrease_count: i32,
+}
+
+impl PaperOrder {
+    /// True if the order adds YES exposure (buy YES or sell NO).
+    fn buys_yes(&self) -> bool {
+        yes_delta(self.side, self.action, 1) > 0
+    }
+
+    /// Limit expressed as a YES price.
+    fn yes_limit(&self) -> i32 {
+        match self.side {
+            Side::Yes => self.price,
+            Side::No => 100 - self.price,
+        }
+    }
+
+    /// The book side this order rests on and its price there.
+    fn resting_level(&self) -> (Side, i32) {
+        if self.buys_yes() {
+            (Side::Yes, self.yes_limit())
+        } else {
+            (Side::No, 100 - self.yes_limit())
+        }
+    }
+
+    /// The book side this order takes from and the lowest crossing price there.
+    fn taking_level(&self) -> (Side, i32) {
+        if self.buys_yes() {
+            (Side::No, 100 - self.yes_limit())
+        } else {
+            (Side::Yes, self.yes_limit())
+        }
+    }
+
+    fn to_order(&self) -> Order {
+        let (yes_price, no_price) = match self.side {
+            Side::Yes => (self.price, 100 - self.price),
+            Side::No => (100 - self.price, self.price),
+        };
+        Order {
+            order_id: self.order_id.clone(),
+            user_id: None,
+            ticker: self.ticker.clone(),
+            status: self.status,
+            yes_price: Some(yes_price),
+            no_price: Some(no_price),
+            count: Some(self.count),
+            created_time: Some(self.created.to_rfc3339()),
+            last_update_time: Some(self.updated.to_rfc3339()),
+            expiration_time: self
+                .expiration_ts
+                .and_then(|ts| DateTime::from_timestamp(ts, 0))
+                .map(|t| t.to_rfc3339()),
+            fill_count: Some(self.count - self.remaining - self.decrease_count),
+            initial_count: Some(self.count),
+            remaining_count: Some(self.remaining),
+            queue_position: Some(self.queue_ahead as i32),
+            taker_fill_count: Some(self.taker_fill_count),
+            place_count: None,
+            decrease_count: Some(self.decrease_count),
+            maker_fill_count: Some(self.maker_fill_count),
+            fcc_cancel_count: None,
+            close_cancel_count: None,
+            taker_fees: Some(self.taker_fees as i32),
+            taker_fees_dollars: Some(cents_to_dollars(self.taker_fees)),
+            taker_fill_cost: Some(self.taker_fill_cost as i32),
+            taker_fill_cost_dollars: Some(cents_to_dollars(self.taker_fill_cost)),
+            maker_fees: Some(self.maker_fees as i32),
+            maker_fees_dollars: Some(cents_to_dollars(self.maker_fees)),
+            maker_fill_cost: Some(self.maker_fill_cost as i32),
+            maker_fill_cost_dollars: Some(cents_to_dollars(self.maker_fill_cost)),
+            yes_price_dollars: Some(cents_to_dollars(yes_price as i64)),
+            no_price_dollars: Some(cents_to_dollars(no_price as i64)),
+            action: self.action,
+            side: self.side,
+            r#type: match self.order_type {
+                OrderType::Market => "market".to_string(),
+                OrderType::Limit => "limit".to_string(),
+            },
+            client_order_id: self.client_order_id.clone(),
+            order_group_id: self.order_group_id.clone(),
+            self_trade_prevention_type: self.self_trade_prevention_type.as_ref().map(|s| {
+                match s {
+                    SelfTradePreventionType::TakerAtCross => "taker_at_cross",
+                    SelfTradePreventionType::Maker => "maker",
+                }
+                .to_string()
+            }),
+        }
+    }
+}
+
+pub(crate) fn cents_to_dollars(cents: i64) -> String {
+    format!("{:.4}", cents as f64 / 100.0)
+}
+
+/// A simulated position with its traded volume.
+#[derive(Debug, Clone)]
+struct PaperPosition {
+    entry: PositionEntry,
+    total_traded: i64,
+}
+
+#[derive(Debug)]
+struct PaperState {
+    balance: i64,
+    clock: Option<DateTime<Utc>>,
+    books: HashMap<String, SimBook>,
+    orders: HashMap<String, PaperOrder>,
+    positions: HashMap<String, PaperPosition>,
+    settled: HashSet<String>,
+    settlements: Vec<Settlement>,
+    fills: Vec<Fill>,
+    next_seq: u64,
+    fees: FeeCalculator,
+}
+
+impl PaperState {
+    fn now(&self) -> DateTime<Utc> {
+        self.clock.unwrap_or_else(Utc::now)
+    }
+
+    fn position(&self, ticker: &str) -> i32 {
+        self.positions
+            .get(ticker)
+            .map(|p| p.entry.position)
+            .unwrap_or(0)
+    }
+
+    /// Cash needed to put on `delta` YES contracts at `yes_price`, given the current position.
+    fn cost_of(&self, ticker: &str, delta: i32, yes_price: i32) -> i64 {
+        (-cash_flow(self.position(ticker), delta, yes_price)).max(0)
+    }
+
+    /// Cash held back for resting orders, excluding `except`.
+    fn reserved(&self, except: Option<&str>) -> i64 {
+        self.orders
+            .values()
+            .filter(|o| o.status == OrderStatus::Resting && Some(o.order_id.as_str()) != except)
+            .map(|o| {
+                self.cost_of(
+                    &o.ticker,
+                    yes_delta(o.side, o.action, o.remaining),
+                    o.yes_limit(),
+                )
+            })
+            .sum()
+    }
+
+    /// Displayed levels an order could take, best first, as (book price, quantity).
+    fn crossing_levels(&self, order: &PaperOrder) -> Vec<(i32, i64)> {
+        let (side, threshold) = order.taking_level();
+        self.books
+            .get(&order.ticker)
+            .map(|book| {
+                book.side(side)
+                    .range(threshold..)
+                    .rev()
+                    .map(|(p, q)| (*p, *q))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Matches `order_id` against the local book. Takers trade at the book's price; a
+    /// resting order crossed by a book update trades at its own price as maker.
+    fn match_against_book(&mut self, order_id: &str, is_taker: bool, fills: &mut Vec<FillMsg>) {
+        let Some(order) = self.orders.get(order_id).cloned() else {
+            return;
+        };
+        let (side, _) = order.taking_level();
+        let mut remaining = order.remaining;
+        for (level, qty) in self.crossing_levels(&order) {
+            if remaining == 0 {
+                break;
+            }
+            let take = qty.min(remaining as i64) as i32;
+            let yes_price = if !is_taker {
+                order.yes_limit()
+            } else if side == Side::No {
+                100 - level
+            } else {
+                level
+            };
+            if let Some(book) = self.books.get_mut(&order.ticker) {
+                book.take(side, level, take as i64);
+            }
+            fills.push(self.fill(order_id, take, yes_price, is_taker));
+            remaining -= take;
+        }
+    }
+
+    /// Books a fill of `count` contracts at `yes_price` against `order_id`.
+    fn fill(&mut self, order_id: &str, count: i32, yes_price: i32, is_taker: bool) -> FillMsg {
+        let now = self.now();
+        let order = self.orders.get(order_id).cloned().expect("order exists");
+        let delta = yes_delta(order.side, order.action, count);
+        let side_price = match order.side {
+            Side::Yes => yes_price,
+            Side::No => 100 - yes_price,
+        };
+        let role = if is_taker {
+            FeeRole::Taker
+        } else {
+            FeeRole::Maker
+        };
+        let fee = self
+            .fees
+            .fee_for(&order.ticker, count, side_price, role, now);
+
+        self.balance += cash_flow(self.position(&order.ticker), delta, yes_price) - fee;
+        let pos = self
+            .positions
+            .entry(order.ticker.clone())
+            .or_insert_with(|| PaperPosition {
+                entry: PositionEntry::new(&order.ticker, event_ticker_of(&order.ticker)),
+                total_traded: 0,
+            });
+        pos.entry.apply_trade(delta, yes_price as f64);
+        pos.entry.fees_paid += fee as f64;
+        pos.total_traded += count as i64 * side_price as i64;
+        let post_position = pos.entry.position;
+
+        let o = self.orders.get_mut(order_id).expect("order exists");
+        o.remaining -= count;
+        o.updated = now;
+        if is_taker {
+            o.taker_fill_count += count;
+            o.taker_fill_cost += count as i64 * side_price as i64;
+            o.taker_fees += fee;
+        } else {
+            o.maker_fill_count += count;
+            o.maker_fill_cost += count as i64 * side_price as i64;
+            o.maker_fees += fee;
+        }
+        if o.remaining == 0 {
+            o.status = OrderStatus::Executed;
+        }
+
+        let trade_id = Uuid::new_v4().to_string();
+        self.fills.push(Fill {
+            action: order.action,
+            count,
+            created_time: now.to_rfc3339(),
+            is_taker,
+            no_price: (100 - yes_price) as i64,
+            order_id: order_id.to_string(),
+            side: order.side,
+            ticker: order.ticker.clone(),
+            trade_id: trade_id.clone(),
+            yes_price: yes_price as i64,
+        });
+        FillMsg {
+            trade_id,
+            order_id: order_id.to_string(),
+            market_ticker: order.ticker,
+            side: order.side,
+            action: order.action,
+            count,
+            post_position,
+            yes_price: Some(yes_price),
+            no_price: Some(100 - yes_price),
+            is_taker: Some(is_taker),
+            client_order_id: Some(order.client_order_id),
+            ts: Some(now.timestamp()),
+        }
+    }
+
+    /// Resting orders in `ticker`, best price first, then by time.
+    fn resting_in(&self, ticker: &str) -> Vec<String> {
+        let mut resting: Vec<&PaperOrder> = self
+            .orders
+            .values()
+            .filter(|o| o.ticker == ticker && o.status == OrderStatus::Resting)
+            .collect();
+        resting.sort_by_key(|o| {
+            let priority = if o.buys_yes() {
+                -o.yes_limit()
+            } else {
+                o.yes_limit()
+            };
+            (priority, o.seq)
+        });
+        resting.iter().map(|o| o.order_id.clone()).collect()
+    }
+
+    /// Places a new order on the book: queue position behind everything displayed at its
+    /// price, including earlier paper orders.
+    fn rest(&mut self, order_id: &str) {
+        let Some(order) = self.orders.get(order_id) else {
+            return;
+        };
+        let (side, price) = order.resting_level();
+        let displayed = self
+            .books
+            .get(&order.ticker)
+            .map(|b| b.level(side, price))
+            .unwrap_or(0);
+        let own_ahead: i64 = self
+            .orders
+            .values()
+            .filter(|o| {
+                o.order_id != order.order_id
+                    && o.ticker == order.ticker
+                    && o.status == OrderStatus::Resting
+                    && o.resting_level() == (side, price)
+            })
+            .map(|o| o.remaining as i64)
+            .sum();
+        let queue_ahead = displayed + own_ahead;
+        if let Some(o) = self.orders.get_mut(order_id) {
+            o.queue_ahead = queue_ahead;
+        }
+    }
+
+    /// Re-checks resting orders in `ticker` after the book changed.
+    fn on_book_change(&mut self, ticker: &str, fills: &mut Vec<FillMsg>) {
+        for order_id in self.resting_in(ticker) {
+            let Some(order) = self.orders.get(&order_id) else {
+                continue;
+            };
+            let (side, price) = order.resting_level();
+            let displayed = self
+                .books
+                .get(ticker)
+                .map(|b| b.level(side, price))
+                .unwrap_or(0);
+            if let Some(o) = self.orders.get_mut(&order_id) {
+                o.queue_ahead = o.queue_ahead.min(displayed);
+            }
+            self.match_against_book(&order_id, false, fills);
+        }
+    }
+
+    /// Applies a public trade of `count` at `yes_price`. `taker_side` is the side the taker
+    /// bought.
+    fn on_trade(
+        &mut self,
+        ticker: &str,
+        yes_price: i32,
+        count: i32,
+        taker_side: Side,
+        fills: &mut Vec<FillMsg>,
+    ) {
+        // A YES taker lifts resting sellers of YES; a NO taker hits resting buyers of YES.
+        // Orders are visited in price-time priority and share the trade's quantity.
+        let hits_buyers = taker_side == Side::No;
+        let mut left = count as i64;
+        for order_id in self.resting_in(ticker) {
+            if left <= 0 {
+                break;
+            }
+            let Some(order) = self.orders.get_mut(&order_id) else {
+                continue;
+            };
+            if order.buys_yes() != hits_buyers {
+                continue;
+            }
+            let limit = order.yes_limit();
+            let through = if hits_buyers {
+                limit > yes_price
+            } else {
+                limit < yes_price
+            };
+            let fill = if through {
+                left.min(order.remaining as i64) as i32
+            } else if limit == yes_price {
+                let ahead = order.queue_ahead.min(left);
+                order.queue_ahead -= ahead;
+                (left - ahead).min(order.remaining as i64) as i32
+            } else {
+                0
+            };
+            left -= fill as i64;
+            if fill > 0 {
+                fills.push(self.fill(&order_id, fill, limit, false));
+            }
+        }
+    }
+
+    fn expire(&mut self, now: DateTime<Utc>) {
+        for order in self.orders.values_mut() {
+            if order.status == OrderStatus::Resting
+                && order.expiration_ts.is_some_and(|ts| ts <= now.timestamp())
+            {
+                order.status = OrderStatus::Canceled;
+                order.updated = now;
+            }
+        }
+    }
+}
+
+/// Cash received (positive) or paid (negative), in cents, for trading `delta` YES contracts
+/// at `yes_price` from position `old`.
+///
+/// Opening long YES costs the YES price; opening long NO costs the NO price. Closing returns
+/// the price of the side being sold.
+fn cash_flow(old: i32, delta: i32, yes_price: i32) -> i64 {
+    let p = yes_price as i64;
+    if delta > 0 {
+        let closing = delta.min((-old).max(0)) as i64;
+        let opening = delta as i64 - closing;
+        closing * (100 - p) - opening * p
+    } else {
+        let n = -delta;
+        let closing = n.min(old.max(0)) as i64;
+        let opening = n as i64 - closing;
+        closing * p - opening * (100 - p)
+    }
+}
+
+/// A local stand-in for [`Kalshi`](crate::Kalshi) that simulates order matching.
+///
+/// See the [module documentation](crate::paper) for the matching model.
+#[derive(Debug)]
+pub struct PaperKalshi {
+    state: Mutex<PaperState>,
+    fills: broadcast::Sender<FillMsg>,
+}
+
+impl PaperKalshi {
+    /// Creates a simulator with `balance` cents of cash and the default fee schedule.
+    pub fn new(balance: i64) -> Self {
+        let (fills, _) = broadcast::channel(FILL_CHANNEL_CAPACITY);
+        PaperKalshi {
+            state: Mutex::new(PaperState {
+                balance,
+                clock: None,
+                books: HashMap::new(),
+                orders: HashMap::new(),
+                positions: HashMap::new(),
+                settled: HashSet::new(),
+                settlements: Vec::new(),
+                fills: Vec::new(),
+                next_seq: 0,
+                fees: FeeCalculator::default(),
+            }),
+            fills,
+        }
+    }
+
+    /// Uses `fees` instead of the default fee schedule.
+    pub fn with_fees(self, fees: FeeCalculator) -> Self {
+        self.lock().fees = fees;
+        self
+    }
+
+    /// Subscribes to simulated fills.
+    pub fn subscribe(&self) -> broadcast::Receiver<FillMsg> {
+        self.fills.subscribe()
+    }
+
+    /// Pins the simulator clock, for replays. Resting orders whose expiration has passed
+    /// are cancelled.
+    pub fn set_time(&self, now: DateTime<Utc>) {
+        let mut state = self.lock();
+        state.clock = Some(now);
+        state.expire(now);
+    }
+
+    /// Replaces the local book of `ticker` with a REST snapshot.
+    ///
+    /// # Returns
+    ///
+    /// The fills caused by the new book crossing resting paper orders.
+    pub fn apply_orderbook(&self, ticker: &str, book: &Orderbook) -> Vec<FillMsg> {
+        let mut fills = Vec::new();
+        {
+            let mut state = self.lock();
+            state.books.entry(ticker.to_string()).or_default().replace(
+                book.yes.as_deref().unwrap_or_default(),
+                book.no.as_deref().unwrap_or_default(),
+            );
+            state.on_book_change(ticker, &mut fills);
+        }
+        self.publish(&fills);
+        fills
+    }
+
+    /// Applies market data: orderbook snapshots and deltas, and public trades.
+    ///
+    /// # Returns
+    ///
+    /// The fills the message caused.
+    pub fn handle_message(&self, msg: &WebSocketMessage) -> Vec<FillMsg> {
+        let mut fills = Vec::new();
+        {
+            let mut state = self.lock();
+            match msg {
+                WebSocketMessage::OrderbookSnapshot(snap) => {
+                    state
+                        .books
+                        .entry(snap.market_ticker.clone())
+                        .or_default()
+                        .replace(&snap.yes, &snap.no);
+                    state.on_book_change(&snap.market_ticker, &mut fills);
+                }
+                WebSocketMessage::OrderbookDelta(delta) => {
+                    let side = if delta.side == "no" {
+                        Side::No
+                    } else {
+                        Side::Yes
+                    };
+                    state
+                        .books
+                        .entry(delta.market_ticker.clone())
+                        .or_default()
+                        .apply_delta(side, delta.price, delta.delta as i64);
+                    state.on_book_change(&delta.market_ticker, &mut fills);
+                }
+                WebSocketMessage::Trade(trade) => {
+                    let taker_side = if trade.taker_side == "no" {
+                        Side::No
+                    } else {
+                        Side::Yes
+                    };
+                    state.on_trade(
+                        &trade.market_ticker,
+                        trade.yes_price,
+                        trade.count,
+                        taker_side,
+                        &mut fills,
+                    );
+                }
+                _ => {}
+            }
+        }
+        self.publish(&fills);
+        fills
+    }
+
+    /// Settles `ticker`: cancels its resting orders and pays out positions.
+    ///
+    /// Winning contracts pay 100¢. A void market refunds the cost of the position.
+    ///
+    /// # Returns
+    ///
+    /// The payout in cents.
+    pub fn settle(&self, ticker: &str, result: SettlementResult) -> i64 {
+        let mut state = self.lock();
+        let now = state.now();
+        for order in state.orders.values_mut() {
+            if order.ticker == ticker && order.status == OrderStatus::Resting {
+                order.status = OrderStatus::Canceled;
+                order.updated = now;
+            }
+        }
+        state.settled.insert(ticker.to_string());
+        let Some(pos) = state.positions.get_mut(ticker) else {
+            return 0;
+        };
+        let position = pos.entry.position;
+        let cost = pos.entry.cost_basis().round() as i64;
+        let payout = match result {
+            SettlementResult::Yes | SettlementResult::AllYes => position.max(0) as i64 * 100,
+            SettlementResult::No | SettlementResult::AllNo => (-position).max(0) as i64 * 100,
+            SettlementResult::Void => cost,
+        };
+        pos.entry.realized_pnl += (payout - cost) as f64;
+        pos.entry.position = 0;
+        pos.entry.avg_price = 0.0;
+        state.balance += payout;
+        let (yes_count, yes_total_cost, no_count, no_total_cost) = if position >= 0 {
+            (position as i64, cost, 0, 0)
+        } else {
+            (0, 0, (-position) as i64, cost)
+        };
+        state.settlements.push(Settlement {
+            market_result: serde_json::to_value(result)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default(),
+            no_count,
+            no_total_cost,
+            revenue: payout,
+            settled_time: now.to_rfc3339(),
+            ticker: ticker.to_string(),
+            yes_count,
+            yes_total_cost,
+        });
+        payout
+    }
+
+    /// Submits a simulated order. Arguments match [`Kalshi::create_order`](crate::Kalshi::create_order).
+    ///
+    /// Market orders and immediate-or-cancel orders cancel whatever does not fill at once;
+    /// fill-or-kill orders fill completely or not at all. Post-only orders that would cross,
+    /// orders the cash balance cannot cover and orders in settled markets are rejected.
+    /// `buy_max_cost`, `sell_position_floor` and `cancel_order_on_pause` are not simulated
+    /// and are rejected with [`KalshiError::UserInputError`] when set.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_order(
+        &self,
+        action: Action,
+        client_order_id: Option<String>,
+        count: i32,
+        side: Side,
+        ticker: String,
+        input_type: OrderType,
+        buy_max_cost: Option<i64>,
+        expiration_ts: Option<i64>,
+        yes_price: Option<i64>,
+        no_price: Option<i64>,
+        sell_position_floor: Option<i32>,
+        yes_price_dollars: Option<String>,
+        no_price_dollars: Option<String>,
+        time_in_force: Option<TimeInForce>,
+        post_only: Option<bool>,
+        reduce_only: Option<bool>,
+        self_trade_prevention_type: Option<SelfTradePreventionType>,
+        order_group_id: Option<String>,
+        cancel_order_on_pause: Option<bool>,
+    ) -> Result<Order, KalshiError> {
+        let unsupported = [
+            ("buy_max_cost", buy_max_cost.is_some()),
+            ("sell_position_floor", sell_position_floor.is_some()),
+            ("cancel_order_on_pause", cancel_order_on_pause.is_some()),
+        ];
+        if let Some((name, _)) = unsupported.iter().find(|(_, set)| *set) {
+            return Err(KalshiError::UserInputError(format!(
+                "{} is not supported in paper trading",
+                name
+            )));
+        }
+        if count <= 0 {
+            return Err(KalshiError::UserInputError(
+                "count must be positive".to_string(),
+            ));
+        }
+        let price = side_price(
+            side,
+            yes_price,
+            no_price,
+            yes_price_dollars.as_deref(),
+            no_price_dollars.as_deref(),
+        );
+        let price = match (input_type, price) {
+            (OrderType::Limit, None) => {
+                return Err(KalshiError::UserInputError(
+                    "Must provide a price (yes_price, no_price, yes_price_dollars, or no_price_dollars)".to_string(),
+                ))
+            }
+            (_, Some(p)) if !(1..=99).contains(&p) => {
+                return Err(KalshiError::UserInputError(format!(
+                    "price {} must be between 1 and 99 cents",
+                    p
+                )))
+            }
+            (_, Some(p)) => p,
+            (OrderType::Market, None) => match action {
+                Action::Buy => 99,
+                Action::Sell => 1,
+            },
+        };
+
+        let mut fills = Vec::new();
+        let order = {
+            let mut state = self.lock();
+            let now = state.now();
+            let mut count = count;
+            if state.settled.contains(&ticker) {
+                return Err(api_error("market_closed", "market is settled"));
+            }
+
+            if reduce_only == Some(true) {
+                let reducible = match yes_delta(side, action, 1) {
+                    d if d > 0 => (-state.position(&ticker)).max(0),
+                    _ => state.position(&ticker).max(0),
+                };
+                if reducible == 0 {
+                    return Err(api_error(
+                        "reduce_only_violation",
+                        "reduce-only order would not reduce the position",
+                    ));
+                }
+                count = count.min(reducible);
+            }
+
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            let order_id = Uuid::new_v4().to_string();
+            let mut order = PaperOrder {
+                order_id: order_id.clone(),
+                client_order_id: client_order_id.unwrap_or_else(|| Uuid::new_v4().to_string()),
+                ticker: ticker.clone(),
+                side,
+                action,
+                order_type: input_type,
+                price,
+                count,
+                remaining: count,
+                status: OrderStatus::Resting,
+                expiration_ts,
+                order_group_id,
+                self_trade_prevention_type,
+                queue_ahead: 0,
+                seq,
+                created: now,
+                updated: now,
+                taker_fill_count: 0,
+                maker_fill_count: 0,
+                taker_fees: 0,
+                maker_fees: 0,
+                taker_fill_cost: 0,
+                maker_fill_cost: 0,
+                decrease_count: 0,
+            };
+
+            let crossing: i64 = state.crossing_levels(&order).iter().map(|(_, q)| q).sum();
+            if post_only == Some(true) && crossing > 0 {
+                return Err(api_error(
+                    "post_only_cross",
+                    "post-only order would cross the book",
+                ));
+            }
+            let cost = state.cost_of(&ticker, yes_delta(side, action, count), order.yes_limit());
+            if cost > state.balance - state.reserved(None) {
+                return Err(api_error("insufficient_balance", "insufficient balance"));
+            }
+
+            let kill = time_in_force == Some(TimeInForce::FillOrKill) && crossing < count as i64;
+            if kill {
+                order.status = OrderStatus::Canceled;
+            }
+            state.orders.insert(order_id.clone(), order);
+            if !kill {
+                state.match_against_book(&order_id, true, &mut fills);
+                let immediate = input_type == OrderType::Market
+                    || matches!(
+                        time_in_force,
+                        Some(TimeInForce::ImmediateOrCancel | TimeInForce::FillOrKill)
+                    );
+                let still_open = state.orders[&order_id].status == OrderStatus::Resting;
+                if still_open && immediate {
+                    if let Some(o) = state.orders.get_mut(&order_id) {
+                        o.status = OrderStatus::Canceled;
+                    }
+                } else if still_open {
+                    state.rest(&order_id);
+                }
+            }
+            state.orders[&order_id].to_order()
+        };
+        self.publish(&fills);
+        Ok(order)
+    }
+
+    /// Cancels a resting order. Mirrors [`Kalshi::cancel_order`](crate::Kalshi::cancel_order).
+    pub async fn cancel_order(&self, order_id: &str) -> Result<(Order, i32), KalshiError> {
+        let mut state = self.lock();
+        let now = state.now();
+        let order = resting_mut(&mut state, order_id)?;
+        let reduced_by = order.remaining;
+        order.status = OrderStatus::Canceled;
+        order.updated = now;
+        Ok((order.to_order(), reduced_by))
+    }
+
+    /// Shrinks a resting order. Mirrors [`Kalshi::decrease_order`](crate::Kalshi::decrease_order).
+    pub async fn decrease_order(
+        &self,
+        order_id: &str,
+        reduce_by: Option<i32>,
+        reduce_to: Option<i32>,
+    ) -> Result<Order, KalshiError> {
+        let mut state = self.lock();
+        let now = state.now();
+        let order = resting_mut(&mut state, order_id)?;
+        let reduce = match (reduce_by, reduce_to) {
+            (Some(by), None) => by,
+            (None, Some(to)) => order.remaining - to,
+            _ => {
+                return Err(KalshiError::UserInputError(
+                    "Must provide exactly one of reduce_by or reduce_to".to_string(),
+                ))
+            }
+        };
+        let reduce = reduce.clamp(0, order.remaining);
+        order.remaining -= reduce;
+        order.decrease_count += reduce;
+        order.updated = now;
+        if order.remaining == 0 {
+            order.status = OrderStatus::Canceled;
+        }
+        Ok(order.to_order())
+    }
+
+    /// Changes the price and/or size of a resting order. Mirrors
+    /// [`Kalshi::amend_order`](crate::Kalshi::amend_order).
+    ///
+    /// `count` is the new total size including contracts already filled. A price change
+    /// loses queue priority and may fill immediately if it crosses the book.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn amend_order(
+        &self,
+        order_id: &str,
+        ticker: &str,
+        side: Side,
+        action: Action,
+        client_order_id: &str,
+        updated_client_order_id: &str,
+        yes_price: Option<i32>,
+        no_price: Option<i32>,
+        yes_price_dollars: Option<String>,
+        no_price_dollars: Option<String>,
+        count: Option<i32>,
+    ) -> Result<AmendOrderResponse, KalshiError> {
+        let new_price = side_price(
+            side,
+            yes_price.map(i64::from),
+            no_price.map(i64::from),
+            yes_price_dollars.as_deref(),
+            no_price_dollars.as_deref(),
+        );
+        let mut fills = Vec::new();
+        let response = {
+            let mut state = self.lock();
+            let now = state.now();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            let order = resting_mut(&mut state, order_id)?;
+            if order.ticker != ticker
+                || order.side != side
+                || order.action != action
+                || order.client_order_id != client_order_id
+            {
+                return Err(KalshiError::UserInputError(
+                    "ticker, side, action and client_order_id must match the order".to_string(),
+                ));
+            }
+            let old_order = order.to_order();
+            let filled = order.count - order.remaining - order.decrease_count;
+            if let Some(count) = count.filter(|c| *c < filled) {
+                return Err(KalshiError::UserInputError(format!(
+                    "count {} is below the {} contracts already filled",
+                    count, filled
+                )));
+            }
+            let remaining = count.map_or(order.remaining, |c| c - filled - order.decrease_count);
+            let price = new_price.unwrap_or(order.price);
+            let yes_limit = match side {
+                Side::Yes => price,
+                Side::No => 100 - price,
+            };
+            let old_delta = yes_delta(side, action, order.remaining);
+            let old_yes_limit = order.yes_limit();
+            let old_cost = state.cost_of(ticker, old_delta, old_yes_limit);
+            let new_cost =
+                state.cost_of(ticker, yes_delta(side, action, remaining.max(0)), yes_limit);
+            if new_cost > old_cost && new_cost > state.balance - state.reserved(Some(order_id)) {
+                return Err(api_error("insufficient_balance", "insufficient balance"));
+            }
+
+            let order = resting_mut(&mut state, order_id)?;
+            if let Some(count) = count {
+                order.remaining = remaining;
+                order.count = count;
+            }
+            let repriced = price != order.price;
+            order.price = price;
+            order.client_order_id = updated_client_order_id.to_string();
+            order.updated = now;
+            if order.remaining <= 0 {
+                order.remaining = 0;
+                order.status = OrderStatus::Canceled;
+            }
+            if repriced {
+                order.seq = seq;
+                state.match_against_book(order_id, true, &mut fills);
+                if state.orders[order_id].status == OrderStatus::Resting {
+                    state.rest(order_id);
+                }
+            }
+            AmendOrderResponse {
+                old_order,
+                order: state.orders[order_id].to_order(),
+            }
+        };
+        self.publish(&fills);
+        Ok(response)
+    }
+
+    /// Lists simulated orders. Mirrors [`Kalshi::get_orders`](crate::Kalshi::get_orders);
+    /// the cursor is an offset into the result set.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_orders(
+        &self,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+        status: Option<OrderStatus>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> Result<(Option<String>, Vec<Order>), KalshiError> {
+        let state = self.lock();
+        let mut orders: Vec<&PaperOrder> = state
+            .orders
+            .values()
+            .filter(|o| ticker.as_ref().is_none_or(|t| &o.ticker == t))
+            .filter(|o| {
+                event_ticker
+                    .as_ref()
+                    .is_none_or(|e| &event_ticker_of(&o.ticker) == e)
+            })
+            .filter(|o| status.is_none_or(|s| o.status == s))
+            .filter(|o| min_ts.is_none_or(|ts| o.created.timestamp() >= ts))
+            .filter(|o| max_ts.is_none_or(|ts| o.created.timestamp() <= ts))
+            .collect();
+        orders.sort_by_key(|o| std::cmp::Reverse(o.seq));
+        let orders: Vec<Order> = orders.into_iter().map(PaperOrder::to_order).collect();
+        Ok(paginate(orders, limit, cursor))
+    }
+
+    /// Lists simulated positions. Mirrors [`Kalshi::get_positions`](crate::Kalshi::get_positions).
+    ///
+    /// `settlement_status` accepts `"settled"`, `"unsettled"` or `"all"`; `count_filter`
+    /// containing `"position"` drops flat markets.
+    pub async fn get_positions(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        settlement_status: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        count_filter: Option<String>,
+    ) -> Result<(Option<String>, Vec<EventPosition>, Vec<MarketPosition>), KalshiError> {
+        let state = self.lock();
+        let mut markets: Vec<MarketPosition> = state
+            .positions
+            .values()
+            .filter(|p| ticker.as_ref().is_none_or(|t| &p.entry.ticker == t))
+            .filter(|p| {
+                event_ticker
+                    .as_ref()
+                    .is_none_or(|e| &p.entry.event_ticker == e)
+            })
+            .filter(|p| {
+                let settled = state.settled.contains(&p.entry.ticker);
+                match settlement_status.as_deref() {
+                    Some("settled") => settled,
+                    Some("all") => true,
+                    _ => !settled,
+                }
+            })
+            .filter(|p| {
+                !count_filter
+                    .as_deref()
+                    .is_some_and(|f| f.contains("position"))
+                    || p.entry.position != 0
+            })
+            .map(|p| MarketPosition {
+                fees_paid: p.entry.fees_paid.round() as i64,
+                market_exposure: p.entry.cost_basis().round() as i64,
+                position: p.entry.position,
+                realized_pnl: p.entry.realized_pnl.round() as i64,
+                resting_orders_count: Some(
+                    state
+                        .orders
+                        .values()
+                        .filter(|o| o.ticker == p.entry.ticker && o.status == OrderStatus::Resting)
+                        .count() as i32,
+                ),
+                ticker: p.entry.ticker.clone(),
+                total_traded: p.total_traded,
+            })
+            .collect();
+        markets.sort_by(|a, b| a.ticker.cmp(&b.ticker));
+
+        let mut events: BTreeMap<String, EventPosition> = BTreeMap::new();
+        for m in &markets {
+            let e = events
+                .entry(event_ticker_of(&m.ticker))
+                .or_insert_with(|| EventPosition {
+                    event_exposure: 0,
+                    event_ticker: event_ticker_of(&m.ticker),
+                    fees_paid: 0,
+                    realized_pnl: 0,
+                    resting_order_count: Some(0),
+                    total_cost: 0,
+                });
+            e.event_exposure += m.market_exposure;
+            e.fees_paid += m.fees_paid;
+            e.realized_pnl += m.realized_pnl;
+            e.total_cost += m.market_exposure;
+            e.resting_order_count =
+                Some(e.resting_order_count.unwrap_or(0) + m.resting_orders_count.unwrap_or(0));
+        }
+
+        let (next, markets) = paginate(markets, limit.map(|l| l as i32), cursor);
+        Ok((next, events.into_values().collect(), markets))
+    }
+
+    /// Returns the simulated cash balance in cents. Mirrors
+    /// [`Kalshi::get_balance`](crate::Kalshi::get_balance).
+    pub async fn get_balance(&self) -> Result<i64, KalshiError> {
+        Ok(self.lock().balance)
+    }
+
+    /// Submits up to 20 simulated orders. Mirrors
+    /// [`Kalshi::batch_create_order`](crate::Kalshi::batch_create_order).
+    pub async fn batch_create_order(
+        &self,
+        batch: Vec<OrderCreationField>,
+    ) -> Result<Vec<Result<Order, KalshiError>>, KalshiError> {
+        if batch.len() > crate::portfolio::MAX_BATCH_SIZE {
+            return Err(KalshiError::UserInputError(
+                "Batch size exceeds 20; split the request".into(),
+            ));
+        }
+        let mut out = Vec::with_capacity(batch.len());
+        for field in batch {
+            out.push(
+                self.create_order(
+                    field.action,
+                    field.client_order_id,
+                    field.count,
+                    field.side,
+                    field.ticker,
+                    field.input_type,
+                    field.buy_max_cost,
+                    field.expiration_ts,
+                    field.yes_price,
+                    field.no_price,
+                    field.sell_position_floor,
+                    field.yes_price_dollars,
+                    field.no_price_dollars,
+                    field.time_in_force,
+                    field.post_only,
+                    field.reduce_only,
+                    field.self_trade_prevention_type,
+                    field.order_group_id,
+                    field.cancel_order_on_pause,
+                )
+                .await,
+            );
+        }
+        Ok(out)
+    }
+
+    /// Cancels up to 20 simulated orders. Mirrors
+    /// [`Kalshi::batch_cancel_order`](crate::Kalshi::batch_cancel_order).
+    pub async fn batch_cancel_order(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<Vec<Result<(Order, i32), KalshiError>>, KalshiError> {
+        if ids.len() > crate::portfolio::MAX_BATCH_SIZE {
+            return Err(KalshiError::UserInputError(
+                "Batch size exceeds 20; split the request".into(),
+            ));
+        }
+        let mut out = Vec::with_capacity(ids.len());
+        for id in ids {
+            out.push(self.cancel_order(&id).await);
+        }
+        Ok(out)
+    }
+
+    /// Looks up one simulated order. Mirrors
+    /// [`Kalshi::get_single_order`](crate::Kalshi::get_single_order).
+    pub async fn get_single_order(&self, order_id: &String) -> Result<Order, KalshiError> {
+        self.lock()
+            .orders
+            .get(order_id)
+            .map(PaperOrder::to_order)
+            .ok_or_else(|| api_error("not_found", "order not found"))
+    }
+
+    /// Returns the value of resting simulated orders in cents: limit price times remaining
+    /// count. Mirrors
+    /// [`Kalshi::get_total_resting_order_value`](crate::Kalshi::get_total_resting_order_value).
+    pub async fn get_total_resting_order_value(&self) -> Result<i64, KalshiError> {
+        Ok(self
+            .lock()
+            .orders
+            .values()
+            .filter(|o| o.status == OrderStatus::Resting)
+            .map(|o| o.price as i64 * o.remaining as i64)
+            .sum())
+    }
+
+    /// Reports queue positions for the resting orders among `order_ids`; other ids are
+    /// skipped. The depth counts displayed size plus paper orders at the same price.
+    /// Mirrors [`Kalshi::get_queue_positions`](crate::Kalshi::get_queue_positions).
+    pub async fn get_queue_positions(
+        &self,
+        order_ids: Vec<String>,
+    ) -> Result<Vec<OrderQueuePosition>, KalshiError> {
+        let state = self.lock();
+        Ok(order_ids
+            .iter()
+            .filter_map(|id| state.orders.get(id))
+            .filter(|o| o.status == OrderStatus::Resting)
+            .map(|order| {
+                let level = order.resting_level();
+                let displayed = state
+                    .books
+                    .get(&order.ticker)
+                    .map(|b| b.level(level.0, level.1))
+                    .unwrap_or(0);
+                let own: i64 = state
+                    .orders
+                    .values()
+                    .filter(|o| {
+                        o.ticker == order.ticker
+                            && o.status == OrderStatus::Resting
+                            && o.resting_level() == level
+                    })
+                    .map(|o| o.remaining as i64)
+                    .sum();
+                OrderQueuePosition {
+                    order_id: order.order_id.clone(),
+                    queue_position: Some(order.queue_ahead),
+                    total_queue_depth: Some(displayed + own),
+                }
+            })
+            .collect())
+    }
+
+    /// Order groups are not simulated, so there are none. Mirrors
+    /// [`Kalshi::get_order_groups`](crate::Kalshi::get_order_groups).
+    pub async fn get_order_groups(&self) -> Result<Vec<OrderGroup>, KalshiError> {
+        Ok(Vec::new())
+    }
+
+    /// Order groups are not simulated, so every lookup is not found. Mirrors
+    /// [`Kalshi::get_order_group`](crate::Kalshi::get_order_group).
+    pub async fn get_order_group(&self, _order_group_id: &str) -> Result<OrderGroup, KalshiError> {
+        Err(api_error("not_found", "order group not found"))
+    }
+
+    /// Lists simulated settlements, newest first. Mirrors
+    /// [`Kalshi::get_settlements`](crate::Kalshi::get_settlements).
+    pub async fn get_settlements(
+        &self,
+        limit: Option<i64>,
+        cursor: Option<String>,
+        ticker: Option<String>,
+        event_ticker: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> Result<(Option<String>, Vec<Settlement>), KalshiError> {
+        let state = self.lock();
+        let settlements: Vec<Settlement> = state
+            .settlements
+            .iter()
+            .rev()
+            .filter(|s| ticker.as_ref().is_none_or(|t| &s.ticker == t))
+            .filter(|s| {
+                event_ticker
+                    .as_ref()
+                    .is_none_or(|e| &event_ticker_of(&s.ticker) == e)
+            })
+            .filter(|s| in_time_range(parse_ts(&s.settled_time), min_ts, max_ts))
+            .cloned()
+            .collect();
+        Ok(paginate(settlements, limit.map(|l| l as i32), cursor))
+    }
+
+    /// Lists simulated fills, newest first. Mirrors [`Kalshi::get_fills`](crate::Kalshi::get_fills).
+    pub async fn get_fills(
+        &self,
+        ticker: Option<String>,
+        order_id: Option<String>,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+        limit: Option<i32>,
+        cursor: Option<String>,
+    ) -> Result<(Option<String>, Vec<Fill>), KalshiError> {
+        let state = self.lock();
+        let fills: Vec<Fill> = state
+            .fills
+            .iter()
+            .rev()
+            .filter(|f| ticker.as_ref().is_none_or(|t| &f.ticker == t))
+            .filter(|f| order_id.as_ref().is_none_or(|id| &f.order_id == id))
+            .filter(|f| in_time_range(parse_ts(&f.created_time), min_ts, max_ts))
+            .cloned()
+            .collect();
+        Ok(paginate(fills, limit, cursor))
+    }
+
+    fn publish(&self, fills: &[FillMsg]) {
+        for fill in fills {
+            let _ = self.fills.send(fill.clone());
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PaperState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Whether a record at `ts` passes the `min_ts`/`max_ts` filters. A record without a
+/// parsable time only passes when neither filter is set.
+fn in_time_range(ts: Option<i64>, min_ts: Option<i64>, max_ts: Option<i64>) -> bool {
+    match ts {
+        Some(ts) => min_ts.is_none_or(|m| ts >= m) && max_ts.is_none_or(|m| ts <= m),
+        None => min_ts.is_none() && max_ts.is_none(),
+    }
+}
+
+fn resting_mut<'a>(
+    state: &'a mut PaperState,
+    order_id: &str,
+) -> Result<&'a mut PaperOrder, KalshiError> {
+    match state.orders.get_mut(order_id) {
+        Some(order) if order.status == OrderStatus::Resting => Ok(order),
+        Some(_) => Err(api_error("order_not_resting", "order is no longer resting")),
+        None => Err(api_error("not_found", "order not found")),
+    }
+}
+
+pub(crate) fn api_error(code: &str, message: &str) -> KalshiError {
+    KalshiError::ApiError(ApiError {
+        code: Some(code.to_string()),
+        message: Some(message.to_string()),
+        details: None,
+    })
+}
+
+/// Applies offset-style pagination, where the cursor is the index of the next item.
+pub(crate) fn paginate<T>(
+    items: Vec<T>,
+    limit: Option<i32>,
+    cursor: Option<String>,
+) -> (Option<String>, Vec<T>) {
+    let start = cursor.and_then(|c| c.parse::<usize>().ok()).unwrap_or(0);
+    let limit = limit.map(|l| l.max(1) as usize).unwrap_or(usize::MAX);
+    let total = items.len();
+    let page: Vec<T> = items.into_iter().skip(start).take(limit).collect();
+    let end = start + page.len();
+    let next = (end < total).then(|| end.to_string());
+    (next, page)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(yes: &[(i32, i32)], no: &[(i32, i32)]) -> Orderbook {
+        let levels = |l: &[(i32, i32)]| Some(l.iter().map(|(p, q)| vec![*p, *q]).collect());
+        Orderbook {
+            yes: levels(yes),
+            no: levels(no),
+            yes_dollars: Vec::new(),
+            no_dollars: Vec::new(),
+        }
+    }
+
+    async fn limit(
+        paper: &PaperKalshi,
+        side: Side,
+        action: Action,
+        count: i32,
+        price: i64,
+    ) -> Order {
+        let (yes, no) = match side {
+            Side::Yes => (Some(price), None),
+            Side::No => (None, Some(price)),
+        };
+        paper
+            .create_order(
+                action,
+                None,
+                count,
+                side,
+                "EV-1-T50".to_string(),
+                OrderType::Limit,
+                None,
+                None,
+                yes,
+                no,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_taker_fill_walks_book_and_charges_fees() {
+        let paper = PaperKalshi::new(10_000);
+        // Best YES ask is 100 - 55 = 45 for 5, then 47 for 10.
+        paper.apply_orderbook("EV-1-T50", &book(&[(40, 10)], &[(55, 5), (53, 10)]));
+
+        let order = limit(&paper, Side::Yes, Action::Buy, 8, 47).await;
+        assert_eq!(order.status, OrderStatus::Executed);
+        let (_, fills) = paper
+            .get_fills(None, None, None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(fills.len(), 2);
+
+        // 5 @ 45 + 3 @ 47 = 366¢, fees ceil(0.07*5*.45*.55*100)=9 + ceil(0.07*3*.47*.53*100)=6.
+        assert_eq!(paper.get_balance().await.unwrap(), 10_000 - 366 - 15);
+        let (_, _, positions) = paper
+            .get_positions(None, None, None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(positions[0].position, 8);
+    }
+
+    #[tokio::test]
+    async fn test_resting_order_waits_for_queue_then_settles() {
+        let paper = PaperKalshi::new(10_000);
+        let mut rx = paper.subscribe();
+        paper.apply_orderbook("EV-1-T50", &book(&[(40, 10)], &[(55, 5)]));
+
+        let order = limit(&paper, Side::Yes, Action::Buy, 5, 40).await;
+        assert_eq!(order.status, OrderStatus::Resting);
+        assert_eq!(order.queue_position, Some(10));
+
+        let trade = |count| {
+            WebSocketMessage::Trade(crate::TradeMsg {
+                market_ticker: "EV-1-T50".to_string(),
+                yes_price: 40,
+                no_price: 60,
+                count,
+                taker_side: "no".to_string(),
+                ts: 0,
+            })
+        };
+        assert!(paper.handle_message(&trade(8)).is_empty());
+        let fills = paper.handle_message(&trade(4));
+        assert_eq!(fills[0].count, 2);
+        assert_eq!(rx.try_recv().unwrap().post_position, 2);
+
+        paper.settle("EV-1-T50", SettlementResult::Yes);
+        let (_, orders) = paper
+            .get_orders(
+                None,
+                None,
+                None,
+                None,
+                Some(OrderStatus::Resting),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(orders.is_empty());
+        // Paid 80¢ for two contracts (no maker fees by default); each pays 100¢ on YES.
+        assert_eq!(paper.get_balance().await.unwrap(), 10_000 - 80 + 200);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_orders_in_settled_markets() {
+        let paper = PaperKalshi::new(10_000);
+        paper.apply_orderbook("EV-1-T50", &book(&[(40, 10)], &[(55, 5)]));
+        paper.settle("EV-1-T50", SettlementResult::No);
+        let err = paper
+            .create_order(
+                Action::Buy,
+                None,
+                1,
+                Side::Yes,
+                "EV-1-T50".to_string(),
+                OrderType::Limit,
+                None,
+                None,
+                Some(45),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, KalshiError::ApiError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_amend_rechecks_balance() {
+        let paper = PaperKalshi::new(1_000);
+        paper.apply_orderbook("EV-1-T50", &book(&[(30, 10)], &[(55, 5)]));
+        let order = limit(&paper, Side::Yes, Action::Buy, 10, 40).await;
+        let client_order_id = order.client_order_id.clone();
+        let amend = |count: i32, price: i32| {
+            paper.amend_order(
+                &order.order_id,
+                "EV-1-T50",
+                Side::Yes,
+                Action::Buy,
+                &client_order_id,
+                &client_order_id,
+                Some(price),
+                None,
+                None,
+                None,
+                Some(count),
+            )
+        };
+
+        // 30 at 40¢ and 25 at 41¢ both cost more than the 1000¢ balance.
+        for (count, price) in [(30, 40), (25, 41)] {
+            let err = amend(count, price).await.unwrap_err();
+            assert!(
+                matches!(&err, KalshiError::ApiError(e) if e.code.as_deref() == Some("insufficient_balance")),
+                "{:?}",
+                err
+            );
+        }
+        let (_, orders) = paper
+            .get_orders(None, None, None, None, None, None, None)
+            .await
+            .unwrap();
+        let resting = &orders[0];
+        assert_eq!(resting.remaining_count, Some(10));
+        assert_eq!(resting.yes_price, Some(40));
+
+        // 20 at 44¢ fits.
+        let amended = amend(20, 44).await.unwrap().order;
+        assert_eq!(amended.remaining_count, Some(20));
+    }
+
+    #[tokio::test]
+    async fn test_trade_through_fills_at_most_its_size() {
+        let paper = PaperKalshi::new(10_000);
+        paper.apply_orderbook("EV-1-T50", &book(&[], &[(55, 5)]));
+        limit(&paper, Side::Yes, Action::Buy, 5, 42).await;
+        limit(&paper, Side::Yes, Action::Buy, 5, 41).await;
+
+        // A 7-lot printing at 40 trades through both bids: the better one fills first.
+        let fills = paper.handle_message(&WebSocketMessage::Trade(crate::TradeMsg {
+            market_ticker: "EV-1-T50".to_string(),
+            yes_price: 40,
+            no_price: 60,
+            count: 7,
+            taker_side: "no".to_string(),
+            ts: 0,
+        }));
+        let filled: Vec<(i32, i32)> = fills
+            .iter()
+            .map(|f| (f.yes_price.unwrap(), f.count))
+            .collect();
+        assert_eq!(filled, [(42, 5), (41, 2)]);
+    }
+}
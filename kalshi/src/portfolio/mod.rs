@@ -307,12 +307,16 @@
 
 use super::Kalshi;
 use crate::kalshi_error::*;
+use crate::risk::{side_price, OrderIntent};
+use futures_util::{stream, StreamExt};
 use std::fmt;
 use uuid::Uuid;
 
 use serde::{Deserialize, Deserializer, Serialize};
 
 const PORTFOLIO_PATH: &str = "/portfolio";
+/// Largest batch accepted by the batched order endpoints.
+pub(crate) const MAX_BATCH_SIZE: usize = 20;
 
 impl Kalshi {
     /// Retrieves the current balance of the authenticated user from the Kalshi exchange.
@@ -461,6 +465,7 @@ impl Kalshi {
     pub async fn cancel_order(&self, order_id: &str) -> Result<(Order, i32), KalshiError> {
         let path = format!("{}/orders/{}", PORTFOLIO_PATH, order_id);
         let result: DeleteOrderResponse = self.signed_delete(&path).await?;
+        self.risk_record(&result.order);
         Ok((result.order, result.reduced_by))
     }
     /// Decreases the size of an existing order on the Kalshi exchange.
@@ -526,6 +531,7 @@ impl Kalshi {
 
         // response is now { "order": { … }, "reduced_by": int }
         let result: DecreaseOrderResponse = self.signed_post(&path, &decrease_payload).await?;
+        self.risk_record(&result.order);
         Ok(result.order)
     }
 
@@ -827,6 +833,23 @@ impl Kalshi {
             }
         }
 
+        self.gate_check()?;
+        self.risk_check(&[OrderIntent {
+            ticker: ticker.clone(),
+            side,
+            action,
+            count,
+            price: side_price(
+                side,
+                yes_price,
+                no_price,
+                yes_price_dollars.as_deref(),
+                no_price_dollars.as_deref(),
+            ),
+            buy_max_cost,
+            reduce_only: reduce_only.unwrap_or(false),
+        }])?;
+
         let unwrapped_id = match client_order_id {
             Some(id) => id,
             _ => String::from(Uuid::new_v4()),
@@ -856,6 +879,7 @@ impl Kalshi {
 
         let path = format!("{}/orders", PORTFOLIO_PATH);
         let result: SingleOrderResponse = self.signed_post(&path, &order_payload).await?;
+        self.risk_record(&result.order);
         Ok(result.order)
     }
 
@@ -869,12 +893,25 @@ impl Kalshi {
         if batch.is_empty() {
             return Ok(Vec::new());
         }
-        if batch.len() > 20 {
-            return Err(KalshiError::UserInputError(
-                "Batch size exceeds 20; split the request".into(),
-            ));
+        if batch.len() > MAX_BATCH_SIZE {
+            return Err(KalshiError::UserInputError(format!(
+                "Batch size exceeds {}; split the request or use batch_create_order_chunked",
+                MAX_BATCH_SIZE
+            )));
         }
 
+        let intents: Vec<OrderIntent> = batch.iter().map(OrderIntent::from_field).collect();
+        self.gate_check()?;
+        self.risk_check(&intents)?;
+        self.send_batch_create(batch).await
+    }
+
+    /// Sends one batch-create request without the gate and risk checks, which callers run
+    /// beforehand.
+    async fn send_batch_create(
+        &self,
+        batch: Vec<OrderCreationField>,
+    ) -> Result<Vec<Result<Order, KalshiError>>, KalshiError> {
         // Convert the user-supplied OrderCreationField into raw payloads
         let orders: Vec<CreateOrderPayload> = batch
             .into_iter()
@@ -891,10 +928,11 @@ impl Kalshi {
         let mut out = Vec::with_capacity(response.orders.len());
         for item in response.orders {
             match (item.order, item.error) {
-                (Some(order), None) => out.push(Ok(order)),
-                (_, Some(err)) => out.push(Err(KalshiError::UserInputError(
-                    err.message.unwrap_or_else(|| "unknown error".into()),
-                ))),
+                (Some(order), None) => {
+                    self.risk_record(&order);
+                    out.push(Ok(order))
+                }
+                (_, Some(err)) => out.push(Err(KalshiError::ApiError(err))),
                 _ => out.push(Err(KalshiError::InternalError(
                     "malformed batch-create response".into(),
                 ))),
@@ -913,10 +951,11 @@ impl Kalshi {
         if ids.is_empty() {
             return Ok(Vec::new());
         }
-        if ids.len() > 20 {
-            return Err(KalshiError::UserInputError(
-                "Batch size exceeds 20; split the request".into(),
-            ));
+        if ids.len() > MAX_BATCH_SIZE {
+            return Err(KalshiError::UserInputError(format!(
+                "Batch size exceeds {}; split the request or use batch_cancel_order_chunked",
+                MAX_BATCH_SIZE
+            )));
         }
 
         let path = format!("{}/orders/batched", PORTFOLIO_PATH);
@@ -928,10 +967,11 @@ impl Kalshi {
         let mut out = Vec::with_capacity(response.orders.len());
         for item in response.orders {
             match (item.order, item.reduced_by, item.error) {
-                (Some(order), Some(reduced_by), None) => out.push(Ok((order, reduced_by))),
-                (_, _, Some(err)) => out.push(Err(KalshiError::UserInputError(
-                    err.message.unwrap_or_else(|| "unknown error".into()),
-                ))),
+                (Some(order), Some(reduced_by), None) => {
+                    self.risk_record(&order);
+                    out.push(Ok((order, reduced_by)))
+                }
+                (_, _, Some(err)) => out.push(Err(KalshiError::ApiError(err))),
                 _ => out.push(Err(KalshiError::InternalError(
                     "malformed batch-cancel response".into(),
                 ))),
@@ -940,6 +980,91 @@ impl Kalshi {
         Ok(out)
     }
 
+    /// Submits any number of orders, splitting them into batches of 20.
+    ///
+    /// Batches are sent concurrently, at most `max_in_flight` at a time, so callers can stay
+    /// within their write rate limit. Every order is checked by the attached `RiskGuard`
+    /// before the first batch is sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `orders` - The orders to create, in any number.
+    /// * `max_in_flight` - Maximum number of batch requests outstanding at once (at least 1).
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<Result<Order, KalshiError>>)`: One result per input order, in input order.
+    ///   Per-order exchange errors are returned as `KalshiError::ApiError`; if a whole batch
+    ///   request fails, each of its orders gets that batch's error.
+    /// - `Err(KalshiError)`: An error if the risk check rejects the orders.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let results = kalshi_instance.batch_create_order_chunked(orders, 2).await?;
+    /// for (field, result) in fields.iter().zip(&results) { /* ... */ }
+    /// ```
+    ///
+    pub async fn batch_create_order_chunked(
+        &self,
+        orders: Vec<OrderCreationField>,
+        max_in_flight: usize,
+    ) -> Result<Vec<Result<Order, KalshiError>>, KalshiError> {
+        let intents: Vec<OrderIntent> = orders.iter().map(OrderIntent::from_field).collect();
+        self.gate_check()?;
+        self.risk_check(&intents)?;
+
+        let chunks: Vec<Vec<OrderCreationField>> =
+            orders.chunks(MAX_BATCH_SIZE).map(|c| c.to_vec()).collect();
+        let responses: Vec<_> = stream::iter(chunks)
+            .map(|chunk| async move {
+                let len = chunk.len();
+                (len, self.send_batch_create(chunk).await)
+            })
+            .buffered(max_in_flight.max(1))
+            .collect()
+            .await;
+        Ok(flatten_batches(responses, "batch-create"))
+    }
+
+    /// Cancels any number of orders, splitting them into batches of 20.
+    ///
+    /// Batches are sent concurrently, at most `max_in_flight` at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - The order IDs to cancel, in any number.
+    /// * `max_in_flight` - Maximum number of batch requests outstanding at once (at least 1).
+    ///
+    /// # Returns
+    ///
+    /// One result per input ID, in input order. Per-order exchange errors are returned as
+    /// `KalshiError::ApiError`; if a whole batch request fails, each of its IDs gets that
+    /// batch's error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let results = kalshi_instance.batch_cancel_order_chunked(ids, 2).await;
+    /// ```
+    ///
+    pub async fn batch_cancel_order_chunked(
+        &self,
+        ids: Vec<String>,
+        max_in_flight: usize,
+    ) -> Vec<Result<(Order, i32), KalshiError>> {
+        let chunks: Vec<Vec<String>> = ids.chunks(MAX_BATCH_SIZE).map(|c| c.to_vec()).collect();
+        let responses: Vec<_> = stream::iter(chunks)
+            .map(|chunk| async move {
+                let len = chunk.len();
+                (len, self.batch_cancel_order(chunk).await)
+            })
+            .buffered(max_in_flight.max(1))
+            .collect()
+            .await;
+        flatten_batches(responses, "batch-cancel")
+    }
+
     /// Retrieves the total value of all resting orders for the authenticated user.
     ///
     /// This endpoint is primarily intended for use by FCM members.
@@ -1208,6 +1333,20 @@ impl Kalshi {
             ));
         }
 
+        self.gate_check()?;
+        if let Some(guard) = &self.risk_guard {
+            let price = side_price(
+                side,
+                yes_price.map(i64::from),
+                no_price.map(i64::from),
+                yes_price_dollars.as_deref(),
+                no_price_dollars.as_deref(),
+            );
+            guard
+                .check_amend(order_id, ticker, side, action, price, count)
+                .map_err(KalshiError::RiskViolation)?;
+        }
+
         let path = format!("{}/orders/{}/amend", PORTFOLIO_PATH, order_id);
         let body = AmendOrderRequest {
             ticker: ticker.to_string(),
@@ -1221,7 +1360,10 @@ impl Kalshi {
             no_price_dollars,
             count,
         };
-        self.signed_post(&path, &body).await
+        let response: AmendOrderResponse = self.signed_post(&path, &body).await?;
+        self.risk_record(&response.old_order);
+        self.risk_record(&response.order);
+        Ok(response)
     }
 
     /// Retrieves the queue position for a single order.
@@ -1255,6 +1397,64 @@ impl Kalshi {
     }
 }
 
+/// Per-item results of one batch request.
+type BatchResults<T> = Result<Vec<Result<T, KalshiError>>, KalshiError>;
+
+/// Flattens per-batch responses into one result per input item, keeping input order.
+///
+/// Short responses are padded and failed batches expanded so that the output always has
+/// exactly one entry per submitted item. Every item of a failed batch gets a copy of the
+/// batch's error; HTTP errors cannot be copied, so only the first item keeps the original
+/// and the rest get an `InternalError` describing it.
+fn flatten_batches<T>(
+    responses: Vec<(usize, BatchResults<T>)>,
+    what: &str,
+) -> Vec<Result<T, KalshiError>> {
+    let mut out = Vec::new();
+    for (len, response) in responses {
+        match response {
+            Ok(results) => {
+                let mut results = results.into_iter();
+                for _ in 0..len {
+                    out.push(results.next().unwrap_or_else(|| {
+                        Err(KalshiError::InternalError(format!(
+                            "item missing from {} response",
+                            what
+                        )))
+                    }));
+                }
+            }
+            Err(e) => {
+                let copies: Vec<_> = (1..len).map(|_| Err(copy_error(&e, what))).collect();
+                if len > 0 {
+                    out.push(Err(e));
+                }
+                out.extend(copies);
+            }
+        }
+    }
+    out
+}
+
+/// Copies a batch-level error for one item of the batch.
+fn copy_error(e: &KalshiError, what: &str) -> KalshiError {
+    match e {
+        KalshiError::UserInputError(m) => KalshiError::UserInputError(m.clone()),
+        KalshiError::HttpError { status, body } => KalshiError::HttpError {
+            status: *status,
+            body: body.clone(),
+        },
+        KalshiError::InternalError(m) => KalshiError::InternalError(m.clone()),
+        KalshiError::Auth(m) => KalshiError::Auth(m.clone()),
+        KalshiError::ApiError(err) => KalshiError::ApiError(err.clone()),
+        KalshiError::RiskViolation(v) => KalshiError::RiskViolation(v.clone()),
+        KalshiError::ExchangeUnavailable(u) => KalshiError::ExchangeUnavailable(u.clone()),
+        KalshiError::RequestError(_) => {
+            KalshiError::InternalError(format!("{} request failed: {}", what, e))
+        }
+    }
+}
+
 // PRIVATE STRUCTS
 // used in getbalance method
 #[derive(Debug, Serialize, Deserialize)]
@@ -1366,7 +1566,7 @@ struct CreateOrderPayload {
 ///
 /// This struct details an individual order, including its identification, status, prices, and various metrics related to its lifecycle.
 ///
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Order {
     /// Unique identifier for the order.
     pub order_id: String,
@@ -1483,7 +1683,7 @@ pub struct Order {
 /// This struct details a single fill instance, including the action taken, the quantity,
 /// the involved prices, and the identifiers of the order and trade.
 ///
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Fill {
     /// The action (buy/sell) of the fill.
     pub action: Action,
@@ -1512,7 +1712,7 @@ pub struct Fill {
 /// This struct provides details of a market settlement, including the result, quantities,
 /// costs involved, and the timestamp of settlement.
 ///
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Settlement {
     /// The result of the market settlement.
     pub market_result: String,
@@ -1536,7 +1736,7 @@ pub struct Settlement {
 ///
 /// Details the user's exposure, costs, profits, and the number of resting orders related to a particular event.
 ///
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct EventPosition {
     /// The total exposure amount in the event.
     pub event_exposure: i64,
@@ -1558,7 +1758,7 @@ pub struct EventPosition {
 /// This struct includes details about the user's market position, including exposure, fees,
 /// profits, and the number of resting orders.
 ///
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MarketPosition {
     /// The total fees paid in the market in cents.
     pub fees_paid: i64,
@@ -1582,7 +1782,7 @@ pub struct MarketPosition {
 /// This struct is used to encapsulate all the data needed to create a new order. It includes details about the order type,
 /// the action being taken (buy/sell), the market ticker, and various other optional parameters that can be specified
 /// to fine-tune the order according to the user's needs.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OrderCreationField {
     /// The action (buy/sell) of the order.
     pub action: Action,
@@ -1658,7 +1858,7 @@ impl OrderCreationField {
 ///
 /// This enum is used to indicate whether a market position, order, or trade is associated with the 'Yes' or 'No' outcome of a market event.
 ///
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Side {
     /// Represents a position, order, or trade associated with the 'Yes' outcome of a market event.
@@ -1669,7 +1869,7 @@ pub enum Side {
 
 /// This enum is used to specify the type of action a user wants to take in an order, either buying or selling.
 ///
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Action {
     /// Represents a buy action.
@@ -1691,7 +1891,7 @@ impl fmt::Display for Action {
 ///
 /// This enum categorizes an order's lifecycle state, from creation to completion or cancellation.
 ///
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderStatus {
     /// The order is active but not yet filled or partially filled and still in the order book.
@@ -1719,7 +1919,7 @@ impl fmt::Display for OrderStatus {
 ///
 /// This enum is used to specify the nature of the order, particularly how it interacts with the market.
 ///
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum OrderType {
     /// A market order is executed immediately at the current market price.
@@ -1769,12 +1969,6 @@ struct BatchCancelOrderPayload {
     ids: Vec<String>,
 }
 
-/// One element in the `orders` array that the batch-create endpoint returns.
-#[derive(Debug, Serialize, Deserialize)]
-struct ApiError {
-    message: Option<String>,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 struct BatchCreateOrderResponseItem {
     order: Option<Order>,
@@ -1865,7 +2059,7 @@ struct AmendOrderRequest {
 }
 
 /// Response from the amend_order endpoint, containing both the old and new order.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AmendOrderResponse {
     /// The original order before amendment.
     pub old_order: Order,
@@ -1885,4 +2079,28 @@ mod test {
         assert!(result.cursor.is_none());
         Ok(())
     }
+
+    #[test]
+    fn test_batch_error_keeps_code_and_alignment() -> serde_json::Result<()> {
+        use crate::kalshi_error::KalshiError;
+        use crate::portfolio::{flatten_batches, BatchCancelOrdersResponse};
+
+        let json = r#"{"orders":[{"order":null,"reduced_by":null,"error":{"code":"not_found","message":"order not found"}}]}"#;
+        let response = serde_json::from_str::<BatchCancelOrdersResponse>(json)?;
+        let error = response.orders[0].error.clone().unwrap();
+        assert_eq!(error.code.as_deref(), Some("not_found"));
+
+        let results: Vec<Result<i32, KalshiError>> = flatten_batches(
+            vec![
+                (2, Ok(vec![Ok(1)])),
+                (1, Err(KalshiError::UserInputError("boom".into()))),
+            ],
+            "test",
+        );
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[0], Ok(1)));
+        assert!(results[1].is_err());
+        assert!(matches!(&results[2], Err(KalshiError::UserInputError(m)) if m == "boom"));
+        Ok(())
+    }
 }
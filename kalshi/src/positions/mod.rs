@@ -0,0 +1,676 @@
+//! Real-time position and P&L tracking.
+//!
+//! [`PositionBook`] maintains per-market and per-event positions from three sources:
+//!
+//! - **Fills** ([`FillMsg`]) update position and average cost as trades happen.
+//! - **Position pushes** ([`MarketPositionMsg`]) carry the exchange's own view, in
+//!   centi-cents, and are used to correct drift.
+//! - **REST snapshots** from `get_positions` are reconciled with [`PositionBook::sync`].
+//!
+//! All prices and amounts exposed by this module are in cents. Positions are signed in
+//! YES terms, following the exchange convention: a positive position is long YES, a
+//! negative position is long NO. Buying NO at 40¢ is booked as selling YES at 60¢.
+//!
+//! Attach a [`FeeCalculator`] with [`PositionBook::with_fee_calculator`] to book estimated
+//! fees as fills arrive, so P&L is net of fees before the exchange reports them.
+//!
+//! Unrealized P&L is marked either from a local orderbook ([`PositionBook::mark_from_orderbook`])
+//! or from `Market.last_price` ([`PositionBook::mark_from_market`]).
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::{PositionBook, WebSocketMessage};
+//!
+//! # async fn example(kalshi: &kalshi::Kalshi, msg: WebSocketMessage) -> Result<(), kalshi::KalshiError> {
+//! let book = PositionBook::new();
+//! let mut alerts = book.subscribe();
+//! book.sync(kalshi).await?;
+//!
+//! // In your WebSocket loop:
+//! book.handle_message(&msg);
+//!
+//! let pnl = book.total_pnl();
+//! println!("realized {} unrealized {} fees {}", pnl.realized_pnl, pnl.unrealized_pnl, pnl.fees_paid);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::fees::{FeeCalculator, FeeRole};
+use crate::kalshi_error::*;
+use crate::{
+    Action, FillMsg, Kalshi, Market, MarketPosition, MarketPositionMsg, Orderbook, Side,
+    WebSocketMessage,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Centi-cents per cent, the unit used by the `market_position` channel.
+pub const CENTI_CENTS_PER_CENT: i64 = 100;
+
+/// Capacity of the broadcast channel carrying [`PositionAlert`]s.
+const ALERT_CHANNEL_CAPACITY: usize = 256;
+
+/// Page size used when syncing against `get_positions`.
+const SYNC_PAGE_SIZE: i64 = 200;
+
+/// A market position maintained by the [`PositionBook`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionEntry {
+    /// Market ticker.
+    pub ticker: String,
+    /// Parent event ticker.
+    pub event_ticker: String,
+    /// Signed position in YES contracts (negative means long NO).
+    pub position: i32,
+    /// Average entry price of the open position, in YES cents.
+    pub avg_price: f64,
+    /// Realized P&L in cents, before fees.
+    pub realized_pnl: f64,
+    /// Fees paid in cents.
+    pub fees_paid: f64,
+    /// Last mark in YES cents, if the market has been marked.
+    pub mark_price: Option<f64>,
+}
+
+impl PositionEntry {
+    pub(crate) fn new(ticker: &str, event_ticker: String) -> Self {
+        PositionEntry {
+            ticker: ticker.to_string(),
+            event_ticker,
+            position: 0,
+            avg_price: 0.0,
+            realized_pnl: 0.0,
+            fees_paid: 0.0,
+            mark_price: None,
+        }
+    }
+
+    /// Cost of the open position in cents, as the exchange reports it.
+    ///
+    /// A YES position costs `avg_price` per contract; a NO position costs `100 - avg_price`.
+    pub fn cost_basis(&self) -> f64 {
+        if self.position >= 0 {
+            self.position as f64 * self.avg_price
+        } else {
+            (-self.position) as f64 * (100.0 - self.avg_price)
+        }
+    }
+
+    /// Unrealized P&L in cents at the last mark, or zero if never marked.
+    pub fn unrealized_pnl(&self) -> f64 {
+        match self.mark_price {
+            Some(mark) => self.position as f64 * (mark - self.avg_price),
+            None => 0.0,
+        }
+    }
+
+    /// Applies a trade of `delta` YES contracts at `price` YES cents.
+    pub(crate) fn apply_trade(&mut self, delta: i32, price: f64) {
+        if delta == 0 {
+            return;
+        }
+        let old = self.position;
+        let new = old + delta;
+        if old == 0 || old.signum() == delta.signum() {
+            // Opening or adding: blend the average price.
+            let total = old.abs() as f64 * self.avg_price + delta.abs() as f64 * price;
+            self.avg_price = total / new.abs() as f64;
+        } else {
+            // Reducing, closing, or flipping.
+            let closed = delta.abs().min(old.abs());
+            self.realized_pnl += closed as f64 * (price - self.avg_price) * old.signum() as f64;
+            if new.signum() != old.signum() && new != 0 {
+                self.avg_price = price;
+            }
+        }
+        self.position = new;
+        if self.position == 0 {
+            self.avg_price = 0.0;
+        }
+    }
+
+    /// Overwrites position, cost and totals with exchange-reported values in cents.
+    fn adopt(&mut self, position: i32, cost_cents: f64, realized_cents: f64, fees_cents: f64) {
+        self.position = position;
+        self.avg_price = if position > 0 {
+            cost_cents / position as f64
+        } else if position < 0 {
+            100.0 - cost_cents / (-position) as f64
+        } else {
+            0.0
+        };
+        self.realized_pnl = realized_cents;
+        self.fees_paid = fees_cents;
+    }
+}
+
+/// Aggregated P&L over a set of positions, in cents.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PnlSummary {
+    /// Sum of open position costs.
+    pub cost_basis: f64,
+    /// Realized P&L before fees.
+    pub realized_pnl: f64,
+    /// Unrealized P&L at the latest marks.
+    pub unrealized_pnl: f64,
+    /// Fees paid.
+    pub fees_paid: f64,
+}
+
+impl PnlSummary {
+    /// Realized plus unrealized P&L, net of fees.
+    pub fn net_pnl(&self) -> f64 {
+        self.realized_pnl + self.unrealized_pnl - self.fees_paid
+    }
+
+    fn add(&mut self, entry: &PositionEntry) {
+        self.cost_basis += entry.cost_basis();
+        self.realized_pnl += entry.realized_pnl;
+        self.unrealized_pnl += entry.unrealized_pnl();
+        self.fees_paid += entry.fees_paid;
+    }
+}
+
+/// Which field disagreed during reconciliation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftField {
+    /// Contract count.
+    Position,
+    /// Cost of the open position.
+    Cost,
+    /// Realized P&L.
+    RealizedPnl,
+    /// Fees paid.
+    FeesPaid,
+}
+
+/// Where the disagreeing exchange value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftSource {
+    /// A `market_position` WebSocket message.
+    WebSocket,
+    /// A `get_positions` REST snapshot.
+    Rest,
+}
+
+/// Raised when the locally tracked position disagrees with the exchange.
+///
+/// The book always adopts the exchange's values after raising an alert.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionAlert {
+    /// Market ticker.
+    pub ticker: String,
+    /// The field that disagreed.
+    pub field: DriftField,
+    /// Locally tracked value (contracts or cents).
+    pub local: f64,
+    /// Exchange-reported value (contracts or cents).
+    pub remote: f64,
+    /// Where the exchange value came from.
+    pub source: DriftSource,
+}
+
+#[derive(Debug, Default)]
+struct BookState {
+    positions: HashMap<String, PositionEntry>,
+    event_of: HashMap<String, String>,
+    seen_trades: HashSet<String>,
+}
+
+impl BookState {
+    fn entry(&mut self, ticker: &str) -> &mut PositionEntry {
+        let event = self
+            .event_of
+            .get(ticker)
+            .cloned()
+            .unwrap_or_else(|| event_ticker_of(ticker));
+        self.positions
+            .entry(ticker.to_string())
+            .or_insert_with(|| PositionEntry::new(ticker, event))
+    }
+}
+
+/// Maintains positions, average cost and P&L per market and per event.
+///
+/// See the [module documentation](crate::positions) for an overview.
+#[derive(Debug)]
+pub struct PositionBook {
+    state: Mutex<BookState>,
+    alerts: broadcast::Sender<PositionAlert>,
+    /// Differences at or below this many cents are not reported as drift.
+    drift_tolerance_cents: f64,
+    /// Estimates fees for fills until the exchange reports the actual amount.
+    fees: Option<Arc<FeeCalculator>>,
+}
+
+impl Default for PositionBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PositionBook {
+    /// Creates an empty book with a one-cent drift tolerance.
+    pub fn new() -> Self {
+        Self::with_drift_tolerance(1.0)
+    }
+
+    /// Creates an empty book that ignores cost/P&L differences up to `cents`.
+    pub fn with_drift_tolerance(cents: f64) -> Self {
+        let (alerts, _) = broadcast::channel(ALERT_CHANNEL_CAPACITY);
+        PositionBook {
+            state: Mutex::new(BookState::default()),
+            alerts,
+            drift_tolerance_cents: cents,
+            fees: None,
+        }
+    }
+
+    /// Books an estimated fee for each fill that reports whether it was a taker.
+    ///
+    /// Estimates are replaced by the exchange's figure on the next position update.
+    pub fn with_fee_calculator(mut self, fees: Arc<FeeCalculator>) -> Self {
+        self.fees = Some(fees);
+        self
+    }
+
+    /// Subscribes to drift alerts.
+    pub fn subscribe(&self) -> broadcast::Receiver<PositionAlert> {
+        self.alerts.subscribe()
+    }
+
+    /// Records the event a market belongs to.
+    ///
+    /// Without this, the event ticker is derived from the market ticker by dropping its
+    /// last `-` segment, which matches Kalshi's ticker scheme for standard markets.
+    pub fn register_market(&self, market: &Market) {
+        let mut state = self.lock();
+        state
+            .event_of
+            .insert(market.ticker.clone(), market.event_ticker.clone());
+        if let Some(entry) = state.positions.get_mut(&market.ticker) {
+            entry.event_ticker = market.event_ticker.clone();
+        }
+    }
+
+    /// Returns the position in `ticker`, if any activity has been recorded.
+    pub fn position(&self, ticker: &str) -> Option<PositionEntry> {
+        self.lock().positions.get(ticker).cloned()
+    }
+
+    /// Returns every tracked position, including flat ones.
+    pub fn positions(&self) -> Vec<PositionEntry> {
+        self.lock().positions.values().cloned().collect()
+    }
+
+    /// Returns the positions belonging to `event_ticker`.
+    pub fn event_positions(&self, event_ticker: &str) -> Vec<PositionEntry> {
+        self.lock()
+            .positions
+            .values()
+            .filter(|p| p.event_ticker == event_ticker)
+            .cloned()
+            .collect()
+    }
+
+    /// Aggregates P&L for one event.
+    pub fn event_pnl(&self, event_ticker: &str) -> PnlSummary {
+        let mut summary = PnlSummary::default();
+        for entry in self.event_positions(event_ticker) {
+            summary.add(&entry);
+        }
+        summary
+    }
+
+    /// Aggregates P&L for every tracked market.
+    pub fn total_pnl(&self) -> PnlSummary {
+        let state = self.lock();
+        let mut summary = PnlSummary::default();
+        for entry in state.positions.values() {
+            summary.add(entry);
+        }
+        summary
+    }
+
+    /// Applies any relevant WebSocket message: fills and position updates.
+    pub fn handle_message(&self, msg: &WebSocketMessage) {
+        match msg {
+            WebSocketMessage::Fill(fill) => {
+                self.apply_fill(fill);
+            }
+            WebSocketMessage::MarketPosition(pos) => self.apply_position_msg(pos),
+            _ => {}
+        }
+    }
+
+    /// Applies a fill, deduplicated by `trade_id`.
+    ///
+    /// Fills that carry no price move the position at the current average price, so they
+    /// never create spurious P&L; the next position push corrects the cost.
+    ///
+    /// Returns false if the fill was already applied.
+    pub fn apply_fill(&self, fill: &FillMsg) -> bool {
+        let mut state = self.lock();
+        if !state.seen_trades.insert(fill.trade_id.clone()) {
+            return false;
+        }
+        let entry = state.entry(&fill.market_ticker);
+        let quoted = fill
+            .yes_price
+            .map(f64::from)
+            .or(fill.no_price.map(|p| 100.0 - p as f64));
+        entry.apply_trade(
+            yes_delta(fill.side, fill.action, fill.count),
+            quoted.unwrap_or(entry.avg_price),
+        );
+        if let (Some(fees), Some(price), Some(is_taker)) = (&self.fees, quoted, fill.is_taker) {
+            let role = if is_taker {
+                FeeRole::Taker
+            } else {
+                FeeRole::Maker
+            };
+            let at = fill
+                .ts
+                .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+                .unwrap_or_else(chrono::Utc::now);
+            entry.fees_paid +=
+                fees.fee_for(&fill.market_ticker, fill.count, price as i32, role, at) as f64;
+        }
+        true
+    }
+
+    /// Records a trade directly, for callers that source fills elsewhere (REST `get_fills`,
+    /// simulators). `price` is in cents on `side`.
+    pub fn apply_trade(&self, ticker: &str, side: Side, action: Action, count: i32, price: i32) {
+        let yes_price = match side {
+            Side::Yes => price as f64,
+            Side::No => 100.0 - price as f64,
+        };
+        self.lock()
+            .entry(ticker)
+            .apply_trade(yes_delta(side, action, count), yes_price);
+    }
+
+    /// Adds `cents` of fees to a market.
+    pub fn apply_fee(&self, ticker: &str, cents: f64) {
+        self.lock().entry(ticker).fees_paid += cents;
+    }
+
+    /// Applies a `market_position` push, converting from centi-cents.
+    ///
+    /// Raises a [`PositionAlert`] for each field that disagrees with the local view, then
+    /// adopts the exchange's values.
+    pub fn apply_position_msg(&self, msg: &MarketPositionMsg) {
+        self.reconcile_one(
+            &msg.market_ticker,
+            msg.position,
+            centi_cents_to_cents(msg.position_cost),
+            centi_cents_to_cents(msg.realized_pnl),
+            centi_cents_to_cents(msg.fees_paid),
+            DriftSource::WebSocket,
+        );
+    }
+
+    /// Reconciles against a REST `MarketPosition`, whose amounts are in cents.
+    pub fn apply_market_position(&self, pos: &MarketPosition) {
+        self.reconcile_market_position(pos);
+    }
+
+    /// Reconciles against a complete `get_positions` snapshot.
+    ///
+    /// Tracked markets with an open position that are missing from the snapshot are
+    /// reconciled as flat, keeping their realized P&L and fees.
+    ///
+    /// # Returns
+    ///
+    /// The drift found, which is also published to subscribers.
+    ///
+    pub fn apply_positions_snapshot(&self, markets: &[MarketPosition]) -> Vec<PositionAlert> {
+        let mut drift: Vec<PositionAlert> = markets
+            .iter()
+            .flat_map(|pos| self.reconcile_market_position(pos))
+            .collect();
+        let reported: HashSet<&str> = markets.iter().map(|p| p.ticker.as_str()).collect();
+        let missing: Vec<(String, f64, f64)> = self
+            .lock()
+            .positions
+            .values()
+            .filter(|p| p.position != 0 && !reported.contains(p.ticker.as_str()))
+            .map(|p| (p.ticker.clone(), p.realized_pnl, p.fees_paid))
+            .collect();
+        for (ticker, realized, fees) in missing {
+            drift.extend(self.reconcile_one(&ticker, 0, 0.0, realized, fees, DriftSource::Rest));
+        }
+        drift
+    }
+
+    /// Pages through `get_positions` and reconciles the book against the full snapshot
+    /// with [`apply_positions_snapshot`](Self::apply_positions_snapshot).
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<PositionAlert>)`: The drift found (also published to subscribers).
+    /// - `Err(KalshiError)`: An error if a request failed.
+    ///
+    pub async fn sync(&self, kalshi: &Kalshi) -> Result<Vec<PositionAlert>, KalshiError> {
+        let mut markets = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (next, _events, page) = kalshi
+                .get_positions(Some(SYNC_PAGE_SIZE), cursor, None, None, None, None)
+                .await?;
+            let empty = page.is_empty();
+            markets.extend(page);
+            match next {
+                Some(c) if !c.is_empty() && !empty => cursor = Some(c),
+                _ => break,
+            }
+        }
+        Ok(self.apply_positions_snapshot(&markets))
+    }
+
+    /// Marks a market at `yes_price` cents.
+    pub fn mark(&self, ticker: &str, yes_price: f64) {
+        if let Some(entry) = self.lock().positions.get_mut(ticker) {
+            entry.mark_price = Some(yes_price);
+        }
+    }
+
+    /// Marks a market at its `last_price`.
+    pub fn mark_from_market(&self, market: &Market) {
+        self.mark(&market.ticker, market.last_price as f64);
+    }
+
+    /// Marks a market at the mid of its best YES bid and ask.
+    ///
+    /// Falls back to whichever side exists when the book is one-sided; leaves the mark
+    /// unchanged when the book is empty.
+    pub fn mark_from_orderbook(&self, ticker: &str, book: &Orderbook) {
+        let bid = best_level(book.yes.as_ref()).map(|p| p as f64);
+        let ask = best_level(book.no.as_ref()).map(|p| 100.0 - p as f64);
+        let mark = match (bid, ask) {
+            (Some(b), Some(a)) => Some((b + a) / 2.0),
+            (Some(b), None) => Some(b),
+            (None, Some(a)) => Some(a),
+            (None, None) => None,
+        };
+        if let Some(mark) = mark {
+            self.mark(ticker, mark);
+        }
+    }
+
+    fn reconcile_market_position(&self, pos: &MarketPosition) -> Vec<PositionAlert> {
+        self.reconcile_one(
+            &pos.ticker,
+            pos.position,
+            pos.market_exposure as f64,
+            pos.realized_pnl as f64,
+            pos.fees_paid as f64,
+            DriftSource::Rest,
+        )
+    }
+
+    /// Adopts the exchange's values for one market and returns the drift found, which is
+    /// also published to subscribers.
+    fn reconcile_one(
+        &self,
+        ticker: &str,
+        position: i32,
+        cost: f64,
+        realized: f64,
+        fees: f64,
+        source: DriftSource,
+    ) -> Vec<PositionAlert> {
+        let tolerance = self.drift_tolerance_cents;
+        let alerts = {
+            let mut state = self.lock();
+            let entry = state.entry(ticker);
+            let mut alerts = Vec::new();
+            let mut check = |field, local: f64, remote: f64, tol: f64| {
+                if (local - remote).abs() > tol {
+                    alerts.push(PositionAlert {
+                        ticker: ticker.to_string(),
+                        field,
+                        local,
+                        remote,
+                        source,
+                    });
+                }
+            };
+            check(
+                DriftField::Position,
+                entry.position as f64,
+                position as f64,
+                0.0,
+            );
+            check(DriftField::Cost, entry.cost_basis(), cost, tolerance);
+            check(
+                DriftField::RealizedPnl,
+                entry.realized_pnl,
+                realized,
+                tolerance,
+            );
+            check(DriftField::FeesPaid, entry.fees_paid, fees, tolerance);
+            entry.adopt(position, cost, realized, fees);
+            alerts
+        };
+        for alert in &alerts {
+            let _ = self.alerts.send(alert.clone());
+        }
+        alerts
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BookState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Converts a centi-cent amount from the `market_position` channel to cents.
+pub fn centi_cents_to_cents(value: i64) -> f64 {
+    value as f64 / CENTI_CENTS_PER_CENT as f64
+}
+
+/// Derives the event ticker from a market ticker by dropping the final `-` segment.
+pub(crate) fn event_ticker_of(ticker: &str) -> String {
+    ticker
+        .rsplit_once('-')
+        .map(|(event, _)| event.to_string())
+        .unwrap_or_else(|| ticker.to_string())
+}
+
+/// Signed change in YES contracts for a trade.
+pub(crate) fn yes_delta(side: Side, action: Action, count: i32) -> i32 {
+    match (side, action) {
+        (Side::Yes, Action::Buy) | (Side::No, Action::Sell) => count,
+        (Side::Yes, Action::Sell) | (Side::No, Action::Buy) => -count,
+    }
+}
+
+/// Highest price among `[[price, count], ...]` levels with non-zero size.
+pub(crate) fn best_level(levels: Option<&Vec<Vec<i32>>>) -> Option<i32> {
+    levels?
+        .iter()
+        .filter(|l| l.len() >= 2 && l[1] > 0)
+        .map(|l| l[0])
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_average_cost_and_realized_pnl() {
+        let book = PositionBook::new();
+        book.apply_trade("EV-1-T50", Side::Yes, Action::Buy, 10, 40);
+        book.apply_trade("EV-1-T50", Side::Yes, Action::Buy, 10, 60);
+        let p = book.position("EV-1-T50").unwrap();
+        assert_eq!(p.position, 20);
+        assert!((p.avg_price - 50.0).abs() < 1e-9);
+        assert_eq!(p.event_ticker, "EV-1");
+
+        // Buying NO at 30 is selling YES at 70.
+        book.apply_trade("EV-1-T50", Side::No, Action::Buy, 5, 30);
+        let p = book.position("EV-1-T50").unwrap();
+        assert_eq!(p.position, 15);
+        assert!((p.realized_pnl - 100.0).abs() < 1e-9);
+
+        book.mark("EV-1-T50", 55.0);
+        assert!((book.total_pnl().unrealized_pnl - 75.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_position_msg_drift_uses_centi_cents() {
+        let book = PositionBook::new();
+        let mut alerts = book.subscribe();
+        book.apply_trade("EV-1-T50", Side::Yes, Action::Buy, 10, 50);
+
+        book.apply_position_msg(&MarketPositionMsg {
+            market_ticker: "EV-1-T50".to_string(),
+            position: 10,
+            position_cost: 50_000,
+            realized_pnl: 0,
+            fees_paid: 0,
+        });
+        assert!(alerts.try_recv().is_err(), "matching push raises nothing");
+
+        book.apply_position_msg(&MarketPositionMsg {
+            market_ticker: "EV-1-T50".to_string(),
+            position: 12,
+            position_cost: 62_000,
+            realized_pnl: 0,
+            fees_paid: 2_000,
+        });
+        let first = alerts.try_recv().unwrap();
+        assert_eq!(first.field, DriftField::Position);
+        let p = book.position("EV-1-T50").unwrap();
+        assert_eq!(p.position, 12);
+        assert!((p.cost_basis() - 620.0).abs() < 1e-9);
+        assert!((p.fees_paid - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_snapshot_flattens_missing_markets() {
+        let book = PositionBook::new();
+        book.apply_trade("EV-1-T50", Side::Yes, Action::Buy, 10, 50);
+        book.apply_trade("EV-1-T60", Side::Yes, Action::Buy, 5, 40);
+
+        let drift = book.apply_positions_snapshot(&[MarketPosition {
+            fees_paid: 0,
+            market_exposure: 500,
+            position: 10,
+            realized_pnl: 0,
+            resting_orders_count: None,
+            ticker: "EV-1-T50".to_string(),
+            total_traded: 500,
+        }]);
+        assert!(drift.iter().all(|a| a.ticker == "EV-1-T60"));
+        assert!(drift
+            .iter()
+            .any(|a| a.field == DriftField::Position && a.local == 5.0 && a.remote == 0.0));
+        assert_eq!(book.position("EV-1-T60").unwrap().position, 0);
+        assert_eq!(book.position("EV-1-T50").unwrap().position, 10);
+    }
+}
@@ -0,0 +1,468 @@
+//! Cached market, event and series metadata kept current from lifecycle messages.
+//!
+//! A [`MarketRegistry`] is filled from REST with [`MarketRegistry::load_series`],
+//! [`MarketRegistry::load_event`] or the `insert_*` methods, then kept up to date by
+//! passing it messages from the [`MarketRegistry::CHANNELS`] subscriptions. Each
+//! `market_lifecycle_v2` message updates the cached market in place:
+//!
+//! | Event                | Effect                                              |
+//! |----------------------|-----------------------------------------------------|
+//! | `created`            | status `initialized`, open and close times          |
+//! | `activated`          | status `active`                                     |
+//! | `deactivated`        | status `inactive`, or `active` when un-paused       |
+//! | `close_date_updated` | close time                                          |
+//! | `determined`         | status `determined`, result                         |
+//! | `settled`            | status `settled`                                    |
+//!
+//! Messages for markets or events not in the registry are remembered; call
+//! [`MarketRegistry::fetch_missing`] to load them. Every applied message is announced on
+//! [`MarketRegistry::subscribe`], so strategies can react to settlements without polling
+//! `get_market`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::{MarketLifecycleEvent, MarketRegistry, RegistryUpdate};
+//!
+//! let registry = MarketRegistry::new();
+//! registry.load_series(&kalshi, "KXHIGHNY").await?;
+//! ws.subscribe(MarketRegistry::CHANNELS.to_vec(), None, None).await?;
+//!
+//! let closing = registry.closing_within(chrono::Utc::now(), chrono::Duration::hours(1));
+//! let mut updates = registry.subscribe();
+//! // In the message loop: registry.handle_message(&msg);
+//! ```
+
+use crate::kalshi_error::*;
+use crate::utils::parse_time;
+use crate::{
+    Channel, Event, EventLifecycleMsg, Market, MarketData, MarketLifecycleEvent,
+    MarketLifecycleMsg, Series, WebSocketMessage,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+/// Page size used for event and market listings.
+const PAGE_SIZE: i64 = 1000;
+
+/// Capacity of the broadcast channel carrying [`RegistryUpdate`]s.
+const UPDATE_CHANNEL_CAPACITY: usize = 1024;
+
+/// A lifecycle message applied by a [`MarketRegistry`].
+#[derive(Debug, Clone)]
+pub enum RegistryUpdate {
+    /// A market transition. `market` is the updated market, or `None` if it is not
+    /// loaded yet.
+    Market {
+        ticker: String,
+        event: MarketLifecycleEvent,
+        market: Option<Box<Market>>,
+    },
+    /// A new event was announced.
+    EventCreated { event_ticker: String, title: String },
+}
+
+#[derive(Debug, Default)]
+struct RegistryState {
+    markets: HashMap<String, Market>,
+    events: HashMap<String, Event>,
+    series: HashMap<String, Series>,
+    missing_markets: HashSet<String>,
+    missing_events: HashSet<String>,
+}
+
+/// Market, event and series metadata. See the [module documentation](crate::registry).
+#[derive(Debug)]
+pub struct MarketRegistry {
+    state: Mutex<RegistryState>,
+    updates: broadcast::Sender<RegistryUpdate>,
+}
+
+impl Default for MarketRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarketRegistry {
+    /// WebSocket channels whose messages keep the registry current.
+    pub const CHANNELS: [Channel; 2] = [Channel::MarketLifecycleV2, Channel::EventLifecycle];
+
+    pub fn new() -> Self {
+        let (updates, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
+        MarketRegistry {
+            state: Mutex::new(RegistryState::default()),
+            updates,
+        }
+    }
+
+    /// Receives every applied lifecycle message.
+    pub fn subscribe(&self) -> broadcast::Receiver<RegistryUpdate> {
+        self.updates.subscribe()
+    }
+
+    /// Adds or replaces a market.
+    pub fn insert_market(&self, market: Market) {
+        let mut state = self.lock();
+        state.missing_markets.remove(&market.ticker);
+        state.markets.insert(market.ticker.clone(), market);
+    }
+
+    /// Adds or replaces an event. Nested markets are stored as markets.
+    pub fn insert_event(&self, mut event: Event) {
+        let markets = event.markets.take().unwrap_or_default();
+        {
+            let mut state = self.lock();
+            state.missing_events.remove(&event.event_ticker);
+            state.events.insert(event.event_ticker.clone(), event);
+        }
+        for market in markets {
+            self.insert_market(market);
+        }
+    }
+
+    /// Adds or replaces a series. Series without a ticker are ignored.
+    pub fn insert_series(&self, series: Series) {
+        if let Some(ticker) = series.ticker.clone() {
+            self.lock().series.insert(ticker, series);
+        }
+    }
+
+    /// Loads a series with all its events and markets.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(usize)`: The number of markets loaded.
+    /// - `Err(KalshiError)`: A request failed.
+    ///
+    pub async fn load_series<B: MarketData>(
+        &self,
+        backend: &B,
+        series_ticker: &str,
+    ) -> Result<usize, KalshiError> {
+        self.insert_series(backend.get_series(series_ticker).await?);
+
+        let mut cursor = None;
+        loop {
+            let (next, page) = backend
+                .get_events(
+                    Some(PAGE_SIZE),
+                    cursor.clone(),
+                    None,
+                    Some(series_ticker.to_string()),
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+            for event in &page {
+                self.insert_event(Event {
+                    markets: None,
+                    ..event.clone()
+                });
+            }
+            match next {
+                Some(c) if !c.is_empty() && !page.is_empty() => cursor = Some(c),
+                _ => break,
+            }
+        }
+
+        let mut loaded = 0;
+        let mut cursor = None;
+        loop {
+            let (next, page) = backend
+                .get_markets(
+                    Some(PAGE_SIZE),
+                    cursor.clone(),
+                    None,
+                    Some(series_ticker.to_string()),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+            let empty = page.is_empty();
+            loaded += page.len();
+            for market in page {
+                self.insert_market(market);
+            }
+            match next {
+                Some(c) if !c.is_empty() && !empty => cursor = Some(c),
+                _ => break,
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Loads an event with its markets.
+    pub async fn load_event<B: MarketData>(
+        &self,
+        backend: &B,
+        event_ticker: &str,
+    ) -> Result<(), KalshiError> {
+        let event = crate::backend::event_with_markets(backend, event_ticker).await?;
+        self.insert_event(event);
+        Ok(())
+    }
+
+    /// Loads markets and events seen in lifecycle messages but not yet in the registry.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(usize)`: The number of markets and events loaded.
+    /// - `Err(KalshiError)`: A request failed. Items not yet loaded stay pending.
+    ///
+    pub async fn fetch_missing<B: MarketData>(&self, backend: &B) -> Result<usize, KalshiError> {
+        let (events, markets) = {
+            let state = self.lock();
+            let mut events: Vec<String> = state.missing_events.iter().cloned().collect();
+            let mut markets: Vec<String> = state.missing_markets.iter().cloned().collect();
+            events.sort();
+            markets.sort();
+            (events, markets)
+        };
+        let mut loaded = 0;
+        for event_ticker in events {
+            self.load_event(backend, &event_ticker).await?;
+            loaded += 1;
+        }
+        for ticker in markets {
+            if self.market(&ticker).is_some() {
+                continue;
+            }
+            self.insert_market(backend.get_market(&ticker).await?);
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    /// Applies lifecycle messages. Other messages are ignored.
+    pub fn handle_message(&self, msg: &WebSocketMessage) -> Option<RegistryUpdate> {
+        match msg {
+            WebSocketMessage::MarketLifecycle(m) => Some(self.apply_market_lifecycle(m)),
+            WebSocketMessage::EventLifecycle(e) => Some(self.apply_event_lifecycle(e)),
+            _ => None,
+        }
+    }
+
+    /// Applies a `market_lifecycle_v2` message.
+    pub fn apply_market_lifecycle(&self, msg: &MarketLifecycleMsg) -> RegistryUpdate {
+        let market = {
+            let mut state = self.lock();
+            match state.markets.get_mut(&msg.market_ticker) {
+                Some(market) => {
+                    apply_transition(market, msg);
+                    Some(Box::new(market.clone()))
+                }
+                None => {
+                    state.missing_markets.insert(msg.market_ticker.clone());
+                    None
+                }
+            }
+        };
+        let update = RegistryUpdate::Market {
+            ticker: msg.market_ticker.clone(),
+            event: msg.event_type,
+            market,
+        };
+        let _ = self.updates.send(update.clone());
+        update
+    }
+
+    /// Applies an `event_lifecycle` message.
+    pub fn apply_event_lifecycle(&self, msg: &EventLifecycleMsg) -> RegistryUpdate {
+        {
+            let mut state = self.lock();
+            if !state.events.contains_key(&msg.event_ticker) {
+                state.missing_events.insert(msg.event_ticker.clone());
+            }
+        }
+        let update = RegistryUpdate::EventCreated {
+            event_ticker: msg.event_ticker.clone(),
+            title: msg.title.clone(),
+        };
+        let _ = self.updates.send(update.clone());
+        update
+    }
+
+    pub fn market(&self, ticker: &str) -> Option<Market> {
+        self.lock().markets.get(ticker).cloned()
+    }
+
+    /// The event, without nested markets; see [`markets_of_event`](Self::markets_of_event).
+    pub fn event(&self, event_ticker: &str) -> Option<Event> {
+        self.lock().events.get(event_ticker).cloned()
+    }
+
+    pub fn series(&self, series_ticker: &str) -> Option<Series> {
+        self.lock().series.get(series_ticker).cloned()
+    }
+
+    /// Markets of an event, by ticker.
+    pub fn markets_of_event(&self, event_ticker: &str) -> Vec<Market> {
+        self.select(|m| m.event_ticker == event_ticker)
+    }
+
+    /// Markets with the given status, by ticker.
+    pub fn markets_with_status(&self, status: &str) -> Vec<Market> {
+        self.select(|m| m.status == status)
+    }
+
+    /// Open markets whose close time is in `[now, now + within]`, soonest first.
+    pub fn closing_within(&self, now: DateTime<Utc>, within: chrono::Duration) -> Vec<Market> {
+        let end = now + within;
+        let mut markets = self.select(|m| {
+            is_open(&m.status) && parse_time(&m.close_time).is_some_and(|t| t >= now && t <= end)
+        });
+        markets.sort_by_key(|m| parse_time(&m.close_time));
+        markets
+    }
+
+    /// Tickers of markets seen in lifecycle messages but not loaded.
+    pub fn missing_markets(&self) -> Vec<String> {
+        let mut tickers: Vec<String> = self.lock().missing_markets.iter().cloned().collect();
+        tickers.sort();
+        tickers
+    }
+
+    /// Number of cached markets.
+    pub fn len(&self) -> usize {
+        self.lock().markets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn select(&self, keep: impl Fn(&Market) -> bool) -> Vec<Market> {
+        let mut markets: Vec<Market> = self
+            .lock()
+            .markets
+            .values()
+            .filter(|m| keep(m))
+            .cloned()
+            .collect();
+        markets.sort_by(|a, b| a.ticker.cmp(&b.ticker));
+        markets
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RegistryState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Updates a cached market from a lifecycle message.
+fn apply_transition(market: &mut Market, msg: &MarketLifecycleMsg) {
+    if let Some(open) = msg.open_ts.and_then(format_ts) {
+        market.open_time = open;
+    }
+    if let Some(close) = msg.close_ts.and_then(format_ts) {
+        market.close_time = close;
+    }
+    let status = match msg.event_type {
+        MarketLifecycleEvent::Created => Some("initialized"),
+        MarketLifecycleEvent::Activated => Some("active"),
+        MarketLifecycleEvent::Deactivated if msg.is_deactivated == Some(false) => Some("active"),
+        MarketLifecycleEvent::Deactivated => Some("inactive"),
+        MarketLifecycleEvent::CloseDateUpdated => None,
+        MarketLifecycleEvent::Determined => Some("determined"),
+        MarketLifecycleEvent::Settled => Some("settled"),
+    };
+    if let Some(status) = status {
+        market.status = status.to_string();
+    }
+    if let Some(result) = msg.result {
+        market.result = result;
+    }
+}
+
+/// Statuses in which a market trades. REST listings use `open`, market objects `active`.
+fn is_open(status: &str) -> bool {
+    matches!(status, "open" | "active")
+}
+
+fn format_ts(ts: i64) -> Option<String> {
+    DateTime::from_timestamp(ts, 0).map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InMemoryKalshi, SettlementResult};
+    use serde_json::json;
+
+    fn market(ticker: &str, close_time: &str) -> Market {
+        crate::test_market(json!({
+            "ticker": ticker, "event_ticker": "KXEVT-24", "status": "active",
+            "open_time": "2024-07-01T00:00:00Z", "close_time": close_time
+        }))
+    }
+
+    fn lifecycle(value: serde_json::Value) -> WebSocketMessage {
+        WebSocketMessage::MarketLifecycle(serde_json::from_value(value).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle_messages_update_cached_markets() {
+        let registry = MarketRegistry::new();
+        registry.insert_market(market("KXEVT-24-A", "2024-07-01T12:30:00Z"));
+        registry.insert_market(market("KXEVT-24-B", "2024-07-01T18:00:00Z"));
+        let mut updates = registry.subscribe();
+        let now: DateTime<Utc> = "2024-07-01T12:00:00Z".parse().unwrap();
+
+        let closing = registry.closing_within(now, chrono::Duration::hours(1));
+        assert_eq!(closing.len(), 1);
+        assert_eq!(closing[0].ticker, "KXEVT-24-A");
+
+        // B's close moves forward into the next hour: 2024-07-01T12:45:00Z.
+        registry.handle_message(&lifecycle(json!({
+            "market_ticker": "KXEVT-24-B", "event_type": "close_date_updated",
+            "close_ts": 1719837900
+        })));
+        let closing = registry.closing_within(now, chrono::Duration::hours(1));
+        assert_eq!(closing.len(), 2);
+        assert_eq!(closing[1].close_time, "2024-07-01T12:45:00Z");
+
+        registry.handle_message(&lifecycle(json!({
+            "market_ticker": "KXEVT-24-A", "event_type": "determined", "result": "yes"
+        })));
+        let a = registry.market("KXEVT-24-A").unwrap();
+        assert_eq!(a.status, "determined");
+        assert_eq!(a.result, SettlementResult::Yes);
+        assert_eq!(
+            registry
+                .closing_within(now, chrono::Duration::hours(1))
+                .len(),
+            1
+        );
+
+        updates.try_recv().unwrap();
+        match updates.try_recv().unwrap() {
+            RegistryUpdate::Market { event, market, .. } => {
+                assert_eq!(event, MarketLifecycleEvent::Determined);
+                assert_eq!(market.unwrap().status, "determined");
+            }
+            other => panic!("unexpected update {:?}", other),
+        }
+
+        // Unknown markets are fetched on request.
+        registry.handle_message(&lifecycle(json!({
+            "market_ticker": "KXEVT-24-C", "event_type": "created",
+            "open_ts": 1719835200, "close_ts": 1719921600
+        })));
+        assert_eq!(registry.missing_markets(), vec!["KXEVT-24-C"]);
+        let backend = InMemoryKalshi::new(0);
+        backend.insert_market(market("KXEVT-24-C", "2024-07-02T12:00:00Z"));
+        assert_eq!(registry.fetch_missing(&backend).await.unwrap(), 1);
+        assert!(registry.missing_markets().is_empty());
+        assert_eq!(registry.len(), 3);
+    }
+}
@@ -0,0 +1,79 @@
+//! Request-for-quote workflows built on the communications endpoints.
+//!
+//! - [`QuoteResponder`] answers RFQs as a market maker: it prices each RFQ announced on
+//!   the `communications` channel with a [`QuotePricer`], submits a quote, withdraws it
+//!   when it expires, and confirms accepted quotes that fit within [`QuoteLimits`].
+//! - [`RfqRequester`] takes liquidity: it posts an RFQ, collects quotes for a window,
+//!   accepts the cheapest, and waits for the quoter to confirm.
+//!
+//! Every quote the responder handles is tracked as a [`TrackedQuote`] whose
+//! [`QuoteState`] follows the quote from submission to confirmation, expiry or refusal.
+//!
+//! Prices are in cents throughout; they are converted to the API's dollar strings when
+//! sent.
+
+mod requester;
+mod responder;
+
+pub use requester::*;
+pub use responder::*;
+
+use crate::{Rfq, RfqCreatedMsg};
+
+/// An RFQ to be priced, from either a `rfq_created` message or [`Kalshi::get_rfqs`].
+///
+/// [`Kalshi::get_rfqs`]: crate::Kalshi::get_rfqs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RfqRequest {
+    pub id: String,
+    pub market_ticker: String,
+    /// Requested contracts, if the RFQ is sized in contracts.
+    pub contracts: Option<i32>,
+    /// Requested cost in centi-cents, if the RFQ is sized by cost.
+    pub target_cost_centi_cents: Option<i64>,
+}
+
+impl From<&RfqCreatedMsg> for RfqRequest {
+    fn from(msg: &RfqCreatedMsg) -> Self {
+        RfqRequest {
+            id: msg.id.clone(),
+            market_ticker: msg.market_ticker.clone(),
+            contracts: msg.contracts,
+            target_cost_centi_cents: msg.target_cost_centi_cents,
+        }
+    }
+}
+
+impl From<&Rfq> for RfqRequest {
+    fn from(rfq: &Rfq) -> Self {
+        RfqRequest {
+            id: rfq.id.clone(),
+            market_ticker: rfq.market_ticker.clone().unwrap_or_default(),
+            contracts: rfq.contracts,
+            target_cost_centi_cents: rfq.target_cost_centi_cents,
+        }
+    }
+}
+
+/// Two-sided quote prices in cents: what the quoter pays per YES and per NO contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotePrice {
+    pub yes_bid: i32,
+    pub no_bid: i32,
+}
+
+impl QuotePrice {
+    /// The price the quoter pays when the requester takes `accepted_side`: a requester
+    /// taking YES leaves the quoter long NO at `no_bid`, and vice versa.
+    pub fn cost_for(&self, accepted_side: crate::Side) -> i32 {
+        match accepted_side {
+            crate::Side::Yes => self.no_bid,
+            crate::Side::No => self.yes_bid,
+        }
+    }
+
+    /// The price the requester pays per contract when taking `side`.
+    pub fn taker_price(&self, side: crate::Side) -> i32 {
+        100 - self.cost_for(side)
+    }
+}
@@ -0,0 +1,385 @@
+use super::QuotePrice;
+use crate::kalshi_error::*;
+use crate::{Fill, Kalshi, Side, WebSocketMessage};
+use chrono::Utc;
+use futures_util::{Stream, StreamExt};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Quote statuses meaning the accepted quote was executed.
+const EXECUTED_STATUSES: [&str; 2] = ["executed", "confirmed"];
+
+/// Quote statuses meaning the accepted quote will not execute.
+const DEAD_STATUSES: [&str; 4] = ["cancelled", "canceled", "expired", "rejected"];
+
+/// A quote received in response to an RFQ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceivedQuote {
+    pub quote_id: String,
+    pub price: QuotePrice,
+    /// What the requester pays per contract for the requested side, in cents.
+    pub taker_price: i32,
+}
+
+/// How an RFQ ended.
+#[derive(Debug, Clone)]
+pub enum RfqResult {
+    /// The best quote was accepted and executed.
+    Filled {
+        quote: ReceivedQuote,
+        /// Fills from the accepted quote's order when the exchange reports its
+        /// `rfq_creator_order_id`; otherwise an approximation: every fill in the market
+        /// for the requested side since the RFQ was created.
+        fills: Vec<Fill>,
+    },
+    /// No quotes arrived within the quote window.
+    NoQuotes,
+    /// Quotes arrived, but none at or below the price limit.
+    AboveLimit { best: ReceivedQuote },
+    /// The best quote was accepted, but the quoter did not confirm before the timeout.
+    NotConfirmed { quote: ReceivedQuote },
+    /// The best quote was accepted, but ended without executing.
+    QuoteEnded {
+        quote: ReceivedQuote,
+        status: String,
+    },
+}
+
+/// The outcome of [`RfqRequester::request`].
+#[derive(Debug, Clone)]
+pub struct RfqOutcome {
+    pub rfq_id: String,
+    /// Every quote received, best first.
+    pub quotes: Vec<ReceivedQuote>,
+    pub result: RfqResult,
+    /// Whether the RFQ was deleted when no quote was taken.
+    pub rfq_deleted: bool,
+}
+
+impl RfqOutcome {
+    pub fn is_filled(&self) -> bool {
+        matches!(self.result, RfqResult::Filled { .. })
+    }
+}
+
+/// Takes liquidity through RFQs. See the [module documentation](crate::rfq).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use kalshi::{RfqRequester, Side};
+///
+/// let requester = RfqRequester::new(kalshi.clone())
+///     .with_quote_window(std::time::Duration::from_secs(10))
+///     .with_max_price(60);
+///
+/// // `ws` is subscribed to the communications channel.
+/// let outcome = requester
+///     .request("KXEVT-24-A", Side::Yes, 500, &mut ws.messages())
+///     .await?;
+/// println!("{:?}", outcome.result);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RfqRequester {
+    kalshi: Kalshi,
+    quote_window: Duration,
+    confirm_timeout: Duration,
+    poll_interval: Duration,
+    max_price: Option<i32>,
+    rest_remainder: bool,
+}
+
+impl RfqRequester {
+    /// Creates a requester with a 5-second quote window, a 30-second confirmation timeout
+    /// and no price limit.
+    pub fn new(kalshi: Kalshi) -> Self {
+        RfqRequester {
+            kalshi,
+            quote_window: Duration::from_secs(5),
+            confirm_timeout: Duration::from_secs(30),
+            poll_interval: Duration::from_secs(1),
+            max_price: None,
+            rest_remainder: false,
+        }
+    }
+
+    /// How long to collect quotes before choosing one.
+    pub fn with_quote_window(mut self, window: Duration) -> Self {
+        self.quote_window = window;
+        self
+    }
+
+    /// How long to wait for the quoter to confirm an accepted quote.
+    pub fn with_confirm_timeout(mut self, timeout: Duration) -> Self {
+        self.confirm_timeout = timeout;
+        self
+    }
+
+    /// How often to poll the accepted quote's status while waiting for confirmation.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Highest per-contract price, in cents, the requester will accept.
+    pub fn with_max_price(mut self, cents: i32) -> Self {
+        self.max_price = Some(cents);
+        self
+    }
+
+    /// Sets `rest_remainder` on created RFQs.
+    pub fn with_rest_remainder(mut self, rest_remainder: bool) -> Self {
+        self.rest_remainder = rest_remainder;
+        self
+    }
+
+    /// Requests quotes for `contracts` contracts of `side` in a market and takes the best.
+    ///
+    /// `messages` must carry the `communications` channel; messages for other RFQs are
+    /// skipped. Quotes are ranked by [`QuotePrice::taker_price`], earliest first on ties.
+    /// If no quote is taken, or a request fails after the RFQ was created, the RFQ is
+    /// deleted.
+    ///
+    /// # Arguments
+    ///
+    /// * `market_ticker` - The market to request quotes in.
+    /// * `side` - The side the requester wants to buy.
+    /// * `contracts` - The number of contracts.
+    /// * `messages` - WebSocket messages, e.g. from [`KalshiWebSocket::messages`].
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(RfqOutcome)`: The quotes received and how the RFQ ended.
+    /// - `Err(KalshiError)`: Creating the RFQ, accepting, or checking the quote failed.
+    ///
+    /// [`KalshiWebSocket::messages`]: crate::KalshiWebSocket::messages
+    pub async fn request<S>(
+        &self,
+        market_ticker: &str,
+        side: Side,
+        contracts: i32,
+        messages: &mut S,
+    ) -> Result<RfqOutcome, KalshiError>
+    where
+        S: Stream<Item = WebSocketMessage> + Unpin,
+    {
+        let started = Utc::now();
+        let rfq_id = self
+            .kalshi
+            .create_rfq(
+                market_ticker,
+                self.rest_remainder,
+                Some(contracts),
+                None,
+                None,
+                None,
+            )
+            .await?
+            .id;
+
+        match self
+            .take_best(&rfq_id, market_ticker, side, messages, started.timestamp())
+            .await
+        {
+            Ok((quotes, result @ RfqResult::Filled { .. })) => Ok(RfqOutcome {
+                rfq_id,
+                quotes,
+                result,
+                rfq_deleted: false,
+            }),
+            Ok((quotes, result)) => Ok(self.give_up(rfq_id, quotes, result).await),
+            Err(e) => {
+                let _ = self.kalshi.delete_rfq(&rfq_id).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Collects quotes for a created RFQ, accepts the best one within the limit and waits
+    /// for it to execute or end. The caller deletes the RFQ unless the result is `Filled`.
+    async fn take_best<S>(
+        &self,
+        rfq_id: &str,
+        market_ticker: &str,
+        side: Side,
+        messages: &mut S,
+        started_ts: i64,
+    ) -> Result<(Vec<ReceivedQuote>, RfqResult), KalshiError>
+    where
+        S: Stream<Item = WebSocketMessage> + Unpin,
+    {
+        let received = collect_quotes(rfq_id, messages, Instant::now() + self.quote_window).await;
+        let quotes = rank_quotes(received, side);
+
+        let Some(best) = quotes.first().cloned() else {
+            return Ok((quotes, RfqResult::NoQuotes));
+        };
+        if self.max_price.is_some_and(|max| best.taker_price > max) {
+            return Ok((quotes, RfqResult::AboveLimit { best }));
+        }
+
+        self.kalshi.accept_quote(&best.quote_id, side).await?;
+
+        let deadline = Instant::now() + self.confirm_timeout;
+        loop {
+            let quote = self.kalshi.get_quote(&best.quote_id).await?;
+            let status = quote.status.clone().unwrap_or_default().to_lowercase();
+            if EXECUTED_STATUSES.contains(&status.as_str()) {
+                let order_id = quote
+                    .extra
+                    .get("rfq_creator_order_id")
+                    .and_then(|v| v.as_str());
+                let fills = self
+                    .fills_since(market_ticker, side, order_id, started_ts)
+                    .await?;
+                return Ok((quotes, RfqResult::Filled { quote: best, fills }));
+            }
+            if DEAD_STATUSES.contains(&status.as_str()) {
+                let result = RfqResult::QuoteEnded {
+                    quote: best,
+                    status,
+                };
+                return Ok((quotes, result));
+            }
+            if Instant::now() + self.poll_interval > deadline {
+                return Ok((quotes, RfqResult::NotConfirmed { quote: best }));
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Deletes the RFQ and builds the outcome. A failed delete is reported through
+    /// [`RfqOutcome::rfq_deleted`] rather than as an error, so the result is not lost.
+    async fn give_up(
+        &self,
+        rfq_id: String,
+        quotes: Vec<ReceivedQuote>,
+        result: RfqResult,
+    ) -> RfqOutcome {
+        let rfq_deleted = self.kalshi.delete_rfq(&rfq_id).await.is_ok();
+        RfqOutcome {
+            rfq_id,
+            quotes,
+            result,
+            rfq_deleted,
+        }
+    }
+
+    /// Fills in `ticker` for `side` since `min_ts`, limited to `order_id` when known.
+    async fn fills_since(
+        &self,
+        ticker: &str,
+        side: Side,
+        order_id: Option<&str>,
+        min_ts: i64,
+    ) -> Result<Vec<Fill>, KalshiError> {
+        let mut fills = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (next, page) = self
+                .kalshi
+                .get_fills(
+                    Some(ticker.to_string()),
+                    order_id.map(str::to_string),
+                    Some(min_ts),
+                    None,
+                    None,
+                    cursor,
+                )
+                .await?;
+            let empty = page.is_empty();
+            fills.extend(page.into_iter().filter(|f| f.side == side));
+            match next {
+                Some(c) if !c.is_empty() && !empty => cursor = Some(c),
+                _ => break,
+            }
+        }
+        Ok(fills)
+    }
+}
+
+/// Collects quotes for `rfq_id` until `deadline` or the end of the stream, in arrival
+/// order.
+async fn collect_quotes<S>(
+    rfq_id: &str,
+    messages: &mut S,
+    deadline: Instant,
+) -> Vec<(String, QuotePrice)>
+where
+    S: Stream<Item = WebSocketMessage> + Unpin,
+{
+    let mut quotes = Vec::new();
+    while let Ok(Some(msg)) = tokio::time::timeout_at(deadline, messages.next()).await {
+        match msg {
+            WebSocketMessage::QuoteCreated(quote) if quote.rfq_id == rfq_id => {
+                let price = QuotePrice {
+                    yes_bid: quote.yes_bid,
+                    no_bid: quote.no_bid,
+                };
+                quotes.push((quote.quote_id, price));
+            }
+            _ => {}
+        }
+    }
+    quotes
+}
+
+/// Orders quotes cheapest first for a requester taking `side`.
+fn rank_quotes(quotes: Vec<(String, QuotePrice)>, side: Side) -> Vec<ReceivedQuote> {
+    let mut ranked: Vec<ReceivedQuote> = quotes
+        .into_iter()
+        .map(|(quote_id, price)| ReceivedQuote {
+            quote_id,
+            taker_price: price.taker_price(side),
+            price,
+        })
+        .collect();
+    ranked.sort_by_key(|q| q.taker_price);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{QuoteCreatedMsg, RfqCreatedMsg};
+    use futures_util::stream;
+
+    fn quote(quote_id: &str, rfq_id: &str, yes_bid: i32, no_bid: i32) -> WebSocketMessage {
+        WebSocketMessage::QuoteCreated(QuoteCreatedMsg {
+            quote_id: quote_id.to_string(),
+            rfq_id: rfq_id.to_string(),
+            yes_bid,
+            no_bid,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_collects_and_ranks_quotes_for_rfq() {
+        let mut messages = stream::iter(vec![
+            quote("q1", "rfq-1", 40, 55),
+            WebSocketMessage::RfqCreated(RfqCreatedMsg {
+                id: "rfq-2".to_string(),
+                market_ticker: "KXEVT-24-A".to_string(),
+                contracts: Some(10),
+                target_cost_centi_cents: None,
+            }),
+            quote("q2", "rfq-2", 10, 90),
+            quote("q3", "rfq-1", 42, 58),
+            quote("q4", "rfq-1", 44, 58),
+        ]);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let received = collect_quotes("rfq-1", &mut messages, deadline).await;
+        assert_eq!(received.len(), 3);
+
+        // Taking YES costs 100 - no_bid; ties keep arrival order.
+        let yes = rank_quotes(received.clone(), Side::Yes);
+        let ids: Vec<&str> = yes.iter().map(|q| q.quote_id.as_str()).collect();
+        assert_eq!(ids, ["q3", "q4", "q1"]);
+        assert_eq!(yes[0].taker_price, 42);
+
+        // Taking NO costs 100 - yes_bid.
+        let no = rank_quotes(received, Side::No);
+        assert_eq!(no[0].quote_id, "q4");
+        assert_eq!(no[0].taker_price, 56);
+    }
+}
@@ -0,0 +1,688 @@
+use super::{QuotePrice, RfqRequest};
+use crate::kalshi_error::*;
+use crate::paper::cents_to_dollars;
+use crate::{Kalshi, Side, WebSocketMessage};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+/// Capacity of the broadcast channel carrying [`TrackedQuote`] updates.
+const UPDATE_CHANNEL_CAPACITY: usize = 256;
+
+/// Prices RFQs for a [`QuoteResponder`]. Return `None` to pass on an RFQ.
+///
+/// Closures taking `&RfqRequest` and returning `Option<QuotePrice>` are pricers.
+pub trait QuotePricer: Send + Sync {
+    fn price(&self, rfq: &RfqRequest) -> Option<QuotePrice>;
+}
+
+impl<F: Fn(&RfqRequest) -> Option<QuotePrice> + Send + Sync> QuotePricer for F {
+    fn price(&self, rfq: &RfqRequest) -> Option<QuotePrice> {
+        self(rfq)
+    }
+}
+
+/// Limits a [`QuoteResponder`] enforces. `None` disables a check.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuoteLimits {
+    /// Largest RFQ, in contracts, to quote.
+    pub max_contracts: Option<i32>,
+    /// Most quotes outstanding at once.
+    pub max_open_quotes: Option<usize>,
+    /// Most contracts confirmed per market.
+    pub max_market_contracts: Option<i64>,
+    /// Most cost, in cents, confirmed across all markets.
+    pub max_total_cost: Option<i64>,
+}
+
+/// Where a quote is in its lifecycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuoteState {
+    /// Submitted and waiting for the requester.
+    Open,
+    /// Accepted by the requester; confirmation pending.
+    Accepted(Side),
+    /// Confirmed; the trade executed.
+    Confirmed { side: Side, fill_id: Option<String> },
+    /// Accepted but withdrawn instead of confirmed because it would breach a limit.
+    Declined(String),
+    /// Withdrawn after its time to live.
+    Expired,
+    /// Withdrawn by [`QuoteResponder::cancel_all`].
+    Cancelled,
+    /// An API call for this quote failed.
+    Failed(String),
+}
+
+impl QuoteState {
+    /// True while the quote can still be accepted or confirmed.
+    pub fn is_open(&self) -> bool {
+        matches!(self, QuoteState::Open | QuoteState::Accepted(_))
+    }
+}
+
+/// A quote submitted by a [`QuoteResponder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedQuote {
+    pub quote_id: String,
+    pub rfq_id: String,
+    pub market_ticker: String,
+    pub contracts: Option<i32>,
+    /// Requested cost in centi-cents, if the RFQ is sized by cost.
+    pub target_cost_centi_cents: Option<i64>,
+    pub price: QuotePrice,
+    pub state: QuoteState,
+    pub submitted_at: DateTime<Utc>,
+    /// When the responder withdraws the quote if still open.
+    pub expires_at: DateTime<Utc>,
+}
+
+impl TrackedQuote {
+    /// Contracts traded if the requester takes `side`: the RFQ's size or, for an RFQ sized
+    /// by cost, what its target cost buys at the quoted price. `None` if the RFQ has
+    /// neither.
+    pub fn contracts_for(&self, side: Side) -> Option<i64> {
+        if let Some(contracts) = self.contracts {
+            return Some(contracts as i64);
+        }
+        let price = self.price.taker_price(side) as i64;
+        self.target_cost_centi_cents
+            .filter(|_| price > 0)
+            .map(|cost| cost / (price * 100))
+    }
+}
+
+#[derive(Debug, Default)]
+struct ResponderState {
+    quotes: HashMap<String, TrackedQuote>,
+    /// Confirmed contracts per market.
+    market_contracts: HashMap<String, i64>,
+    /// Confirmed cost in cents.
+    total_cost: i64,
+}
+
+/// Answers RFQs automatically. See the [module documentation](crate::rfq).
+///
+/// Feed `communications` channel messages to [`handle_message`](Self::handle_message)
+/// and call [`expire_quotes`](Self::expire_quotes) periodically.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use kalshi::{QuoteLimits, QuotePrice, QuoteResponder, RfqRequest};
+///
+/// let responder = QuoteResponder::new(kalshi.clone(), |rfq: &RfqRequest| {
+///     Some(QuotePrice { yes_bid: 45, no_bid: 50 })
+/// })
+/// .with_limits(QuoteLimits { max_contracts: Some(500), ..Default::default() })
+/// .with_quote_ttl(chrono::Duration::seconds(30));
+///
+/// // In the WebSocket loop:
+/// responder.handle_message(&msg).await?;
+/// responder.expire_quotes(chrono::Utc::now()).await?;
+/// ```
+pub struct QuoteResponder {
+    kalshi: Kalshi,
+    pricer: Box<dyn QuotePricer>,
+    limits: QuoteLimits,
+    ttl: chrono::Duration,
+    rest_remainder: bool,
+    auto_confirm: bool,
+    state: Mutex<ResponderState>,
+    updates: broadcast::Sender<TrackedQuote>,
+}
+
+impl fmt::Debug for QuoteResponder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuoteResponder")
+            .field("limits", &self.limits)
+            .field("ttl", &self.ttl)
+            .field("rest_remainder", &self.rest_remainder)
+            .field("auto_confirm", &self.auto_confirm)
+            .finish_non_exhaustive()
+    }
+}
+
+impl QuoteResponder {
+    /// Creates a responder with no limits, a 30-second quote lifetime and automatic
+    /// confirmation.
+    pub fn new(kalshi: Kalshi, pricer: impl QuotePricer + 'static) -> Self {
+        let (updates, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
+        QuoteResponder {
+            kalshi,
+            pricer: Box::new(pricer),
+            limits: QuoteLimits::default(),
+            ttl: chrono::Duration::seconds(30),
+            rest_remainder: false,
+            auto_confirm: true,
+            state: Mutex::new(ResponderState::default()),
+            updates,
+        }
+    }
+
+    pub fn with_limits(mut self, limits: QuoteLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// How long a quote stays up before [`expire_quotes`](Self::expire_quotes) withdraws it.
+    pub fn with_quote_ttl(mut self, ttl: chrono::Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets `rest_remainder` on submitted quotes.
+    pub fn with_rest_remainder(mut self, rest_remainder: bool) -> Self {
+        self.rest_remainder = rest_remainder;
+        self
+    }
+
+    /// With `false`, accepted quotes stay [`QuoteState::Accepted`] until
+    /// [`confirm`](Self::confirm) is called.
+    pub fn with_auto_confirm(mut self, auto_confirm: bool) -> Self {
+        self.auto_confirm = auto_confirm;
+        self
+    }
+
+    /// Receives every quote state change.
+    pub fn subscribe(&self) -> broadcast::Receiver<TrackedQuote> {
+        self.updates.subscribe()
+    }
+
+    pub fn quote(&self, quote_id: &str) -> Option<TrackedQuote> {
+        self.lock().quotes.get(quote_id).cloned()
+    }
+
+    /// Every tracked quote, oldest first.
+    pub fn quotes(&self) -> Vec<TrackedQuote> {
+        let mut quotes: Vec<TrackedQuote> = self.lock().quotes.values().cloned().collect();
+        quotes.sort_by(|a, b| {
+            a.submitted_at
+                .cmp(&b.submitted_at)
+                .then_with(|| a.quote_id.cmp(&b.quote_id))
+        });
+        quotes
+    }
+
+    /// Quotes that can still be accepted or confirmed.
+    pub fn open_quotes(&self) -> Vec<TrackedQuote> {
+        self.quotes()
+            .into_iter()
+            .filter(|q| q.state.is_open())
+            .collect()
+    }
+
+    /// Contracts confirmed in `ticker`.
+    pub fn market_contracts(&self, ticker: &str) -> i64 {
+        self.lock()
+            .market_contracts
+            .get(ticker)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Cost in cents confirmed across all markets.
+    pub fn total_cost(&self) -> i64 {
+        self.lock().total_cost
+    }
+
+    /// Quotes new RFQs and confirms accepted quotes. Other messages are ignored.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(TrackedQuote))`: The quote submitted or updated.
+    /// - `Ok(None)`: Nothing to do, e.g. the RFQ was passed on, the quote is not ours, or
+    ///   the acceptance names an unknown side.
+    /// - `Err(KalshiError::ExchangeUnavailable)`: An RFQ arrived while an attached
+    ///   [`ExchangeGate`](crate::ExchangeGate) is paused; it was not quoted.
+    /// - `Err(KalshiError)`: An API call failed.
+    ///
+    pub async fn handle_message(
+        &self,
+        msg: &WebSocketMessage,
+    ) -> Result<Option<TrackedQuote>, KalshiError> {
+        match msg {
+            WebSocketMessage::RfqCreated(rfq) => self.respond(&RfqRequest::from(rfq)).await,
+            WebSocketMessage::QuoteAccepted(accepted) => {
+                let side = match accepted.accepted_side.as_str() {
+                    "yes" => Side::Yes,
+                    "no" => Side::No,
+                    _ => return Ok(None),
+                };
+                self.on_accepted(&accepted.quote_id, side).await
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Prices an RFQ and submits a quote.
+    ///
+    /// Returns `Ok(None)` without sending anything when the pricer passes, the RFQ is
+    /// larger than [`QuoteLimits::max_contracts`], too many quotes are open, or the RFQ
+    /// has no size and confirmation limits are set. Fails with
+    /// [`ExchangeUnavailable::Paused`](crate::ExchangeUnavailable::Paused) while an
+    /// attached [`ExchangeGate`](crate::ExchangeGate) is paused.
+    pub async fn respond(&self, rfq: &RfqRequest) -> Result<Option<TrackedQuote>, KalshiError> {
+        self.kalshi.gate_check()?;
+        if self.quote_refusal(rfq).is_some() {
+            return Ok(None);
+        }
+        let Some(price) = self.pricer.price(rfq) else {
+            return Ok(None);
+        };
+        if !(1..=99).contains(&price.yes_bid)
+            || !(1..=99).contains(&price.no_bid)
+            || price.yes_bid + price.no_bid > 100
+        {
+            return Err(KalshiError::UserInputError(format!(
+                "invalid quote {}¢/{}¢ for RFQ {}",
+                price.yes_bid, price.no_bid, rfq.id
+            )));
+        }
+
+        let response = self
+            .kalshi
+            .create_quote(
+                &rfq.id,
+                &cents_to_dollars(price.yes_bid as i64),
+                &cents_to_dollars(price.no_bid as i64),
+                self.rest_remainder,
+            )
+            .await?;
+        let now = Utc::now();
+        let quote = TrackedQuote {
+            quote_id: response.id,
+            rfq_id: rfq.id.clone(),
+            market_ticker: rfq.market_ticker.clone(),
+            contracts: rfq.contracts,
+            target_cost_centi_cents: rfq.target_cost_centi_cents,
+            price,
+            state: QuoteState::Open,
+            submitted_at: now,
+            expires_at: now + self.ttl,
+        };
+        self.lock()
+            .quotes
+            .insert(quote.quote_id.clone(), quote.clone());
+        let _ = self.updates.send(quote.clone());
+        Ok(Some(quote))
+    }
+
+    /// Records an acceptance of one of our quotes and, with auto-confirm, confirms it if
+    /// it fits within the limits. Unknown quote IDs are ignored.
+    pub async fn on_accepted(
+        &self,
+        quote_id: &str,
+        side: Side,
+    ) -> Result<Option<TrackedQuote>, KalshiError> {
+        if self
+            .update(quote_id, |q| {
+                q.state.is_open().then_some(QuoteState::Accepted(side))
+            })
+            .is_none()
+        {
+            return Ok(None);
+        }
+        if !self.auto_confirm {
+            return Ok(self.quote(quote_id));
+        }
+        self.confirm(quote_id).await
+    }
+
+    /// Confirms an accepted quote, or declines it with `delete_quote` if it would breach a
+    /// limit.
+    pub async fn confirm(&self, quote_id: &str) -> Result<Option<TrackedQuote>, KalshiError> {
+        let Some(quote) = self.quote(quote_id) else {
+            return Ok(None);
+        };
+        let QuoteState::Accepted(side) = quote.state else {
+            return Ok(Some(quote));
+        };
+        if let Some(reason) = self.confirm_refusal(&quote, side) {
+            if let Err(e) = self.kalshi.delete_quote(quote_id).await {
+                self.update(quote_id, |_| Some(QuoteState::Failed(e.to_string())));
+                return Err(e);
+            }
+            return Ok(self.update(quote_id, |_| Some(QuoteState::Declined(reason))));
+        }
+        match self.kalshi.confirm_quote(quote_id).await {
+            Ok(confirmed) => {
+                self.record_confirmed(&quote, side);
+                Ok(self.update(quote_id, |_| {
+                    Some(QuoteState::Confirmed {
+                        side,
+                        fill_id: confirmed.fill_id.clone(),
+                    })
+                }))
+            }
+            Err(e) => {
+                self.update(quote_id, |_| Some(QuoteState::Failed(e.to_string())));
+                Err(e)
+            }
+        }
+    }
+
+    /// Withdraws open quotes whose time to live has passed.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<TrackedQuote>)`: The quotes expired.
+    /// - `Err(KalshiError)`: The first failed delete; remaining quotes are still processed.
+    ///
+    pub async fn expire_quotes(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<TrackedQuote>, KalshiError> {
+        let due: Vec<String> = self
+            .lock()
+            .quotes
+            .values()
+            .filter(|q| q.state == QuoteState::Open && q.expires_at <= now)
+            .map(|q| q.quote_id.clone())
+            .collect();
+        self.withdraw(due, QuoteState::Expired).await
+    }
+
+    /// Withdraws every open quote, e.g. on shutdown.
+    pub async fn cancel_all(&self) -> Result<Vec<TrackedQuote>, KalshiError> {
+        let open: Vec<String> = self
+            .lock()
+            .quotes
+            .values()
+            .filter(|q| q.state == QuoteState::Open)
+            .map(|q| q.quote_id.clone())
+            .collect();
+        self.withdraw(open, QuoteState::Cancelled).await
+    }
+
+    async fn withdraw(
+        &self,
+        quote_ids: Vec<String>,
+        state: QuoteState,
+    ) -> Result<Vec<TrackedQuote>, KalshiError> {
+        let mut withdrawn = Vec::new();
+        let mut error = None;
+        for quote_id in quote_ids {
+            match self.kalshi.delete_quote(&quote_id).await {
+                Ok(()) => withdrawn.extend(self.update(&quote_id, |q| {
+                    (q.state == QuoteState::Open).then(|| state.clone())
+                })),
+                Err(e) => {
+                    self.update(&quote_id, |_| Some(QuoteState::Failed(e.to_string())));
+                    error = error.or(Some(e));
+                }
+            }
+        }
+        match error {
+            Some(e) => Err(e),
+            None => Ok(withdrawn),
+        }
+    }
+
+    /// Why an RFQ should not be quoted, if it should not.
+    fn quote_refusal(&self, rfq: &RfqRequest) -> Option<String> {
+        if let Some(max) = self.limits.max_contracts {
+            match rfq.contracts {
+                Some(contracts) if contracts > max => {
+                    return Some(format!("{} contracts exceeds {}", contracts, max))
+                }
+                None => return Some("RFQ is not sized in contracts".to_string()),
+                _ => {}
+            }
+        }
+        if rfq.contracts.is_none()
+            && rfq.target_cost_centi_cents.is_none()
+            && self.has_confirm_limits()
+        {
+            return Some("RFQ has no size".to_string());
+        }
+        if let Some(max) = self.limits.max_open_quotes {
+            let open = self
+                .lock()
+                .quotes
+                .values()
+                .filter(|q| q.state.is_open())
+                .count();
+            if open >= max {
+                return Some(format!("{} quotes already open", open));
+            }
+        }
+        None
+    }
+
+    /// Why an accepted quote should not be confirmed, if it should not.
+    fn confirm_refusal(&self, quote: &TrackedQuote, side: Side) -> Option<String> {
+        let Some(contracts) = quote.contracts_for(side) else {
+            return self
+                .has_confirm_limits()
+                .then(|| "RFQ has no size".to_string());
+        };
+        let cost = contracts * quote.price.cost_for(side) as i64;
+        let state = self.lock();
+        if let Some(max) = self.limits.max_market_contracts {
+            let held = state
+                .market_contracts
+                .get(&quote.market_ticker)
+                .copied()
+                .unwrap_or(0);
+            if held + contracts > max {
+                return Some(format!(
+                    "{} contracts in {} would exceed {}",
+                    held + contracts,
+                    quote.market_ticker,
+                    max
+                ));
+            }
+        }
+        self.limits
+            .max_total_cost
+            .filter(|max| state.total_cost + cost > *max)
+            .map(|max| {
+                format!(
+                    "total cost {}¢ would exceed {}¢",
+                    state.total_cost + cost,
+                    max
+                )
+            })
+    }
+
+    /// Whether confirmations are checked against per-market or total limits.
+    fn has_confirm_limits(&self) -> bool {
+        self.limits.max_market_contracts.is_some() || self.limits.max_total_cost.is_some()
+    }
+
+    fn record_confirmed(&self, quote: &TrackedQuote, side: Side) {
+        let contracts = quote.contracts_for(side).unwrap_or(0);
+        let mut state = self.lock();
+        *state
+            .market_contracts
+            .entry(quote.market_ticker.clone())
+            .or_insert(0) += contracts;
+        state.total_cost += contracts * quote.price.cost_for(side) as i64;
+    }
+
+    /// Moves a quote to the state returned by `next`, if any, and publishes it.
+    fn update(
+        &self,
+        quote_id: &str,
+        next: impl FnOnce(&TrackedQuote) -> Option<QuoteState>,
+    ) -> Option<TrackedQuote> {
+        let quote = {
+            let mut state = self.lock();
+            let quote = state.quotes.get_mut(quote_id)?;
+            quote.state = next(quote)?;
+            quote.clone()
+        };
+        let _ = self.updates.send(quote.clone());
+        Some(quote)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ResponderState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn parse(text: &str) -> WebSocketMessage {
+        WebSocketMessage::parse(text).unwrap()
+    }
+
+    fn rfq(id: &str, size: &str) -> WebSocketMessage {
+        parse(&format!(
+            r#"{{"type":"rfq_created","sid":1,"msg":{{"id":"{}","market_ticker":"KXEVT-24-A",{}}}}}"#,
+            id, size
+        ))
+    }
+
+    fn accepted(quote_id: &str, side: &str) -> WebSocketMessage {
+        parse(&format!(
+            r#"{{"type":"quote_accepted","sid":1,"msg":{{"quote_id":"{}","accepted_side":"{}"}}}}"#,
+            quote_id, side
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_messages_drive_quotes_through_limits() {
+        let quotes = AtomicUsize::new(0);
+        let (url, seen) = crate::test_server(move |line| {
+            if line.starts_with("POST") {
+                let n = quotes.fetch_add(1, Ordering::SeqCst) + 1;
+                ("201 Created", format!(r#"{{"id":"q{}"}}"#, n))
+            } else if line.ends_with("/confirm") {
+                let id = line.split('/').nth_back(1).unwrap_or_default();
+                (
+                    "200 OK",
+                    format!(r#"{{"quote_id":"{}","status":"confirmed"}}"#, id),
+                )
+            } else {
+                ("200 OK", "{}".to_string())
+            }
+        })
+        .await;
+        let mut kalshi = Kalshi::test_client();
+        kalshi.base_url = url;
+        let responder = QuoteResponder::new(kalshi, |_: &RfqRequest| {
+            Some(QuotePrice {
+                yes_bid: 45,
+                no_bid: 50,
+            })
+        })
+        .with_limits(QuoteLimits {
+            max_market_contracts: Some(400),
+            max_total_cost: Some(10_000),
+            ..Default::default()
+        });
+        let mut updates = responder.subscribe();
+
+        // Requester takes YES on 100: we buy 100 NO at 50¢.
+        let q1 = responder
+            .handle_message(&rfq("rfq-1", r#""contracts":100"#))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(q1.state, QuoteState::Open);
+        let q1 = responder
+            .handle_message(&accepted(&q1.quote_id, "yes"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            q1.state,
+            QuoteState::Confirmed {
+                side: Side::Yes,
+                ..
+            }
+        ));
+        assert_eq!(responder.total_cost(), 5_000);
+        assert_eq!(responder.market_contracts("KXEVT-24-A"), 100);
+        let states: Vec<QuoteState> =
+            std::iter::from_fn(|| updates.try_recv().ok().map(|q| q.state)).collect();
+        assert_eq!(states.len(), 3);
+        assert_eq!(states[1], QuoteState::Accepted(Side::Yes));
+
+        // An RFQ for $120 costs 240 NO at 50¢ when YES is taken: over the total cost limit.
+        let q2 = responder
+            .handle_message(&rfq("rfq-2", r#""target_cost_centi_cents":1200000"#))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(q2.contracts_for(Side::Yes), Some(240));
+        let q2 = responder
+            .handle_message(&accepted(&q2.quote_id, "yes"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            matches!(&q2.state, QuoteState::Declined(reason) if reason.contains("total cost")),
+            "{:?}",
+            q2.state
+        );
+        assert_eq!(responder.total_cost(), 5_000);
+
+        // Unknown sides and unknown quotes are ignored.
+        assert_eq!(
+            responder
+                .handle_message(&accepted("q1", "maybe"))
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            responder
+                .handle_message(&accepted("nope", "no"))
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                "POST /communications/quotes",
+                "PUT /communications/quotes/q1/confirm",
+                "POST /communications/quotes",
+                "DELETE /communications/quotes/q2",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refused_rfqs_send_nothing_and_pauses_fail() {
+        let gate = std::sync::Arc::new(crate::ExchangeGate::new(Kalshi::test_client()));
+        let kalshi = Kalshi::test_client().with_exchange_gate(gate.clone());
+        let responder = QuoteResponder::new(kalshi, |_: &RfqRequest| {
+            Some(QuotePrice {
+                yes_bid: 45,
+                no_bid: 50,
+            })
+        })
+        .with_limits(QuoteLimits {
+            max_contracts: Some(100),
+            ..Default::default()
+        });
+
+        // Oversized and cost-sized RFQs are passed on without a request.
+        for size in [r#""contracts":200"#, r#""target_cost_centi_cents":1000"#] {
+            let msg = rfq("rfq-1", size);
+            assert_eq!(responder.handle_message(&msg).await.unwrap(), None);
+        }
+
+        gate.record_status(crate::ExchangeStatus {
+            trading_active: false,
+            exchange_active: true,
+        });
+        let err = responder
+            .handle_message(&rfq("rfq-1", r#""contracts":10"#))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            KalshiError::ExchangeUnavailable(crate::ExchangeUnavailable::Paused(_))
+        ));
+    }
+}
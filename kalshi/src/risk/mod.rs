@@ -0,0 +1,661 @@
+//! Client-side pre-trade risk checks.
+//!
+//! A [`RiskGuard`] attached with [`Kalshi::with_risk_guard`] is consulted by `create_order`,
+//! `batch_create_order` and `amend_order` before anything is sent. A breach returns
+//! [`KalshiError::RiskViolation`] and no request is made.
+//!
+//! The guard enforces, when configured in [`RiskLimits`]:
+//!
+//! - maximum contracts per order,
+//! - maximum notional per order (fat-finger) and per market, event and series,
+//! - maximum number of open orders,
+//! - a price collar around the mid of the last known book,
+//! - a daily loss limit.
+//!
+//! Reduce-only orders are exempt from the exposure, open-order, price collar and loss
+//! checks so that positions can always be closed.
+//!
+//! The guard learns about resting orders from the responses of guarded calls and from
+//! WebSocket messages passed to [`RiskGuard::handle_message`] (fills, position updates and
+//! tickers). Books and P&L can also be fed in directly.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::{Kalshi, RiskGuard, RiskLimits};
+//! use std::sync::Arc;
+//!
+//! let guard = Arc::new(RiskGuard::new(RiskLimits {
+//!     max_order_size: Some(500),
+//!     max_market_notional: Some(50_000),
+//!     max_open_orders: Some(100),
+//!     price_collar: Some(10),
+//!     daily_loss_limit: Some(20_000),
+//!     ..Default::default()
+//! }));
+//! let kalshi = kalshi.with_risk_guard(guard.clone());
+//! ```
+
+use crate::kalshi_error::*;
+use crate::positions::event_ticker_of;
+use crate::{
+    Action, FillMsg, Kalshi, Order, OrderCreationField, OrderStatus, Orderbook, Side,
+    WebSocketMessage,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Configurable limits enforced by a [`RiskGuard`].
+///
+/// Every limit is optional; `None` disables the check. Money amounts are in cents.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RiskLimits {
+    /// Maximum contracts in a single order.
+    pub max_order_size: Option<i32>,
+    /// Maximum notional of a single order (fat-finger check).
+    pub max_order_notional: Option<i64>,
+    /// Maximum notional (positions plus resting orders) in one market.
+    pub max_market_notional: Option<i64>,
+    /// Maximum notional across all markets of one event.
+    pub max_event_notional: Option<i64>,
+    /// Maximum notional across all markets of one series.
+    pub max_series_notional: Option<i64>,
+    /// Maximum number of resting orders.
+    pub max_open_orders: Option<usize>,
+    /// Maximum distance in cents between a limit price and the book mid.
+    pub price_collar: Option<i32>,
+    /// Maximum loss for the current UTC day; new risk is refused once reached.
+    pub daily_loss_limit: Option<i64>,
+}
+
+/// A pre-trade check that failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RiskViolation {
+    /// The order is larger than `max_order_size`.
+    OrderSize { count: i32, limit: i32 },
+    /// The order's notional exceeds `max_order_notional`, or its price is outside 1-99¢.
+    FatFinger { detail: String },
+    /// The order would take a market, event or series over its notional limit.
+    Notional {
+        scope: RiskScope,
+        key: String,
+        projected: i64,
+        limit: i64,
+    },
+    /// The open-order limit has been reached.
+    OpenOrders { open: usize, limit: usize },
+    /// The limit price is too far from the book mid.
+    PriceCollar {
+        price: i32,
+        reference: f64,
+        collar: i32,
+    },
+    /// The daily loss limit has been reached.
+    DailyLoss { pnl: i64, limit: i64 },
+}
+
+/// The aggregation level of a notional limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskScope {
+    Market,
+    Event,
+    Series,
+}
+
+impl fmt::Display for RiskViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskViolation::OrderSize { count, limit } => {
+                write!(f, "order size {} exceeds limit {}", count, limit)
+            }
+            RiskViolation::FatFinger { detail } => write!(f, "fat-finger check: {}", detail),
+            RiskViolation::Notional {
+                scope,
+                key,
+                projected,
+                limit,
+            } => write!(
+                f,
+                "{:?} {} notional would be {}¢, limit {}¢",
+                scope, key, projected, limit
+            ),
+            RiskViolation::OpenOrders { open, limit } => {
+                write!(f, "{} open orders, limit {}", open, limit)
+            }
+            RiskViolation::PriceCollar {
+                price,
+                reference,
+                collar,
+            } => write!(
+                f,
+                "price {}¢ is more than {}¢ from book mid {:.1}¢",
+                price, collar, reference
+            ),
+            RiskViolation::DailyLoss { pnl, limit } => {
+                write!(f, "daily P&L {}¢ breaches loss limit {}¢", pnl, limit)
+            }
+        }
+    }
+}
+
+/// The facts about an order that the guard checks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderIntent {
+    pub ticker: String,
+    pub side: Side,
+    pub action: Action,
+    pub count: i32,
+    /// Limit price in cents on `side`; `None` for market orders.
+    pub price: Option<i32>,
+    /// Cap on cost for market buys, in cents.
+    pub buy_max_cost: Option<i64>,
+    pub reduce_only: bool,
+}
+
+impl OrderIntent {
+    /// Builds an intent from a batch order field.
+    pub fn from_field(field: &OrderCreationField) -> Self {
+        OrderIntent {
+            ticker: field.ticker.clone(),
+            side: field.side,
+            action: field.action,
+            count: field.count,
+            price: side_price(
+                field.side,
+                field.yes_price,
+                field.no_price,
+                field.yes_price_dollars.as_deref(),
+                field.no_price_dollars.as_deref(),
+            ),
+            buy_max_cost: field.buy_max_cost,
+            reduce_only: field.reduce_only.unwrap_or(false),
+        }
+    }
+
+    /// Worst-case cost of the order in cents.
+    ///
+    /// Market orders without a price are valued at `buy_max_cost`, or at 100¢ per contract.
+    pub fn notional(&self) -> i64 {
+        match self.price {
+            Some(p) => p as i64 * self.count as i64,
+            None => self.buy_max_cost.unwrap_or(100 * self.count as i64),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RestingOrder {
+    ticker: String,
+    remaining: i32,
+    price: i32,
+}
+
+impl RestingOrder {
+    fn notional(&self) -> i64 {
+        self.remaining as i64 * self.price as i64
+    }
+}
+
+#[derive(Debug, Default)]
+struct RiskState {
+    resting: HashMap<String, RestingOrder>,
+    position_cost: HashMap<String, i64>,
+    /// Mid price in YES cents per market.
+    mids: HashMap<String, f64>,
+    daily_pnl: i64,
+    pnl_day: Option<chrono::NaiveDate>,
+}
+
+impl RiskState {
+    fn roll_day(&mut self) {
+        let today = chrono::Utc::now().date_naive();
+        if self.pnl_day != Some(today) {
+            self.pnl_day = Some(today);
+            self.daily_pnl = 0;
+        }
+    }
+
+    fn notional_where(&self, matches: impl Fn(&str) -> bool) -> i64 {
+        let resting: i64 = self
+            .resting
+            .values()
+            .filter(|o| matches(&o.ticker))
+            .map(RestingOrder::notional)
+            .sum();
+        let positions: i64 = self
+            .position_cost
+            .iter()
+            .filter(|(t, _)| matches(t))
+            .map(|(_, c)| *c)
+            .sum();
+        resting + positions
+    }
+}
+
+/// Enforces [`RiskLimits`] against order intents without touching the network.
+///
+/// See the [module documentation](crate::risk) for an overview.
+#[derive(Debug)]
+pub struct RiskGuard {
+    limits: RiskLimits,
+    state: Mutex<RiskState>,
+}
+
+impl RiskGuard {
+    /// Creates a guard with the given limits and no known orders or positions.
+    pub fn new(limits: RiskLimits) -> Self {
+        RiskGuard {
+            limits,
+            state: Mutex::new(RiskState::default()),
+        }
+    }
+
+    /// Returns the configured limits.
+    pub fn limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    /// Checks a single order.
+    pub fn check(&self, intent: &OrderIntent) -> Result<(), RiskViolation> {
+        self.check_all(std::slice::from_ref(intent))
+    }
+
+    /// Checks a set of orders as if they were all placed, so limits apply to the batch
+    /// as a whole.
+    pub fn check_all(&self, intents: &[OrderIntent]) -> Result<(), RiskViolation> {
+        let mut state = self.lock();
+        self.check_locked(&mut state, intents)
+    }
+
+    /// Checks an amendment of an order to a new price and/or count.
+    ///
+    /// Missing values are taken from the tracked resting order, whose current notional is
+    /// released before the amended one is checked.
+    pub fn check_amend(
+        &self,
+        order_id: &str,
+        ticker: &str,
+        side: Side,
+        action: Action,
+        price: Option<i32>,
+        count: Option<i32>,
+    ) -> Result<(), RiskViolation> {
+        // One guard for release, check and restore, so concurrent updates to the order
+        // are neither overwritten nor missed by other checks.
+        let mut state = self.lock();
+        let removed = state.resting.remove(order_id);
+        let intent = OrderIntent {
+            ticker: ticker.to_string(),
+            side,
+            action,
+            count: count.or(removed.as_ref().map(|o| o.remaining)).unwrap_or(0),
+            price: price.or(removed.as_ref().map(|o| o.price)),
+            buy_max_cost: None,
+            reduce_only: false,
+        };
+        let result = self.check_locked(&mut state, std::slice::from_ref(&intent));
+        if let Some(order) = removed {
+            state.resting.insert(order_id.to_string(), order);
+        }
+        result
+    }
+
+    fn check_locked(
+        &self,
+        state: &mut RiskState,
+        intents: &[OrderIntent],
+    ) -> Result<(), RiskViolation> {
+        state.roll_day();
+        let mut pending: Vec<RestingOrder> = Vec::new();
+        for intent in intents {
+            self.check_static(state, intent)?;
+            if !intent.reduce_only {
+                if let Some(limit) = self.limits.max_open_orders {
+                    let open = state.resting.len() + pending.len();
+                    if open >= limit {
+                        return Err(RiskViolation::OpenOrders { open, limit });
+                    }
+                }
+                self.check_notional(state, &pending, intent)?;
+            }
+            pending.push(RestingOrder {
+                ticker: intent.ticker.clone(),
+                remaining: intent.count,
+                price: (intent.notional() / intent.count.max(1) as i64) as i32,
+            });
+        }
+        Ok(())
+    }
+
+    /// Records an order returned by the exchange, tracking it while it rests.
+    pub fn record_order(&self, order: &Order) {
+        let mut state = self.lock();
+        let remaining = order.remaining_count.unwrap_or(0);
+        let price = match order.side {
+            Side::Yes => order.yes_price,
+            Side::No => order.no_price,
+        };
+        match (order.status, price) {
+            (OrderStatus::Resting | OrderStatus::Pending, Some(price)) if remaining > 0 => {
+                state.resting.insert(
+                    order.order_id.clone(),
+                    RestingOrder {
+                        ticker: order.ticker.clone(),
+                        remaining,
+                        price,
+                    },
+                );
+            }
+            _ => {
+                state.resting.remove(&order.order_id);
+            }
+        }
+    }
+
+    /// Applies fills, position updates and tickers from the WebSocket.
+    pub fn handle_message(&self, msg: &WebSocketMessage) {
+        match msg {
+            WebSocketMessage::Fill(fill) => self.apply_fill(fill),
+            WebSocketMessage::MarketPosition(pos) => {
+                // position_cost is in centi-cents.
+                self.set_position_cost(&pos.market_ticker, pos.position_cost / 100);
+            }
+            WebSocketMessage::Ticker(t) => {
+                if let (Some(bid), Some(ask)) = (t.yes_bid, t.yes_ask) {
+                    self.set_mid(&t.market_ticker, (bid + ask) as f64 / 2.0);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Reduces the resting size of the filled order.
+    pub fn apply_fill(&self, fill: &FillMsg) {
+        let mut state = self.lock();
+        let done = match state.resting.get_mut(&fill.order_id) {
+            Some(order) => {
+                order.remaining -= fill.count;
+                order.remaining <= 0
+            }
+            None => false,
+        };
+        if done {
+            state.resting.remove(&fill.order_id);
+        }
+    }
+
+    /// Sets the cost of the open position in `ticker`, in cents.
+    pub fn set_position_cost(&self, ticker: &str, cents: i64) {
+        self.lock().position_cost.insert(ticker.to_string(), cents);
+    }
+
+    /// Sets the YES mid price of `ticker` used by the price collar.
+    pub fn set_mid(&self, ticker: &str, yes_mid: f64) {
+        self.lock().mids.insert(ticker.to_string(), yes_mid);
+    }
+
+    /// Updates the collar reference from an orderbook snapshot.
+    pub fn update_book(&self, ticker: &str, book: &Orderbook) {
+        let bid = crate::positions::best_level(book.yes.as_ref());
+        let ask = crate::positions::best_level(book.no.as_ref()).map(|p| 100 - p);
+        if let (Some(bid), Some(ask)) = (bid, ask) {
+            self.set_mid(ticker, (bid + ask) as f64 / 2.0);
+        }
+    }
+
+    /// Adds realized P&L for the current UTC day, in cents.
+    pub fn record_pnl(&self, cents: i64) {
+        let mut state = self.lock();
+        state.roll_day();
+        state.daily_pnl += cents;
+    }
+
+    /// Overwrites the P&L for the current UTC day, in cents.
+    pub fn set_daily_pnl(&self, cents: i64) {
+        let mut state = self.lock();
+        state.roll_day();
+        state.daily_pnl = cents;
+    }
+
+    /// Returns the number of resting orders the guard knows about.
+    pub fn open_order_count(&self) -> usize {
+        self.lock().resting.len()
+    }
+
+    fn check_static(&self, state: &RiskState, intent: &OrderIntent) -> Result<(), RiskViolation> {
+        if let Some(limit) = self.limits.max_order_size.filter(|l| intent.count > *l) {
+            return Err(RiskViolation::OrderSize {
+                count: intent.count,
+                limit,
+            });
+        }
+        if let Some(price) = intent.price.filter(|p| !(1..=99).contains(p)) {
+            return Err(RiskViolation::FatFinger {
+                detail: format!("price {}¢ is outside 1-99¢", price),
+            });
+        }
+        let notional = intent.notional();
+        if let Some(limit) = self.limits.max_order_notional.filter(|l| notional > *l) {
+            return Err(RiskViolation::FatFinger {
+                detail: format!("order notional {}¢ exceeds {}¢", notional, limit),
+            });
+        }
+        let mid = state.mids.get(&intent.ticker);
+        let collar = self.limits.price_collar.filter(|_| !intent.reduce_only);
+        if let (Some(collar), Some(price), Some(mid)) = (collar, intent.price, mid) {
+            let reference = match intent.side {
+                Side::Yes => *mid,
+                Side::No => 100.0 - mid,
+            };
+            if (price as f64 - reference).abs() > collar as f64 {
+                return Err(RiskViolation::PriceCollar {
+                    price,
+                    reference,
+                    collar,
+                });
+            }
+        }
+        let loss_limit = self.limits.daily_loss_limit.filter(|_| !intent.reduce_only);
+        if let Some(limit) = loss_limit.filter(|l| state.daily_pnl <= -l) {
+            return Err(RiskViolation::DailyLoss {
+                pnl: state.daily_pnl,
+                limit,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_notional(
+        &self,
+        state: &RiskState,
+        pending: &[RestingOrder],
+        intent: &OrderIntent,
+    ) -> Result<(), RiskViolation> {
+        let event = event_ticker_of(&intent.ticker);
+        let series = series_ticker_of(&intent.ticker);
+        let scopes = [
+            (
+                RiskScope::Market,
+                intent.ticker.clone(),
+                self.limits.max_market_notional,
+            ),
+            (RiskScope::Event, event, self.limits.max_event_notional),
+            (RiskScope::Series, series, self.limits.max_series_notional),
+        ];
+        for (scope, key, limit) in scopes {
+            let Some(limit) = limit else { continue };
+            let matches = |ticker: &str| match scope {
+                RiskScope::Market => ticker == key,
+                RiskScope::Event => event_ticker_of(ticker) == key,
+                RiskScope::Series => series_ticker_of(ticker) == key,
+            };
+            let pending_notional: i64 = pending
+                .iter()
+                .filter(|o| matches(&o.ticker))
+                .map(RestingOrder::notional)
+                .sum();
+            let projected = state.notional_where(matches) + pending_notional + intent.notional();
+            if projected > limit {
+                return Err(RiskViolation::Notional {
+                    scope,
+                    key,
+                    projected,
+                    limit,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RiskState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Kalshi {
+    /// Attaches a [`RiskGuard`] that every order path consults before sending.
+    pub fn with_risk_guard(mut self, guard: Arc<RiskGuard>) -> Self {
+        self.risk_guard = Some(guard);
+        self
+    }
+
+    /// Returns the attached [`RiskGuard`], if any.
+    pub fn risk_guard(&self) -> Option<&Arc<RiskGuard>> {
+        self.risk_guard.as_ref()
+    }
+
+    pub(crate) fn risk_check(&self, intents: &[OrderIntent]) -> Result<(), KalshiError> {
+        match &self.risk_guard {
+            Some(guard) => guard.check_all(intents).map_err(KalshiError::RiskViolation),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn risk_record(&self, order: &Order) {
+        if let Some(guard) = &self.risk_guard {
+            guard.record_order(order);
+        }
+    }
+}
+
+/// Derives the series ticker from a market or event ticker: everything before the first `-`.
+pub(crate) fn series_ticker_of(ticker: &str) -> String {
+    ticker.split('-').next().unwrap_or(ticker).to_string()
+}
+
+/// Picks the limit price in cents for `side` from the cent or dollar fields of an order.
+pub(crate) fn side_price(
+    side: Side,
+    yes_price: Option<i64>,
+    no_price: Option<i64>,
+    yes_price_dollars: Option<&str>,
+    no_price_dollars: Option<&str>,
+) -> Option<i32> {
+    let dollars_to_cents = |d: &str| d.parse::<f64>().ok().map(|v| (v * 100.0).round() as i64);
+    let yes = yes_price.or_else(|| yes_price_dollars.and_then(dollars_to_cents));
+    let no = no_price.or_else(|| no_price_dollars.and_then(dollars_to_cents));
+    let price = match side {
+        Side::Yes => yes.or(no.map(|p| 100 - p)),
+        Side::No => no.or(yes.map(|p| 100 - p)),
+    };
+    price.map(|p| p as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buy(ticker: &str, count: i32, price: i32) -> OrderIntent {
+        OrderIntent {
+            ticker: ticker.to_string(),
+            side: Side::Yes,
+            action: Action::Buy,
+            count,
+            price: Some(price),
+            buy_max_cost: None,
+            reduce_only: false,
+        }
+    }
+
+    #[test]
+    fn test_limits_reject_before_sending() {
+        let guard = RiskGuard::new(RiskLimits {
+            max_order_size: Some(100),
+            max_event_notional: Some(5_000),
+            price_collar: Some(10),
+            daily_loss_limit: Some(1_000),
+            ..Default::default()
+        });
+
+        assert!(matches!(
+            guard.check(&buy("SER-EV-A", 101, 50)),
+            Err(RiskViolation::OrderSize { .. })
+        ));
+
+        // Two legs of the same event together exceed the event limit.
+        let batch = [buy("SER-EV-A", 60, 50), buy("SER-EV-B", 60, 50)];
+        assert!(matches!(
+            guard.check_all(&batch),
+            Err(RiskViolation::Notional {
+                scope: RiskScope::Event,
+                ..
+            })
+        ));
+
+        guard.set_mid("SER-EV-A", 40.0);
+        assert!(matches!(
+            guard.check(&buy("SER-EV-A", 10, 55)),
+            Err(RiskViolation::PriceCollar { .. })
+        ));
+        assert!(guard.check(&buy("SER-EV-A", 10, 45)).is_ok());
+
+        guard.record_pnl(-1_000);
+        assert!(matches!(
+            guard.check(&buy("SER-EV-A", 10, 45)),
+            Err(RiskViolation::DailyLoss { .. })
+        ));
+        let mut close = buy("SER-EV-A", 10, 45);
+        close.reduce_only = true;
+        assert!(guard.check(&close).is_ok());
+        // Reduce-only orders are not collared either.
+        close.price = Some(1);
+        assert!(guard.check(&close).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_guarded_client_returns_typed_error() {
+        let guard = Arc::new(RiskGuard::new(RiskLimits {
+            max_order_size: Some(5),
+            ..Default::default()
+        }));
+        let kalshi = Kalshi::test_client().with_risk_guard(guard);
+        let result = kalshi
+            .create_order(
+                Action::Buy,
+                None,
+                10,
+                Side::Yes,
+                "SER-EV-A".to_string(),
+                crate::OrderType::Limit,
+                None,
+                None,
+                Some(50),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(KalshiError::RiskViolation(RiskViolation::OrderSize { .. }))
+        ));
+    }
+}
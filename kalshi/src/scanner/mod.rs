@@ -0,0 +1,593 @@
+//! Market discovery with declarative filters and ranking.
+//!
+//! A [`Scanner`] fetches the markets matching a [`ScanFilter`] and orders them by a list
+//! of [`ScanRank`] keys. Filters the API supports (series, event, status, close window,
+//! MVE inclusion) are sent with the request; the rest are applied locally. Category and
+//! tag filters are resolved through `get_series_list`, so only markets of matching series
+//! are fetched.
+//!
+//! [`ScanFilter`] derives `Deserialize`, so scans can be kept in configuration files:
+//!
+//! ```json
+//! { "categories": ["Economics"], "min_volume_24h": 1000, "max_spread": 3 }
+//! ```
+//!
+//! ## Live mode
+//!
+//! After a scan, subscribe to the `ticker` channel for [`Scanner::tickers`] and pass
+//! messages to [`Scanner::handle_message`]. Prices, volume and open interest are updated
+//! in place, the filter and ranking are re-applied, and markets entering or leaving the
+//! results are announced on [`Scanner::subscribe`]. Rescan periodically to pick up newly
+//! listed markets.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::{ScanFilter, ScanRank, Scanner};
+//!
+//! let scanner = Scanner::new(ScanFilter::new().category("Economics").min_volume_24h(1000))
+//!     .with_ranking(vec![ScanRank::Volume24h, ScanRank::TightestSpread])
+//!     .with_limit(20);
+//! for market in scanner.scan(&kalshi).await? {
+//!     println!("{} {}-{}", market.ticker, market.yes_bid, market.yes_ask);
+//! }
+//! ```
+
+use crate::kalshi_error::*;
+use crate::risk::series_ticker_of;
+use crate::utils::parse_ts;
+use crate::{Market, MarketData, MveFilter, TickerMsg, WebSocketMessage};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+/// Page size used for market and series listings.
+const PAGE_SIZE: i64 = 1000;
+
+/// Capacity of the broadcast channel carrying [`ScanChange`]s.
+const CHANGE_CHANNEL_CAPACITY: usize = 256;
+
+/// Which markets a [`Scanner`] keeps. Empty lists and `None` values do not filter.
+///
+/// Prices and spreads are in cents. The price band applies to the Yes mid price when the
+/// market is quoted on both sides and to the last traded price otherwise.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanFilter {
+    /// Market status, e.g. `open`.
+    pub status: Option<String>,
+    pub series_tickers: Vec<String>,
+    pub event_tickers: Vec<String>,
+    /// Series categories, any of.
+    pub categories: Vec<String>,
+    /// Series tags, any of.
+    pub tags: Vec<String>,
+    /// Earliest close time, Unix seconds.
+    pub min_close_ts: Option<i64>,
+    /// Latest close time, Unix seconds.
+    pub max_close_ts: Option<i64>,
+    pub min_volume: Option<i64>,
+    pub min_volume_24h: Option<i64>,
+    pub min_liquidity: Option<i64>,
+    pub min_open_interest: Option<i64>,
+    /// Widest Yes bid/ask spread. Markets without a two-sided quote never match.
+    pub max_spread: Option<i64>,
+    pub min_yes_price: Option<i64>,
+    pub max_yes_price: Option<i64>,
+    /// Whether multivariate (combo) markets are included, excluded or the only ones kept.
+    pub mve: Option<MveFilter>,
+}
+
+impl ScanFilter {
+    /// A filter for open markets.
+    pub fn new() -> Self {
+        ScanFilter {
+            status: Some("open".to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn series(mut self, series_ticker: &str) -> Self {
+        self.series_tickers.push(series_ticker.to_string());
+        self
+    }
+
+    pub fn event(mut self, event_ticker: &str) -> Self {
+        self.event_tickers.push(event_ticker.to_string());
+        self
+    }
+
+    pub fn category(mut self, category: &str) -> Self {
+        self.categories.push(category.to_string());
+        self
+    }
+
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// Keeps markets closing between `min_ts` and `max_ts` (Unix seconds).
+    pub fn closing_between(mut self, min_ts: i64, max_ts: i64) -> Self {
+        self.min_close_ts = Some(min_ts);
+        self.max_close_ts = Some(max_ts);
+        self
+    }
+
+    pub fn min_volume(mut self, volume: i64) -> Self {
+        self.min_volume = Some(volume);
+        self
+    }
+
+    pub fn min_volume_24h(mut self, volume: i64) -> Self {
+        self.min_volume_24h = Some(volume);
+        self
+    }
+
+    pub fn min_liquidity(mut self, liquidity: i64) -> Self {
+        self.min_liquidity = Some(liquidity);
+        self
+    }
+
+    pub fn min_open_interest(mut self, open_interest: i64) -> Self {
+        self.min_open_interest = Some(open_interest);
+        self
+    }
+
+    pub fn max_spread(mut self, cents: i64) -> Self {
+        self.max_spread = Some(cents);
+        self
+    }
+
+    /// Keeps markets whose Yes price is between `min` and `max` cents.
+    pub fn yes_price_between(mut self, min: i64, max: i64) -> Self {
+        self.min_yes_price = Some(min);
+        self.max_yes_price = Some(max);
+        self
+    }
+
+    pub fn mve(mut self, mve: MveFilter) -> Self {
+        self.mve = Some(mve);
+        self
+    }
+
+    /// Whether `market` passes the filters that only need the market itself: status,
+    /// event, close window, activity, spread and price band.
+    ///
+    /// Series, category, tag and MVE filters are applied when fetching.
+    pub fn matches(&self, market: &Market) -> bool {
+        let at_least = |min: Option<i64>, value: i64| min.is_none_or(|min| value >= min);
+        let close = parse_ts(&market.close_time);
+        let price = yes_price(market);
+        self.status.as_ref().is_none_or(|s| &market.status == s)
+            && (self.event_tickers.is_empty() || self.event_tickers.contains(&market.event_ticker))
+            && self
+                .min_close_ts
+                .is_none_or(|min| close.is_some_and(|c| c >= min))
+            && self
+                .max_close_ts
+                .is_none_or(|max| close.is_some_and(|c| c <= max))
+            && at_least(self.min_volume, market.volume)
+            && at_least(self.min_volume_24h, market.volume_24h)
+            && at_least(self.min_liquidity, market.liquidity)
+            && at_least(self.min_open_interest, market.open_interest)
+            && self
+                .max_spread
+                .is_none_or(|max| spread(market).is_some_and(|s| s <= max))
+            && self.min_yes_price.is_none_or(|min| price >= min)
+            && self.max_yes_price.is_none_or(|max| price <= max)
+    }
+}
+
+/// A ranking key. Each key sorts best first; later keys break ties of earlier ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanRank {
+    /// Highest total volume.
+    Volume,
+    /// Highest 24-hour volume.
+    Volume24h,
+    /// Highest liquidity.
+    Liquidity,
+    /// Highest open interest.
+    OpenInterest,
+    /// Narrowest Yes spread; unquoted markets last.
+    TightestSpread,
+    /// Earliest close time.
+    ClosingSoonest,
+}
+
+impl ScanRank {
+    /// Sort key; lower is better.
+    fn key(self, market: &Market) -> i64 {
+        match self {
+            ScanRank::Volume => -market.volume,
+            ScanRank::Volume24h => -market.volume_24h,
+            ScanRank::Liquidity => -market.liquidity,
+            ScanRank::OpenInterest => -market.open_interest,
+            ScanRank::TightestSpread => spread(market).unwrap_or(i64::MAX),
+            ScanRank::ClosingSoonest => parse_ts(&market.close_time).unwrap_or(i64::MAX),
+        }
+    }
+}
+
+/// A market entering or leaving a live [`Scanner`]'s results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanChange {
+    Entered(String),
+    Left(String),
+}
+
+#[derive(Debug, Default)]
+struct ScanState {
+    /// Every fetched market, matching or not, keyed by ticker.
+    markets: HashMap<String, Market>,
+    /// Tickers of the current results, best first.
+    results: Vec<String>,
+}
+
+/// Finds and ranks markets. See the [module documentation](crate::scanner).
+#[derive(Debug)]
+pub struct Scanner {
+    filter: ScanFilter,
+    ranking: Vec<ScanRank>,
+    limit: Option<usize>,
+    state: Mutex<ScanState>,
+    changes: broadcast::Sender<ScanChange>,
+}
+
+impl Scanner {
+    pub fn new(filter: ScanFilter) -> Self {
+        let (changes, _) = broadcast::channel(CHANGE_CHANNEL_CAPACITY);
+        Scanner {
+            filter,
+            ranking: Vec::new(),
+            limit: None,
+            state: Mutex::new(ScanState::default()),
+            changes,
+        }
+    }
+
+    /// Orders results by `ranking`. Without a ranking, results are sorted by ticker.
+    pub fn with_ranking(mut self, ranking: Vec<ScanRank>) -> Self {
+        self.ranking = ranking;
+        self
+    }
+
+    /// Keeps at most `limit` results.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn filter(&self) -> &ScanFilter {
+        &self.filter
+    }
+
+    /// Receives markets entering and leaving the results during live updates.
+    pub fn subscribe(&self) -> broadcast::Receiver<ScanChange> {
+        self.changes.subscribe()
+    }
+
+    /// Fetches the markets matching the filter, replacing earlier scan results.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - Where to read series and markets from.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<Market>)`: The ranked results.
+    /// - `Err(KalshiError)`: A request failed; earlier results are kept.
+    ///
+    pub async fn scan<B: MarketData>(&self, backend: &B) -> Result<Vec<Market>, KalshiError> {
+        let mut markets = Vec::new();
+        match self.series_scope(backend).await? {
+            // Category or tag filters that no series matches.
+            Some(series) if series.is_empty() => {}
+            Some(series) => {
+                for series_ticker in series {
+                    markets.extend(
+                        self.fetch_markets(backend, None, Some(series_ticker))
+                            .await?,
+                    );
+                }
+            }
+            None if !self.filter.event_tickers.is_empty() => {
+                for event_ticker in &self.filter.event_tickers {
+                    markets.extend(
+                        self.fetch_markets(backend, Some(event_ticker.clone()), None)
+                            .await?,
+                    );
+                }
+            }
+            None => markets = self.fetch_markets(backend, None, None).await?,
+        }
+
+        let mut state = self.lock();
+        state.markets = markets.into_iter().map(|m| (m.ticker.clone(), m)).collect();
+        state.results = self.rank(&state.markets);
+        Ok(results_of(&state))
+    }
+
+    /// The current results, best first.
+    pub fn results(&self) -> Vec<Market> {
+        results_of(&self.lock())
+    }
+
+    /// Tickers of every scanned market, for subscribing to the `ticker` channel.
+    ///
+    /// This includes markets that do not match yet, so they can enter the results.
+    pub fn tickers(&self) -> Vec<String> {
+        let mut tickers: Vec<String> = self.lock().markets.keys().cloned().collect();
+        tickers.sort();
+        tickers
+    }
+
+    /// Applies `ticker` messages to the scanned markets and re-ranks. Other messages are
+    /// ignored.
+    ///
+    /// # Returns
+    ///
+    /// Whether the results changed, in membership or order.
+    ///
+    pub fn handle_message(&self, msg: &WebSocketMessage) -> bool {
+        match msg {
+            WebSocketMessage::Ticker(ticker) => self.apply_ticker(ticker),
+            _ => false,
+        }
+    }
+
+    /// Updates one market from a `ticker` message and re-ranks.
+    pub fn apply_ticker(&self, msg: &TickerMsg) -> bool {
+        let mut state = self.lock();
+        let Some(market) = state.markets.get_mut(&msg.market_ticker) else {
+            return false;
+        };
+        if let Some(price) = msg.price {
+            market.last_price = price as i64;
+        }
+        if let Some(bid) = msg.yes_bid {
+            market.yes_bid = bid as i64;
+            market.no_ask = 100 - bid as i64;
+        }
+        if let Some(ask) = msg.yes_ask {
+            market.yes_ask = ask as i64;
+            market.no_bid = 100 - ask as i64;
+        }
+        if let Some(volume) = msg.volume {
+            market.volume = volume;
+        }
+        if let Some(open_interest) = msg.open_interest {
+            market.open_interest = open_interest;
+        }
+
+        let results = self.rank(&state.markets);
+        if results == state.results {
+            return false;
+        }
+        let before: HashSet<&String> = state.results.iter().collect();
+        let after: HashSet<&String> = results.iter().collect();
+        for ticker in after.difference(&before) {
+            let _ = self.changes.send(ScanChange::Entered((*ticker).clone()));
+        }
+        for ticker in before.difference(&after) {
+            let _ = self.changes.send(ScanChange::Left((*ticker).clone()));
+        }
+        state.results = results;
+        true
+    }
+
+    /// Series to fetch markets for, or `None` to fetch without a series.
+    async fn series_scope<B: MarketData>(
+        &self,
+        backend: &B,
+    ) -> Result<Option<Vec<String>>, KalshiError> {
+        let filter = &self.filter;
+        if filter.categories.is_empty() && filter.tags.is_empty() {
+            return Ok((!filter.series_tickers.is_empty()).then(|| filter.series_tickers.clone()));
+        }
+        let tags = (!filter.tags.is_empty()).then(|| filter.tags.join(","));
+        let categories: Vec<Option<String>> = if filter.categories.is_empty() {
+            vec![None]
+        } else {
+            filter.categories.iter().cloned().map(Some).collect()
+        };
+        let mut series = Vec::new();
+        for category in categories {
+            let mut cursor = None;
+            loop {
+                let (next, page) = backend
+                    .get_series_list(Some(PAGE_SIZE), cursor, category.clone(), tags.clone())
+                    .await?;
+                series.extend(page.iter().filter_map(|s| s.ticker.clone()));
+                match next {
+                    Some(c) if !c.is_empty() && !page.is_empty() => cursor = Some(c),
+                    _ => break,
+                }
+            }
+        }
+        if !filter.series_tickers.is_empty() {
+            series.retain(|s| filter.series_tickers.contains(s));
+        }
+        series.sort();
+        series.dedup();
+        Ok(Some(series))
+    }
+
+    async fn fetch_markets<B: MarketData>(
+        &self,
+        backend: &B,
+        event_ticker: Option<String>,
+        series_ticker: Option<String>,
+    ) -> Result<Vec<Market>, KalshiError> {
+        let mut markets = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (next, page) = backend
+                .get_markets(
+                    Some(PAGE_SIZE),
+                    cursor,
+                    event_ticker.clone(),
+                    series_ticker.clone(),
+                    self.filter.status.clone(),
+                    None,
+                    self.filter.min_close_ts,
+                    self.filter.max_close_ts,
+                    None,
+                    None,
+                    None,
+                    None,
+                    self.filter.mve.clone(),
+                )
+                .await?;
+            let done = page.is_empty();
+            markets.extend(page);
+            match next {
+                Some(c) if !c.is_empty() && !done => cursor = Some(c),
+                _ => break,
+            }
+        }
+        if !self.filter.series_tickers.is_empty() && series_ticker.is_none() {
+            markets.retain(|m| {
+                self.filter
+                    .series_tickers
+                    .contains(&series_ticker_of(&m.ticker))
+            });
+        }
+        Ok(markets)
+    }
+
+    /// Tickers of the matching markets, best first.
+    fn rank(&self, markets: &HashMap<String, Market>) -> Vec<String> {
+        let mut matching: Vec<&Market> = markets
+            .values()
+            .filter(|m| self.filter.matches(m))
+            .collect();
+        matching.sort_by(|a, b| {
+            self.ranking
+                .iter()
+                .map(|rank| rank.key(a).cmp(&rank.key(b)))
+                .find(|o| o.is_ne())
+                .unwrap_or_else(|| a.ticker.cmp(&b.ticker))
+        });
+        matching
+            .into_iter()
+            .take(self.limit.unwrap_or(usize::MAX))
+            .map(|m| m.ticker.clone())
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ScanState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn results_of(state: &ScanState) -> Vec<Market> {
+    state
+        .results
+        .iter()
+        .filter_map(|t| state.markets.get(t).cloned())
+        .collect()
+}
+
+/// Yes ask minus Yes bid, when both sides are quoted.
+fn spread(market: &Market) -> Option<i64> {
+    (market.yes_bid > 0 && market.yes_ask > 0 && market.yes_ask < 100)
+        .then(|| market.yes_ask - market.yes_bid)
+}
+
+/// Yes mid price when quoted on both sides, otherwise the last price.
+fn yes_price(market: &Market) -> i64 {
+    match spread(market) {
+        Some(_) => (market.yes_bid + market.yes_ask) / 2,
+        None => market.last_price,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InMemoryKalshi, Series};
+
+    fn market(ticker: &str, volume_24h: i64, yes_bid: i64, yes_ask: i64) -> Market {
+        crate::test_market(serde_json::json!({
+            "ticker": ticker, "event_ticker": ticker.rsplit_once('-').unwrap().0,
+            "open_time": "2024-01-01T00:00:00Z", "close_time": "2024-01-02T00:00:00Z",
+            "yes_bid": yes_bid, "yes_ask": yes_ask, "no_bid": 100 - yes_ask,
+            "no_ask": 100 - yes_bid, "last_price": yes_bid, "volume": volume_24h,
+            "volume_24h": volume_24h
+        }))
+    }
+
+    fn series(ticker: &str, category: &str) -> Series {
+        serde_json::from_value(serde_json::json!({
+            "ticker": ticker, "category": category, "tags": ["Weather"]
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_scan_filters_ranks_and_follows_ticker_updates() {
+        let backend = InMemoryKalshi::new(0);
+        backend.insert_series(series("KXHIGHNY", "Climate"));
+        backend.insert_series(series("KXCPI", "Economics"));
+        backend.insert_market(market("KXHIGHNY-24JAN01-T40", 500, 40, 42));
+        backend.insert_market(market("KXHIGHNY-24JAN01-T45", 900, 30, 40));
+        backend.insert_market(market("KXHIGHNY-24JAN01-T50", 50, 20, 21));
+        backend.insert_market(market("KXCPI-24JAN-T3", 5000, 50, 51));
+
+        let scanner = Scanner::new(ScanFilter::new().category("Climate").min_volume_24h(100))
+            .with_ranking(vec![ScanRank::TightestSpread, ScanRank::Volume24h]);
+        let tickers = |markets: Vec<Market>| -> Vec<String> {
+            markets.into_iter().map(|m| m.ticker).collect()
+        };
+        assert_eq!(
+            tickers(scanner.scan(&backend).await.unwrap()),
+            ["KXHIGHNY-24JAN01-T40", "KXHIGHNY-24JAN01-T45"]
+        );
+        assert_eq!(scanner.tickers().len(), 3);
+
+        let mut changes = scanner.subscribe();
+        let update = TickerMsg {
+            market_ticker: "KXHIGHNY-24JAN01-T50".to_string(),
+            price: None,
+            yes_bid: None,
+            yes_ask: None,
+            price_dollars: None,
+            volume: Some(1000),
+            open_interest: None,
+            ts: None,
+        };
+        // Only total volume was sent, which the 24h filter does not look at.
+        assert!(!scanner.handle_message(&WebSocketMessage::Ticker(update)));
+
+        let mut market = market("KXHIGHNY-24JAN01-T50", 0, 20, 21);
+        market.volume_24h = 200;
+        scanner.lock().markets.insert(market.ticker.clone(), market);
+        let tighten = TickerMsg {
+            market_ticker: "KXHIGHNY-24JAN01-T45".to_string(),
+            price: None,
+            yes_bid: Some(39),
+            yes_ask: None,
+            price_dollars: None,
+            volume: None,
+            open_interest: None,
+            ts: None,
+        };
+        assert!(scanner.apply_ticker(&tighten));
+        assert_eq!(
+            tickers(scanner.results()),
+            [
+                "KXHIGHNY-24JAN01-T45",
+                "KXHIGHNY-24JAN01-T50",
+                "KXHIGHNY-24JAN01-T40"
+            ]
+        );
+        assert_eq!(
+            changes.try_recv().unwrap(),
+            ScanChange::Entered("KXHIGHNY-24JAN01-T50".to_string())
+        );
+    }
+}
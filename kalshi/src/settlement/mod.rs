@@ -0,0 +1,466 @@
+//! Automatic reconciliation of market settlements into realized P&L.
+//!
+//! A [`SettlementWatcher`] learns which markets are held from `get_positions` snapshots
+//! ([`SettlementWatcher::sync_positions`]) and `market_position` messages. When a
+//! `market_lifecycle_v2` message reports a held market as `determined` or `settled`, the
+//! ticker is queued; [`SettlementWatcher::process_pending`] then fetches its settlement
+//! with `get_settlements` and emits a [`SettlementEvent`] with realized P&L net of the
+//! fees paid in that market. Determined markets stay queued until their settlement is
+//! published.
+//!
+//! Settlements that happened while the process was offline are found by
+//! [`SettlementWatcher::catch_up`], which lists every settlement after the checkpoint.
+//! The checkpoint only advances in `catch_up`, and only past a `settled_time` once every
+//! settlement at or before it has been processed, so a failed request or a later market
+//! settling first never skips an earlier settlement. Persist
+//! [`SettlementWatcher::last_settled`] after `catch_up` and restore it with
+//! [`SettlementWatcher::with_last_settled`] across restarts.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::SettlementWatcher;
+//!
+//! let watcher = SettlementWatcher::new().with_last_settled(checkpoint);
+//! let mut settled = watcher.subscribe();
+//! watcher.sync_positions(&kalshi).await?;
+//! watcher.catch_up(&kalshi).await?;
+//!
+//! // In the WebSocket loop:
+//! if watcher.handle_message(&msg) {
+//!     watcher.process_pending(&kalshi).await?;
+//! }
+//! ```
+
+use crate::kalshi_error::*;
+use crate::positions::{centi_cents_to_cents, event_ticker_of};
+use crate::utils::parse_time;
+use crate::{MarketLifecycleEvent, MarketPosition, Portfolio, Settlement, WebSocketMessage};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+/// Page size used for settlement and position listings.
+const PAGE_SIZE: i64 = 200;
+
+/// Capacity of the broadcast channel carrying [`SettlementEvent`]s.
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// A settled market with its realized P&L. Amounts are in cents.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SettlementEvent {
+    pub ticker: String,
+    pub event_ticker: String,
+    /// The market result, e.g. `yes`, `no` or empty for a void market.
+    pub market_result: String,
+    pub settled_time: DateTime<Utc>,
+    /// Contracts held at settlement, signed in YES terms (negative means NO).
+    pub position: i64,
+    /// Total cost of the settled contracts.
+    pub cost: i64,
+    /// Payout received.
+    pub revenue: i64,
+    /// Fees paid in the market.
+    pub fees: i64,
+    /// `revenue - cost - fees`.
+    pub realized_pnl: i64,
+    /// True if found by [`SettlementWatcher::catch_up`] rather than a lifecycle message.
+    pub caught_up: bool,
+}
+
+#[derive(Debug, Default)]
+struct WatcherState {
+    /// Fees paid in each held market, in cents.
+    held: HashMap<String, i64>,
+    pending: BTreeSet<String>,
+    /// Settlements already emitted, by ticker and `settled_time`.
+    seen: HashSet<(String, String)>,
+    /// Every settlement up to and including this time has been processed.
+    last_settled: Option<DateTime<Utc>>,
+}
+
+/// Turns settlements into [`SettlementEvent`]s. See the
+/// [module documentation](crate::settlement).
+#[derive(Debug)]
+pub struct SettlementWatcher {
+    state: Mutex<WatcherState>,
+    events: broadcast::Sender<SettlementEvent>,
+}
+
+impl Default for SettlementWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SettlementWatcher {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        SettlementWatcher {
+            state: Mutex::new(WatcherState::default()),
+            events,
+        }
+    }
+
+    /// Treats settlements up to and including `settled_time` as processed.
+    pub fn with_last_settled(self, settled_time: Option<DateTime<Utc>>) -> Self {
+        self.lock().last_settled = settled_time;
+        self
+    }
+
+    /// The `settled_time` up to which every settlement has been processed, to persist as a
+    /// checkpoint. Advanced by [`catch_up`](Self::catch_up) only.
+    pub fn last_settled(&self) -> Option<DateTime<Utc>> {
+        self.lock().last_settled
+    }
+
+    /// Receives every [`SettlementEvent`].
+    pub fn subscribe(&self) -> broadcast::Receiver<SettlementEvent> {
+        self.events.subscribe()
+    }
+
+    /// Tickers waiting for their settlement to be published.
+    pub fn pending(&self) -> Vec<String> {
+        self.lock().pending.iter().cloned().collect()
+    }
+
+    /// Records held positions from a `get_positions` snapshot.
+    pub fn record_positions(&self, positions: &[MarketPosition]) {
+        let mut state = self.lock();
+        for p in positions {
+            state.held.insert(p.ticker.clone(), p.fees_paid);
+        }
+    }
+
+    /// Fetches unsettled positions and records them.
+    pub async fn sync_positions<B: Portfolio>(&self, backend: &B) -> Result<(), KalshiError> {
+        let mut cursor = None;
+        loop {
+            let (next, _, page) = backend
+                .get_positions(Some(PAGE_SIZE), cursor.clone(), None, None, None, None)
+                .await?;
+            self.record_positions(&page);
+            match next {
+                Some(c) if !c.is_empty() && !page.is_empty() => cursor = Some(c),
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies `market_position` and `market_lifecycle_v2` messages. Other messages are
+    /// ignored.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a held market was determined or settled and is now pending; call
+    /// [`process_pending`](Self::process_pending).
+    ///
+    pub fn handle_message(&self, msg: &WebSocketMessage) -> bool {
+        let mut state = self.lock();
+        match msg {
+            WebSocketMessage::MarketPosition(p) => {
+                let fees = centi_cents_to_cents(p.fees_paid).round() as i64;
+                state.held.insert(p.market_ticker.clone(), fees);
+                false
+            }
+            WebSocketMessage::MarketLifecycle(m)
+                if matches!(
+                    m.event_type,
+                    MarketLifecycleEvent::Determined | MarketLifecycleEvent::Settled
+                ) && state.held.contains_key(&m.market_ticker) =>
+            {
+                state.pending.insert(m.market_ticker.clone())
+            }
+            _ => false,
+        }
+    }
+
+    /// Queues a market for [`process_pending`](Self::process_pending), e.g. from a
+    /// [`RegistryUpdate`](crate::RegistryUpdate).
+    pub fn queue(&self, ticker: &str) {
+        self.lock().pending.insert(ticker.to_string());
+    }
+
+    /// Fetches settlements for pending markets and emits them.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<SettlementEvent>)`: The settlements found. Markets without a published
+    ///   settlement stay pending.
+    /// - `Err(KalshiError)`: A request failed.
+    ///
+    pub async fn process_pending<B: Portfolio>(
+        &self,
+        backend: &B,
+    ) -> Result<Vec<SettlementEvent>, KalshiError> {
+        let mut out = Vec::new();
+        for ticker in self.pending() {
+            let (_, settlements) = backend
+                .get_settlements(
+                    Some(PAGE_SIZE),
+                    None,
+                    Some(ticker.clone()),
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+            if settlements.is_empty() {
+                continue;
+            }
+            for settlement in &settlements {
+                if let Some(event) = self.settle(backend, settlement, false).await? {
+                    out.push(event);
+                }
+            }
+            self.lock().pending.remove(&ticker);
+        }
+        Ok(out)
+    }
+
+    /// Emits every settlement after [`last_settled`](Self::last_settled), oldest first, and
+    /// advances the checkpoint. Without a checkpoint, every settlement on the account is
+    /// emitted. Settlements already emitted by [`process_pending`](Self::process_pending)
+    /// are not emitted again but still advance the checkpoint.
+    ///
+    /// The checkpoint moves past a `settled_time` only after all settlements sharing it
+    /// are processed, so an error partway through leaves it where the next call can
+    /// resume without losing any.
+    pub async fn catch_up<B: Portfolio>(
+        &self,
+        backend: &B,
+    ) -> Result<Vec<SettlementEvent>, KalshiError> {
+        let since = self.last_settled();
+        let mut settlements = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (next, page) = backend
+                .get_settlements(
+                    Some(PAGE_SIZE),
+                    cursor.clone(),
+                    None,
+                    None,
+                    since.map(|t| t.timestamp()),
+                    None,
+                )
+                .await?;
+            let empty = page.is_empty();
+            settlements.extend(
+                page.into_iter()
+                    .filter_map(|s| Some((parse_time(&s.settled_time)?, s)))
+                    .filter(|(t, _)| since.is_none_or(|since| *t > since)),
+            );
+            match next {
+                Some(c) if !c.is_empty() && !empty => cursor = Some(c),
+                _ => break,
+            }
+        }
+        settlements.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.ticker.cmp(&b.1.ticker)));
+
+        let mut out = Vec::new();
+        for (i, (settled_time, settlement)) in settlements.iter().enumerate() {
+            if let Some(event) = self.settle(backend, settlement, true).await? {
+                out.push(event);
+            }
+            let last_at_time = settlements
+                .get(i + 1)
+                .is_none_or(|(next, _)| next != settled_time);
+            if last_at_time {
+                self.lock().last_settled = Some(*settled_time);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Builds, records and emits the event for one settlement, unless already emitted.
+    async fn settle<B: Portfolio>(
+        &self,
+        backend: &B,
+        settlement: &Settlement,
+        caught_up: bool,
+    ) -> Result<Option<SettlementEvent>, KalshiError> {
+        let key = (settlement.ticker.clone(), settlement.settled_time.clone());
+        let Some(settled_time) = parse_time(&settlement.settled_time) else {
+            return Ok(None);
+        };
+        let held = {
+            let state = self.lock();
+            if state.seen.contains(&key) {
+                return Ok(None);
+            }
+            state.held.get(&settlement.ticker).copied()
+        };
+        let fees = match held {
+            Some(fees) => fees,
+            None => fees_paid(backend, &settlement.ticker).await?,
+        };
+
+        let cost = settlement.yes_total_cost + settlement.no_total_cost;
+        let event = SettlementEvent {
+            ticker: settlement.ticker.clone(),
+            event_ticker: event_ticker_of(&settlement.ticker),
+            market_result: settlement.market_result.clone(),
+            settled_time,
+            position: settlement.yes_count - settlement.no_count,
+            cost,
+            revenue: settlement.revenue,
+            fees,
+            realized_pnl: settlement.revenue - cost - fees,
+            caught_up,
+        };
+        {
+            let mut state = self.lock();
+            if !state.seen.insert(key) {
+                return Ok(None);
+            }
+            state.held.remove(&settlement.ticker);
+            state.pending.remove(&settlement.ticker);
+        }
+        let _ = self.events.send(event.clone());
+        Ok(Some(event))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, WatcherState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Fees paid in a market according to `get_positions`, settled or not.
+async fn fees_paid<B: Portfolio>(backend: &B, ticker: &str) -> Result<i64, KalshiError> {
+    let (_, _, positions) = backend
+        .get_positions(
+            None,
+            None,
+            Some("all".to_string()),
+            Some(ticker.to_string()),
+            None,
+            None,
+        )
+        .await?;
+    Ok(positions
+        .iter()
+        .filter(|p| p.ticker == ticker)
+        .map(|p| p.fees_paid)
+        .sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, OrderType, Orderbook, PaperKalshi, SettlementResult, Side};
+
+    async fn buy(paper: &PaperKalshi, ticker: &str, at: &str) {
+        paper.set_time(at.parse().unwrap());
+        paper.apply_orderbook(
+            ticker,
+            &Orderbook {
+                yes: Some(vec![vec![40, 10]]),
+                no: Some(vec![vec![55, 5], vec![53, 10]]),
+                yes_dollars: Vec::new(),
+                no_dollars: Vec::new(),
+            },
+        );
+        // 5 @ 45 + 3 @ 47 = 366¢ plus 15¢ taker fees.
+        paper
+            .create_order(
+                Action::Buy,
+                None,
+                8,
+                Side::Yes,
+                ticker.to_string(),
+                OrderType::Limit,
+                None,
+                None,
+                Some(47),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_settlements_from_lifecycle_and_catch_up() {
+        let paper = PaperKalshi::new(10_000);
+        let watcher = SettlementWatcher::new();
+        let mut events = watcher.subscribe();
+        buy(&paper, "EV-1-T50", "2024-07-01T12:00:00Z").await;
+        watcher.sync_positions(&paper).await.unwrap();
+        paper.settle("EV-1-T50", SettlementResult::Yes);
+
+        // Unheld markets are ignored.
+        let lifecycle = |ticker: &str| {
+            WebSocketMessage::MarketLifecycle(
+                serde_json::from_value(serde_json::json!({
+                    "market_ticker": ticker, "event_type": "settled"
+                }))
+                .unwrap(),
+            )
+        };
+        assert!(!watcher.handle_message(&lifecycle("EV-1-T60")));
+        assert!(watcher.handle_message(&lifecycle("EV-1-T50")));
+
+        let settled = watcher.process_pending(&paper).await.unwrap();
+        assert_eq!(settled.len(), 1);
+        assert_eq!(settled[0].position, 8);
+        assert_eq!(settled[0].revenue, 800);
+        assert_eq!(settled[0].fees, 15);
+        assert_eq!(settled[0].realized_pnl, 800 - 366 - 15);
+        assert!(!settled[0].caught_up);
+        assert_eq!(events.try_recv().unwrap(), settled[0]);
+        assert!(watcher.pending().is_empty());
+
+        // Lifecycle settlements don't move the checkpoint; catch_up does, without
+        // emitting them twice.
+        assert_eq!(watcher.last_settled(), None);
+        assert!(watcher.catch_up(&paper).await.unwrap().is_empty());
+
+        // A restart from the checkpoint only sees the settlement made while offline.
+        let checkpoint = watcher.last_settled();
+        assert!(checkpoint.is_some());
+        buy(&paper, "EV-1-T70", "2024-07-02T12:00:00Z").await;
+        paper.settle("EV-1-T70", SettlementResult::Yes);
+        let restarted = SettlementWatcher::new().with_last_settled(checkpoint);
+        let missed = restarted.catch_up(&paper).await.unwrap();
+        assert_eq!(missed.len(), 1);
+        assert_eq!(missed[0].ticker, "EV-1-T70");
+        assert_eq!(missed[0].fees, 15);
+        assert!(missed[0].caught_up);
+        assert!(restarted.catch_up(&paper).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_later_settlement_does_not_skip_earlier_one() {
+        let paper = PaperKalshi::new(10_000);
+        let watcher = SettlementWatcher::new();
+        buy(&paper, "EV-1-T50", "2024-07-01T12:00:00Z").await;
+        paper.settle("EV-1-T50", SettlementResult::Yes);
+        buy(&paper, "EV-1-T60", "2024-07-02T12:00:00Z").await;
+        paper.settle("EV-1-T60", SettlementResult::No);
+
+        // Only the later market arrives through a lifecycle message.
+        watcher.queue("EV-1-T60");
+        let settled = watcher.process_pending(&paper).await.unwrap();
+        assert_eq!(settled.len(), 1);
+        assert_eq!(watcher.last_settled(), None);
+
+        let missed = watcher.catch_up(&paper).await.unwrap();
+        assert_eq!(missed.len(), 1);
+        assert_eq!(missed[0].ticker, "EV-1-T50");
+        assert_eq!(
+            watcher.last_settled(),
+            Some("2024-07-02T12:00:00Z".parse().unwrap())
+        );
+    }
+}
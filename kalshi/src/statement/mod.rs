@@ -0,0 +1,767 @@
+//! Account statements and tax-lot accounting.
+//!
+//! [`StatementBuilder`] replays fills and settlements in time order through a lot ledger
+//! and produces a [`TaxLedger`]: every closed lot with its cost basis, proceeds and
+//! realized gain, the lots still open, and the fees paid. [`TaxLedger::statements`] groups
+//! that into monthly or annual [`Statement`]s, which can be written as CSV or JSON.
+//!
+//! ## Lot accounting
+//!
+//! Each buy opens a lot on its side at the side's price. A sell closes lots on the same
+//! side. Because a Yes and a No contract together always pay 100¢, buying one side while
+//! holding the other also closes lots: the pair is redeemed and each closed lot's proceeds
+//! are `100 − price`. Lots are closed first-in first-out unless [`LotMethod::SpecificLot`]
+//! names the lots a closing fill should use.
+//!
+//! A settlement closes every remaining lot in its market at the settlement value. If the
+//! settlement reports more contracts than the ledger holds, for example because the fills
+//! that opened them are outside the fetched range, the difference is booked with a cost
+//! basis taken from `yes_total_cost`/`no_total_cost` and no acquisition date.
+//!
+//! Fills carry no fee, so fees are estimated with a [`FeeCalculator`]. Purchase fees are
+//! added to the lot's cost basis and sale fees deducted from proceeds, so realized gains
+//! are net of fees. Periods are calendar months or years in UTC. All amounts are in cents.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::{StatementBuilder, StatementPeriod};
+//!
+//! let ledger = StatementBuilder::new().fetch(&kalshi, None, None).await?.build();
+//! for statement in ledger.statements(StatementPeriod::Monthly) {
+//!     statement.write_csv("statements")?;
+//!     statement.write_json(format!("statements/{}.json", statement.period))?;
+//! }
+//! ```
+
+use crate::fees::{FeeCalculator, FeeRole};
+use crate::history::{col, io_error, HistoryColumn, HistoryColumnType, HistoryTable, HistoryValue};
+use crate::kalshi_error::*;
+use crate::utils::parse_time;
+use crate::{Action, Fill, Portfolio, Settlement, Side};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Page size used when fetching fills and settlements.
+const PAGE_SIZE: i64 = 200;
+
+use HistoryColumnType::{Int64, Utf8};
+
+/// Columns of `realized-<period>.csv`.
+pub const REALIZED_SCHEMA: &[HistoryColumn] = &[
+    col("ticker", Utf8),
+    col("side", Utf8),
+    col("lot_id", Utf8),
+    col("acquired", Utf8),
+    col("closed", Utf8),
+    col("close_kind", Utf8),
+    col("closing_id", Utf8),
+    col("count", Int64),
+    col("cost_basis", Int64),
+    col("proceeds", Int64),
+    col("realized_gain", Int64),
+];
+
+/// Columns of `statement-<period>.csv`. The last row is the period total with ticker `TOTAL`.
+pub const MARKET_STATEMENT_SCHEMA: &[HistoryColumn] = &[
+    col("ticker", Utf8),
+    col("contracts_closed", Int64),
+    col("cost_basis", Int64),
+    col("proceeds", Int64),
+    col("realized_gain", Int64),
+    col("settlement_revenue", Int64),
+    col("fees", Int64),
+];
+
+/// How closing fills choose which open lots to close.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum LotMethod {
+    /// Oldest lots first.
+    #[default]
+    Fifo,
+    /// Closing fill trade ID → trade IDs of the lots to close, in order. Fills that are
+    /// not listed, or need more contracts than the listed lots hold, fall back to FIFO.
+    SpecificLot(HashMap<String, Vec<String>>),
+}
+
+/// A block of contracts bought together.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaxLot {
+    pub ticker: String,
+    pub side: Side,
+    /// Trade ID of the fill that opened the lot.
+    pub lot_id: String,
+    pub acquired: DateTime<Utc>,
+    /// Contracts still open.
+    pub count: i64,
+    /// Cost of the open contracts including purchase fees.
+    pub cost_basis: i64,
+}
+
+/// How a lot was closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloseKind {
+    Sale,
+    Settlement,
+}
+
+/// Contracts of one lot closed by one fill or settlement.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RealizedLot {
+    pub ticker: String,
+    pub side: Side,
+    /// `None` when the settlement reported contracts the ledger had no lot for.
+    pub lot_id: Option<String>,
+    pub acquired: Option<DateTime<Utc>>,
+    pub closed: DateTime<Utc>,
+    pub close_kind: CloseKind,
+    /// Trade ID of the closing fill, or the ticker for settlements.
+    pub closing_id: String,
+    pub count: i64,
+    pub cost_basis: i64,
+    /// Amount received, net of sale fees.
+    pub proceeds: i64,
+}
+
+impl RealizedLot {
+    pub fn realized_gain(&self) -> i64 {
+        self.proceeds - self.cost_basis
+    }
+}
+
+/// One market's line in a [`Statement`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MarketStatement {
+    pub ticker: String,
+    pub contracts_closed: i64,
+    pub cost_basis: i64,
+    pub proceeds: i64,
+    pub realized_gain: i64,
+    /// `revenue` of the market's settlement, if it settled in the period.
+    pub settlement_revenue: i64,
+    /// Estimated fees of the period's fills.
+    pub fees: i64,
+}
+
+impl MarketStatement {
+    fn add(&mut self, other: &MarketStatement) {
+        self.contracts_closed += other.contracts_closed;
+        self.cost_basis += other.cost_basis;
+        self.proceeds += other.proceeds;
+        self.realized_gain += other.realized_gain;
+        self.settlement_revenue += other.settlement_revenue;
+        self.fees += other.fees;
+    }
+}
+
+/// Statement length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementPeriod {
+    Monthly,
+    Annual,
+}
+
+impl StatementPeriod {
+    /// Label, first day and last day of the period containing `at`.
+    fn bounds(self, at: DateTime<Utc>) -> (String, NaiveDate, NaiveDate) {
+        let (year, month) = (at.year(), at.month());
+        match self {
+            StatementPeriod::Monthly => {
+                let start = NaiveDate::from_ymd_opt(year, month, 1).unwrap_or_default();
+                let next = if month == 12 {
+                    NaiveDate::from_ymd_opt(year + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(year, month + 1, 1)
+                };
+                let end = next.and_then(|d| d.pred_opt()).unwrap_or(start);
+                (format!("{:04}-{:02}", year, month), start, end)
+            }
+            StatementPeriod::Annual => (
+                format!("{:04}", year),
+                NaiveDate::from_ymd_opt(year, 1, 1).unwrap_or_default(),
+                NaiveDate::from_ymd_opt(year, 12, 31).unwrap_or_default(),
+            ),
+        }
+    }
+}
+
+/// Realized gains and fees of one month or year.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Statement {
+    /// `YYYY-MM` or `YYYY`.
+    pub period: String,
+    pub start: NaiveDate,
+    /// Last day of the period, inclusive.
+    pub end: NaiveDate,
+    /// Totals over all markets.
+    pub total: MarketStatement,
+    /// Per-market lines, sorted by ticker.
+    pub markets: Vec<MarketStatement>,
+    /// Lots closed in the period, in closing order.
+    pub realized: Vec<RealizedLot>,
+}
+
+impl Statement {
+    /// Closed lots laid out by [`REALIZED_SCHEMA`].
+    pub fn realized_table(&self) -> HistoryTable {
+        let mut table = HistoryTable::new(REALIZED_SCHEMA);
+        for r in &self.realized {
+            table.rows.push(vec![
+                r.ticker.clone().into(),
+                side_str(r.side).into(),
+                r.lot_id.clone().into(),
+                r.acquired.map(|t| t.to_rfc3339()).into(),
+                r.closed.to_rfc3339().into(),
+                match r.close_kind {
+                    CloseKind::Sale => "sale",
+                    CloseKind::Settlement => "settlement",
+                }
+                .into(),
+                r.closing_id.clone().into(),
+                r.count.into(),
+                r.cost_basis.into(),
+                r.proceeds.into(),
+                r.realized_gain().into(),
+            ]);
+        }
+        table
+    }
+
+    /// Market lines and the total laid out by [`MARKET_STATEMENT_SCHEMA`].
+    pub fn market_table(&self) -> HistoryTable {
+        let mut table = HistoryTable::new(MARKET_STATEMENT_SCHEMA);
+        let total = MarketStatement {
+            ticker: "TOTAL".to_string(),
+            ..self.total.clone()
+        };
+        for m in self.markets.iter().chain([&total]) {
+            table.rows.push(vec![
+                HistoryValue::from(m.ticker.clone()),
+                m.contracts_closed.into(),
+                m.cost_basis.into(),
+                m.proceeds.into(),
+                m.realized_gain.into(),
+                m.settlement_revenue.into(),
+                m.fees.into(),
+            ]);
+        }
+        table
+    }
+
+    /// Writes `statement-<period>.csv` and `realized-<period>.csv` into `dir`, replacing
+    /// existing files.
+    pub fn write_csv(&self, dir: impl AsRef<Path>) -> Result<(), KalshiError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).map_err(|e| io_error(dir, e))?;
+        self.market_table()
+            .write_csv(&dir.join(format!("statement-{}.csv", self.period)), false)?;
+        self.realized_table()
+            .write_csv(&dir.join(format!("realized-{}.csv", self.period)), false)
+    }
+
+    /// The statement as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, KalshiError> {
+        serde_json::to_string_pretty(self).map_err(|e| KalshiError::InternalError(e.to_string()))
+    }
+
+    /// Writes [`to_json`](Self::to_json) to `path`.
+    pub fn write_json(&self, path: impl AsRef<Path>) -> Result<(), KalshiError> {
+        let path = path.as_ref();
+        fs::write(path, self.to_json()?).map_err(|e| io_error(path, e))
+    }
+}
+
+/// Collects fills and settlements for a [`TaxLedger`].
+#[derive(Debug, Clone, Default)]
+pub struct StatementBuilder {
+    method: LotMethod,
+    fees: FeeCalculator,
+    fills: Vec<Fill>,
+    settlements: Vec<Settlement>,
+    seen_fills: HashSet<String>,
+    seen_settlements: HashSet<String>,
+}
+
+impl StatementBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_lot_method(mut self, method: LotMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Estimates fill fees with `fees` instead of the default fee schedule.
+    pub fn with_fee_calculator(mut self, fees: FeeCalculator) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Adds fills, skipping trade IDs already added.
+    pub fn add_fills(mut self, fills: &[Fill]) -> Self {
+        for f in fills {
+            if self.seen_fills.insert(f.trade_id.clone()) {
+                self.fills.push(f.clone());
+            }
+        }
+        self
+    }
+
+    /// Adds settlements, skipping markets already added.
+    pub fn add_settlements(mut self, settlements: &[Settlement]) -> Self {
+        for s in settlements {
+            if self.seen_settlements.insert(s.ticker.clone()) {
+                self.settlements.push(s.clone());
+            }
+        }
+        self
+    }
+
+    /// Fetches all fills and settlements between `min_ts` and `max_ts` (Unix seconds).
+    ///
+    /// Cost basis needs the fills that opened each position, so leave `min_ts` unset
+    /// unless the account had no open positions at that time.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - Where to read fills and settlements from.
+    /// * `min_ts` - Earliest fill and settlement to fetch.
+    /// * `max_ts` - Latest fill and settlement to fetch.
+    ///
+    pub async fn fetch<B: Portfolio>(
+        mut self,
+        backend: &B,
+        min_ts: Option<i64>,
+        max_ts: Option<i64>,
+    ) -> Result<Self, KalshiError> {
+        let mut cursor = None;
+        loop {
+            let (next, fills) = backend
+                .get_fills(None, None, min_ts, max_ts, Some(PAGE_SIZE as i32), cursor)
+                .await?;
+            self = self.add_fills(&fills);
+            match next {
+                Some(c) if !c.is_empty() && !fills.is_empty() => cursor = Some(c),
+                _ => break,
+            }
+        }
+        let mut cursor = None;
+        loop {
+            let (next, settlements) = backend
+                .get_settlements(Some(PAGE_SIZE), cursor, None, None, min_ts, max_ts)
+                .await?;
+            self = self.add_settlements(&settlements);
+            match next {
+                Some(c) if !c.is_empty() && !settlements.is_empty() => cursor = Some(c),
+                _ => break,
+            }
+        }
+        Ok(self)
+    }
+
+    /// Replays the collected fills and settlements in time order. Those whose time does
+    /// not parse are left out and listed in [`TaxLedger::unparsed`].
+    pub fn build(self) -> TaxLedger {
+        let mut activity: Vec<(DateTime<Utc>, u8, usize)> = Vec::new();
+        let mut unparsed = Vec::new();
+        for (i, f) in self.fills.iter().enumerate() {
+            match parse_time(&f.created_time) {
+                Some(at) => activity.push((at, 0, i)),
+                None => unparsed.push(f.trade_id.clone()),
+            }
+        }
+        for (i, s) in self.settlements.iter().enumerate() {
+            match parse_time(&s.settled_time) {
+                Some(at) => activity.push((at, 1, i)),
+                None => unparsed.push(s.ticker.clone()),
+            }
+        }
+        // Fills before settlements at the same instant; input order otherwise.
+        activity.sort();
+
+        let mut ledger = TaxLedger {
+            method: self.method,
+            fees: self.fees,
+            lots: BTreeMap::new(),
+            realized: Vec::new(),
+            fees_paid: Vec::new(),
+            settlements: Vec::new(),
+            unparsed,
+        };
+        for (at, kind, i) in activity {
+            if kind == 0 {
+                ledger.apply_fill(&self.fills[i], at);
+            } else {
+                ledger.apply_settlement(&self.settlements[i], at);
+            }
+        }
+        ledger
+    }
+}
+
+/// Closed and open lots from a [`StatementBuilder`].
+#[derive(Debug, Clone)]
+pub struct TaxLedger {
+    method: LotMethod,
+    fees: FeeCalculator,
+    lots: BTreeMap<String, Vec<TaxLot>>,
+    realized: Vec<RealizedLot>,
+    /// (ticker, time, estimated fee) per fill.
+    fees_paid: Vec<(String, DateTime<Utc>, i64)>,
+    /// (ticker, time, revenue) per settlement.
+    settlements: Vec<(String, DateTime<Utc>, i64)>,
+    /// Trade IDs and settlement tickers skipped for an unparsable time.
+    unparsed: Vec<String>,
+}
+
+impl TaxLedger {
+    /// Every closed lot, in closing order.
+    pub fn realized(&self) -> &[RealizedLot] {
+        &self.realized
+    }
+
+    /// Trade IDs of fills and tickers of settlements left out of the ledger because their
+    /// time could not be parsed.
+    pub fn unparsed(&self) -> &[String] {
+        &self.unparsed
+    }
+
+    /// Lots still open, by ticker and then age.
+    pub fn open_lots(&self) -> Vec<&TaxLot> {
+        self.lots.values().flatten().collect()
+    }
+
+    /// Total estimated fees of all fills.
+    pub fn total_fees(&self) -> i64 {
+        self.fees_paid.iter().map(|(_, _, fee)| fee).sum()
+    }
+
+    /// One statement per period with activity, oldest first.
+    pub fn statements(&self, period: StatementPeriod) -> Vec<Statement> {
+        let mut periods: BTreeMap<String, Statement> = BTreeMap::new();
+        for r in &self.realized {
+            let line = market_line(&mut periods, period, r.closed, &r.ticker);
+            line.contracts_closed += r.count;
+            line.cost_basis += r.cost_basis;
+            line.proceeds += r.proceeds;
+            line.realized_gain += r.realized_gain();
+            let (label, _, _) = period.bounds(r.closed);
+            if let Some(statement) = periods.get_mut(&label) {
+                statement.realized.push(r.clone());
+            }
+        }
+        for (ticker, at, fee) in &self.fees_paid {
+            market_line(&mut periods, period, *at, ticker).fees += fee;
+        }
+        for (ticker, at, revenue) in &self.settlements {
+            market_line(&mut periods, period, *at, ticker).settlement_revenue += revenue;
+        }
+        periods
+            .into_values()
+            .map(|mut statement| {
+                statement.markets.sort_by(|a, b| a.ticker.cmp(&b.ticker));
+                for m in &statement.markets {
+                    statement.total.add(m);
+                }
+                statement
+            })
+            .collect()
+    }
+
+    fn apply_fill(&mut self, fill: &Fill, at: DateTime<Utc>) {
+        // Selling one side is buying the other at the complementary price.
+        let side = match fill.action {
+            Action::Buy => fill.side,
+            Action::Sell => opposite(fill.side),
+        };
+        let price = match side {
+            Side::Yes => fill.yes_price,
+            Side::No => fill.no_price,
+        };
+        let role = if fill.is_taker {
+            FeeRole::Taker
+        } else {
+            FeeRole::Maker
+        };
+        let fee = self
+            .fees
+            .fee_for(&fill.ticker, fill.count, price as i32, role, at);
+        self.fees_paid.push((fill.ticker.clone(), at, fee));
+
+        let count = fill.count as i64;
+        if count <= 0 {
+            return;
+        }
+        let lots = self.lots.entry(fill.ticker.clone()).or_default();
+        let order = close_order(&self.method, lots, opposite(side), &fill.trade_id);
+        let closed = order.iter().map(|&i| lots[i].count).sum::<i64>().min(count);
+        let close_fee = fee * closed / count;
+        // Each held contract of the other side is redeemed with the new one for 100¢.
+        let proceeds = closed * (100 - price) - close_fee;
+        let mut done = 0;
+        for i in order {
+            if done == closed {
+                break;
+            }
+            let lot = &mut lots[i];
+            let k = lot.count.min(closed - done);
+            let basis = lot.cost_basis * k / lot.count;
+            lot.count -= k;
+            lot.cost_basis -= basis;
+            self.realized.push(RealizedLot {
+                ticker: fill.ticker.clone(),
+                side: lot.side,
+                lot_id: Some(lot.lot_id.clone()),
+                acquired: Some(lot.acquired),
+                closed: at,
+                close_kind: CloseKind::Sale,
+                closing_id: fill.trade_id.clone(),
+                count: k,
+                cost_basis: basis,
+                proceeds: proceeds * (done + k) / closed - proceeds * done / closed,
+            });
+            done += k;
+        }
+        lots.retain(|lot| lot.count > 0);
+
+        let opened = count - closed;
+        if opened > 0 {
+            lots.push(TaxLot {
+                ticker: fill.ticker.clone(),
+                side,
+                lot_id: fill.trade_id.clone(),
+                acquired: at,
+                count: opened,
+                cost_basis: opened * price + fee - close_fee,
+            });
+        }
+    }
+
+    fn apply_settlement(&mut self, s: &Settlement, at: DateTime<Utc>) {
+        self.settlements.push((s.ticker.clone(), at, s.revenue));
+        let yes_value = settlement_value(s);
+        let lots = self.lots.remove(&s.ticker).unwrap_or_default();
+        for (side, value, reported, reported_cost) in [
+            (Side::Yes, yes_value, s.yes_count, s.yes_total_cost),
+            (Side::No, 100 - yes_value, s.no_count, s.no_total_cost),
+        ] {
+            let mut held = 0;
+            for lot in lots.iter().filter(|lot| lot.side == side) {
+                held += lot.count;
+                self.realized.push(RealizedLot {
+                    ticker: s.ticker.clone(),
+                    side,
+                    lot_id: Some(lot.lot_id.clone()),
+                    acquired: Some(lot.acquired),
+                    closed: at,
+                    close_kind: CloseKind::Settlement,
+                    closing_id: s.ticker.clone(),
+                    count: lot.count,
+                    cost_basis: lot.cost_basis,
+                    proceeds: lot.count * value,
+                });
+            }
+            if reported > held {
+                let missing = reported - held;
+                self.realized.push(RealizedLot {
+                    ticker: s.ticker.clone(),
+                    side,
+                    lot_id: None,
+                    acquired: None,
+                    closed: at,
+                    close_kind: CloseKind::Settlement,
+                    closing_id: s.ticker.clone(),
+                    count: missing,
+                    cost_basis: reported_cost * missing / reported,
+                    proceeds: missing * value,
+                });
+            }
+        }
+    }
+}
+
+/// The statement line for `ticker` in the period containing `at`.
+fn market_line<'a>(
+    periods: &'a mut BTreeMap<String, Statement>,
+    period: StatementPeriod,
+    at: DateTime<Utc>,
+    ticker: &str,
+) -> &'a mut MarketStatement {
+    let (label, start, end) = period.bounds(at);
+    let statement = periods.entry(label.clone()).or_insert_with(|| Statement {
+        period: label,
+        start,
+        end,
+        total: MarketStatement {
+            ticker: "TOTAL".to_string(),
+            ..Default::default()
+        },
+        markets: Vec::new(),
+        realized: Vec::new(),
+    });
+    match statement.markets.iter().position(|m| m.ticker == ticker) {
+        Some(i) => &mut statement.markets[i],
+        None => {
+            statement.markets.push(MarketStatement {
+                ticker: ticker.to_string(),
+                ..Default::default()
+            });
+            statement.markets.last_mut().unwrap()
+        }
+    }
+}
+
+/// Indices of the open `side` lots in the order a closing fill consumes them.
+fn close_order(method: &LotMethod, lots: &[TaxLot], side: Side, trade_id: &str) -> Vec<usize> {
+    let mut order: Vec<usize> = Vec::new();
+    if let LotMethod::SpecificLot(selection) = method {
+        for id in selection.get(trade_id).into_iter().flatten() {
+            let found = lots
+                .iter()
+                .position(|lot| lot.side == side && &lot.lot_id == id);
+            if let Some(i) = found.filter(|i| !order.contains(i)) {
+                order.push(i);
+            }
+        }
+    }
+    for (i, lot) in lots.iter().enumerate() {
+        if lot.side == side && !order.contains(&i) {
+            order.push(i);
+        }
+    }
+    order
+}
+
+/// Value of one Yes contract at settlement, in cents.
+///
+/// Binary results pay 100 or 0. For other results (scalar or void) the value is solved
+/// from the reported revenue and counts.
+fn settlement_value(s: &Settlement) -> i64 {
+    match s.market_result.as_str() {
+        "yes" => 100,
+        "no" => 0,
+        _ if s.yes_count != s.no_count => {
+            ((s.revenue - 100 * s.no_count) / (s.yes_count - s.no_count)).clamp(0, 100)
+        }
+        // Equal counts pay 100 per pair whatever the value.
+        _ => 50,
+    }
+}
+
+fn opposite(side: Side) -> Side {
+    match side {
+        Side::Yes => Side::No,
+        Side::No => Side::Yes,
+    }
+}
+
+fn side_str(side: Side) -> &'static str {
+    match side {
+        Side::Yes => "yes",
+        Side::No => "no",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(trade_id: &str, side: Side, action: Action, count: i32, yes: i64, at: &str) -> Fill {
+        Fill {
+            action,
+            count,
+            created_time: at.to_string(),
+            is_taker: false,
+            no_price: 100 - yes,
+            order_id: format!("o-{}", trade_id),
+            side,
+            ticker: "KXTEST-24JAN01-T1".to_string(),
+            trade_id: trade_id.to_string(),
+            yes_price: yes,
+        }
+    }
+
+    fn settlement(result: &str, yes_count: i64, yes_total_cost: i64, at: &str) -> Settlement {
+        Settlement {
+            market_result: result.to_string(),
+            no_count: 0,
+            no_total_cost: 0,
+            revenue: if result == "yes" { yes_count * 100 } else { 0 },
+            settled_time: at.to_string(),
+            ticker: "KXTEST-24JAN01-T1".to_string(),
+            yes_count,
+            yes_total_cost,
+        }
+    }
+
+    // Maker fills in a series without maker fees, so amounts are fee-free.
+    fn builder() -> StatementBuilder {
+        StatementBuilder::new().add_fills(&[
+            fill("a", Side::Yes, Action::Buy, 10, 30, "2024-01-05T00:00:00Z"),
+            fill("b", Side::Yes, Action::Buy, 10, 50, "2024-01-06T00:00:00Z"),
+            fill("c", Side::Yes, Action::Sell, 10, 60, "2024-02-01T00:00:00Z"),
+        ])
+    }
+
+    #[test]
+    fn test_fifo_and_specific_lot_realize_different_gains() {
+        let fifo = builder()
+            .add_fills(&[fill("x", Side::Yes, Action::Sell, 10, 90, "")])
+            .build();
+        assert_eq!(fifo.unparsed(), ["x"]);
+        assert_eq!(fifo.realized().len(), 1);
+        assert_eq!(fifo.realized()[0].lot_id.as_deref(), Some("a"));
+        assert_eq!(fifo.realized()[0].realized_gain(), 300);
+
+        let specific = builder()
+            .with_lot_method(LotMethod::SpecificLot(HashMap::from([(
+                "c".to_string(),
+                vec!["b".to_string()],
+            )])))
+            .build();
+        assert_eq!(specific.realized()[0].lot_id.as_deref(), Some("b"));
+        assert_eq!(specific.realized()[0].realized_gain(), 100);
+        assert_eq!(specific.open_lots()[0].lot_id, "a");
+    }
+
+    #[test]
+    fn test_settlement_closes_lots_and_statements_split_by_month() {
+        let ledger = builder()
+            // Buying No while long Yes redeems the pair: 5 of lot "b" close at 100 − 45.
+            .add_fills(&[fill(
+                "d",
+                Side::No,
+                Action::Buy,
+                5,
+                55,
+                "2024-02-02T00:00:00Z",
+            )])
+            .add_settlements(&[settlement("yes", 5, 250, "2024-03-01T00:00:00Z")])
+            .build();
+        assert!(ledger.open_lots().is_empty());
+
+        let months = ledger.statements(StatementPeriod::Monthly);
+        let labels: Vec<&str> = months.iter().map(|s| s.period.as_str()).collect();
+        assert_eq!(labels, ["2024-01", "2024-02", "2024-03"]);
+        assert_eq!(months[0].total.contracts_closed, 0);
+        // Feb: lot "a" sold 60 vs 30, half of "b" redeemed at 55 vs 50.
+        assert_eq!(months[1].total.realized_gain, 300 + 25);
+        // Mar: the rest of "b" pays 100 vs 50.
+        assert_eq!(months[2].total.realized_gain, 250);
+        assert_eq!(months[2].total.settlement_revenue, 500);
+
+        let year = &ledger.statements(StatementPeriod::Annual)[0];
+        assert_eq!(year.period, "2024");
+        assert_eq!(year.total.realized_gain, 575);
+        assert_eq!(year.market_table().rows.len(), 2);
+        assert!(year
+            .to_json()
+            .unwrap()
+            .contains("\"close_kind\": \"settlement\""));
+    }
+}
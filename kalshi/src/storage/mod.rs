@@ -0,0 +1,780 @@
+//! SQLite persistence for orders, fills, positions and settlements.
+//!
+//! Enabled with the `storage` feature. A [`Store`] keeps an audit trail that survives
+//! restarts:
+//!
+//! - orders from `get_orders`, upserted by order ID so status changes overwrite older rows,
+//! - fills from `get_fills` and the `fill` WebSocket channel, deduplicated by trade ID,
+//! - settlements from `get_settlements`, one row per market,
+//! - the latest position per market and event from `get_positions` and the
+//!   `market_position` WebSocket channel.
+//!
+//! The schema is created and upgraded by numbered migrations tracked in SQLite's
+//! `user_version`, so opening an older database brings it up to date.
+//!
+//! Fills carry no fee, so each fill's fee is estimated from a [`FeeCalculator`] when it is
+//! stored. The exchange-reported totals are in the `fees_paid` column of the positions.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use kalshi::Store;
+//!
+//! let store = Store::open("audit.db")?;
+//! store.sync(&kalshi).await?;
+//! while let Some(msg) = stream.next().await {
+//!     store.handle_message(&msg?)?;
+//! }
+//! for day in store.daily_pnl(from, to)? {
+//!     println!("{} net {}¢ (fees {}¢)", day.date, day.net(), day.fees);
+//! }
+//! ```
+
+use crate::fees::{FeeCalculator, FeeRole};
+use crate::kalshi_error::*;
+use crate::positions::centi_cents_to_cents;
+use crate::utils::parse_ts;
+use crate::{
+    Action, EventPosition, Fill, FillMsg, MarketPosition, MarketPositionMsg, Order, Portfolio,
+    Settlement, Side, Trading, WebSocketMessage,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Page size used when syncing from the API.
+const PAGE_SIZE: i64 = 200;
+
+/// Schema migrations, applied in order. The database's `user_version` is the number of
+/// migrations already applied. Never edit a released migration; append a new one.
+const MIGRATIONS: &[&str] = &[
+    // 1: initial schema
+    "CREATE TABLE orders (
+        order_id TEXT PRIMARY KEY,
+        client_order_id TEXT,
+        ticker TEXT NOT NULL,
+        side TEXT NOT NULL,
+        action TEXT NOT NULL,
+        order_type TEXT NOT NULL,
+        status TEXT NOT NULL,
+        yes_price INTEGER,
+        no_price INTEGER,
+        initial_count INTEGER,
+        fill_count INTEGER,
+        remaining_count INTEGER,
+        taker_fees INTEGER,
+        maker_fees INTEGER,
+        created_time TEXT,
+        last_update_time TEXT,
+        raw TEXT NOT NULL
+    );
+    CREATE INDEX orders_ticker ON orders (ticker);
+
+    CREATE TABLE fills (
+        trade_id TEXT PRIMARY KEY,
+        order_id TEXT NOT NULL,
+        ticker TEXT NOT NULL,
+        side TEXT NOT NULL,
+        action TEXT NOT NULL,
+        count INTEGER NOT NULL,
+        yes_price INTEGER NOT NULL,
+        no_price INTEGER NOT NULL,
+        is_taker INTEGER,
+        ts INTEGER NOT NULL,
+        estimated_fee INTEGER NOT NULL,
+        source TEXT NOT NULL
+    );
+    CREATE INDEX fills_ts ON fills (ts);
+    CREATE INDEX fills_ticker ON fills (ticker);
+
+    CREATE TABLE settlements (
+        ticker TEXT PRIMARY KEY,
+        market_result TEXT NOT NULL,
+        yes_count INTEGER NOT NULL,
+        yes_total_cost INTEGER NOT NULL,
+        no_count INTEGER NOT NULL,
+        no_total_cost INTEGER NOT NULL,
+        revenue INTEGER NOT NULL,
+        settled_time TEXT NOT NULL,
+        ts INTEGER NOT NULL
+    );
+    CREATE INDEX settlements_ts ON settlements (ts);
+
+    CREATE TABLE market_positions (
+        ticker TEXT PRIMARY KEY,
+        position INTEGER NOT NULL,
+        market_exposure INTEGER NOT NULL,
+        realized_pnl INTEGER NOT NULL,
+        fees_paid INTEGER NOT NULL,
+        total_traded INTEGER,
+        resting_orders_count INTEGER,
+        updated_ts INTEGER NOT NULL
+    );
+
+    CREATE TABLE event_positions (
+        event_ticker TEXT PRIMARY KEY,
+        event_exposure INTEGER NOT NULL,
+        realized_pnl INTEGER NOT NULL,
+        fees_paid INTEGER NOT NULL,
+        total_cost INTEGER NOT NULL,
+        resting_order_count INTEGER,
+        updated_ts INTEGER NOT NULL
+    );",
+    // 2: REST sync watermarks
+    "CREATE TABLE sync_state (
+        name TEXT PRIMARY KEY,
+        ts INTEGER NOT NULL
+    );",
+];
+
+/// Cash movements of one UTC day, in cents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyPnl {
+    pub date: NaiveDate,
+    /// Sale proceeds minus purchase costs of the day's fills.
+    pub trading_cash_flow: i64,
+    /// Payouts of markets settled that day.
+    pub settlement_revenue: i64,
+    /// Estimated fees of the day's fills.
+    pub fees: i64,
+}
+
+impl DailyPnl {
+    /// Cash P&L of the day: trading cash flow plus settlement revenue, less fees.
+    ///
+    /// This is cash-basis: buying a contract is a loss on the day it is bought and its
+    /// payout a gain on the day it settles.
+    pub fn net(&self) -> i64 {
+        self.trading_cash_flow + self.settlement_revenue - self.fees
+    }
+}
+
+/// Fee totals for one market, in cents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeTotal {
+    pub ticker: String,
+    pub fills: i64,
+    pub taker_fees: i64,
+    pub maker_fees: i64,
+}
+
+impl FeeTotal {
+    pub fn total(&self) -> i64 {
+        self.taker_fees + self.maker_fees
+    }
+}
+
+/// Rows added or updated by [`Store::sync`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StoreSyncReport {
+    pub orders: usize,
+    pub new_fills: usize,
+    pub new_settlements: usize,
+    pub positions: usize,
+}
+
+/// A SQLite-backed audit trail. See the [module documentation](crate::storage).
+///
+/// Methods block on SQLite; each call is a short transaction.
+pub struct Store {
+    conn: Mutex<Connection>,
+    fees: FeeCalculator,
+}
+
+impl Store {
+    /// Opens or creates the database at `path` and applies pending migrations.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, KalshiError> {
+        Self::from_connection(Connection::open(path).map_err(sql_error)?)
+    }
+
+    /// Opens a private in-memory database, for tests.
+    pub fn open_in_memory() -> Result<Self, KalshiError> {
+        Self::from_connection(Connection::open_in_memory().map_err(sql_error)?)
+    }
+
+    fn from_connection(mut conn: Connection) -> Result<Self, KalshiError> {
+        migrate(&mut conn)?;
+        Ok(Store {
+            conn: Mutex::new(conn),
+            fees: FeeCalculator::default(),
+        })
+    }
+
+    /// Estimates fill fees with `fees` instead of the default fee schedule.
+    pub fn with_fee_calculator(mut self, fees: FeeCalculator) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Number of migrations applied to the database.
+    pub fn schema_version(&self) -> Result<usize, KalshiError> {
+        let conn = self.lock();
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |r| r.get(0))
+            .map_err(sql_error)?;
+        Ok(version as usize)
+    }
+
+    /// Inserts or updates orders by order ID.
+    pub fn record_orders(&self, orders: &[Order]) -> Result<usize, KalshiError> {
+        let mut conn = self.lock();
+        let tx = conn.transaction().map_err(sql_error)?;
+        for o in orders {
+            let raw =
+                serde_json::to_string(o).map_err(|e| KalshiError::InternalError(e.to_string()))?;
+            tx.execute(
+                "INSERT INTO orders (order_id, client_order_id, ticker, side, action, order_type,
+                    status, yes_price, no_price, initial_count, fill_count, remaining_count,
+                    taker_fees, maker_fees, created_time, last_update_time, raw)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+                 ON CONFLICT (order_id) DO UPDATE SET
+                    status = excluded.status,
+                    yes_price = excluded.yes_price,
+                    no_price = excluded.no_price,
+                    initial_count = excluded.initial_count,
+                    fill_count = excluded.fill_count,
+                    remaining_count = excluded.remaining_count,
+                    taker_fees = excluded.taker_fees,
+                    maker_fees = excluded.maker_fees,
+                    last_update_time = excluded.last_update_time,
+                    raw = excluded.raw",
+                params![
+                    o.order_id,
+                    o.client_order_id,
+                    o.ticker,
+                    enum_str(&o.side),
+                    enum_str(&o.action),
+                    o.r#type,
+                    enum_str(&o.status),
+                    o.yes_price,
+                    o.no_price,
+                    o.initial_count,
+                    o.fill_count,
+                    o.remaining_count,
+                    o.taker_fees,
+                    o.maker_fees,
+                    o.created_time,
+                    o.last_update_time,
+                    raw,
+                ],
+            )
+            .map_err(sql_error)?;
+        }
+        tx.commit().map_err(sql_error)?;
+        Ok(orders.len())
+    }
+
+    /// Stores fills from `get_fills`. Returns how many were new.
+    pub fn record_fills(&self, fills: &[Fill]) -> Result<usize, KalshiError> {
+        let rows: Vec<FillRow> = fills
+            .iter()
+            .map(|f| FillRow {
+                trade_id: &f.trade_id,
+                order_id: &f.order_id,
+                ticker: &f.ticker,
+                side: f.side,
+                action: f.action,
+                count: f.count,
+                yes_price: f.yes_price,
+                no_price: f.no_price,
+                is_taker: Some(f.is_taker),
+                ts: parse_ts(&f.created_time).unwrap_or_else(|| Utc::now().timestamp()),
+                source: "rest",
+            })
+            .collect();
+        self.insert_fills(&rows)
+    }
+
+    /// Stores a fill from the `fill` WebSocket channel. Returns false if it was already
+    /// stored, for example by an earlier `get_fills` sync.
+    pub fn record_fill_msg(&self, fill: &FillMsg) -> Result<bool, KalshiError> {
+        let yes_price = fill
+            .yes_price
+            .or_else(|| fill.no_price.map(|p| 100 - p))
+            .unwrap_or_default() as i64;
+        let row = FillRow {
+            trade_id: &fill.trade_id,
+            order_id: &fill.order_id,
+            ticker: &fill.market_ticker,
+            side: fill.side,
+            action: fill.action,
+            count: fill.count,
+            yes_price,
+            no_price: fill.no_price.map(i64::from).unwrap_or(100 - yes_price),
+            is_taker: fill.is_taker,
+            ts: fill.ts.unwrap_or_else(|| Utc::now().timestamp()),
+            source: "ws",
+        };
+        Ok(self.insert_fills(&[row])? == 1)
+    }
+
+    fn insert_fills(&self, fills: &[FillRow]) -> Result<usize, KalshiError> {
+        let mut conn = self.lock();
+        let tx = conn.transaction().map_err(sql_error)?;
+        let mut added = 0;
+        for f in fills {
+            let price = match f.side {
+                Side::Yes => f.yes_price,
+                Side::No => f.no_price,
+            } as i32;
+            let role = if f.is_taker == Some(false) {
+                FeeRole::Maker
+            } else {
+                FeeRole::Taker
+            };
+            let at = DateTime::from_timestamp(f.ts, 0).unwrap_or_default();
+            let fee = self.fees.fee_for(f.ticker, f.count, price, role, at);
+            added += tx
+                .execute(
+                    "INSERT OR IGNORE INTO fills (trade_id, order_id, ticker, side, action, count,
+                        yes_price, no_price, is_taker, ts, estimated_fee, source)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                    params![
+                        f.trade_id,
+                        f.order_id,
+                        f.ticker,
+                        enum_str(&f.side),
+                        enum_str(&f.action),
+                        f.count,
+                        f.yes_price,
+                        f.no_price,
+                        f.is_taker,
+                        f.ts,
+                        fee,
+                        f.source,
+                    ],
+                )
+                .map_err(sql_error)?;
+        }
+        tx.commit().map_err(sql_error)?;
+        Ok(added)
+    }
+
+    /// Stores settlements. Returns how many were new.
+    pub fn record_settlements(&self, settlements: &[Settlement]) -> Result<usize, KalshiError> {
+        let mut conn = self.lock();
+        let tx = conn.transaction().map_err(sql_error)?;
+        let mut added = 0;
+        for s in settlements {
+            added += tx
+                .execute(
+                    "INSERT OR IGNORE INTO settlements (ticker, market_result, yes_count,
+                        yes_total_cost, no_count, no_total_cost, revenue, settled_time, ts)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        s.ticker,
+                        s.market_result,
+                        s.yes_count,
+                        s.yes_total_cost,
+                        s.no_count,
+                        s.no_total_cost,
+                        s.revenue,
+                        s.settled_time,
+                        parse_ts(&s.settled_time).unwrap_or_else(|| Utc::now().timestamp()),
+                    ],
+                )
+                .map_err(sql_error)?;
+        }
+        tx.commit().map_err(sql_error)?;
+        Ok(added)
+    }
+
+    /// Replaces the stored positions of the given events and markets.
+    pub fn record_positions(
+        &self,
+        events: &[EventPosition],
+        markets: &[MarketPosition],
+    ) -> Result<(), KalshiError> {
+        let now = Utc::now().timestamp();
+        let mut conn = self.lock();
+        let tx = conn.transaction().map_err(sql_error)?;
+        for e in events {
+            tx.execute(
+                "INSERT OR REPLACE INTO event_positions (event_ticker, event_exposure,
+                    realized_pnl, fees_paid, total_cost, resting_order_count, updated_ts)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    e.event_ticker,
+                    e.event_exposure,
+                    e.realized_pnl,
+                    e.fees_paid,
+                    e.total_cost,
+                    e.resting_order_count,
+                    now,
+                ],
+            )
+            .map_err(sql_error)?;
+        }
+        for m in markets {
+            tx.execute(
+                "INSERT OR REPLACE INTO market_positions (ticker, position, market_exposure,
+                    realized_pnl, fees_paid, total_traded, resting_orders_count, updated_ts)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    m.ticker,
+                    m.position,
+                    m.market_exposure,
+                    m.realized_pnl,
+                    m.fees_paid,
+                    m.total_traded,
+                    m.resting_orders_count,
+                    now,
+                ],
+            )
+            .map_err(sql_error)?;
+        }
+        tx.commit().map_err(sql_error)
+    }
+
+    /// Stores a position update from the `market_position` WebSocket channel.
+    ///
+    /// Amounts arrive in centi-cents and are stored in cents. Columns the message does not
+    /// carry keep their stored values.
+    pub fn record_position_msg(&self, msg: &MarketPositionMsg) -> Result<(), KalshiError> {
+        let cents = |v: i64| centi_cents_to_cents(v).round() as i64;
+        self.lock()
+            .execute(
+                "INSERT INTO market_positions (ticker, position, market_exposure, realized_pnl,
+                    fees_paid, updated_ts)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (ticker) DO UPDATE SET
+                    position = excluded.position,
+                    market_exposure = excluded.market_exposure,
+                    realized_pnl = excluded.realized_pnl,
+                    fees_paid = excluded.fees_paid,
+                    updated_ts = excluded.updated_ts",
+                params![
+                    msg.market_ticker,
+                    msg.position,
+                    cents(msg.position_cost),
+                    cents(msg.realized_pnl),
+                    cents(msg.fees_paid),
+                    Utc::now().timestamp(),
+                ],
+            )
+            .map_err(sql_error)?;
+        Ok(())
+    }
+
+    /// Stores `fill` and `market_position` messages; other messages are ignored.
+    pub fn handle_message(&self, msg: &WebSocketMessage) -> Result<(), KalshiError> {
+        match msg {
+            WebSocketMessage::Fill(fill) => self.record_fill_msg(fill).map(|_| ()),
+            WebSocketMessage::MarketPosition(pos) => self.record_position_msg(pos),
+            _ => Ok(()),
+        }
+    }
+
+    /// Pulls orders, fills, settlements and positions from `backend` and stores them.
+    ///
+    /// Fills and settlements are fetched from the newest stored timestamp onwards; orders
+    /// and positions are fetched in full so status changes are picked up.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(StoreSyncReport)`: Row counts stored.
+    /// - `Err(KalshiError)`: A request or database error. Pages stored before the error
+    ///   are kept.
+    ///
+    pub async fn sync<B: Trading + Portfolio>(
+        &self,
+        backend: &B,
+    ) -> Result<StoreSyncReport, KalshiError> {
+        let mut report = StoreSyncReport::default();
+
+        let mut cursor = None;
+        loop {
+            let (next, orders) = backend
+                .get_orders(None, None, None, None, None, Some(PAGE_SIZE as i32), cursor)
+                .await?;
+            report.orders += self.record_orders(&orders)?;
+            match next {
+                Some(c) if !c.is_empty() && !orders.is_empty() => cursor = Some(c),
+                _ => break,
+            }
+        }
+
+        // Pages arrive newest first, so a watermark only moves once every page is stored;
+        // an interrupted sync starts again from the previous one.
+        let since = self.watermark("fills")?;
+        let mut newest = since;
+        let mut cursor = None;
+        loop {
+            let (next, fills) = backend
+                .get_fills(None, None, since, None, Some(PAGE_SIZE as i32), cursor)
+                .await?;
+            report.new_fills += self.record_fills(&fills)?;
+            newest = newest.max(fills.iter().filter_map(|f| parse_ts(&f.created_time)).max());
+            match next {
+                Some(c) if !c.is_empty() && !fills.is_empty() => cursor = Some(c),
+                _ => break,
+            }
+        }
+        self.set_watermark("fills", newest)?;
+
+        let since = self.watermark("settlements")?;
+        let mut newest = since;
+        let mut cursor = None;
+        loop {
+            let (next, settlements) = backend
+                .get_settlements(Some(PAGE_SIZE), cursor, None, None, since, None)
+                .await?;
+            report.new_settlements += self.record_settlements(&settlements)?;
+            newest = newest.max(
+                settlements
+                    .iter()
+                    .filter_map(|s| parse_ts(&s.settled_time))
+                    .max(),
+            );
+            match next {
+                Some(c) if !c.is_empty() && !settlements.is_empty() => cursor = Some(c),
+                _ => break,
+            }
+        }
+        self.set_watermark("settlements", newest)?;
+
+        let mut cursor = None;
+        loop {
+            let (next, events, markets) = backend
+                .get_positions(Some(PAGE_SIZE), cursor, None, None, None, None)
+                .await?;
+            self.record_positions(&events, &markets)?;
+            report.positions += markets.len();
+            match next {
+                Some(c) if !c.is_empty() && !markets.is_empty() => cursor = Some(c),
+                _ => break,
+            }
+        }
+        Ok(report)
+    }
+
+    /// Cash P&L per UTC day in `[from, to]`, for days with activity.
+    pub fn daily_pnl(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<DailyPnl>, KalshiError> {
+        let (start, end) = day_bounds(from, to);
+        let conn = self.lock();
+        let mut stmt = conn
+            .prepare(
+                "SELECT day, SUM(cash), SUM(revenue), SUM(fee) FROM (
+                    SELECT date(ts, 'unixepoch') AS day,
+                        count * (CASE side WHEN 'yes' THEN yes_price ELSE no_price END)
+                            * (CASE action WHEN 'sell' THEN 1 ELSE -1 END) AS cash,
+                        0 AS revenue,
+                        estimated_fee AS fee
+                    FROM fills WHERE ts >= ?1 AND ts < ?2
+                    UNION ALL
+                    SELECT date(ts, 'unixepoch'), 0, revenue, 0
+                    FROM settlements WHERE ts >= ?1 AND ts < ?2
+                 )
+                 GROUP BY day ORDER BY day",
+            )
+            .map_err(sql_error)?;
+        let rows = stmt
+            .query_map(params![start, end], |r| {
+                Ok((
+                    r.get::<_, String>(0)?,
+                    r.get::<_, i64>(1)?,
+                    r.get::<_, i64>(2)?,
+                    r.get::<_, i64>(3)?,
+                ))
+            })
+            .map_err(sql_error)?;
+        let mut days = Vec::new();
+        for row in rows {
+            let (day, trading_cash_flow, settlement_revenue, fees) = row.map_err(sql_error)?;
+            let date = NaiveDate::parse_from_str(&day, "%Y-%m-%d")
+                .map_err(|e| KalshiError::InternalError(format!("bad date {}: {}", day, e)))?;
+            days.push(DailyPnl {
+                date,
+                trading_cash_flow,
+                settlement_revenue,
+                fees,
+            });
+        }
+        Ok(days)
+    }
+
+    /// Estimated fees per market for fills in `[from, to]` (UTC days), sorted by ticker.
+    pub fn fee_totals(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<FeeTotal>, KalshiError> {
+        let (start, end) = day_bounds(from, to);
+        let conn = self.lock();
+        let mut stmt = conn
+            .prepare(
+                "SELECT ticker, COUNT(*),
+                    SUM(CASE WHEN is_taker = 0 THEN 0 ELSE estimated_fee END),
+                    SUM(CASE WHEN is_taker = 0 THEN estimated_fee ELSE 0 END)
+                 FROM fills WHERE ts >= ?1 AND ts < ?2
+                 GROUP BY ticker ORDER BY ticker",
+            )
+            .map_err(sql_error)?;
+        let rows = stmt
+            .query_map(params![start, end], |r| {
+                Ok(FeeTotal {
+                    ticker: r.get(0)?,
+                    fills: r.get(1)?,
+                    taker_fees: r.get(2)?,
+                    maker_fees: r.get(3)?,
+                })
+            })
+            .map_err(sql_error)?;
+        rows.collect::<Result<_, _>>().map_err(sql_error)
+    }
+
+    /// Newest `ts` covered by a completed REST sync of `name`. WebSocket rows and
+    /// interrupted syncs never move it.
+    fn watermark(&self, name: &str) -> Result<Option<i64>, KalshiError> {
+        self.lock()
+            .query_row("SELECT ts FROM sync_state WHERE name = ?1", [name], |r| {
+                r.get(0)
+            })
+            .optional()
+            .map_err(sql_error)
+    }
+
+    fn set_watermark(&self, name: &str, ts: Option<i64>) -> Result<(), KalshiError> {
+        let Some(ts) = ts else {
+            return Ok(());
+        };
+        self.lock()
+            .execute(
+                "INSERT INTO sync_state (name, ts) VALUES (?1, ?2)
+                 ON CONFLICT (name) DO UPDATE SET ts = MAX(ts, excluded.ts)",
+                params![name, ts],
+            )
+            .map_err(sql_error)?;
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A fill from either source, ready to insert.
+struct FillRow<'a> {
+    trade_id: &'a str,
+    order_id: &'a str,
+    ticker: &'a str,
+    side: Side,
+    action: Action,
+    count: i32,
+    yes_price: i64,
+    no_price: i64,
+    is_taker: Option<bool>,
+    ts: i64,
+    source: &'static str,
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), KalshiError> {
+    let applied: i64 = conn
+        .query_row("PRAGMA user_version", [], |r| r.get(0))
+        .map_err(sql_error)?;
+    for (i, sql) in MIGRATIONS.iter().enumerate().skip(applied as usize) {
+        let tx = conn.transaction().map_err(sql_error)?;
+        tx.execute_batch(sql).map_err(sql_error)?;
+        tx.pragma_update(None, "user_version", (i + 1) as i64)
+            .map_err(sql_error)?;
+        tx.commit().map_err(sql_error)?;
+    }
+    Ok(())
+}
+
+/// Unix-second bounds `[start of from, end of to)`.
+fn day_bounds(from: NaiveDate, to: NaiveDate) -> (i64, i64) {
+    let start = from.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    let end = to
+        .succ_opt()
+        .unwrap_or(to)
+        .and_hms_opt(0, 0, 0)
+        .unwrap_or_default()
+        .and_utc();
+    (start.timestamp(), end.timestamp())
+}
+
+/// The serde name of a unit enum variant, e.g. `"yes"` for `Side::Yes`.
+fn enum_str<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn sql_error(e: rusqlite::Error) -> KalshiError {
+    KalshiError::InternalError(format!("sqlite: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(trade_id: &str, action: Action, yes_price: i64, created_time: &str) -> Fill {
+        Fill {
+            action,
+            count: 10,
+            created_time: created_time.to_string(),
+            is_taker: true,
+            no_price: 100 - yes_price,
+            order_id: "o1".to_string(),
+            side: Side::Yes,
+            ticker: "KXTEST-24JAN01-T1".to_string(),
+            trade_id: trade_id.to_string(),
+            yes_price,
+        }
+    }
+
+    #[test]
+    fn test_fills_dedupe_across_sources_and_roll_up_by_day() {
+        let store = Store::open_in_memory().unwrap();
+        assert_eq!(store.schema_version().unwrap(), MIGRATIONS.len());
+
+        let fills = [
+            fill("t1", Action::Buy, 40, "2024-01-01T10:00:00Z"),
+            fill("t2", Action::Sell, 55, "2024-01-02T10:00:00Z"),
+        ];
+        assert_eq!(store.record_fills(&fills).unwrap(), 2);
+        assert_eq!(store.record_fills(&fills).unwrap(), 0);
+
+        let ws = FillMsg {
+            trade_id: "t1".to_string(),
+            order_id: "o1".to_string(),
+            market_ticker: "KXTEST-24JAN01-T1".to_string(),
+            side: Side::Yes,
+            action: Action::Buy,
+            count: 10,
+            post_position: 10,
+            yes_price: Some(40),
+            no_price: None,
+            is_taker: Some(true),
+            client_order_id: None,
+            ts: Some(1_704_103_200),
+        };
+        assert!(!store.record_fill_msg(&ws).unwrap());
+
+        let from = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let days = store.daily_pnl(from, to).unwrap();
+        assert_eq!(days.len(), 2);
+        // 0.07 × 10 × 0.40 × 0.60 = 16.8¢ → 17¢.
+        assert_eq!(days[0].trading_cash_flow, -400);
+        assert_eq!(days[0].fees, 17);
+        assert_eq!(days[1].trading_cash_flow, 550);
+
+        let fees = store.fee_totals(from, to).unwrap();
+        assert_eq!(fees.len(), 1);
+        assert_eq!(fees[0].fills, 2);
+        assert_eq!(fees[0].total(), days[0].fees + days[1].fees);
+
+        // Stored rows, WebSocket or REST, never move the sync watermark by themselves.
+        let newer = FillMsg {
+            trade_id: "t9".to_string(),
+            ts: Some(1_800_000_000),
+            ..ws
+        };
+        assert!(store.record_fill_msg(&newer).unwrap());
+        assert_eq!(store.watermark("fills").unwrap(), None);
+        store.set_watermark("fills", Some(1_704_103_200)).unwrap();
+        store.set_watermark("fills", Some(1_700_000_000)).unwrap();
+        assert_eq!(store.watermark("fills").unwrap(), Some(1_704_103_200));
+    }
+}
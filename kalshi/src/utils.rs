@@ -1,4 +1,5 @@
 use crate::TradingEnvironment;
+use chrono::{DateTime, Utc};
 // MACROS
 
 #[macro_export]
@@ -19,3 +20,17 @@ pub fn build_base_url(trading_env: TradingEnvironment) -> &'static str {
         TradingEnvironment::DemoMode => "https://demo-api.kalshi.co/trade-api/v2",
     }
 }
+
+// Timestamp helpers
+
+/// Parses an RFC 3339 timestamp, as used throughout the API, into UTC.
+pub(crate) fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Parses an RFC 3339 timestamp into Unix seconds.
+pub(crate) fn parse_ts(s: &str) -> Option<i64> {
+    parse_time(s).map(|t| t.timestamp())
+}
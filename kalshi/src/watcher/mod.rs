@@ -0,0 +1,485 @@
+//! Background watcher for exchange announcements, trading status and data freshness.
+//!
+//! [`ExchangeWatcher`] polls [`Kalshi::get_exchange_announcements`],
+//! [`Kalshi::get_exchange_status`] and [`Kalshi::get_user_data_timestamp`] and turns what
+//! changed into [`ExchangeEvent`]s:
+//!
+//! - a new announcement (announcements present on the first poll are taken as already
+//!   seen),
+//! - trading halted or resumed,
+//! - a maintenance window from the [`ExchangeCalendar`] starting soon,
+//! - portfolio data older than the staleness threshold, and fresh again.
+//!
+//! Events are published to a broadcast channel, available as a [`Stream`] from
+//! [`ExchangeWatcher::events`], and handed to every attached [`Notifier`]. A watcher given
+//! an [`ExchangeGate`] also records each status it fetches there, so one poll loop keeps
+//! order paths paused during halts.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use futures_util::StreamExt;
+//! use kalshi::{ExchangeWatcher, StderrNotifier};
+//! use std::sync::Arc;
+//!
+//! let watcher = Arc::new(ExchangeWatcher::new(kalshi.clone()).with_notifier(StderrNotifier));
+//! let mut events = Box::pin(watcher.events());
+//! let task = watcher.spawn();
+//! while let Some(event) = events.next().await {
+//!     println!("{}", event);
+//! }
+//! ```
+
+use crate::gate::is_active;
+use crate::kalshi_error::*;
+use crate::{
+    AnnouncementStatus, ExchangeAnnouncement, ExchangeCalendar, ExchangeGate, ExchangeStatus,
+    Kalshi, UserDataTimestamp,
+};
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+/// Capacity of the broadcast channel carrying [`ExchangeEvent`]s.
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Announcements requested per poll.
+const ANNOUNCEMENT_PAGE_SIZE: i64 = 100;
+
+/// A change seen by an [`ExchangeWatcher`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExchangeEvent {
+    /// An announcement not seen before.
+    NewAnnouncement { announcement: ExchangeAnnouncement },
+    /// Trading stopped.
+    TradingHalted { status: ExchangeStatus },
+    /// Trading resumed after a halt.
+    TradingResumed { status: ExchangeStatus },
+    /// A maintenance window starts within the warning lead time.
+    MaintenanceSoon {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+    /// Portfolio data was last validated longer ago than the staleness threshold.
+    PortfolioDataStale { last_validated: DateTime<Utc> },
+    /// Portfolio data is fresh again after being stale.
+    PortfolioDataFresh { last_validated: DateTime<Utc> },
+}
+
+impl fmt::Display for ExchangeEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExchangeEvent::NewAnnouncement { announcement } => {
+                write!(f, "Announcement: {}", announcement.message)
+            }
+            ExchangeEvent::TradingHalted { status } => write!(
+                f,
+                "Trading halted (trading_active: {}, exchange_active: {})",
+                status.trading_active, status.exchange_active
+            ),
+            ExchangeEvent::TradingResumed { .. } => write!(f, "Trading resumed"),
+            ExchangeEvent::MaintenanceSoon { start, end } => {
+                write!(f, "Maintenance from {} to {}", start, end)
+            }
+            ExchangeEvent::PortfolioDataStale { last_validated } => {
+                write!(f, "Portfolio data stale since {}", last_validated)
+            }
+            ExchangeEvent::PortfolioDataFresh { last_validated } => {
+                write!(f, "Portfolio data fresh as of {}", last_validated)
+            }
+        }
+    }
+}
+
+/// A sink for [`ExchangeEvent`]s, called from the watcher's poll loop.
+///
+/// Implementations should return quickly; slow deliveries belong on a spawned task.
+/// Closures taking `&ExchangeEvent` are notifiers.
+pub trait Notifier: Send + Sync {
+    fn notify(&self, event: &ExchangeEvent);
+}
+
+impl<F: Fn(&ExchangeEvent) + Send + Sync> Notifier for F {
+    fn notify(&self, event: &ExchangeEvent) {
+        self(event)
+    }
+}
+
+/// Writes each event to standard error.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StderrNotifier;
+
+impl Notifier for StderrNotifier {
+    fn notify(&self, event: &ExchangeEvent) {
+        eprintln!("[kalshi] {}", event);
+    }
+}
+
+/// Posts each event as JSON to a webhook, e.g. a Slack or Discord incoming webhook.
+///
+/// The body is `{"text": "<event>", "event": {...}}`. Deliveries run on tasks spawned on
+/// the current Tokio runtime and failures are ignored. Events raised outside a runtime are
+/// dropped.
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: &str) -> Self {
+        WebhookNotifier {
+            url: url.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify(&self, event: &ExchangeEvent) {
+        let body = serde_json::json!({ "text": event.to_string(), "event": event });
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let request = self.client.post(&self.url).json(&body);
+        runtime.spawn(async move {
+            let _ = request.send().await;
+        });
+    }
+}
+
+#[derive(Debug, Default)]
+struct WatchState {
+    /// Announcements seen, keyed by timestamp and message. `None` before the first poll.
+    seen: Option<HashSet<(String, String)>>,
+    status: Option<ExchangeStatus>,
+    stale: bool,
+    /// Start times of maintenance windows already warned about.
+    warned: HashSet<DateTime<Utc>>,
+}
+
+/// Polls exchange endpoints and reports changes. See the
+/// [module documentation](crate::watcher).
+pub struct ExchangeWatcher {
+    kalshi: Kalshi,
+    interval: Duration,
+    calendar: Option<ExchangeCalendar>,
+    maintenance_lead: chrono::Duration,
+    stale_after: chrono::Duration,
+    gate: Option<Arc<ExchangeGate>>,
+    notifiers: Vec<Box<dyn Notifier>>,
+    state: Mutex<WatchState>,
+    events: broadcast::Sender<ExchangeEvent>,
+}
+
+impl fmt::Debug for ExchangeWatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExchangeWatcher")
+            .field("interval", &self.interval)
+            .field("calendar", &self.calendar)
+            .field("maintenance_lead", &self.maintenance_lead)
+            .field("stale_after", &self.stale_after)
+            .field("notifiers", &self.notifiers.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ExchangeWatcher {
+    /// Creates a watcher polling every minute, warning 15 minutes before maintenance and
+    /// treating portfolio data older than 5 minutes as stale.
+    pub fn new(kalshi: Kalshi) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        ExchangeWatcher {
+            kalshi,
+            interval: Duration::from_secs(60),
+            calendar: None,
+            maintenance_lead: chrono::Duration::minutes(15),
+            stale_after: chrono::Duration::minutes(5),
+            gate: None,
+            notifiers: Vec::new(),
+            state: Mutex::new(WatchState::default()),
+            events,
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Enables [`ExchangeEvent::MaintenanceSoon`], raised `lead` before each window.
+    pub fn with_calendar(mut self, calendar: ExchangeCalendar, lead: chrono::Duration) -> Self {
+        self.calendar = Some(calendar);
+        self.maintenance_lead = lead;
+        self
+    }
+
+    /// Age after which portfolio data is reported stale.
+    pub fn with_stale_after(mut self, stale_after: chrono::Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// Records every fetched status in `gate`.
+    pub fn with_gate(mut self, gate: Arc<ExchangeGate>) -> Self {
+        self.gate = Some(gate);
+        self
+    }
+
+    /// Adds a sink that receives every event.
+    pub fn with_notifier(mut self, notifier: impl Notifier + 'static) -> Self {
+        self.notifiers.push(Box::new(notifier));
+        self
+    }
+
+    /// Receives [`ExchangeEvent`]s.
+    pub fn subscribe(&self) -> broadcast::Receiver<ExchangeEvent> {
+        self.events.subscribe()
+    }
+
+    /// Events as a stream. Events missed by a slow consumer are skipped; the stream ends
+    /// when the watcher is dropped.
+    pub fn events(&self) -> impl Stream<Item = ExchangeEvent> + Send + 'static {
+        futures_util::stream::unfold(self.subscribe(), |mut rx| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) => return Some((event, rx)),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+
+    /// Spawns a task that calls [`poll`](Self::poll) every interval until the handle is
+    /// aborted. Failed polls are retried at the next interval.
+    pub fn spawn(self: &Arc<Self>) -> JoinHandle<()> {
+        let watcher = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                let _ = watcher.poll().await;
+                tokio::time::sleep(watcher.interval).await;
+            }
+        })
+    }
+
+    /// Fetches all three endpoints once and emits what changed.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Vec<ExchangeEvent>)`: The events emitted by this poll.
+    /// - `Err(KalshiError)`: The first failed request. Results of the other requests are
+    ///   still applied.
+    ///
+    pub async fn poll(&self) -> Result<Vec<ExchangeEvent>, KalshiError> {
+        let (announcements, status, user_data) = tokio::join!(
+            self.kalshi
+                .get_exchange_announcements(Some(ANNOUNCEMENT_PAGE_SIZE), None),
+            self.kalshi.get_exchange_status(),
+            self.kalshi.get_user_data_timestamp(),
+        );
+        let now = Utc::now();
+        let mut events = Vec::new();
+        let mut error = None;
+        match announcements {
+            Ok((_, announcements)) => events.extend(self.apply_announcements(&announcements)),
+            Err(e) => error = error.or(Some(e)),
+        }
+        match status {
+            Ok(status) => events.extend(self.apply_status(status)),
+            Err(e) => error = error.or(Some(e)),
+        }
+        match user_data {
+            Ok(user_data) => events.extend(self.apply_user_data(&user_data, now)),
+            Err(e) => error = error.or(Some(e)),
+        }
+        events.extend(self.check_maintenance(now));
+        match error {
+            Some(e) => Err(e),
+            None => Ok(events),
+        }
+    }
+
+    /// Emits [`ExchangeEvent::NewAnnouncement`] for active announcements not seen before.
+    /// The first call only records what exists.
+    pub fn apply_announcements(
+        &self,
+        announcements: &[ExchangeAnnouncement],
+    ) -> Vec<ExchangeEvent> {
+        let mut events = Vec::new();
+        {
+            let mut state = self.lock();
+            let first = state.seen.is_none();
+            let seen = state.seen.get_or_insert_with(HashSet::new);
+            for a in announcements {
+                let fresh = seen.insert((a.ts.clone(), a.message.clone()));
+                if fresh && !first && a.status == AnnouncementStatus::Active {
+                    events.push(ExchangeEvent::NewAnnouncement {
+                        announcement: a.clone(),
+                    });
+                }
+            }
+        }
+        self.emit_all(&events);
+        events
+    }
+
+    /// Emits [`ExchangeEvent::TradingHalted`] or [`ExchangeEvent::TradingResumed`] when
+    /// trading stops or starts. A halt on the first status is reported; activity is not.
+    pub fn apply_status(&self, status: ExchangeStatus) -> Option<ExchangeEvent> {
+        if let Some(gate) = &self.gate {
+            gate.record_status(status.clone());
+        }
+        let active = is_active(&status);
+        let event = {
+            let mut state = self.lock();
+            let was_active = state.status.as_ref().is_none_or(is_active);
+            state.status = Some(status.clone());
+            match (was_active, active) {
+                (true, false) => Some(ExchangeEvent::TradingHalted { status }),
+                (false, true) => Some(ExchangeEvent::TradingResumed { status }),
+                _ => None,
+            }
+        };
+        self.emit_all(event.as_slice());
+        event
+    }
+
+    /// Emits [`ExchangeEvent::PortfolioDataStale`] when data becomes older than the
+    /// threshold and [`ExchangeEvent::PortfolioDataFresh`] when it recovers.
+    pub fn apply_user_data(
+        &self,
+        user_data: &UserDataTimestamp,
+        now: DateTime<Utc>,
+    ) -> Option<ExchangeEvent> {
+        let last_validated = user_data.last_validated()?;
+        let stale = now - last_validated > self.stale_after;
+        let event = {
+            let mut state = self.lock();
+            let was_stale = std::mem::replace(&mut state.stale, stale);
+            match (was_stale, stale) {
+                (false, true) => Some(ExchangeEvent::PortfolioDataStale { last_validated }),
+                (true, false) => Some(ExchangeEvent::PortfolioDataFresh { last_validated }),
+                _ => None,
+            }
+        };
+        self.emit_all(event.as_slice());
+        event
+    }
+
+    /// Emits [`ExchangeEvent::MaintenanceSoon`] once for each calendar window starting
+    /// within the lead time of `now`.
+    pub fn check_maintenance(&self, now: DateTime<Utc>) -> Option<ExchangeEvent> {
+        let (start, end) = self.calendar.as_ref()?.next_maintenance(now)?;
+        if start - now > self.maintenance_lead || !self.lock().warned.insert(start) {
+            return None;
+        }
+        let event = ExchangeEvent::MaintenanceSoon { start, end };
+        self.emit_all(std::slice::from_ref(&event));
+        Some(event)
+    }
+
+    fn emit_all(&self, events: &[ExchangeEvent]) {
+        for event in events {
+            for notifier in &self.notifiers {
+                notifier.notify(event);
+            }
+            let _ = self.events.send(event.clone());
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, WatchState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    fn announcement(ts: &str, message: &str, status: &str) -> ExchangeAnnouncement {
+        serde_json::from_value(serde_json::json!({
+            "message": message, "ts": ts, "status": status
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_watcher_reports_changes_to_stream_and_notifiers() {
+        let notified = Arc::new(Mutex::new(Vec::new()));
+        let sink = notified.clone();
+        let watcher = ExchangeWatcher::new(Kalshi::test_client())
+            .with_notifier(move |e: &ExchangeEvent| sink.lock().unwrap().push(e.clone()));
+        let mut stream = Box::pin(watcher.events());
+
+        let old = announcement("2024-07-01T12:00:00Z", "Welcome", "active");
+        assert!(watcher
+            .apply_announcements(std::slice::from_ref(&old))
+            .is_empty());
+        let new = announcement("2024-07-02T12:00:00Z", "Maintenance tonight", "active");
+        let expired = announcement("2024-07-02T13:00:00Z", "Old news", "expired");
+        let events = watcher.apply_announcements(&[old, new.clone(), expired]);
+        assert_eq!(
+            events,
+            vec![ExchangeEvent::NewAnnouncement {
+                announcement: new.clone()
+            }]
+        );
+        assert_eq!(
+            new.timestamp(),
+            Some("2024-07-02T12:00:00Z".parse().unwrap())
+        );
+
+        let halted = ExchangeStatus {
+            trading_active: false,
+            exchange_active: true,
+        };
+        assert!(matches!(
+            watcher.apply_status(halted.clone()),
+            Some(ExchangeEvent::TradingHalted { .. })
+        ));
+        assert_eq!(watcher.apply_status(halted), None);
+
+        let now: DateTime<Utc> = "2024-07-02T12:10:00Z".parse().unwrap();
+        let stale = UserDataTimestamp {
+            last_validated_ts: "2024-07-02T12:00:00Z".to_string(),
+        };
+        assert!(matches!(
+            watcher.apply_user_data(&stale, now),
+            Some(ExchangeEvent::PortfolioDataStale { .. })
+        ));
+        assert_eq!(watcher.apply_user_data(&stale, now), None);
+
+        assert_eq!(notified.lock().unwrap().len(), 3);
+        assert!(matches!(
+            stream.next().await,
+            Some(ExchangeEvent::NewAnnouncement { .. })
+        ));
+        assert!(matches!(
+            stream.next().await,
+            Some(ExchangeEvent::TradingHalted { .. })
+        ));
+    }
+
+    #[test]
+    fn test_webhook_outside_runtime_drops_events() {
+        let watcher = ExchangeWatcher::new(Kalshi::test_client())
+            .with_notifier(WebhookNotifier::new("http://127.0.0.1:9/hook"));
+        let halted = ExchangeStatus {
+            trading_active: false,
+            exchange_active: true,
+        };
+        assert!(matches!(
+            watcher.apply_status(halted),
+            Some(ExchangeEvent::TradingHalted { .. })
+        ));
+    }
+}
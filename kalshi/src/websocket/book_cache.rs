@@ -0,0 +1,220 @@
+use super::WebSocketMessage;
+use crate::Orderbook;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Mutex;
+
+/// Resting quantity per price level, in cents.
+type Levels = BTreeMap<i32, i64>;
+
+#[derive(Debug, Default)]
+struct Book {
+    yes: Levels,
+    no: Levels,
+    /// Subscription the book's snapshot arrived on, if it came from the WebSocket.
+    sid: Option<i32>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    books: HashMap<String, Book>,
+    /// Last sequence number seen per subscription.
+    seqs: HashMap<i32, i64>,
+    /// Markets waiting for a snapshot; see [`OrderbookCache::stale`].
+    stale: BTreeSet<String>,
+}
+
+/// Local orderbooks maintained from `orderbook_delta` channel messages.
+///
+/// Feed every message to [`handle_message`](Self::handle_message); snapshots replace a
+/// market's book and deltas adjust it. Books read back with [`get`](Self::get) have the
+/// same layout as [`Kalshi::get_orderbook`](crate::Kalshi::get_orderbook) responses, so
+/// they can be used wherever a REST book is expected.
+///
+/// A delta is only applied on top of a snapshot. Deltas for markets without one, and a
+/// gap in a subscription's sequence numbers, drop the affected books and list the markets
+/// in [`stale`](Self::stale) until a new snapshot arrives.
+#[derive(Debug, Default)]
+pub struct OrderbookCache {
+    state: Mutex<CacheState>,
+}
+
+impl OrderbookCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies orderbook snapshots and deltas. Other messages are ignored.
+    ///
+    /// # Returns
+    ///
+    /// The ticker whose book changed, if any. Dropped deltas return `None`.
+    ///
+    pub fn handle_message(&self, msg: &WebSocketMessage) -> Option<String> {
+        let mut state = self.lock();
+        match msg {
+            WebSocketMessage::OrderbookSnapshot(snap) => {
+                if let (Some(sid), Some(seq)) = (snap.sid, snap.seq) {
+                    state.seqs.insert(sid, seq);
+                }
+                state.stale.remove(&snap.market_ticker);
+                state.books.insert(
+                    snap.market_ticker.clone(),
+                    Book {
+                        yes: to_levels(&snap.yes),
+                        no: to_levels(&snap.no),
+                        sid: snap.sid,
+                    },
+                );
+                Some(snap.market_ticker.clone())
+            }
+            WebSocketMessage::OrderbookDelta(delta) => {
+                if let (Some(sid), Some(seq)) = (delta.sid, delta.seq) {
+                    let last = state.seqs.insert(sid, seq);
+                    if last.is_some_and(|last| seq != last + 1) {
+                        state.drop_subscription(sid);
+                    }
+                }
+                let Some(book) = state.books.get_mut(&delta.market_ticker) else {
+                    state.stale.insert(delta.market_ticker.clone());
+                    return None;
+                };
+                let levels = if delta.side == "no" {
+                    &mut book.no
+                } else {
+                    &mut book.yes
+                };
+                let qty = levels.entry(delta.price).or_insert(0);
+                *qty += delta.delta as i64;
+                if *qty <= 0 {
+                    levels.remove(&delta.price);
+                }
+                Some(delta.market_ticker.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Replaces a market's book, e.g. with a REST snapshot.
+    pub fn insert(&self, ticker: &str, book: &Orderbook) {
+        let yes = to_levels(book.yes.as_deref().unwrap_or_default());
+        let no = to_levels(book.no.as_deref().unwrap_or_default());
+        let mut state = self.lock();
+        state.stale.remove(ticker);
+        state
+            .books
+            .insert(ticker.to_string(), Book { yes, no, sid: None });
+    }
+
+    /// The current book of `ticker`, levels in ascending price order.
+    pub fn get(&self, ticker: &str) -> Option<Orderbook> {
+        let state = self.lock();
+        let book = state.books.get(ticker)?;
+        let cents = |levels: &Levels| -> Vec<Vec<i32>> {
+            levels
+                .iter()
+                .map(|(price, qty)| vec![*price, *qty as i32])
+                .collect()
+        };
+        let dollars = |levels: &Levels| -> Vec<(f32, i32)> {
+            levels
+                .iter()
+                .map(|(price, qty)| (*price as f32 / 100.0, *qty as i32))
+                .collect()
+        };
+        Some(Orderbook {
+            yes: Some(cents(&book.yes)),
+            no: Some(cents(&book.no)),
+            yes_dollars: dollars(&book.yes),
+            no_dollars: dollars(&book.no),
+        })
+    }
+
+    /// Tickers with a book.
+    pub fn tickers(&self) -> Vec<String> {
+        let mut tickers: Vec<String> = self.lock().books.keys().cloned().collect();
+        tickers.sort();
+        tickers
+    }
+
+    /// Markets that need a new snapshot: a delta arrived before any snapshot, or after a
+    /// sequence gap on their subscription. Resubscribe, or [`insert`](Self::insert) a REST
+    /// snapshot, to resume them.
+    pub fn stale(&self) -> Vec<String> {
+        self.lock().stale.iter().cloned().collect()
+    }
+
+    /// Drops a market's book, e.g. after unsubscribing.
+    pub fn remove(&self, ticker: &str) {
+        let mut state = self.lock();
+        state.books.remove(ticker);
+        state.stale.remove(ticker);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl CacheState {
+    /// Drops every book fed by subscription `sid` and marks its markets stale.
+    fn drop_subscription(&mut self, sid: i32) {
+        let tickers: Vec<String> = self
+            .books
+            .iter()
+            .filter(|(_, book)| book.sid == Some(sid))
+            .map(|(ticker, _)| ticker.clone())
+            .collect();
+        for ticker in tickers {
+            self.books.remove(&ticker);
+            self.stale.insert(ticker);
+        }
+    }
+}
+
+fn to_levels(levels: &[Vec<i32>]) -> Levels {
+    levels
+        .iter()
+        .filter_map(|l| match l.as_slice() {
+            [price, qty, ..] if *qty > 0 => Some((*price, *qty as i64)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> WebSocketMessage {
+        WebSocketMessage::parse(text).unwrap()
+    }
+
+    fn delta(ticker: &str, seq: i64) -> WebSocketMessage {
+        parse(&format!(
+            r#"{{"type":"orderbook_delta","sid":1,"seq":{},"msg":{{"market_ticker":"{}","price":45,"price_dollars":"0.45","delta":5,"side":"yes"}}}}"#,
+            seq, ticker
+        ))
+    }
+
+    #[test]
+    fn test_gaps_and_missing_snapshots_mark_books_stale() {
+        let cache = OrderbookCache::new();
+        let snapshot = parse(
+            r#"{"type":"orderbook_snapshot","sid":1,"seq":1,"msg":{"market_ticker":"A","yes":[[45,10]],"yes_dollars":[],"no":[],"no_dollars":[]}}"#,
+        );
+        assert_eq!(cache.handle_message(&delta("B", 2)), None);
+        assert_eq!(cache.stale(), ["B"]);
+
+        cache.handle_message(&snapshot);
+        assert_eq!(cache.handle_message(&delta("A", 2)), Some("A".to_string()));
+        assert_eq!(cache.get("A").unwrap().yes, Some(vec![vec![45, 15]]));
+
+        // Sequence 3 was missed: the subscription's books are dropped.
+        assert_eq!(cache.handle_message(&delta("A", 4)), None);
+        assert!(cache.get("A").is_none());
+        assert_eq!(cache.stale(), ["A", "B"]);
+
+        cache.handle_message(&snapshot);
+        assert_eq!(cache.stale(), ["B"]);
+    }
+}
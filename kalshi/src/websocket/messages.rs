@@ -1,4 +1,5 @@
 use crate::portfolio::{Action, Side};
+use crate::SettlementResult;
 use serde::{Deserialize, Serialize};
 
 /// Envelope for all WebSocket messages.
@@ -77,6 +78,12 @@ pub struct OrderbookSnapshotMsg {
     pub yes_dollars: Vec<Vec<String>>,
     pub no: Vec<Vec<i32>>,
     pub no_dollars: Vec<Vec<String>>,
+    /// Subscription ID from the message envelope.
+    #[serde(skip)]
+    pub sid: Option<i32>,
+    /// Sequence number from the message envelope, consecutive within a subscription.
+    #[serde(skip)]
+    pub seq: Option<i64>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -86,6 +93,12 @@ pub struct OrderbookDeltaMsg {
     pub price_dollars: String,
     pub delta: i32,
     pub side: String, // "yes" or "no"
+    /// Subscription ID from the message envelope.
+    #[serde(skip)]
+    pub sid: Option<i32>,
+    /// Sequence number from the message envelope, consecutive within a subscription.
+    #[serde(skip)]
+    pub seq: Option<i64>,
 }
 
 // --- Market Data Messages ---
@@ -114,7 +127,7 @@ pub struct TradeMsg {
 
 // --- User Data Messages (auth required) ---
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct FillMsg {
     pub trade_id: String,
     pub order_id: String,
@@ -123,6 +136,21 @@ pub struct FillMsg {
     pub action: Action,
     pub count: i32,
     pub post_position: i32,
+    /// Yes price of the fill in cents (not always sent)
+    #[serde(default)]
+    pub yes_price: Option<i32>,
+    /// No price of the fill in cents (not always sent)
+    #[serde(default)]
+    pub no_price: Option<i32>,
+    /// Whether this fill took liquidity (not always sent)
+    #[serde(default)]
+    pub is_taker: Option<bool>,
+    /// Client order ID of the filled order (not always sent)
+    #[serde(default)]
+    pub client_order_id: Option<String>,
+    /// Fill timestamp in unix seconds (not always sent)
+    #[serde(default)]
+    pub ts: Option<i64>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -139,16 +167,26 @@ pub struct MarketPositionMsg {
 
 // --- Lifecycle Messages ---
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MarketLifecycleMsg {
     pub market_ticker: String,
     pub event_type: MarketLifecycleEvent,
     pub open_ts: Option<i64>,
     pub close_ts: Option<i64>,
+    /// Outcome, on `determined` events.
+    #[serde(default)]
+    pub result: Option<SettlementResult>,
+    #[serde(default)]
+    pub determination_ts: Option<i64>,
+    #[serde(default)]
+    pub settled_ts: Option<i64>,
+    /// On `deactivated` events: `true` when trading is paused, `false` when it resumes.
+    #[serde(default)]
+    pub is_deactivated: Option<bool>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
-#[serde(rename_all = "lowercase")]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
 pub enum MarketLifecycleEvent {
     Created,
     Activated,
@@ -158,10 +196,12 @@ pub enum MarketLifecycleEvent {
     Settled,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct EventLifecycleMsg {
     pub event_ticker: String,
     pub title: String,
+    #[serde(default)]
+    pub series_ticker: Option<String>,
 }
 
 // --- Multivariate Messages ---
@@ -169,12 +209,18 @@ pub struct EventLifecycleMsg {
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MultivariateLookupMsg {
     pub collection_ticker: String,
+    /// Event of the combo market (not always sent)
+    #[serde(default)]
+    pub event_ticker: Option<String>,
     pub market_ticker: String,
     pub selected_markets: Vec<SelectedMarket>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SelectedMarket {
+    /// Event of the leg market (not always sent)
+    #[serde(default)]
+    pub event_ticker: Option<String>,
     pub market_ticker: String,
     pub side: String,
 }
@@ -185,7 +231,12 @@ pub struct SelectedMarket {
 pub struct RfqCreatedMsg {
     pub id: String,
     pub market_ticker: String,
-    pub contracts: i32,
+    /// Requested contracts, if the RFQ is sized in contracts.
+    #[serde(default)]
+    pub contracts: Option<i32>,
+    /// Requested cost in centi-cents, if the RFQ is sized by cost.
+    #[serde(default)]
+    pub target_cost_centi_cents: Option<i64>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -223,13 +274,17 @@ impl WebSocketMessage {
                 Ok(WebSocketMessage::Error(msg))
             }
             "orderbook_snapshot" => {
-                let msg: OrderbookSnapshotMsg =
+                let mut msg: OrderbookSnapshotMsg =
                     serde_json::from_value(envelope.msg.unwrap_or_default())?;
+                msg.sid = envelope.sid;
+                msg.seq = envelope.seq;
                 Ok(WebSocketMessage::OrderbookSnapshot(msg))
             }
             "orderbook_delta" => {
-                let msg: OrderbookDeltaMsg =
+                let mut msg: OrderbookDeltaMsg =
                     serde_json::from_value(envelope.msg.unwrap_or_default())?;
+                msg.sid = envelope.sid;
+                msg.seq = envelope.seq;
                 Ok(WebSocketMessage::OrderbookDelta(msg))
             }
             "ticker" => {
@@ -176,12 +176,15 @@
 //! - [`WebSocketMessage`](messages::WebSocketMessage) - All message types
 //! - [`Channel`](channels::Channel) - Available subscription channels
 //! - [`Subscription`](subscription::Subscription) - Subscription management
+//! - [`OrderbookCache`] - Local orderbooks built from `orderbook_delta` messages
 
+mod book_cache;
 mod channels;
 mod connection;
 mod messages;
 mod subscription;
 
+pub use book_cache::OrderbookCache;
 pub use channels::Channel;
 pub use connection::{CommandResponse, KalshiWebSocket};
 pub use messages::*;